@@ -0,0 +1,24 @@
+//! Generates include/tsdf.h from the C API in src/capi when the crate is
+//! built with the `capi` feature. Without that feature, this does nothing.
+
+fn main() {
+    #[cfg(feature = "capi")]
+    generate_header();
+}
+
+/// Runs cbindgen over the crate, writing the header to include/tsdf.h.
+#[cfg(feature = "capi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file("cbindgen.toml").unwrap();
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate the C header.")
+        .write_to_file("include/tsdf.h");
+
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/capi");
+}
@@ -0,0 +1,317 @@
+#ifndef TSDF_H
+#define TSDF_H
+
+/* Generated by cbindgen from src/capi. Don't edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The status returned by every function in the C API. Anything other than
+// TSDF_OK means the call failed, and tsdf_last_error_message describes why.
+typedef enum TsdfStatus {
+  // The call succeeded.
+  TSDF_OK = 0,
+  // A required pointer argument was null.
+  TSDF_NULL_POINTER = 1,
+  // An argument was invalid, like a name containing '/' or a string that
+  // isn't utf-8.
+  TSDF_INVALID_ARGUMENT = 2,
+  // The named file, object or tag doesn't exist.
+  TSDF_NOT_FOUND = 3,
+  // An object with the given name already exists.
+  TSDF_ALREADY_EXISTS = 4,
+  // The object exists, but isn't what was asked for (for example, a Dir
+  // where an Array was expected), or an array was used with the wrong data
+  // type or shape.
+  TSDF_WRONG_TYPE = 5,
+  // An output buffer was too small. Any length outputs still hold the size
+  // that was needed.
+  TSDF_BUFFER_TOO_SMALL = 6,
+  // The file is corrupt.
+  TSDF_INVALID_DATA = 7,
+  // The operating system reported an I/O error.
+  TSDF_IO_ERROR = 8,
+  // tsdf hit a bug and panicked. The handles involved shouldn't be used
+  // again.
+  TSDF_PANIC = 9,
+} TsdfStatus;
+
+// The type of every element in an array.
+typedef enum TsdfDataType {
+  TSDF_INT8 = 0,
+  TSDF_INT16 = 1,
+  TSDF_INT32 = 2,
+  TSDF_INT64 = 3,
+  TSDF_UINT8 = 4,
+  TSDF_UINT16 = 5,
+  TSDF_UINT32 = 6,
+  TSDF_UINT64 = 7,
+  TSDF_FLOAT32 = 8,
+  TSDF_FLOAT64 = 9,
+} TsdfDataType;
+
+// How a file is opened for writing.
+typedef enum TsdfWriteMode {
+  // Use tsdf's default, which is TSDF_WRITE_LOCKLESS.
+  TSDF_WRITE_DEFAULT = 0,
+  // Write without locking the file, so that readers can read it at the
+  // same time.
+  TSDF_WRITE_LOCKLESS = 1,
+  // Lock the file while writing to it.
+  TSDF_WRITE_LOCKING = 2,
+} TsdfWriteMode;
+
+// The format that a file is written in.
+typedef enum TsdfFileFormat {
+  // Use the existing file's format, or TSDF_FORMAT_BINARY for new files.
+  TSDF_FORMAT_DEFAULT = 0,
+  // The compact binary format. This should be used in production.
+  TSDF_FORMAT_BINARY = 1,
+  // The plain text format, for debugging.
+  TSDF_FORMAT_TEXT = 2,
+} TsdfFileFormat;
+
+// An array in an open tsdf file.
+typedef struct TsdfArrayHandle TsdfArrayHandle;
+
+// A dir in an open tsdf file.
+typedef struct TsdfDirHandle TsdfDirHandle;
+
+// An open tsdf file. Dirs and arrays taken from the file keep it open until
+// they're closed too.
+typedef struct TsdfFileHandle TsdfFileHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Closes an array handle. Closing a null handle does nothing.
+//
+// # Safety
+// `array` must be null or a handle from tsdf that hasn't been closed yet.
+void tsdf_array_close(struct TsdfArrayHandle *array);
+
+// Gets the number of frames in the array.
+//
+// # Safety
+// `array` must be a live array handle, and `out` must be valid for a write.
+enum TsdfStatus tsdf_array_len(const struct TsdfArrayHandle *array, uint64_t *out);
+
+// Gets the type of every element in the array.
+//
+// # Safety
+// `array` must be a live array handle, and `out` must be valid for a write.
+enum TsdfStatus tsdf_array_data_type(const struct TsdfArrayHandle *array, enum TsdfDataType *out);
+
+// Copies the shape of a single frame into `shape_out`, which has room for
+// `shape_len` dimensions. The number of dimensions is always written to
+// `ndim_out`, and TSDF_BUFFER_TOO_SMALL is returned if they don't fit.
+//
+// # Safety
+// `array` must be a live array handle, `shape_out` must be valid for
+// `shape_len` writes and `ndim_out` must be valid for a write.
+enum TsdfStatus tsdf_array_frame_shape(const struct TsdfArrayHandle *array,
+                                       uint64_t *shape_out,
+                                       size_t shape_len,
+                                       size_t *ndim_out);
+
+// Reads frames `start..end` into `buf`, one after another, as native endian
+// values of `data_type`. `buf_len` is the size of `buf` in bytes, and must be
+// at least the number of frames times the number of elements in a frame
+// times the size of an element.
+//
+// # Safety
+// `array` must be a live array handle, and `buf` must be valid for `buf_len`
+// bytes. `buf` doesn't need to be aligned.
+enum TsdfStatus tsdf_array_read(const struct TsdfArrayHandle *array,
+                                enum TsdfDataType data_type,
+                                uint64_t start,
+                                uint64_t end,
+                                void *buf,
+                                size_t buf_len);
+
+// Appends `num_elements` values of `data_type`, which must be a whole number
+// of frames, to the end of the array.
+//
+// # Safety
+// `array` must be a live array handle, and `data` must be valid for reads of
+// `num_elements` values of `data_type`. `data` doesn't need to be aligned.
+enum TsdfStatus tsdf_array_append(const struct TsdfArrayHandle *array,
+                                  enum TsdfDataType data_type,
+                                  const void *data,
+                                  size_t num_elements);
+
+// Copies the value of the tag with the given name into `buf`. See
+// tsdf_dir_get_tag for how the buffer is filled.
+//
+// # Safety
+// `array` must be a live array handle, `name` must be a nul terminated
+// string, `buf` must be valid for `buf_len` bytes and `len_out` must be null
+// or valid for a write.
+enum TsdfStatus tsdf_array_get_tag(const struct TsdfArrayHandle *array,
+                                   const char *name,
+                                   char *buf,
+                                   size_t buf_len,
+                                   size_t *len_out);
+
+// Sets the tag with the given name to the given value.
+//
+// # Safety
+// `array` must be a live array handle, and `name` and `value` must be nul
+// terminated strings.
+enum TsdfStatus tsdf_array_set_tag(const struct TsdfArrayHandle *array,
+                                   const char *name,
+                                   const char *value);
+
+// Closes a dir handle. Closing a null handle does nothing.
+//
+// # Safety
+// `dir` must be null or a handle from tsdf that hasn't been closed yet.
+void tsdf_dir_close(struct TsdfDirHandle *dir);
+
+// Gets the number of dirs and arrays in the dir.
+//
+// # Safety
+// `dir` must be a live dir handle, and `out` must be valid for a write.
+enum TsdfStatus tsdf_dir_num_children(const struct TsdfDirHandle *dir, size_t *out);
+
+// Copies the name of the child at `index` into `buf`. Children are sorted by
+// name. See tsdf_dir_get_tag for how the buffer is filled.
+//
+// # Safety
+// `dir` must be a live dir handle, `buf` must be valid for `buf_len` bytes
+// and `len_out` must be null or valid for a write.
+enum TsdfStatus tsdf_dir_child_name(const struct TsdfDirHandle *dir,
+                                    size_t index,
+                                    char *buf,
+                                    size_t buf_len,
+                                    size_t *len_out);
+
+// Opens the child dir with the given name. On success, `*out` is set to a
+// handle that must be closed with tsdf_dir_close.
+//
+// # Safety
+// `dir` must be a live dir handle, `name` must be a nul terminated string
+// and `out` must be valid for a write.
+enum TsdfStatus tsdf_dir_get_dir(const struct TsdfDirHandle *dir,
+                                 const char *name,
+                                 struct TsdfDirHandle **out);
+
+// Opens the child array with the given name. On success, `*out` is set to a
+// handle that must be closed with tsdf_array_close.
+//
+// # Safety
+// `dir` must be a live dir handle, `name` must be a nul terminated string
+// and `out` must be valid for a write.
+enum TsdfStatus tsdf_dir_get_array(const struct TsdfDirHandle *dir,
+                                   const char *name,
+                                   struct TsdfArrayHandle **out);
+
+// Creates a new, empty child dir. On success, `*out` is set to a handle that
+// must be closed with tsdf_dir_close.
+//
+// # Safety
+// `dir` must be a live dir handle, `name` must be a nul terminated string
+// and `out` must be valid for a write.
+enum TsdfStatus tsdf_dir_create_dir(const struct TsdfDirHandle *dir,
+                                    const char *name,
+                                    struct TsdfDirHandle **out);
+
+// Creates a new, empty child array. Every frame appended to the array has
+// the `ndim` dimensional shape in `frame_shape`, and frames are stored in
+// chunks of `frames_per_chunk`. On success, `*out` is set to a handle that
+// must be closed with tsdf_array_close.
+//
+// # Safety
+// `dir` must be a live dir handle, `name` must be a nul terminated string,
+// `frame_shape` must be valid for `ndim` reads (or null if `ndim` is 0) and
+// `out` must be valid for a write.
+enum TsdfStatus tsdf_dir_create_array(const struct TsdfDirHandle *dir,
+                                      const char *name,
+                                      enum TsdfDataType data_type,
+                                      const uint64_t *frame_shape,
+                                      size_t ndim,
+                                      uint64_t frames_per_chunk,
+                                      struct TsdfArrayHandle **out);
+
+// Copies the value of the tag with the given name into `buf`, followed by a
+// nul. The length of the value, without the nul, is always written to
+// `len_out` (if it isn't null); if the value doesn't fit, the call returns
+// TSDF_BUFFER_TOO_SMALL and can be retried with a bigger buffer. Returns
+// TSDF_NOT_FOUND if the dir has no such tag.
+//
+// # Safety
+// `dir` must be a live dir handle, `name` must be a nul terminated string,
+// `buf` must be valid for `buf_len` bytes and `len_out` must be null or
+// valid for a write.
+enum TsdfStatus tsdf_dir_get_tag(const struct TsdfDirHandle *dir,
+                                 const char *name,
+                                 char *buf,
+                                 size_t buf_len,
+                                 size_t *len_out);
+
+// Sets the tag with the given name to the given value.
+//
+// # Safety
+// `dir` must be a live dir handle, and `name` and `value` must be nul
+// terminated strings.
+enum TsdfStatus tsdf_dir_set_tag(const struct TsdfDirHandle *dir,
+                                 const char *name,
+                                 const char *value);
+
+// Opens an existing file for reading. On success, `*out` is set to a handle
+// that must be closed with tsdf_file_close.
+//
+// # Safety
+// `path` must be a nul terminated string, and `out` must be valid for a
+// write.
+enum TsdfStatus tsdf_file_open_reader(const char *path, struct TsdfFileHandle **out);
+
+// Opens a file for writing, creating it if it doesn't exist. On success,
+// `*out` is set to a handle that must be closed with tsdf_file_close.
+//
+// # Safety
+// `path` must be a nul terminated string, and `out` must be valid for a
+// write.
+enum TsdfStatus tsdf_file_open_writer(const char *path,
+                                      enum TsdfWriteMode write_mode,
+                                      enum TsdfFileFormat file_format,
+                                      struct TsdfFileHandle **out);
+
+// Creates a new file for writing, overwriting any existing file. On success,
+// `*out` is set to a handle that must be closed with tsdf_file_close.
+//
+// # Safety
+// `path` must be a nul terminated string, and `out` must be valid for a
+// write.
+enum TsdfStatus tsdf_file_create(const char *path,
+                                 enum TsdfWriteMode write_mode,
+                                 enum TsdfFileFormat file_format,
+                                 struct TsdfFileHandle **out);
+
+// Closes a file handle. Closing a null handle does nothing.
+//
+// # Safety
+// `file` must be null or a handle from tsdf that hasn't been closed yet.
+void tsdf_file_close(struct TsdfFileHandle *file);
+
+// Gets the root dir of the file. On success, `*out` is set to a handle that
+// must be closed with tsdf_dir_close.
+//
+// # Safety
+// `file` must be a live file handle, and `out` must be valid for a write.
+enum TsdfStatus tsdf_file_root(const struct TsdfFileHandle *file, struct TsdfDirHandle **out);
+
+// Returns a description of the last error raised on the calling thread, or
+// null if there hasn't been one. The string is owned by tsdf and stays valid
+// until the next failing call on the same thread.
+const char *tsdf_last_error_message(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TSDF_H */
@@ -0,0 +1,408 @@
+use std::{
+    ffi::{c_char, c_void},
+    mem::size_of,
+    sync::Arc,
+};
+
+use crate::core::{
+    enums::{with_element_type, ArrayDataType},
+    structs::{Addr, Array, TsdfFile},
+    traits::{ArrayElement, ArrayTrait, TsdfFileTrait},
+};
+
+use super::{
+    ffi_call, get_tag, read_handle, read_str, write_str, FfiError,
+    TsdfDataType, TsdfStatus,
+};
+
+/// An array in an open tsdf file.
+pub struct TsdfArrayHandle {
+    /// The file that the array lives in.
+    file: Arc<TsdfFile>,
+
+    /// The address of the array in the file.
+    loc: Addr,
+}
+
+impl TsdfArrayHandle {
+    /// Constructs a new TsdfArrayHandle for the array at the given location.
+    pub(crate) fn new(file: Arc<TsdfFile>, loc: Addr) -> Self {
+        Self { file, loc }
+    }
+
+    /// Opens the array.
+    fn open(&self) -> Result<Array<'_, '_>, FfiError> {
+        Ok(Array::open(
+            self.loc,
+            self.file.get_io_metadata(),
+            self.file.get_backend(),
+        )?)
+    }
+}
+
+/// Returns an error if the caller's data type isn't the array's.
+fn check_data_type(
+    array: &Array,
+    data_type: ArrayDataType,
+) -> Result<(), FfiError> {
+    if array.get_data_type() != data_type {
+        return Err(FfiError::new(
+            TsdfStatus::TSDF_WRONG_TYPE,
+            format!(
+                "Array stores {:?}, not {:?}.",
+                array.get_data_type(),
+                data_type
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Closes an array handle. Closing a null handle does nothing.
+///
+/// # Safety
+/// `array` must be null or a handle from tsdf that hasn't been closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_close(array: *mut TsdfArrayHandle) {
+    if !array.is_null() {
+        drop(Box::from_raw(array));
+    }
+}
+
+/// Gets the number of frames in the array.
+///
+/// # Safety
+/// `array` must be a live array handle, and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_len(
+    array: *const TsdfArrayHandle,
+    out: *mut u64,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let len = read_handle(array, "array")?.open()?.len() as u64;
+        let out = out.as_mut().ok_or_else(|| FfiError::null_pointer("out"))?;
+        *out = len;
+        Ok(())
+    })
+}
+
+/// Gets the type of every element in the array.
+///
+/// # Safety
+/// `array` must be a live array handle, and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_data_type(
+    array: *const TsdfArrayHandle,
+    out: *mut TsdfDataType,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let data_type = read_handle(array, "array")?.open()?.get_data_type();
+        let out = out.as_mut().ok_or_else(|| FfiError::null_pointer("out"))?;
+        *out = data_type.into();
+        Ok(())
+    })
+}
+
+/// Copies the shape of a single frame into `shape_out`, which has room for
+/// `shape_len` dimensions. The number of dimensions is always written to
+/// `ndim_out`, and TSDF_BUFFER_TOO_SMALL is returned if they don't fit.
+///
+/// # Safety
+/// `array` must be a live array handle, `shape_out` must be valid for
+/// `shape_len` writes and `ndim_out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_frame_shape(
+    array: *const TsdfArrayHandle,
+    shape_out: *mut u64,
+    shape_len: usize,
+    ndim_out: *mut usize,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let array = read_handle(array, "array")?.open()?;
+        let frame_shape = array.get_header().get_frame_shape();
+        let ndim_out = ndim_out
+            .as_mut()
+            .ok_or_else(|| FfiError::null_pointer("ndim_out"))?;
+        *ndim_out = frame_shape.len();
+        if frame_shape.len() > shape_len {
+            return Err(FfiError::new(
+                TsdfStatus::TSDF_BUFFER_TOO_SMALL,
+                format!("Frames have {} dimensions.", frame_shape.len()),
+            ));
+        }
+        if !frame_shape.is_empty() {
+            if shape_out.is_null() {
+                return Err(FfiError::null_pointer("shape_out"));
+            }
+            shape_out.copy_from_nonoverlapping(
+                frame_shape.as_ptr(),
+                frame_shape.len(),
+            );
+        }
+        Ok(())
+    })
+}
+
+/// Reads frames `start..end` into `buf`, one after another, as native endian
+/// values of `data_type`. `buf_len` is the size of `buf` in bytes, and must be
+/// at least the number of frames times the number of elements in a frame
+/// times the size of an element.
+///
+/// # Safety
+/// `array` must be a live array handle, and `buf` must be valid for `buf_len`
+/// bytes. `buf` doesn't need to be aligned.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_read(
+    array: *const TsdfArrayHandle,
+    data_type: TsdfDataType,
+    start: u64,
+    end: u64,
+    buf: *mut c_void,
+    buf_len: usize,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let array = read_handle(array, "array")?.open()?;
+        let data_type = ArrayDataType::from(data_type);
+        check_data_type(&array, data_type)?;
+        with_element_type!(data_type, T => {
+            read_frames::<T>(&array, start, end, buf as *mut u8, buf_len)
+        })
+    })
+}
+
+/// Appends `num_elements` values of `data_type`, which must be a whole number
+/// of frames, to the end of the array.
+///
+/// # Safety
+/// `array` must be a live array handle, and `data` must be valid for reads of
+/// `num_elements` values of `data_type`. `data` doesn't need to be aligned.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_append(
+    array: *const TsdfArrayHandle,
+    data_type: TsdfDataType,
+    data: *const c_void,
+    num_elements: usize,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let array = read_handle(array, "array")?.open()?;
+        let data_type = ArrayDataType::from(data_type);
+        check_data_type(&array, data_type)?;
+        if data.is_null() && num_elements > 0 {
+            return Err(FfiError::null_pointer("data"));
+        }
+        with_element_type!(data_type, T => {
+            let data = data as *const T;
+            let frames: Vec<T> = (0..num_elements)
+                .map(|i| data.add(i).read_unaligned())
+                .collect();
+            Ok(array.append_frames(&frames)?)
+        })
+    })
+}
+
+/// Copies the value of the tag with the given name into `buf`. See
+/// tsdf_dir_get_tag for how the buffer is filled.
+///
+/// # Safety
+/// `array` must be a live array handle, `name` must be a nul terminated
+/// string, `buf` must be valid for `buf_len` bytes and `len_out` must be null
+/// or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_get_tag(
+    array: *const TsdfArrayHandle,
+    name: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+    len_out: *mut usize,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let array = read_handle(array, "array")?.open()?;
+        let value = get_tag(&array, read_str(name, "name")?)?;
+        write_str(&value, buf, buf_len, len_out)
+    })
+}
+
+/// Sets the tag with the given name to the given value.
+///
+/// # Safety
+/// `array` must be a live array handle, and `name` and `value` must be nul
+/// terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_array_set_tag(
+    array: *const TsdfArrayHandle,
+    name: *const c_char,
+    value: *const c_char,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let array = read_handle(array, "array")?.open()?;
+        array.set_tag(read_str(name, "name")?, read_str(value, "value")?)?;
+        Ok(())
+    })
+}
+
+/// Reads frames start..end into a caller supplied byte buffer.
+///
+/// # Safety
+/// `buf` must be valid for `buf_len` bytes.
+unsafe fn read_frames<T: ArrayElement>(
+    array: &Array,
+    start: u64,
+    end: u64,
+    buf: *mut u8,
+    buf_len: usize,
+) -> Result<(), FfiError> {
+    let frame_len = array.get_header().get_frame_len();
+    let needed = end.saturating_sub(start) * frame_len * size_of::<T>() as u64;
+    if needed > buf_len as u64 {
+        return Err(FfiError::new(
+            TsdfStatus::TSDF_BUFFER_TOO_SMALL,
+            format!("Reading those frames needs {} bytes.", needed),
+        ));
+    }
+    if buf.is_null() && needed > 0 {
+        return Err(FfiError::null_pointer("buf"));
+    }
+
+    let frames = array.get_frames::<T>(start as usize, end as usize)?;
+    let num_bytes = frames.len() * size_of::<T>();
+    buf.copy_from_nonoverlapping(frames.as_ptr() as *const u8, num_bytes);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, CString},
+        ptr,
+    };
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::capi::{
+        dir::{tsdf_dir_close, tsdf_dir_create_array, tsdf_dir_get_array},
+        file::{
+            tsdf_file_close, tsdf_file_create, tsdf_file_open_reader,
+            tsdf_file_root,
+        },
+        status::tsdf_last_error_message,
+        TsdfFileFormat, TsdfWriteMode,
+    };
+
+    /// Drive a whole write and read through the C API, the way a C caller
+    /// would.
+    #[test]
+    fn test_append_and_read() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("capi.tsdf");
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        let name = CString::new("counts").unwrap();
+
+        unsafe {
+            let mut file = ptr::null_mut();
+            let status = tsdf_file_create(
+                path.as_ptr(),
+                TsdfWriteMode::TSDF_WRITE_DEFAULT,
+                TsdfFileFormat::TSDF_FORMAT_DEFAULT,
+                &mut file,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            let mut root = ptr::null_mut();
+            assert_eq!(tsdf_file_root(file, &mut root), TsdfStatus::TSDF_OK);
+
+            let mut array = ptr::null_mut();
+            let frame_shape = [2_u64, 3];
+            let status = tsdf_dir_create_array(
+                root,
+                name.as_ptr(),
+                TsdfDataType::TSDF_FLOAT32,
+                frame_shape.as_ptr(),
+                frame_shape.len(),
+                4,
+                &mut array,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+
+            // The file stays open for as long as the array does.
+            tsdf_dir_close(root);
+            tsdf_file_close(file);
+
+            let frames: Vec<f32> = (0..30).map(|i| i as f32).collect();
+            let status = tsdf_array_append(
+                array,
+                TsdfDataType::TSDF_FLOAT32,
+                frames.as_ptr() as *const c_void,
+                frames.len(),
+            );
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+
+            // A partial frame and the wrong type are both rejected.
+            let status = tsdf_array_append(
+                array,
+                TsdfDataType::TSDF_FLOAT32,
+                frames.as_ptr() as *const c_void,
+                5,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_INVALID_ARGUMENT);
+            let status = tsdf_array_append(
+                array,
+                TsdfDataType::TSDF_FLOAT64,
+                frames.as_ptr() as *const c_void,
+                6,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_WRONG_TYPE);
+            let message = CStr::from_ptr(tsdf_last_error_message());
+            assert!(message.to_str().unwrap().contains("Float32"));
+            tsdf_array_close(array);
+
+            // Read everything back through a reader.
+            let mut file = ptr::null_mut();
+            let status = tsdf_file_open_reader(path.as_ptr(), &mut file);
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            let mut root = ptr::null_mut();
+            assert_eq!(tsdf_file_root(file, &mut root), TsdfStatus::TSDF_OK);
+            let mut array = ptr::null_mut();
+            let status = tsdf_dir_get_array(root, name.as_ptr(), &mut array);
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+
+            let mut len = 0;
+            assert_eq!(tsdf_array_len(array, &mut len), TsdfStatus::TSDF_OK);
+            assert_eq!(len, 5);
+            let mut data_type = TsdfDataType::TSDF_INT8;
+            let status = tsdf_array_data_type(array, &mut data_type);
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            assert_eq!(data_type, TsdfDataType::TSDF_FLOAT32);
+            let mut shape = [0_u64; 1];
+            let mut ndim = 0;
+            let status =
+                tsdf_array_frame_shape(array, shape.as_mut_ptr(), 1, &mut ndim);
+            assert_eq!(status, TsdfStatus::TSDF_BUFFER_TOO_SMALL);
+            assert_eq!(ndim, 2);
+
+            let mut read = vec![0_f32; 12];
+            let status = tsdf_array_read(
+                array,
+                TsdfDataType::TSDF_FLOAT32,
+                1,
+                3,
+                read.as_mut_ptr() as *mut c_void,
+                read.len() * size_of::<f32>(),
+            );
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            assert_eq!(read, frames[6..18].to_vec());
+            let status = tsdf_array_read(
+                array,
+                TsdfDataType::TSDF_FLOAT32,
+                1,
+                4,
+                read.as_mut_ptr() as *mut c_void,
+                read.len() * size_of::<f32>(),
+            );
+            assert_eq!(status, TsdfStatus::TSDF_BUFFER_TOO_SMALL);
+
+            tsdf_array_close(array);
+            tsdf_dir_close(root);
+            tsdf_file_close(file);
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use std::{ffi::c_char, slice, sync::Arc};
+
+use crate::core::{
+    structs::{Addr, Dir, TsdfFile},
+    traits::{HasMetadataTags, Locatable, TsdfFileTrait},
+};
+
+use super::{
+    ffi_call, read_handle, read_str, write_handle, write_str, FfiError,
+    TsdfArrayHandle, TsdfDataType, TsdfStatus,
+};
+
+/// A dir in an open tsdf file.
+pub struct TsdfDirHandle {
+    /// The file that the dir lives in.
+    file: Arc<TsdfFile>,
+
+    /// The address of the dir in the file.
+    loc: Addr,
+
+    /// The name of the dir within its parent.
+    name: String,
+}
+
+impl TsdfDirHandle {
+    /// Constructs a new TsdfDirHandle for the dir at the given location.
+    pub(crate) fn new(file: Arc<TsdfFile>, loc: Addr, name: String) -> Self {
+        Self { file, loc, name }
+    }
+
+    /// Opens the dir.
+    fn open(&self) -> Result<Dir<'_, '_>, FfiError> {
+        Ok(Dir::open(
+            self.loc,
+            &self.name,
+            self.file.get_io_metadata(),
+            self.file.get_backend(),
+        )?)
+    }
+}
+
+/// Closes a dir handle. Closing a null handle does nothing.
+///
+/// # Safety
+/// `dir` must be null or a handle from tsdf that hasn't been closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_close(dir: *mut TsdfDirHandle) {
+    if !dir.is_null() {
+        drop(Box::from_raw(dir));
+    }
+}
+
+/// Gets the number of dirs and arrays in the dir.
+///
+/// # Safety
+/// `dir` must be a live dir handle, and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_num_children(
+    dir: *const TsdfDirHandle,
+    out: *mut usize,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let num_children = read_handle(dir, "dir")?.open()?.list_dir()?.len();
+        let out = out.as_mut().ok_or_else(|| FfiError::null_pointer("out"))?;
+        *out = num_children;
+        Ok(())
+    })
+}
+
+/// Copies the name of the child at `index` into `buf`. Children are sorted by
+/// name. See tsdf_dir_get_tag for how the buffer is filled.
+///
+/// # Safety
+/// `dir` must be a live dir handle, `buf` must be valid for `buf_len` bytes
+/// and `len_out` must be null or valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_child_name(
+    dir: *const TsdfDirHandle,
+    index: usize,
+    buf: *mut c_char,
+    buf_len: usize,
+    len_out: *mut usize,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let names = read_handle(dir, "dir")?.open()?.list_dir()?;
+        let name = names.get(index).ok_or_else(|| {
+            FfiError::new(
+                TsdfStatus::TSDF_INVALID_ARGUMENT,
+                format!("The dir has no child {}.", index),
+            )
+        })?;
+        write_str(name, buf, buf_len, len_out)
+    })
+}
+
+/// Opens the child dir with the given name. On success, `*out` is set to a
+/// handle that must be closed with tsdf_dir_close.
+///
+/// # Safety
+/// `dir` must be a live dir handle, `name` must be a nul terminated string
+/// and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_get_dir(
+    dir: *const TsdfDirHandle,
+    name: *const c_char,
+    out: *mut *mut TsdfDirHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let handle = read_handle(dir, "dir")?;
+        let name = read_str(name, "name")?;
+        let loc = *handle.open()?.get_dir(name).map_err(wrong_type)?.get_addr();
+        let child =
+            TsdfDirHandle::new(handle.file.clone(), loc, name.to_string());
+        write_handle(child, out)
+    })
+}
+
+/// Opens the child array with the given name. On success, `*out` is set to a
+/// handle that must be closed with tsdf_array_close.
+///
+/// # Safety
+/// `dir` must be a live dir handle, `name` must be a nul terminated string
+/// and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_get_array(
+    dir: *const TsdfDirHandle,
+    name: *const c_char,
+    out: *mut *mut TsdfArrayHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let handle = read_handle(dir, "dir")?;
+        let name = read_str(name, "name")?;
+        let dir = handle.open()?;
+        let loc = *dir.get_array(name).map_err(wrong_type)?.get_addr();
+        write_handle(TsdfArrayHandle::new(handle.file.clone(), loc), out)
+    })
+}
+
+/// Creates a new, empty child dir. On success, `*out` is set to a handle that
+/// must be closed with tsdf_dir_close.
+///
+/// # Safety
+/// `dir` must be a live dir handle, `name` must be a nul terminated string
+/// and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_create_dir(
+    dir: *const TsdfDirHandle,
+    name: *const c_char,
+    out: *mut *mut TsdfDirHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let handle = read_handle(dir, "dir")?;
+        let name = read_str(name, "name")?;
+        let loc = *handle.open()?.create_dir(name)?.get_addr();
+        let child =
+            TsdfDirHandle::new(handle.file.clone(), loc, name.to_string());
+        write_handle(child, out)
+    })
+}
+
+/// Creates a new, empty child array. Every frame appended to the array has
+/// the `ndim` dimensional shape in `frame_shape`, and frames are stored in
+/// chunks of `frames_per_chunk`. On success, `*out` is set to a handle that
+/// must be closed with tsdf_array_close.
+///
+/// # Safety
+/// `dir` must be a live dir handle, `name` must be a nul terminated string,
+/// `frame_shape` must be valid for `ndim` reads (or null if `ndim` is 0) and
+/// `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_create_array(
+    dir: *const TsdfDirHandle,
+    name: *const c_char,
+    data_type: TsdfDataType,
+    frame_shape: *const u64,
+    ndim: usize,
+    frames_per_chunk: u64,
+    out: *mut *mut TsdfArrayHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let handle = read_handle(dir, "dir")?;
+        let name = read_str(name, "name")?;
+        let frame_shape = match ndim {
+            0 => &[],
+            _ if frame_shape.is_null() => {
+                return Err(FfiError::null_pointer("frame_shape"))
+            }
+            _ => slice::from_raw_parts(frame_shape, ndim),
+        };
+        let loc = *handle
+            .open()?
+            .create_array(
+                name,
+                data_type.into(),
+                frame_shape,
+                frames_per_chunk,
+            )?
+            .get_addr();
+        write_handle(TsdfArrayHandle::new(handle.file.clone(), loc), out)
+    })
+}
+
+/// Copies the value of the tag with the given name into `buf`, followed by a
+/// nul. The length of the value, without the nul, is always written to
+/// `len_out` (if it isn't null); if the value doesn't fit, the call returns
+/// TSDF_BUFFER_TOO_SMALL and can be retried with a bigger buffer. Returns
+/// TSDF_NOT_FOUND if the dir has no such tag.
+///
+/// # Safety
+/// `dir` must be a live dir handle, `name` must be a nul terminated string,
+/// `buf` must be valid for `buf_len` bytes and `len_out` must be null or
+/// valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_get_tag(
+    dir: *const TsdfDirHandle,
+    name: *const c_char,
+    buf: *mut c_char,
+    buf_len: usize,
+    len_out: *mut usize,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let dir = read_handle(dir, "dir")?.open()?;
+        let value = get_tag(&dir, read_str(name, "name")?)?;
+        write_str(&value, buf, buf_len, len_out)
+    })
+}
+
+/// Sets the tag with the given name to the given value.
+///
+/// # Safety
+/// `dir` must be a live dir handle, and `name` and `value` must be nul
+/// terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_dir_set_tag(
+    dir: *const TsdfDirHandle,
+    name: *const c_char,
+    value: *const c_char,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let dir = read_handle(dir, "dir")?.open()?;
+        dir.set_tag(read_str(name, "name")?, read_str(value, "value")?)?;
+        Ok(())
+    })
+}
+
+/// Gets the value of a tag, returning TSDF_NOT_FOUND if there's no such tag.
+pub(crate) fn get_tag(
+    object: &impl HasMetadataTags,
+    name: &str,
+) -> Result<String, FfiError> {
+    object.get_tag(name)?.ok_or_else(|| {
+        FfiError::new(
+            TsdfStatus::TSDF_NOT_FOUND,
+            format!("There is no tag called {:?}.", name),
+        )
+    })
+}
+
+/// Asking for the wrong kind of object is reported as InvalidInput by the
+/// DirTrait, but it gets its own status in the C API.
+fn wrong_type(err: std::io::Error) -> FfiError {
+    match err.kind() {
+        std::io::ErrorKind::InvalidInput => {
+            FfiError::new(TsdfStatus::TSDF_WRONG_TYPE, err.to_string())
+        }
+        _ => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, CString},
+        ptr,
+    };
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::capi::{
+        file::{tsdf_file_close, tsdf_file_create, tsdf_file_root},
+        TsdfFileFormat, TsdfWriteMode,
+    };
+
+    /// Make sure that children and tags can be listed and read through
+    /// caller supplied buffers, and that errors come back as the right status.
+    #[test]
+    fn test_children_tags_and_errors() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("capi_dir.tsdf");
+        let path = CString::new(path.to_str().unwrap()).unwrap();
+        let detector = CString::new("detector").unwrap();
+        let sample = CString::new("sample").unwrap();
+        let silicon = CString::new("silicon").unwrap();
+
+        unsafe {
+            let mut file = ptr::null_mut();
+            let status = tsdf_file_create(
+                path.as_ptr(),
+                TsdfWriteMode::TSDF_WRITE_LOCKLESS,
+                TsdfFileFormat::TSDF_FORMAT_TEXT,
+                &mut file,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            let mut root = ptr::null_mut();
+            assert_eq!(tsdf_file_root(file, &mut root), TsdfStatus::TSDF_OK);
+            tsdf_file_close(file);
+
+            let mut child = ptr::null_mut();
+            let status =
+                tsdf_dir_create_dir(root, detector.as_ptr(), &mut child);
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            tsdf_dir_close(child);
+            let status =
+                tsdf_dir_create_dir(root, detector.as_ptr(), &mut child);
+            assert_eq!(status, TsdfStatus::TSDF_ALREADY_EXISTS);
+            let mut array = ptr::null_mut();
+            let status =
+                tsdf_dir_get_array(root, detector.as_ptr(), &mut array);
+            assert_eq!(status, TsdfStatus::TSDF_WRONG_TYPE);
+            let status = tsdf_dir_get_dir(root, sample.as_ptr(), &mut child);
+            assert_eq!(status, TsdfStatus::TSDF_NOT_FOUND);
+            let status = tsdf_dir_get_dir(root, ptr::null(), &mut child);
+            assert_eq!(status, TsdfStatus::TSDF_NULL_POINTER);
+
+            let mut num_children = 0;
+            let status = tsdf_dir_num_children(root, &mut num_children);
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            assert_eq!(num_children, 1);
+            let mut buf = [0 as c_char; 16];
+            let mut len = 0;
+            let status =
+                tsdf_dir_child_name(root, 0, buf.as_mut_ptr(), 16, &mut len);
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()), detector.as_c_str());
+
+            // Ask for the length with an empty buffer, then fetch the value.
+            let status =
+                tsdf_dir_set_tag(root, sample.as_ptr(), silicon.as_ptr());
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            let status = tsdf_dir_get_tag(
+                root,
+                sample.as_ptr(),
+                ptr::null_mut(),
+                0,
+                &mut len,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_BUFFER_TOO_SMALL);
+            assert_eq!(len, 7);
+            let mut buf = vec![0 as c_char; len + 1];
+            let status = tsdf_dir_get_tag(
+                root,
+                sample.as_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut len,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_OK);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()), silicon.as_c_str());
+            let status = tsdf_dir_get_tag(
+                root,
+                detector.as_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut len,
+            );
+            assert_eq!(status, TsdfStatus::TSDF_NOT_FOUND);
+
+            tsdf_dir_close(root);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::{ffi::c_char, sync::Arc};
+
+use crate::core::{structs::TsdfFile, traits::Locatable};
+
+use super::{
+    ffi_call, read_handle, read_path, write_handle, TsdfDirHandle,
+    TsdfFileFormat, TsdfStatus, TsdfWriteMode,
+};
+
+/// An open tsdf file. Dirs and arrays taken from the file keep it open until
+/// they're closed too.
+pub struct TsdfFileHandle {
+    /// The file, shared with every dir and array taken from it.
+    pub(crate) file: Arc<TsdfFile>,
+}
+
+impl TsdfFileHandle {
+    /// Wraps a TsdfFile that has already been opened.
+    fn new(file: TsdfFile) -> Self {
+        Self { file: file.into() }
+    }
+}
+
+/// Opens an existing file for reading. On success, `*out` is set to a handle
+/// that must be closed with tsdf_file_close.
+///
+/// # Safety
+/// `path` must be a nul terminated string, and `out` must be valid for a
+/// write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_file_open_reader(
+    path: *const c_char,
+    out: *mut *mut TsdfFileHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let file = TsdfFile::new_reader(read_path(path)?)?;
+        write_handle(TsdfFileHandle::new(file), out)
+    })
+}
+
+/// Opens a file for writing, creating it if it doesn't exist. On success,
+/// `*out` is set to a handle that must be closed with tsdf_file_close.
+///
+/// # Safety
+/// `path` must be a nul terminated string, and `out` must be valid for a
+/// write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_file_open_writer(
+    path: *const c_char,
+    write_mode: TsdfWriteMode,
+    file_format: TsdfFileFormat,
+    out: *mut *mut TsdfFileHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let file = TsdfFile::new_writer(
+            read_path(path)?,
+            write_mode.to_write_mode(),
+            file_format.to_file_format(),
+        )?;
+        write_handle(TsdfFileHandle::new(file), out)
+    })
+}
+
+/// Creates a new file for writing, overwriting any existing file. On success,
+/// `*out` is set to a handle that must be closed with tsdf_file_close.
+///
+/// # Safety
+/// `path` must be a nul terminated string, and `out` must be valid for a
+/// write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_file_create(
+    path: *const c_char,
+    write_mode: TsdfWriteMode,
+    file_format: TsdfFileFormat,
+    out: *mut *mut TsdfFileHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let file = TsdfFile::new_overwriting_writer(
+            read_path(path)?,
+            write_mode.to_write_mode(),
+            file_format.to_file_format(),
+        )?;
+        write_handle(TsdfFileHandle::new(file), out)
+    })
+}
+
+/// Closes a file handle. Closing a null handle does nothing.
+///
+/// # Safety
+/// `file` must be null or a handle from tsdf that hasn't been closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_file_close(file: *mut TsdfFileHandle) {
+    if !file.is_null() {
+        drop(Box::from_raw(file));
+    }
+}
+
+/// Gets the root dir of the file. On success, `*out` is set to a handle that
+/// must be closed with tsdf_dir_close.
+///
+/// # Safety
+/// `file` must be a live file handle, and `out` must be valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn tsdf_file_root(
+    file: *const TsdfFileHandle,
+    out: *mut *mut TsdfDirHandle,
+) -> TsdfStatus {
+    ffi_call(|| {
+        let file = read_handle(file, "file")?;
+        let loc = *file.file.get_root_dir()?.get_addr();
+        let dir = TsdfDirHandle::new(file.file.clone(), loc, "/".to_string());
+        write_handle(dir, out)
+    })
+}
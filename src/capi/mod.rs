@@ -0,0 +1,30 @@
+//! A C ABI for tsdf, so that it can be used from C, C# (via P/Invoke) and
+//! anything else that can call C. The header is generated into
+//! `include/tsdf.h` by cbindgen whenever the crate is built with the `capi`
+//! feature.
+//!
+//! # Conventions
+//! - Files, dirs and arrays are opaque handles. Every handle returned through
+//!   an `out` pointer must be closed with the matching `_close` function.
+//!   Dirs and arrays keep their file open until they're closed.
+//! - Every function that can fail returns a TsdfStatus. On failure,
+//!   tsdf_last_error_message describes what went wrong.
+//! - Strings are nul terminated utf-8. Strings are returned by copying them
+//!   into caller supplied buffers.
+//! - Handles can be shared between threads, but a file handle opened for
+//!   writing should only be written to from one thread at a time.
+
+pub(crate) mod array;
+pub(crate) mod dir;
+pub(crate) mod file;
+pub(crate) mod status;
+pub(crate) mod strings;
+pub(crate) mod types;
+
+pub(crate) use self::array::TsdfArrayHandle;
+pub(crate) use self::dir::{get_tag, TsdfDirHandle};
+pub(crate) use self::status::{ffi_call, FfiError, TsdfStatus};
+pub(crate) use self::strings::{
+    read_handle, read_path, read_str, write_handle, write_str,
+};
+pub(crate) use self::types::{TsdfDataType, TsdfFileFormat, TsdfWriteMode};
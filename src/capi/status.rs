@@ -0,0 +1,125 @@
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    io,
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+/// The status returned by every function in the C API. Anything other than
+/// TSDF_OK means the call failed, and tsdf_last_error_message describes why.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum TsdfStatus {
+    /// The call succeeded.
+    TSDF_OK = 0,
+
+    /// A required pointer argument was null.
+    TSDF_NULL_POINTER = 1,
+
+    /// An argument was invalid, like a name containing '/' or a string that
+    /// isn't utf-8.
+    TSDF_INVALID_ARGUMENT = 2,
+
+    /// The named file, object or tag doesn't exist.
+    TSDF_NOT_FOUND = 3,
+
+    /// An object with the given name already exists.
+    TSDF_ALREADY_EXISTS = 4,
+
+    /// The object exists, but isn't what was asked for (for example, a Dir
+    /// where an Array was expected), or an array was used with the wrong data
+    /// type or shape.
+    TSDF_WRONG_TYPE = 5,
+
+    /// An output buffer was too small. Any length outputs still hold the size
+    /// that was needed.
+    TSDF_BUFFER_TOO_SMALL = 6,
+
+    /// The file is corrupt.
+    TSDF_INVALID_DATA = 7,
+
+    /// The operating system reported an I/O error.
+    TSDF_IO_ERROR = 8,
+
+    /// tsdf hit a bug and panicked. The handles involved shouldn't be used
+    /// again.
+    TSDF_PANIC = 9,
+}
+
+/// An error raised inside the C API, ready to be handed back to the caller.
+pub(crate) struct FfiError {
+    /// The status to return.
+    status: TsdfStatus,
+
+    /// A description of what went wrong.
+    message: String,
+}
+
+impl FfiError {
+    /// Constructs a new FfiError.
+    pub(crate) fn new(status: TsdfStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// Makes the error returned when a required pointer is null.
+    pub(crate) fn null_pointer(name: &str) -> Self {
+        Self::new(
+            TsdfStatus::TSDF_NULL_POINTER,
+            format!("{} must not be null.", name),
+        )
+    }
+}
+
+impl From<io::Error> for FfiError {
+    fn from(err: io::Error) -> Self {
+        let status = match err.kind() {
+            io::ErrorKind::NotFound => TsdfStatus::TSDF_NOT_FOUND,
+            io::ErrorKind::AlreadyExists => TsdfStatus::TSDF_ALREADY_EXISTS,
+            io::ErrorKind::InvalidInput => TsdfStatus::TSDF_INVALID_ARGUMENT,
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+                TsdfStatus::TSDF_INVALID_DATA
+            }
+            _ => TsdfStatus::TSDF_IO_ERROR,
+        };
+        Self::new(status, err.to_string())
+    }
+}
+
+thread_local! {
+    /// The message describing the last error on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs the body of a C API function, turning errors and panics into a status
+/// and recording the error message for tsdf_last_error_message.
+pub(crate) fn ffi_call<F>(f: F) -> TsdfStatus
+where
+    F: FnOnce() -> Result<(), FfiError>,
+{
+    let err = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return TsdfStatus::TSDF_OK,
+        Ok(Err(err)) => err,
+        Err(_) => FfiError::new(TsdfStatus::TSDF_PANIC, "tsdf panicked."),
+    };
+
+    // Interior nul bytes can't be represented, so they're dropped.
+    let message = CString::new(err.message.replace('\0', "")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    err.status
+}
+
+/// Returns a description of the last error raised on the calling thread, or
+/// null if there hasn't been one. The string is owned by tsdf and stays valid
+/// until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn tsdf_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
@@ -0,0 +1,100 @@
+use std::{
+    ffi::{c_char, CStr},
+    path::Path,
+};
+
+use super::{FfiError, TsdfStatus};
+
+/// Borrows a nul terminated, utf-8 string passed in from C.
+///
+/// # Safety
+/// If `string` isn't null, it must point to a nul terminated string that
+/// outlives the returned reference.
+pub(crate) unsafe fn read_str<'a>(
+    string: *const c_char,
+    name: &str,
+) -> Result<&'a str, FfiError> {
+    if string.is_null() {
+        return Err(FfiError::null_pointer(name));
+    }
+    CStr::from_ptr(string).to_str().map_err(|_| {
+        FfiError::new(
+            TsdfStatus::TSDF_INVALID_ARGUMENT,
+            format!("{} is not valid utf-8.", name),
+        )
+    })
+}
+
+/// Borrows a path passed in from C.
+///
+/// # Safety
+/// See read_str.
+pub(crate) unsafe fn read_path<'a>(
+    path: *const c_char,
+) -> Result<&'a Path, FfiError> {
+    Ok(Path::new(read_str(path, "path")?))
+}
+
+/// Copies a string into a caller supplied buffer, nul terminating it. The
+/// length of the string (without the nul) is always written to `len_out`, if
+/// it isn't null, so that callers can size their buffer and try again.
+///
+/// # Safety
+/// `buf` must be valid for `buf_len` bytes, and `len_out` must be null or
+/// valid for a write.
+pub(crate) unsafe fn write_str(
+    string: &str,
+    buf: *mut c_char,
+    buf_len: usize,
+    len_out: *mut usize,
+) -> Result<(), FfiError> {
+    if !len_out.is_null() {
+        *len_out = string.len();
+    }
+    // Checked first, so that callers can pass a null, empty buffer to find
+    // out how big the buffer needs to be.
+    if string.len() >= buf_len {
+        return Err(FfiError::new(
+            TsdfStatus::TSDF_BUFFER_TOO_SMALL,
+            format!(
+                "A buffer of {} bytes can't hold {} bytes and a nul.",
+                buf_len,
+                string.len()
+            ),
+        ));
+    }
+    if buf.is_null() {
+        return Err(FfiError::null_pointer("buf"));
+    }
+
+    let buf = buf as *mut u8;
+    buf.copy_from_nonoverlapping(string.as_ptr(), string.len());
+    *buf.add(string.len()) = 0;
+    Ok(())
+}
+
+/// Moves a handle onto the heap and hands ownership of it to C.
+///
+/// # Safety
+/// `out` must be valid for a write.
+pub(crate) unsafe fn write_handle<T>(
+    handle: T,
+    out: *mut *mut T,
+) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(FfiError::null_pointer("out"));
+    }
+    *out = Box::into_raw(Box::new(handle));
+    Ok(())
+}
+
+/// Borrows a handle that was handed to C by write_handle.
+///
+/// # Safety
+/// `handle` must be null or a live handle returned by tsdf.
+pub(crate) unsafe fn read_handle<'a, T>(
+    handle: *const T,
+    name: &str,
+) -> Result<&'a T, FfiError> {
+    handle.as_ref().ok_or_else(|| FfiError::null_pointer(name))
+}
@@ -0,0 +1,109 @@
+use crate::core::enums::{ArrayDataType, FileFormat, WriteMode};
+
+/// The type of every element in an array.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum TsdfDataType {
+    TSDF_INT8 = 0,
+    TSDF_INT16 = 1,
+    TSDF_INT32 = 2,
+    TSDF_INT64 = 3,
+    TSDF_UINT8 = 4,
+    TSDF_UINT16 = 5,
+    TSDF_UINT32 = 6,
+    TSDF_UINT64 = 7,
+    TSDF_FLOAT32 = 8,
+    TSDF_FLOAT64 = 9,
+}
+
+impl From<TsdfDataType> for ArrayDataType {
+    fn from(data_type: TsdfDataType) -> Self {
+        match data_type {
+            TsdfDataType::TSDF_INT8 => ArrayDataType::Int8,
+            TsdfDataType::TSDF_INT16 => ArrayDataType::Int16,
+            TsdfDataType::TSDF_INT32 => ArrayDataType::Int32,
+            TsdfDataType::TSDF_INT64 => ArrayDataType::Int64,
+            TsdfDataType::TSDF_UINT8 => ArrayDataType::UInt8,
+            TsdfDataType::TSDF_UINT16 => ArrayDataType::UInt16,
+            TsdfDataType::TSDF_UINT32 => ArrayDataType::UInt32,
+            TsdfDataType::TSDF_UINT64 => ArrayDataType::UInt64,
+            TsdfDataType::TSDF_FLOAT32 => ArrayDataType::Float32,
+            TsdfDataType::TSDF_FLOAT64 => ArrayDataType::Float64,
+        }
+    }
+}
+
+impl From<ArrayDataType> for TsdfDataType {
+    fn from(data_type: ArrayDataType) -> Self {
+        match data_type {
+            ArrayDataType::Int8 => TsdfDataType::TSDF_INT8,
+            ArrayDataType::Int16 => TsdfDataType::TSDF_INT16,
+            ArrayDataType::Int32 => TsdfDataType::TSDF_INT32,
+            ArrayDataType::Int64 => TsdfDataType::TSDF_INT64,
+            ArrayDataType::UInt8 => TsdfDataType::TSDF_UINT8,
+            ArrayDataType::UInt16 => TsdfDataType::TSDF_UINT16,
+            ArrayDataType::UInt32 => TsdfDataType::TSDF_UINT32,
+            ArrayDataType::UInt64 => TsdfDataType::TSDF_UINT64,
+            ArrayDataType::Float32 => TsdfDataType::TSDF_FLOAT32,
+            ArrayDataType::Float64 => TsdfDataType::TSDF_FLOAT64,
+        }
+    }
+}
+
+/// How a file is opened for writing.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// The variants are only ever constructed by C callers.
+#[allow(non_camel_case_types, dead_code)]
+pub enum TsdfWriteMode {
+    /// Use tsdf's default, which is TSDF_WRITE_LOCKLESS.
+    TSDF_WRITE_DEFAULT = 0,
+
+    /// Write without locking the file, so that readers can read it at the
+    /// same time.
+    TSDF_WRITE_LOCKLESS = 1,
+
+    /// Lock the file while writing to it.
+    TSDF_WRITE_LOCKING = 2,
+}
+
+impl TsdfWriteMode {
+    /// Returns the WriteMode to use, or None to use the default.
+    pub(crate) fn to_write_mode(self) -> Option<WriteMode> {
+        match self {
+            TsdfWriteMode::TSDF_WRITE_DEFAULT => None,
+            TsdfWriteMode::TSDF_WRITE_LOCKLESS => {
+                Some(WriteMode::LocklessWrite)
+            }
+            TsdfWriteMode::TSDF_WRITE_LOCKING => Some(WriteMode::LockingWrite),
+        }
+    }
+}
+
+/// The format that a file is written in.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// As with TsdfWriteMode, C callers construct the variants.
+#[allow(non_camel_case_types, dead_code)]
+pub enum TsdfFileFormat {
+    /// Use the existing file's format, or TSDF_FORMAT_BINARY for new files.
+    TSDF_FORMAT_DEFAULT = 0,
+
+    /// The compact binary format. This should be used in production.
+    TSDF_FORMAT_BINARY = 1,
+
+    /// The plain text format, for debugging.
+    TSDF_FORMAT_TEXT = 2,
+}
+
+impl TsdfFileFormat {
+    /// Returns the FileFormat to use, or None to use the default.
+    pub(crate) fn to_file_format(self) -> Option<FileFormat> {
+        match self {
+            TsdfFileFormat::TSDF_FORMAT_DEFAULT => None,
+            TsdfFileFormat::TSDF_FORMAT_BINARY => Some(FileFormat::Binary),
+            TsdfFileFormat::TSDF_FORMAT_TEXT => Some(FileFormat::Text),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::Args;
+
+/// The arguments of tsdf cat.
+#[derive(Args)]
+pub(crate) struct CatArgs {
+    /// The file to read.
+    file: PathBuf,
+
+    /// The array to print.
+    path: String,
+
+    /// The first frame to print. Negative values count back from the end of
+    /// the array.
+    #[arg(long, allow_hyphen_values = true)]
+    start: Option<i64>,
+
+    /// The frame to stop before. Negative values count back from the end of
+    /// the array.
+    #[arg(long, allow_hyphen_values = true)]
+    end: Option<i64>,
+}
+
+/// Prints a slice of an array's frames, one per line.
+pub(crate) fn run(args: CatArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file = super::open_reader(&args.file)?;
+    let array = super::get_array(&file, &args.path)?;
+    let (start, end) = super::resolve_range(args.start, args.end, array.len());
+    super::write_frames(&array, start, end, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{make_file, run};
+
+    /// Slices should work like Python's, including negative indices.
+    #[test]
+    fn test_cat() {
+        let (_dir, path) = make_file();
+        let path = path.to_str().unwrap();
+        let array = "/detector/counts";
+
+        assert_eq!(
+            run(&["cat", path, array]).unwrap(),
+            "0: [1, 2]\n1: [3, 4]\n2: [5, 6]\n"
+        );
+        assert_eq!(
+            run(&["cat", path, array, "--start", "-2", "--end", "-1"]).unwrap(),
+            "1: [3, 4]\n"
+        );
+        assert_eq!(run(&["cat", path, array, "--start", "5"]).unwrap(), "");
+        assert!(run(&["cat", path, "/detector"]).is_err());
+    }
+}
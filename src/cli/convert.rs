@@ -0,0 +1,91 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::{Args, ValueEnum};
+use tsdf::{FileFormat, OpenOptions};
+
+/// The arguments of tsdf convert.
+#[derive(Args)]
+pub(crate) struct ConvertArgs {
+    /// The file to convert.
+    file: PathBuf,
+
+    /// The format to convert the file to.
+    #[arg(long, value_enum)]
+    to: FileFormatArg,
+
+    /// Where to write the converted file. If this is left out, the file is
+    /// replaced once the converted copy is complete.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// The values that --to accepts.
+#[derive(Clone, Copy, ValueEnum)]
+enum FileFormatArg {
+    Binary,
+    Text,
+}
+
+impl From<FileFormatArg> for FileFormat {
+    fn from(arg: FileFormatArg) -> Self {
+        match arg {
+            FileFormatArg::Binary => FileFormat::Binary,
+            FileFormatArg::Text => FileFormat::Text,
+        }
+    }
+}
+
+/// Rewrites a file in another format. See TsdfFile::convert.
+pub(crate) fn run(args: ConvertArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file_format = FileFormat::from(args.to);
+    let mut options = OpenOptions::new();
+    options.file_format(file_format);
+    let (original_size, converted_size) =
+        super::rewrite(&args.file, args.output.as_deref(), &options)?;
+    writeln!(
+        out,
+        "Converted {} bytes into {} bytes of {:?}.",
+        original_size, converted_size, file_format
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tsdf::{FileFormat, TsdfFile};
+
+    use super::super::tests::{make_file, run};
+
+    /// Converting to Text and back into a new file should keep the contents.
+    #[test]
+    fn test_convert() {
+        let (dir, path) = make_file();
+        let path_str = path.to_str().unwrap();
+        let binary = dir.path().join("binary.tsdf");
+
+        run(&["convert", path_str, "--to", "text"]).unwrap();
+        assert_eq!(
+            TsdfFile::new_reader(&path).unwrap().get_file_format(),
+            &FileFormat::Text
+        );
+        run(&[
+            "convert",
+            path_str,
+            "--to",
+            "binary",
+            "-o",
+            binary.to_str().unwrap(),
+        ])
+        .unwrap();
+
+        let binary = binary.to_str().unwrap();
+        assert_eq!(
+            run(&["cat", binary, "/detector/counts"]).unwrap(),
+            run(&["cat", path_str, "/detector/counts"]).unwrap()
+        );
+        assert_eq!(
+            run(&["tags", "get", binary, "/detector"]).unwrap(),
+            "sample = silicon\n"
+        );
+    }
+}
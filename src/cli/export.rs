@@ -0,0 +1,173 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::Args;
+use tsdf::HighLevelObject;
+
+/// The arguments of tsdf export.
+#[derive(Args)]
+pub(crate) struct ExportArgs {
+    /// The file to read.
+    file: PathBuf,
+
+    /// The array or dir to export.
+    path: String,
+
+    /// Where to write the export. Its extension picks the format: .npy for an
+    /// array, or .npz for a dir, whose tags also go to a .json file next to
+    /// it. A .csv file holds the arrays directly in a dir, which must have
+    /// scalar frames, as columns. With the arrow feature, .parquet writes an
+    /// array, or the arrays directly in a dir, as a table.
+    output: PathBuf,
+
+    /// The first frame to export. Negative values count back from the end of
+    /// the array.
+    #[arg(long, allow_hyphen_values = true)]
+    start: Option<i64>,
+
+    /// The frame to stop before. Negative values count back from the end of
+    /// the array.
+    #[arg(long, allow_hyphen_values = true)]
+    end: Option<i64>,
+}
+
+/// Exports an array or dir to another tool's format. See Array::export_npy
+/// and Dir::export_npz.
+pub(crate) fn run(args: ExportArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file = super::open_reader(&args.file)?;
+    let extension = args.output.extension().and_then(|ext| ext.to_str());
+    match (file.get(&args.path)?, extension) {
+        (HighLevelObject::Array(array), Some("npy")) => {
+            let (start, end) =
+                super::resolve_range(args.start, args.end, array.len());
+            array.export_npy_frames(&args.output, start, end)?;
+            writeln!(
+                out,
+                "Exported {} frames to {}.",
+                end - start,
+                args.output.display()
+            )?;
+        }
+        #[cfg(feature = "arrow")]
+        (HighLevelObject::Array(array), Some("parquet")) => {
+            let (start, end) =
+                super::resolve_range(args.start, args.end, array.len());
+            array.export_parquet(&args.output, start, end)?;
+            writeln!(
+                out,
+                "Exported {} frames to {}.",
+                end - start,
+                args.output.display()
+            )?;
+        }
+        (HighLevelObject::Dir(_), _)
+            if args.start.is_some() || args.end.is_some() =>
+        {
+            return Err(invalid_input(
+                "--start and --end only apply to arrays.".to_string(),
+            ))
+        }
+        #[cfg(feature = "arrow")]
+        (HighLevelObject::Dir(dir), Some("parquet")) => {
+            dir.export_parquet(&args.output)?;
+            writeln!(
+                out,
+                "Exported {} to {}.",
+                args.path,
+                args.output.display()
+            )?;
+        }
+        (HighLevelObject::Dir(dir), Some("csv")) => {
+            dir.export_csv(&args.output)?;
+            writeln!(
+                out,
+                "Exported {} to {}.",
+                args.path,
+                args.output.display()
+            )?;
+        }
+        (HighLevelObject::Dir(dir), Some("npz")) => {
+            dir.export_npz(&args.output)?;
+            writeln!(
+                out,
+                "Exported {} to {} and {}.",
+                args.path,
+                args.output.display(),
+                args.output.with_extension("json").display()
+            )?;
+        }
+        (HighLevelObject::Array(_), _) => {
+            return Err(invalid_input(format!(
+                "Can't export an array to {:?}.",
+                args.output
+            )))
+        }
+        (HighLevelObject::Dir(_), _) => {
+            return Err(invalid_input(format!(
+                "Can't export a dir to {:?}.",
+                args.output
+            )))
+        }
+        (HighLevelObject::MetadataTag(_), _) => {
+            return Err(invalid_input(format!(
+                "{:?} is a tag, not an array or dir.",
+                args.path
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Makes an InvalidInput error with the given message.
+fn invalid_input(message: String) -> tsdf::Error {
+    tsdf::Error::new(tsdf::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{make_file, run};
+
+    /// Arrays export to .npy and dirs to .npz, and nothing else.
+    #[test]
+    fn test_export() {
+        let (dir, path) = make_file();
+        let path = path.to_str().unwrap();
+        let npy = dir.path().join("counts.npy");
+        let npy = npy.to_str().unwrap();
+        let npz = dir.path().join("detector.npz");
+        let npz = npz.to_str().unwrap();
+
+        assert_eq!(
+            run(&["export", path, "/detector/counts", npy, "--start", "-2"])
+                .unwrap(),
+            format!("Exported 2 frames to {}.\n", npy)
+        );
+        let bytes = std::fs::read(npy).unwrap();
+        assert!(bytes.starts_with(b"\x93NUMPY"));
+        assert_eq!(&bytes[bytes.len() - 8..], &[3, 0, 4, 0, 5, 0, 6, 0]);
+
+        run(&["export", path, "/detector", npz]).unwrap();
+        assert!(dir.path().join("detector.json").exists());
+
+        assert!(run(&["export", path, "/detector", npy]).is_err());
+        assert!(run(&["export", path, "/detector/counts", npz]).is_err());
+        assert!(run(&["export", path, "/detector", npz, "--end", "1"]).is_err());
+    }
+
+    /// Arrays and dirs both export to Parquet.
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_export_parquet() {
+        let (dir, path) = make_file();
+        let path = path.to_str().unwrap();
+        let parquet = dir.path().join("counts.parquet");
+        let parquet = parquet.to_str().unwrap();
+
+        assert_eq!(
+            run(&["export", path, "/detector/counts", parquet, "--end", "1"])
+                .unwrap(),
+            format!("Exported 1 frames to {}.\n", parquet)
+        );
+        assert!(std::fs::read(parquet).unwrap().starts_with(b"PAR1"));
+        run(&["export", path, "/detector", parquet]).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::Args;
+use tsdf::{
+    ArrayDataType, CsvImportOptions, Dir, HighLevelObject, OpenOptions,
+    TsdfFile, WriteMode,
+};
+
+/// The arguments of tsdf import.
+#[derive(Args)]
+pub(crate) struct ImportArgs {
+    /// The file to import into, which is created if it doesn't exist.
+    file: PathBuf,
+
+    /// The dir to put the arrays in. It's created if it doesn't exist, but
+    /// its parent must.
+    path: String,
+
+    /// The CSV file to import.
+    csv: PathBuf,
+
+    /// The column holding each row's timestamp, as nanoseconds since the Unix
+    /// epoch or an RFC 3339 date-time.
+    #[arg(long)]
+    timestamp_column: Option<String>,
+
+    /// The data type of a column, as NAME=TYPE, where TYPE is a NumPy style
+    /// name like uint16 or float32. Can be given more than once.
+    #[arg(long = "type", value_parser = parse_column_type)]
+    types: Vec<(String, ArrayDataType)>,
+
+    /// The data type of columns without a --type.
+    #[arg(long, default_value = "float64", value_parser = parse_data_type)]
+    default_type: ArrayDataType,
+
+    /// The character that separates fields.
+    #[arg(long, default_value_t = ',')]
+    delimiter: char,
+}
+
+/// Every ArrayDataType, alongside the name used for it on the command line.
+const DATA_TYPE_NAMES: [(ArrayDataType, &str); 10] = [
+    (ArrayDataType::Int8, "int8"),
+    (ArrayDataType::Int16, "int16"),
+    (ArrayDataType::Int32, "int32"),
+    (ArrayDataType::Int64, "int64"),
+    (ArrayDataType::UInt8, "uint8"),
+    (ArrayDataType::UInt16, "uint16"),
+    (ArrayDataType::UInt32, "uint32"),
+    (ArrayDataType::UInt64, "uint64"),
+    (ArrayDataType::Float32, "float32"),
+    (ArrayDataType::Float64, "float64"),
+];
+
+/// Streams a CSV file into a dir of arrays. See CsvImportOptions.
+pub(crate) fn run(args: ImportArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    if !args.delimiter.is_ascii() {
+        return Err(tsdf::Error::new(
+            tsdf::ErrorKind::InvalidInput,
+            "The delimiter must be an ASCII character.",
+        ));
+    }
+    let mut options = CsvImportOptions::new();
+    options
+        .default_type(args.default_type)
+        .delimiter(args.delimiter as u8);
+    if let Some(column) = &args.timestamp_column {
+        options.timestamp_column(column);
+    }
+    for (column, data_type) in &args.types {
+        options.column_type(column, *data_type);
+    }
+
+    let file = OpenOptions::new()
+        .write_mode(WriteMode::LockingWrite)
+        .open_writer(&args.file)?;
+    let dir = get_or_create_dir(&file, &args.path)?;
+    options.import(&args.csv, &dir)?;
+    writeln!(out, "Imported {} into {}.", args.csv.display(), args.path)?;
+    Ok(())
+}
+
+/// Returns the dir at the given path, creating it in its parent if there's
+/// nothing there yet.
+fn get_or_create_dir<'f>(
+    file: &'f TsdfFile,
+    path: &str,
+) -> tsdf::Result<Dir<'f, 'f>> {
+    match file.get(path) {
+        Ok(HighLevelObject::Dir(dir)) => return Ok(dir),
+        Ok(_) => {
+            return Err(tsdf::Error::new(
+                tsdf::ErrorKind::InvalidInput,
+                format!("{:?} is not a dir.", path),
+            ))
+        }
+        Err(err) if err.kind() == tsdf::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+
+    let trimmed = path.trim_end_matches('/');
+    let (parent, name) = trimmed.rsplit_once('/').unwrap_or(("", trimmed));
+    match file.get(if parent.is_empty() { "/" } else { parent })? {
+        HighLevelObject::Dir(parent) => {
+            parent.create_dir(name)?;
+            match file.get(path)? {
+                HighLevelObject::Dir(dir) => Ok(dir),
+                _ => unreachable!(),
+            }
+        }
+        _ => Err(tsdf::Error::new(
+            tsdf::ErrorKind::InvalidInput,
+            format!("{:?} is not a dir.", parent),
+        )),
+    }
+}
+
+/// Parses a NumPy style data type name.
+fn parse_data_type(name: &str) -> Result<ArrayDataType, String> {
+    DATA_TYPE_NAMES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(data_type, _)| *data_type)
+        .ok_or_else(|| format!("{:?} is not a data type.", name))
+}
+
+/// Parses a NAME=TYPE column type.
+fn parse_column_type(arg: &str) -> Result<(String, ArrayDataType), String> {
+    let (name, data_type) = arg
+        .rsplit_once('=')
+        .ok_or_else(|| format!("{:?} is not of the form NAME=TYPE.", arg))?;
+    Ok((name.to_string(), parse_data_type(data_type)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{make_file, run};
+
+    /// Import a CSV into a new dir, and export it again.
+    #[test]
+    fn test_import() {
+        let (dir, path) = make_file();
+        let path = path.to_str().unwrap();
+        let csv = dir.path().join("scan.csv");
+        std::fs::write(&csv, "# sample = silicon\ntime;counts\n5;1\n6;2\n")
+            .unwrap();
+        let csv = csv.to_str().unwrap();
+
+        let output = run(&[
+            "import",
+            path,
+            "/scan",
+            csv,
+            "--timestamp-column",
+            "time",
+            "--type",
+            "counts=uint8",
+            "--delimiter",
+            ";",
+        ])
+        .unwrap();
+        assert_eq!(output, format!("Imported {} into /scan.\n", csv));
+        assert_eq!(
+            run(&["ls", path, "/scan"]).unwrap(),
+            "counts  UInt8 [2]\ntime  Int64 [2]\n"
+        );
+
+        let exported = dir.path().join("exported.csv");
+        let exported = exported.to_str().unwrap();
+        run(&["export", path, "/scan", exported]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(exported).unwrap(),
+            "# sample = silicon\ncounts,time\n1,5\n2,6\n"
+        );
+
+        let again = run(&["import", path, "/scan", csv, "--delimiter", ";"]);
+        assert_eq!(again.unwrap_err().kind(), tsdf::ErrorKind::AlreadyExists);
+        assert!(run(&["import", path, "/detector/counts", csv]).is_err());
+    }
+}
@@ -0,0 +1,23 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::Args;
+
+/// The arguments of tsdf info.
+#[derive(Args)]
+pub(crate) struct InfoArgs {
+    /// The file to describe.
+    file: PathBuf,
+}
+
+/// Prints everything in the file's header.
+pub(crate) fn run(args: InfoArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file = super::open_reader(&args.file)?;
+    writeln!(out, "Path: {}", file.get_path().display())?;
+    writeln!(out, "Version: {}", file.get_version())?;
+    writeln!(out, "Size: {} bytes", file.get_size())?;
+    writeln!(out, "File format: {:?}", file.get_file_format())?;
+    writeln!(out, "Hash algorithm: {:?}", file.get_hash_algorithm())?;
+    writeln!(out, "Shard capacity: {}", file.get_shard_capacity())?;
+    writeln!(out, "Checksums: {}", file.has_checksums())?;
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::Args;
+use tsdf::{Array, Dir, HighLevelObject};
+
+/// The arguments of tsdf ls.
+#[derive(Args)]
+pub(crate) struct LsArgs {
+    /// The file to look in.
+    file: PathBuf,
+
+    /// The dir to list, or an array to describe.
+    #[arg(default_value = "/")]
+    path: String,
+
+    /// List the contents of every dir below this one too.
+    #[arg(short = 'R', long)]
+    recursive: bool,
+}
+
+/// Lists a dir, one child per line. Dirs end in a slash, and arrays are
+/// followed by their data type and shape.
+pub(crate) fn run(args: LsArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file = super::open_reader(&args.file)?;
+    match file.get(&args.path)? {
+        HighLevelObject::Dir(dir) => write_dir(&dir, "", args.recursive, out),
+        HighLevelObject::Array(array) => write_array(&array, &args.path, out),
+        HighLevelObject::MetadataTag(_) => Err(tsdf::Error::new(
+            tsdf::ErrorKind::InvalidInput,
+            format!("{:?} is a metadata tag. Use tsdf tags get.", args.path),
+        )),
+    }
+}
+
+/// Writes a line for each child of the dir, with names prefixed by prefix.
+fn write_dir(
+    dir: &Dir,
+    prefix: &str,
+    recursive: bool,
+    out: &mut dyn Write,
+) -> tsdf::Result<()> {
+    for name in dir.list_dir()? {
+        let path = format!("{}{}", prefix, name);
+        match dir.get(&name)? {
+            HighLevelObject::Dir(child) => {
+                writeln!(out, "{}/", path)?;
+                if recursive {
+                    write_dir(&child, &format!("{}/", path), recursive, out)?;
+                }
+            }
+            HighLevelObject::Array(array) => write_array(&array, &path, out)?,
+            HighLevelObject::MetadataTag(_) => unreachable!(),
+        }
+    }
+    Ok(())
+}
+
+/// Writes the line describing an array.
+fn write_array(
+    array: &Array,
+    path: &str,
+    out: &mut dyn Write,
+) -> tsdf::Result<()> {
+    writeln!(
+        out,
+        "{}  {:?} {:?}",
+        path,
+        array.get_data_type(),
+        array.shape()
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{make_file, run};
+
+    /// Listing should show dirs with a trailing slash, and recurse with -R.
+    #[test]
+    fn test_ls() {
+        let (_dir, path) = make_file();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(run(&["ls", path]).unwrap(), "detector/\n");
+        assert_eq!(
+            run(&["ls", "-R", path]).unwrap(),
+            "detector/\ndetector/counts  UInt16 [3, 2]\n"
+        );
+        assert_eq!(
+            run(&["ls", path, "/detector/counts"]).unwrap(),
+            "/detector/counts  UInt16 [3, 2]\n"
+        );
+        assert!(run(&["ls", path, "/missing"]).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! The tsdf command-line tool, for inspecting and fixing tsdf files from a
+//! shell. Each subcommand lives in its own module, which holds its arguments
+//! and a run function that writes its output to the given writer.
+
+mod cat;
+mod convert;
+mod export;
+mod import;
+mod info;
+mod ls;
+mod repack;
+mod tags;
+mod tail;
+mod verify;
+
+use std::{fmt::Debug, fs, io::Write, path::Path};
+
+use clap::{Parser, Subcommand};
+use tsdf::{
+    Array, ArrayDataType, ArrayElement, HighLevelObject, OpenOptions, TsdfFile,
+};
+
+/// Inspect and fix tsdf files.
+#[derive(Parser)]
+#[command(name = "tsdf", version)]
+pub(crate) struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Every subcommand of the tsdf tool.
+#[derive(Subcommand)]
+enum Command {
+    /// Print the header of a file.
+    Info(info::InfoArgs),
+
+    /// List the contents of a dir.
+    Ls(ls::LsArgs),
+
+    /// Get or set metadata tags.
+    #[command(subcommand)]
+    Tags(tags::TagsCommand),
+
+    /// Print frames of an array.
+    Cat(cat::CatArgs),
+
+    /// Print the last frames of an array, optionally following new ones.
+    Tail(tail::TailArgs),
+
+    /// Check that a file is well formed.
+    Verify(verify::VerifyArgs),
+
+    /// Copy a file into a fresh one, dropping dead space and optionally
+    /// changing its layout.
+    Repack(repack::RepackArgs),
+
+    /// Rewrite a file in the Binary or Text format.
+    Convert(convert::ConvertArgs),
+
+    /// Write an array or dir to a NumPy, CSV or (with the arrow feature)
+    /// Parquet file.
+    Export(export::ExportArgs),
+
+    /// Read the columns of a CSV file into a dir of arrays.
+    Import(import::ImportArgs),
+}
+
+impl Cli {
+    /// Runs the subcommand, writing its output to out.
+    pub(crate) fn run(self, out: &mut dyn Write) -> tsdf::Result<()> {
+        match self.command {
+            Command::Info(args) => info::run(args, out),
+            Command::Ls(args) => ls::run(args, out),
+            Command::Tags(command) => tags::run(command, out),
+            Command::Cat(args) => cat::run(args, out),
+            Command::Tail(args) => tail::run(args, out),
+            Command::Verify(args) => verify::run(args, out),
+            Command::Repack(args) => repack::run(args, out),
+            Command::Convert(args) => convert::run(args, out),
+            Command::Export(args) => export::run(args, out),
+            Command::Import(args) => import::run(args, out),
+        }
+    }
+}
+
+/// Opens an existing file for reading, naming the file in any error.
+fn open_reader(path: &Path) -> tsdf::Result<TsdfFile> {
+    TsdfFile::new_reader(path).map_err(|err| {
+        tsdf::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+    })
+}
+
+/// Repacks a file with the given options (see TsdfFile::repack), returning
+/// the sizes of the original and the copy. If no output is given, the file
+/// is replaced by the copy.
+fn rewrite(
+    path: &Path,
+    output: Option<&Path>,
+    options: &OpenOptions,
+) -> tsdf::Result<(u64, u64)> {
+    let file = open_reader(path)?;
+    let original_size = file.get_size();
+    let Some(output) = output else {
+        // Repack into a temporary dir next to the file, so that the file is
+        // only replaced once the copy is complete, and by a rename that
+        // can't cross file systems.
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let tmp_dir = tempfile::tempdir_in(parent)?;
+        let tmp_path = tmp_dir.path().join("rewritten.tsdf");
+        let rewritten_size = file.repack(&tmp_path, options)?.get_size();
+        drop(file);
+        fs::rename(&tmp_path, path)?;
+        return Ok((original_size, rewritten_size));
+    };
+
+    let rewritten_size = file.repack(output, options)?.get_size();
+    Ok((original_size, rewritten_size))
+}
+
+/// Returns the array at the given path in the file.
+fn get_array<'f>(
+    file: &'f TsdfFile,
+    path: &str,
+) -> tsdf::Result<Array<'f, 'f>> {
+    match file.get(path)? {
+        HighLevelObject::Array(array) => Ok(array),
+        _ => Err(tsdf::Error::new(
+            tsdf::ErrorKind::InvalidInput,
+            format!("{:?} is not an array.", path),
+        )),
+    }
+}
+
+/// Turns optional --start and --end arguments, either of which may count back
+/// from the end of an array of the given length, into a range of frames that
+/// lies within the array.
+fn resolve_range(
+    start: Option<i64>,
+    end: Option<i64>,
+    len: usize,
+) -> (usize, usize) {
+    let resolve = |index: i64| match index < 0 {
+        true => len.saturating_sub(index.unsigned_abs() as usize),
+        false => (index as usize).min(len),
+    };
+    let start = start.map_or(0, resolve);
+    let end = end.map_or(len, resolve);
+    (start, end.max(start))
+}
+
+/// Writes the frames from start (inclusive) to end (exclusive) to out, one
+/// line per frame, each prefixed with its index.
+fn write_frames(
+    array: &Array,
+    start: usize,
+    end: usize,
+    out: &mut dyn Write,
+) -> tsdf::Result<()> {
+    let lines = match array.get_data_type() {
+        ArrayDataType::Int8 => format_frames::<i8>(array, start, end),
+        ArrayDataType::Int16 => format_frames::<i16>(array, start, end),
+        ArrayDataType::Int32 => format_frames::<i32>(array, start, end),
+        ArrayDataType::Int64 => format_frames::<i64>(array, start, end),
+        ArrayDataType::UInt8 => format_frames::<u8>(array, start, end),
+        ArrayDataType::UInt16 => format_frames::<u16>(array, start, end),
+        ArrayDataType::UInt32 => format_frames::<u32>(array, start, end),
+        ArrayDataType::UInt64 => format_frames::<u64>(array, start, end),
+        ArrayDataType::Float32 => format_frames::<f32>(array, start, end),
+        ArrayDataType::Float64 => format_frames::<f64>(array, start, end),
+    }?;
+    for (i, line) in lines.iter().enumerate() {
+        writeln!(out, "{}: {}", start + i, line)?;
+    }
+    Ok(())
+}
+
+/// Formats each of the frames from start to end, with elements of type T.
+fn format_frames<T: ArrayElement + Debug>(
+    array: &Array,
+    start: usize,
+    end: usize,
+) -> tsdf::Result<Vec<String>> {
+    let frame_shape = array.get_frame_shape();
+    let frame_len = frame_shape.iter().product::<u64>() as usize;
+    Ok(array
+        .get_frames::<T>(start, end)?
+        .chunks(frame_len)
+        .map(|frame| format_frame(frame, frame_shape))
+        .collect())
+}
+
+/// Formats a flattened frame as nested lists, one level per dimension of the
+/// frame's shape. Frames with no dimensions are a single value.
+fn format_frame<T: Debug>(frame: &[T], shape: &[u64]) -> String {
+    match shape.split_first() {
+        None => format!("{:?}", frame[0]),
+        Some((_, rest)) => {
+            let stride = rest.iter().product::<u64>() as usize;
+            let parts: Vec<String> = frame
+                .chunks(stride)
+                .map(|part| format_frame(part, rest))
+                .collect();
+            format!("[{}]", parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Makes a small file for the subcommands to work on. It has a dir called
+    /// detector holding a (3, 2) UInt16 array called counts, and a tag on
+    /// each of them.
+    pub(crate) fn make_file() -> (TempDir, std::path::PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cli.tsdf");
+        let file = TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        let root = file.get_root_dir().unwrap();
+        let detector = root.create_dir("detector").unwrap();
+        detector.set_tag("sample", "silicon").unwrap();
+        let counts = detector
+            .create_array("counts", ArrayDataType::UInt16, &[2], 4)
+            .unwrap();
+        counts.append_frames(&[1_u16, 2, 3, 4, 5, 6]).unwrap();
+        counts.set_tag("units", "counts").unwrap();
+        (dir, path)
+    }
+
+    /// Parses and runs a command line, returning what it printed.
+    pub(crate) fn run(args: &[&str]) -> tsdf::Result<String> {
+        let cli = Cli::try_parse_from([&["tsdf"], args].concat()).unwrap();
+        let mut out = Vec::new();
+        cli.run(&mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    /// Frames should be nested one level per dimension.
+    #[test]
+    fn test_format_frame() {
+        assert_eq!(format_frame(&[1.5], &[]), "1.5");
+        assert_eq!(format_frame(&[1, 2, 3], &[3]), "[1, 2, 3]");
+        assert_eq!(
+            format_frame(&[1, 2, 3, 4, 5, 6], &[2, 3]),
+            "[[1, 2, 3], [4, 5, 6]]"
+        );
+    }
+}
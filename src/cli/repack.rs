@@ -0,0 +1,96 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::{Args, ValueEnum};
+use tsdf::{HashAlgorithm, OpenOptions};
+
+/// The arguments of tsdf repack.
+#[derive(Args)]
+pub(crate) struct RepackArgs {
+    /// The file to repack.
+    file: PathBuf,
+
+    /// Where to write the repacked file. If this is left out, the file is
+    /// replaced once the repacked copy is complete.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Whether the repacked file has checksums.
+    #[arg(long)]
+    checksums: Option<bool>,
+
+    /// The shard capacity of the repacked file.
+    #[arg(long)]
+    shard_capacity: Option<u64>,
+
+    /// The hash algorithm of the repacked file.
+    #[arg(long, value_enum)]
+    hash_algorithm: Option<HashAlgorithmArg>,
+}
+
+/// The values that --hash-algorithm accepts.
+#[derive(Clone, Copy, ValueEnum)]
+enum HashAlgorithmArg {
+    SipHash13,
+    Fnv1a,
+}
+
+impl From<HashAlgorithmArg> for HashAlgorithm {
+    fn from(arg: HashAlgorithmArg) -> Self {
+        match arg {
+            HashAlgorithmArg::SipHash13 => HashAlgorithm::SipHash13,
+            HashAlgorithmArg::Fnv1a => HashAlgorithm::Fnv1a,
+        }
+    }
+}
+
+/// Copies a file into a fresh one. See TsdfFile::repack.
+pub(crate) fn run(args: RepackArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let mut options = OpenOptions::new();
+    if let Some(checksums) = args.checksums {
+        options.checksums(checksums);
+    }
+    if let Some(shard_capacity) = args.shard_capacity {
+        options.shard_capacity(shard_capacity);
+    }
+    if let Some(hash_algorithm) = args.hash_algorithm {
+        options.hash_algorithm(hash_algorithm.into());
+    }
+
+    let (original_size, repacked_size) =
+        super::rewrite(&args.file, args.output.as_deref(), &options)?;
+    writeln!(
+        out,
+        "Repacked {} bytes into {} bytes.",
+        original_size, repacked_size
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tsdf::{HighLevelObject, TsdfFile};
+
+    use super::super::tests::{make_file, run};
+
+    /// Repacking in place should keep the contents, and apply any layout
+    /// changes.
+    #[test]
+    fn test_repack_in_place() {
+        let (_dir, path) = make_file();
+        let path_str = path.to_str().unwrap();
+
+        run(&["repack", path_str, "--checksums", "true"]).unwrap();
+        let file = TsdfFile::new_reader(&path).unwrap();
+        assert!(file.has_checksums());
+        match file.get("/detector/counts").unwrap() {
+            HighLevelObject::Array(array) => {
+                assert_eq!(array.get_frame::<u16>(2).unwrap(), vec![5, 6])
+            }
+            _ => panic!("Expected an array."),
+        }
+        assert_eq!(
+            run(&["verify", path_str]).unwrap(),
+            format!("{}: OK\n", path_str)
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::{Args, Subcommand};
+use tsdf::{HighLevelObject, OpenOptions, TsdfFile, WriteMode};
+
+/// The subcommands of tsdf tags.
+#[derive(Subcommand)]
+pub(crate) enum TagsCommand {
+    /// Print one tag, or every tag as name = value lines.
+    Get(GetArgs),
+
+    /// Set a tag, adding it if it's new.
+    Set(SetArgs),
+}
+
+/// The arguments of tsdf tags get.
+#[derive(Args)]
+pub(crate) struct GetArgs {
+    /// The file to look in.
+    file: PathBuf,
+
+    /// The dir or array whose tags to get.
+    path: String,
+
+    /// The tag to get. Every tag is printed if this is left out.
+    name: Option<String>,
+}
+
+/// The arguments of tsdf tags set.
+#[derive(Args)]
+pub(crate) struct SetArgs {
+    /// The file to change.
+    file: PathBuf,
+
+    /// The dir or array whose tag to set.
+    path: String,
+
+    /// The name of the tag.
+    name: String,
+
+    /// The new value of the tag.
+    value: String,
+}
+
+/// Runs a tsdf tags subcommand.
+pub(crate) fn run(
+    command: TagsCommand,
+    out: &mut dyn Write,
+) -> tsdf::Result<()> {
+    match command {
+        TagsCommand::Get(args) => get(args, out),
+        TagsCommand::Set(args) => set(args),
+    }
+}
+
+/// Prints the tags of a dir or array.
+fn get(args: GetArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file = super::open_reader(&args.file)?;
+    let tags = get_tags(&file, &args.path)?;
+    match args.name {
+        Some(name) => match tags.into_iter().find(|(n, _)| *n == name) {
+            Some((_, value)) => writeln!(out, "{}", value)?,
+            None => {
+                return Err(tsdf::Error::new(
+                    tsdf::ErrorKind::NotFound,
+                    format!("{:?} has no tag {:?}.", args.path, name),
+                ))
+            }
+        },
+        None => {
+            for (name, value) in tags {
+                writeln!(out, "{} = {}", name, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Sets a tag on a dir or array. The file is opened with a locking writer,
+/// so this fails rather than racing another locking writer.
+fn set(args: SetArgs) -> tsdf::Result<()> {
+    if !args.file.exists() {
+        return Err(tsdf::Error::new(
+            tsdf::ErrorKind::NotFound,
+            format!("{} does not exist.", args.file.display()),
+        ));
+    }
+    let file = OpenOptions::new()
+        .write_mode(WriteMode::LockingWrite)
+        .open_writer(&args.file)?;
+    match file.get(&args.path)? {
+        HighLevelObject::Dir(dir) => dir.set_tag(&args.name, &args.value),
+        HighLevelObject::Array(array) => array.set_tag(&args.name, &args.value),
+        HighLevelObject::MetadataTag(_) => Err(not_taggable(&args.path)),
+    }
+}
+
+/// Returns every tag of the dir or array at the given path.
+fn get_tags(
+    file: &TsdfFile,
+    path: &str,
+) -> tsdf::Result<Vec<(String, String)>> {
+    match file.get(path)? {
+        HighLevelObject::Dir(dir) => dir.get_tags(),
+        HighLevelObject::Array(array) => array.get_tags(),
+        HighLevelObject::MetadataTag(_) => Err(not_taggable(path)),
+    }
+}
+
+/// Makes the error returned when a path is a tag, which can't have tags.
+fn not_taggable(path: &str) -> tsdf::Error {
+    tsdf::Error::new(
+        tsdf::ErrorKind::InvalidInput,
+        format!("{:?} is a metadata tag, which can't have tags.", path),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{make_file, run};
+
+    /// Tags should be settable on dirs and arrays, and readable one at a time
+    /// or all together.
+    #[test]
+    fn test_tags() {
+        let (_dir, path) = make_file();
+        let path = path.to_str().unwrap();
+
+        run(&["tags", "set", path, "/detector", "sample", "germanium"])
+            .unwrap();
+        run(&["tags", "set", path, "/detector", "gain", "2"]).unwrap();
+        assert_eq!(
+            run(&["tags", "get", path, "/detector"]).unwrap(),
+            "gain = 2\nsample = germanium\n"
+        );
+        assert_eq!(
+            run(&["tags", "get", path, "/detector/counts", "units"]).unwrap(),
+            "counts\n"
+        );
+        assert!(run(&["tags", "get", path, "/detector", "missing"]).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use std::{io::Write, path::PathBuf, thread::sleep, time::Duration};
+
+use clap::Args;
+
+/// The arguments of tsdf tail.
+#[derive(Args)]
+pub(crate) struct TailArgs {
+    /// The file to read.
+    file: PathBuf,
+
+    /// The array to print.
+    path: String,
+
+    /// The number of frames to print.
+    #[arg(short = 'n', long, default_value_t = 10)]
+    frames: usize,
+
+    /// Keep printing frames as they're appended, until interrupted.
+    #[arg(short, long)]
+    follow: bool,
+
+    /// How often to check for new frames when following, in milliseconds.
+    #[arg(long, default_value_t = 200)]
+    interval: u64,
+}
+
+/// Prints the last frames of an array. When following, the array is polled
+/// for new frames, which is safe to do while a writer appends to it.
+pub(crate) fn run(args: TailArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file = super::open_reader(&args.file)?;
+    let array = super::get_array(&file, &args.path)?;
+    let mut len = array.len();
+    super::write_frames(&array, len.saturating_sub(args.frames), len, out)?;
+
+    if !args.follow {
+        return Ok(());
+    }
+    loop {
+        out.flush()?;
+        sleep(Duration::from_millis(args.interval));
+        let new_len = array.len();
+        super::write_frames(&array, len, new_len, out)?;
+        len = new_len;
+    }
+}
@@ -0,0 +1,19 @@
+use std::{io::Write, path::PathBuf};
+
+use clap::Args;
+
+/// The arguments of tsdf verify.
+#[derive(Args)]
+pub(crate) struct VerifyArgs {
+    /// The file to check.
+    file: PathBuf,
+}
+
+/// Checks the whole file, failing with a description of the first problem
+/// found. See TsdfFile::verify.
+pub(crate) fn run(args: VerifyArgs, out: &mut dyn Write) -> tsdf::Result<()> {
+    let file = super::open_reader(&args.file)?;
+    file.verify()?;
+    writeln!(out, "{}: OK", args.file.display())?;
+    Ok(())
+}
@@ -0,0 +1,28 @@
+/// The ways that the values of several frames can be combined into one value
+/// per element, when resampling an array or taking a rolling window over it.
+/// NaNs are left out of every aggregation except First and Last, so the mean
+/// of some values and a NaN is the mean of the values. An aggregation of no
+/// values (or only NaNs) is NaN, except for Count, which is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    /// The mean of the values.
+    Mean,
+
+    /// The smallest value.
+    Min,
+
+    /// The largest value.
+    Max,
+
+    /// The sum of the values.
+    Sum,
+
+    /// The number of values.
+    Count,
+
+    /// The value in the earliest frame.
+    First,
+
+    /// The value in the latest frame.
+    Last,
+}
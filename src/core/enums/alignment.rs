@@ -0,0 +1,11 @@
+/// How an array's values are worked out at times between its frames, when
+/// aligning it onto another time base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// The latest frame taken at or before the time, as in an as-of join.
+    AsOf,
+
+    /// A straight line between the frames taken either side of the time.
+    /// There's no value before the first frame or after the last.
+    Linear,
+}
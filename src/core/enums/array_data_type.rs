@@ -1,5 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{structs::IoMetadata, traits::FixedSizeOnDisk};
+
 /// All the possible data types that can be stored in an array.
-pub(crate) enum ArrayDataType {
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayDataType {
     Int8,
     Int16,
     Int32,
@@ -11,3 +16,117 @@ pub(crate) enum ArrayDataType {
     Float32,
     Float64,
 }
+
+/// Every ArrayDataType, in the order of their binary codes.
+const ALL_DATA_TYPES: [ArrayDataType; 10] = [
+    ArrayDataType::Int8,
+    ArrayDataType::Int16,
+    ArrayDataType::Int32,
+    ArrayDataType::Int64,
+    ArrayDataType::UInt8,
+    ArrayDataType::UInt16,
+    ArrayDataType::UInt32,
+    ArrayDataType::UInt64,
+    ArrayDataType::Float32,
+    ArrayDataType::Float64,
+];
+
+impl ArrayDataType {
+    /// Returns the single byte code used to store this data type in binary
+    /// files.
+    pub(crate) fn to_code(self) -> u8 {
+        ALL_DATA_TYPES.iter().position(|t| *t == self).unwrap() as u8
+    }
+
+    /// Returns the data type with the given binary code, or None if the code
+    /// doesn't correspond to a data type.
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        ALL_DATA_TYPES.get(code as usize).copied()
+    }
+
+    /// Returns the size of a single element of this data type on disk,
+    /// according to the current IoMetadata.
+    pub(crate) fn get_size_on_disk(self, io_metadata: &IoMetadata) -> u64 {
+        match self {
+            ArrayDataType::Int8 => i8::get_size_on_disk(io_metadata),
+            ArrayDataType::Int16 => i16::get_size_on_disk(io_metadata),
+            ArrayDataType::Int32 => i32::get_size_on_disk(io_metadata),
+            ArrayDataType::Int64 => i64::get_size_on_disk(io_metadata),
+            ArrayDataType::UInt8 => u8::get_size_on_disk(io_metadata),
+            ArrayDataType::UInt16 => u16::get_size_on_disk(io_metadata),
+            ArrayDataType::UInt32 => u32::get_size_on_disk(io_metadata),
+            ArrayDataType::UInt64 => u64::get_size_on_disk(io_metadata),
+            ArrayDataType::Float32 => f32::get_size_on_disk(io_metadata),
+            ArrayDataType::Float64 => f64::get_size_on_disk(io_metadata),
+        }
+    }
+}
+
+/// Runs the body with `$t` set to the Rust type that corresponds to the given
+/// ArrayDataType. This is how code that only knows the data type at runtime,
+/// like language bindings, calls the generic array methods.
+macro_rules! with_element_type {
+    ($data_type:expr, $t:ident => $body:expr) => {
+        match $data_type {
+            $crate::core::enums::ArrayDataType::Int8 => {
+                type $t = i8;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::Int16 => {
+                type $t = i16;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::Int32 => {
+                type $t = i32;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::Int64 => {
+                type $t = i64;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::UInt8 => {
+                type $t = u8;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::UInt16 => {
+                type $t = u16;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::UInt32 => {
+                type $t = u32;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::UInt64 => {
+                type $t = u64;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::Float32 => {
+                type $t = f32;
+                $body
+            }
+            $crate::core::enums::ArrayDataType::Float64 => {
+                type $t = f64;
+                $body
+            }
+        }
+    };
+}
+
+pub(crate) use with_element_type;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Make sure that every data type survives a round trip through its code.
+    #[test]
+    fn test_code_round_trip() {
+        for data_type in ALL_DATA_TYPES {
+            assert_eq!(
+                ArrayDataType::from_code(data_type.to_code()),
+                Some(data_type)
+            );
+        }
+        assert_eq!(ArrayDataType::from_code(200), None);
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// The clocks that a frame's timestamp can come from. Every clock counts
+/// nanoseconds since the Unix epoch, but they differ in how closely they agree
+/// with each other, so each timestamp records which clock it was read from.
+#[derive(
+    Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq,
+)]
+pub enum ClockSource {
+    /// The clock isn't known.
+    #[default]
+    Unknown,
+
+    /// The operating system's wall clock, as returned by SystemTime::now.
+    System,
+
+    /// A clock disciplined by the network time protocol.
+    Ntp,
+
+    /// A clock disciplined by the precision time protocol.
+    Ptp,
+
+    /// A clock disciplined by a GPS receiver.
+    Gps,
+}
+
+/// Every ClockSource, in the order of their binary codes.
+const ALL_CLOCK_SOURCES: [ClockSource; 5] = [
+    ClockSource::Unknown,
+    ClockSource::System,
+    ClockSource::Ntp,
+    ClockSource::Ptp,
+    ClockSource::Gps,
+];
+
+impl ClockSource {
+    /// Returns the single byte code used to store this clock source in binary
+    /// files.
+    pub(crate) fn to_code(self) -> u8 {
+        ALL_CLOCK_SOURCES.iter().position(|c| *c == self).unwrap() as u8
+    }
+
+    /// Returns the clock source with the given binary code, or None if the
+    /// code doesn't correspond to a clock source.
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        ALL_CLOCK_SOURCES.get(code as usize).copied()
+    }
+}
@@ -0,0 +1,31 @@
+/// The different ways that the CrashHarness can simulate a crash. Each mode
+/// describes which of the writes issued before the crash made it to storage.
+// Only the CrashHarness and its reports use the crash modes.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CrashMode {
+    /// The first n writes made it to storage in full, and nothing after them
+    /// did. This is what we'd see if the process was killed between two
+    /// writes.
+    Prefix,
+
+    /// The first n writes made it to storage, and only part of write n + 1
+    /// did. Both the start and the end of the torn write are tried, as storage
+    /// is free to persist a multi-byte write in any order.
+    TornWrite,
+
+    /// Some later write made it to storage, but an earlier write that hadn't
+    /// been synced yet did not. This is what we'd see after a power cut, when
+    /// the OS was free to flush dirty pages in any order.
+    Reordered,
+}
+
+#[allow(dead_code)]
+impl CrashMode {
+    /// Every crash mode that the CrashHarness knows how to simulate.
+    pub(crate) const ALL: [CrashMode; 3] = [
+        CrashMode::Prefix,
+        CrashMode::TornWrite,
+        CrashMode::Reordered,
+    ];
+}
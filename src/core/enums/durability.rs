@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+/// How hard a writer works to get changes onto the disk. Readers see changes
+/// as soon as they're written either way; durability is only about what
+/// survives a crash of the whole machine.
+#[derive(
+    Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq,
+)]
+pub enum Durability {
+    /// Leave it to the operating system to decide when changes reach the
+    /// disk. This is fast, but a power cut can lose recent changes.
+    #[default]
+    Buffered,
+
+    /// Sync the file to disk before every append, new object and tag change
+    /// returns.
+    Synced,
+}
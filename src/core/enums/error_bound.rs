@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// How far the values read back from an array with a Quantize filter may be
+/// from the values that were appended to it. The bound is recorded in the
+/// array's header, and returned by Array::get_error_bound.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ErrorBound {
+    /// Every value is read back within this distance of the value appended.
+    Absolute(f64),
+
+    /// Every value is read back within this fraction of the range (max - min)
+    /// of the values in its chunk, ignoring NaNs and infinities. The distance
+    /// differs from chunk to chunk, and Array::get_absolute_error reports it.
+    /// A chunk whose finite values are all the same has a range of 0, so its
+    /// values are stored exactly.
+    Relative(f64),
+}
+
+impl ErrorBound {
+    /// Returns the distance or fraction given by the bound.
+    pub fn get_value(&self) -> f64 {
+        match *self {
+            ErrorBound::Absolute(value) | ErrorBound::Relative(value) => value,
+        }
+    }
+
+    /// Returns whether the bound is positive and finite.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.get_value() > 0.0 && self.get_value().is_finite()
+    }
+
+    /// Returns the distance that values spanning min..max may be read back
+    /// within.
+    pub(crate) fn get_absolute(&self, min: f64, max: f64) -> f64 {
+        match *self {
+            ErrorBound::Absolute(value) => value,
+            ErrorBound::Relative(fraction) => match min <= max {
+                true => fraction * (max - min),
+                false => 0.0,
+            },
+        }
+    }
+}
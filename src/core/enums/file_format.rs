@@ -2,8 +2,9 @@ use serde::{Deserialize, Serialize};
 
 /// Enum to specify the file format of a file. Please note that, for all
 /// production use cases, the file format should be set to `Binary`. The `Text`
-/// mode is only for debugging and development.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// mode is only for debugging and development. A file can be rewritten in the
+/// other format with TsdfFile::convert.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum FileFormat {
     /// Write the file in binary mode. This is the recommended mode for all
     /// production use cases.
@@ -0,0 +1,783 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::structs::{BitReader, BitWriter};
+
+use super::{ArrayDataType, ErrorBound};
+
+/// The filters that can be applied to the sealed chunks of an array, as a
+/// pipeline recorded in the array's header. Shuffle and Delta rearrange the
+/// bytes so that they compress better, and Lz4 and Zstd compress them. A
+/// typical pipeline is Delta, Shuffle, Zstd, or Gorilla, Zstd for slowly
+/// varying floats. Quantize is the only lossy filter.
+///
+/// Filters work on the elements of a chunk's frames, which are laid out one
+/// after another, followed by the frames' timestamps and checksums if they
+/// have them. The chunk that's still being appended to is never filtered.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Filter {
+    /// Groups the first byte of every element together, then the second byte
+    /// of every element and so on. Neighbouring values usually share their
+    /// high bytes, so this makes long runs for the compressors.
+    Shuffle,
+
+    /// Replaces every byte of every element with its difference from the same
+    /// byte of the element before it, so slowly changing values become runs
+    /// of small numbers.
+    Delta,
+
+    /// LZ4 compression, which is very fast.
+    Lz4,
+
+    /// Zstandard compression, which is smaller but slower than LZ4.
+    Zstd,
+
+    /// Gorilla encoding, for arrays of scalar Float32 or Float64 frames. Each
+    /// value is stored as its XOR with the value before it, which is all
+    /// zeros for a repeated value and mostly zeros for a slowly varying one,
+    /// and each timestamp as the change in the time between frames, which is
+    /// usually zero. It must be the first filter, and it leaves the chunks of
+    /// Text files as they are.
+    Gorilla,
+
+    /// Lossy compression for arrays of Float32 or Float64 frames, such as
+    /// detector images. Each value is rounded to the nearest multiple of
+    /// twice the error bound, and the multiples are entropy coded with Zstd.
+    /// Values that can't be rounded within the bound, like NaNs, are stored
+    /// exactly. It must be the first filter, and it leaves the chunks of Text
+    /// files as they are.
+    Quantize(ErrorBound),
+}
+
+/// What filters need to know about the bytes of a full chunk: its elements,
+/// one after another, followed by its frames' timestamps and then anything
+/// else.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ChunkLayout {
+    /// The size of each element on disk.
+    elem_size: usize,
+
+    /// The number of elements.
+    num_elems: usize,
+
+    /// The size of each timestamp on disk.
+    timestamp_size: usize,
+
+    /// The number of timestamps, which is 0 if the frames don't have them.
+    num_timestamps: usize,
+
+    /// Whether the elements and timestamps are in the Binary format.
+    is_binary: bool,
+}
+
+impl ChunkLayout {
+    /// Constructs a new ChunkLayout.
+    pub(crate) fn new(
+        elem_size: usize,
+        num_elems: usize,
+        timestamp_size: usize,
+        num_timestamps: usize,
+        is_binary: bool,
+    ) -> Self {
+        Self {
+            elem_size,
+            num_elems,
+            timestamp_size,
+            num_timestamps,
+            is_binary,
+        }
+    }
+}
+
+/// The most filters that an array can have.
+pub(crate) const MAX_FILTERS: usize = 4;
+
+/// The level that chunks are compressed at by the Zstd filter.
+const ZSTD_LEVEL: i32 = 3;
+
+/// The level that chunks are compressed at by the Quantize filter.
+const QUANTIZE_ZSTD_LEVEL: i32 = 9;
+
+impl Filter {
+    /// Returns the single byte code used to store this filter in binary
+    /// files. Codes start at 1, as 0 means no filter. The value of a Quantize
+    /// filter's bound is stored separately.
+    pub(crate) fn to_code(self) -> u8 {
+        match self {
+            Filter::Shuffle => 1,
+            Filter::Delta => 2,
+            Filter::Lz4 => 3,
+            Filter::Zstd => 4,
+            Filter::Gorilla => 5,
+            Filter::Quantize(ErrorBound::Absolute(_)) => 6,
+            Filter::Quantize(ErrorBound::Relative(_)) => 7,
+        }
+    }
+
+    /// Returns the filter with the given code and parameter, or None if the
+    /// code isn't valid.
+    pub(crate) fn from_code(code: u8, param: f64) -> Option<Self> {
+        match code {
+            1 => Some(Filter::Shuffle),
+            2 => Some(Filter::Delta),
+            3 => Some(Filter::Lz4),
+            4 => Some(Filter::Zstd),
+            5 => Some(Filter::Gorilla),
+            6 => Some(Filter::Quantize(ErrorBound::Absolute(param))),
+            7 => Some(Filter::Quantize(ErrorBound::Relative(param))),
+            _ => None,
+        }
+    }
+
+    /// Returns the error bound of a lossy filter, or None if the filter is
+    /// lossless.
+    pub(crate) fn get_error_bound(self) -> Option<ErrorBound> {
+        match self {
+            Filter::Quantize(bound) => Some(bound),
+            _ => None,
+        }
+    }
+
+    /// Applies the filter to the bytes of a chunk with the given layout.
+    /// Filters other than Gorilla only care about the size of the elements,
+    /// and treat any bytes after the last whole element as left over.
+    fn encode(
+        self,
+        bytes: Vec<u8>,
+        layout: &ChunkLayout,
+    ) -> io::Result<Vec<u8>> {
+        let elem_size = layout.elem_size;
+        Ok(match self {
+            Filter::Shuffle => shuffle(&bytes, elem_size, false),
+            Filter::Delta => delta(bytes, elem_size, false),
+            Filter::Lz4 => lz4_flex::compress_prepend_size(&bytes),
+            Filter::Zstd => zstd::bulk::compress(&bytes, ZSTD_LEVEL)?,
+            Filter::Gorilla => gorilla_encode(bytes, layout),
+            Filter::Quantize(bound) => quantize(bytes, layout, bound)?,
+        })
+    }
+
+    /// Undoes encode.
+    fn decode(
+        self,
+        bytes: Vec<u8>,
+        layout: &ChunkLayout,
+    ) -> io::Result<Vec<u8>> {
+        let invalid_data =
+            |err| io::Error::new(io::ErrorKind::InvalidData, err);
+        let elem_size = layout.elem_size;
+        Ok(match self {
+            Filter::Shuffle => shuffle(&bytes, elem_size, true),
+            Filter::Delta => delta(bytes, elem_size, true),
+            Filter::Lz4 => lz4_flex::decompress_size_prepended(&bytes)
+                .map_err(|err| invalid_data(err.to_string()))?,
+            Filter::Zstd => zstd::decode_all(bytes.as_slice())
+                .map_err(|err| invalid_data(err.to_string()))?,
+            Filter::Gorilla => gorilla_decode(bytes, layout)
+                .ok_or_else(|| invalid_data("Invalid Gorilla data.".into()))?,
+            Filter::Quantize(_) => {
+                dequantize(bytes, layout).ok_or_else(|| {
+                    invalid_data("Invalid quantized data.".into())
+                })?
+            }
+        })
+    }
+}
+
+/// Returns an error if the filters can't be used by an array with the given
+/// data type and frame shape.
+pub(crate) fn check_filters(
+    filters: &[Filter],
+    data_type: ArrayDataType,
+    frame_shape: &[u64],
+) -> io::Result<()> {
+    let invalid_input =
+        |msg: &str| Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+    if filters.len() > MAX_FILTERS {
+        return invalid_input("Arrays can have at most four filters.");
+    }
+    let is_float =
+        matches!(data_type, ArrayDataType::Float32 | ArrayDataType::Float64);
+    for (i, filter) in filters.iter().enumerate() {
+        match filter {
+            Filter::Gorilla | Filter::Quantize(_) if i > 0 => {
+                return invalid_input(
+                    "Gorilla and Quantize must be the first filter.",
+                )
+            }
+            Filter::Gorilla if !is_float || !frame_shape.is_empty() => {
+                return invalid_input(
+                    "Gorilla needs an array of scalar Float32 or Float64 \
+                     frames.",
+                )
+            }
+            Filter::Quantize(_) if !is_float => {
+                return invalid_input(
+                    "Quantize needs an array of Float32 or Float64 frames.",
+                )
+            }
+            Filter::Quantize(bound) if !bound.is_valid() => {
+                return invalid_input("Error bounds must be positive.")
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Passes the bytes of a chunk with the given layout through each filter in
+/// turn.
+pub(crate) fn encode_filters(
+    filters: &[Filter],
+    bytes: Vec<u8>,
+    layout: &ChunkLayout,
+) -> io::Result<Vec<u8>> {
+    filters
+        .iter()
+        .try_fold(bytes, |bytes, filter| filter.encode(bytes, layout))
+}
+
+/// Undoes encode_filters, passing the bytes back through the filters in
+/// reverse.
+pub(crate) fn decode_filters(
+    filters: &[Filter],
+    bytes: Vec<u8>,
+    layout: &ChunkLayout,
+) -> io::Result<Vec<u8>> {
+    filters
+        .iter()
+        .rev()
+        .try_fold(bytes, |bytes, filter| filter.decode(bytes, layout))
+}
+
+/// Returns how far the values of a chunk with the given layout, encoded by
+/// the filters, may be read back from the values that were encoded. This is
+/// half the step that a Quantize filter rounded them to, or 0 if the chunk's
+/// values are exact.
+pub(crate) fn decode_error_bound(
+    filters: &[Filter],
+    bytes: Vec<u8>,
+    layout: &ChunkLayout,
+) -> io::Result<f64> {
+    match filters.first() {
+        Some(Filter::Quantize(_)) if layout.is_binary => {
+            let bytes = decode_filters(&filters[1..], bytes, layout)?;
+            let step = bytes.get(..8).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid quantized data.",
+                )
+            })?;
+            Ok(f64::from_le_bytes(step.try_into().unwrap()) / 2.0)
+        }
+        _ => Ok(0.0),
+    }
+}
+
+/// Transposes the whole elements in the bytes, so that byte j of element i
+/// moves to position j * num_elems + i, or moves them back if unshuffle is
+/// set. Left over bytes stay at the end.
+fn shuffle(bytes: &[u8], elem_size: usize, unshuffle: bool) -> Vec<u8> {
+    let num_elems = bytes.len() / elem_size.max(1);
+    let mut out = bytes.to_vec();
+    for i in 0..num_elems {
+        for j in 0..elem_size {
+            let (from, to) = (i * elem_size + j, j * num_elems + i);
+            match unshuffle {
+                false => out[to] = bytes[from],
+                true => out[from] = bytes[to],
+            }
+        }
+    }
+    out
+}
+
+/// Replaces every byte from elem_size on with its difference from the byte
+/// elem_size before it, or adds the differences back up if undo is set.
+fn delta(mut bytes: Vec<u8>, elem_size: usize, undo: bool) -> Vec<u8> {
+    let elem_size = elem_size.max(1);
+    match undo {
+        false => {
+            for i in (elem_size..bytes.len()).rev() {
+                bytes[i] = bytes[i].wrapping_sub(bytes[i - elem_size]);
+            }
+        }
+        true => {
+            for i in elem_size..bytes.len() {
+                bytes[i] = bytes[i].wrapping_add(bytes[i - elem_size]);
+            }
+        }
+    }
+    bytes
+}
+
+/// Gorilla encodes a chunk of Binary bytes with the given layout: the
+/// elements' XORs, then the timestamps' deltas of deltas, each packed into
+/// bits and padded to a whole byte, then the rest of each timestamp (its
+/// clock source) and then everything after the timestamps as it is.
+fn gorilla_encode(bytes: Vec<u8>, layout: &ChunkLayout) -> Vec<u8> {
+    if !layout.is_binary {
+        return bytes;
+    }
+    let elems_len = layout.elem_size * layout.num_elems;
+    let (elems, rest) = bytes.split_at(elems_len);
+    let (timestamps, rest) =
+        rest.split_at(layout.timestamp_size * layout.num_timestamps);
+
+    // Each XOR is written as a 0 bit if it's zero. Otherwise it's written as
+    // a 1 bit, then a 0 bit and its meaningful bits if they fit within the
+    // last XOR's, or a 1 bit, the number of leading zeros (5 bits), the
+    // number of meaningful bits minus one (6 bits) and the meaningful bits.
+    let num_bits = 8 * layout.elem_size as u32;
+    let mut writer = BitWriter::new();
+    let mut prev = 0;
+    let mut window: Option<(u32, u32)> = None;
+    for elem in elems.chunks(layout.elem_size) {
+        let mut word = [0; 8];
+        word[..elem.len()].copy_from_slice(elem);
+        let value = u64::from_le_bytes(word);
+        let xor = value ^ prev;
+        prev = value;
+        writer.write_bit(xor != 0);
+        if xor == 0 {
+            continue;
+        }
+
+        let leading = (xor.leading_zeros() - (64 - num_bits)).min(31);
+        let trailing = xor.trailing_zeros();
+        match window {
+            Some((lead, len))
+                if leading >= lead && trailing >= num_bits - lead - len =>
+            {
+                writer.write_bit(false);
+                writer.write(xor >> (num_bits - lead - len), len);
+            }
+            _ => {
+                let len = num_bits - leading - trailing;
+                writer.write_bit(true);
+                writer.write(leading as u64, 5);
+                writer.write(len as u64 - 1, 6);
+                writer.write(xor >> trailing, len);
+                window = Some((leading, len));
+            }
+        }
+    }
+    let mut encoded = writer.into_bytes();
+
+    // Each delta of delta is zigzag encoded, then written as a 0 bit if it's
+    // zero, or as a prefix that gives its size and then its bits.
+    let mut writer = BitWriter::new();
+    let mut clock_sources = Vec::new();
+    let (mut prev_nanos, mut prev_delta) = (0_i64, 0_i64);
+    for timestamp in timestamps.chunks(layout.timestamp_size.max(1)) {
+        let nanos = i64::from_le_bytes(timestamp[..8].try_into().unwrap());
+        clock_sources.extend_from_slice(&timestamp[8..]);
+        let delta = nanos.wrapping_sub(prev_nanos);
+        let delta_of_delta = delta.wrapping_sub(prev_delta);
+        (prev_nanos, prev_delta) = (nanos, delta);
+
+        let zigzag = ((delta_of_delta << 1) ^ (delta_of_delta >> 63)) as u64;
+        match DOD_SIZES.iter().position(|size| zigzag >> size == 0) {
+            Some(i) => {
+                writer.write(u64::MAX, i as u32);
+                writer.write_bit(false);
+                writer.write(zigzag, DOD_SIZES[i]);
+            }
+            None => {
+                writer.write(u64::MAX, DOD_SIZES.len() as u32);
+                writer.write(zigzag, 64);
+            }
+        }
+    }
+    encoded.extend(writer.into_bytes());
+    encoded.extend(clock_sources);
+    encoded.extend_from_slice(rest);
+    encoded
+}
+
+/// The number of bits written for a zigzag encoded delta of delta after a
+/// prefix of i 1 bits and a 0 bit. Any larger one is written in 64 bits,
+/// after a prefix of 1 bits alone.
+const DOD_SIZES: [u32; 4] = [0, 7, 9, 12];
+
+/// Undoes gorilla_encode, returning None if the bytes aren't valid.
+fn gorilla_decode(bytes: Vec<u8>, layout: &ChunkLayout) -> Option<Vec<u8>> {
+    if !layout.is_binary {
+        return Some(bytes);
+    }
+    let num_bits = 8 * layout.elem_size as u32;
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut reader = BitReader::new(&bytes);
+    let mut value = 0;
+    let mut window = None;
+    for _ in 0..layout.num_elems {
+        if reader.read_bit()? {
+            if reader.read_bit()? {
+                let lead = reader.read(5)? as u32;
+                let len = reader.read(6)? as u32 + 1;
+                window = Some((lead, len)).filter(|_| lead + len <= num_bits);
+            }
+            let (lead, len) = window?;
+            value ^= reader.read(len)? << (num_bits - lead - len);
+        }
+        decoded.extend_from_slice(&value.to_le_bytes()[..layout.elem_size]);
+    }
+    let mut pos = reader.get_bytes_read();
+
+    let mut reader = BitReader::new(bytes.get(pos..)?);
+    let mut nanos = Vec::with_capacity(layout.num_timestamps);
+    let (mut prev_nanos, mut prev_delta) = (0_i64, 0_i64);
+    for _ in 0..layout.num_timestamps {
+        let mut size = 64;
+        for dod_size in DOD_SIZES {
+            if !reader.read_bit()? {
+                size = dod_size;
+                break;
+            }
+        }
+        let zigzag = reader.read(size)?;
+        let delta_of_delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+        prev_delta = prev_delta.wrapping_add(delta_of_delta);
+        prev_nanos = prev_nanos.wrapping_add(prev_delta);
+        nanos.push(prev_nanos);
+    }
+    pos += reader.get_bytes_read();
+
+    let clock_size = layout.timestamp_size.saturating_sub(8);
+    let clocks_end = pos + clock_size * layout.num_timestamps;
+    let clock_sources = bytes.get(pos..clocks_end)?;
+    for (i, nanos) in nanos.iter().enumerate() {
+        decoded.extend_from_slice(&nanos.to_le_bytes());
+        let clock_source = i * clock_size..(i + 1) * clock_size;
+        decoded.extend_from_slice(&clock_sources[clock_source]);
+    }
+    decoded.extend_from_slice(&bytes[clocks_end..]);
+    Some(decoded)
+}
+
+/// Quantizes the elements of a chunk of Binary bytes with the given layout,
+/// which must be Float32s or Float64s. The result is the step between
+/// quantized values (f64), followed by the Zstd compressed codes of the
+/// elements and then everything after the elements as it is. The step is
+/// twice the bound's distance for the chunk, which is 0 for a relative bound
+/// on a chunk whose finite values are all the same, so none can be rounded
+/// and they're all stored as they are.
+///
+/// Each element that's within the bound of a multiple of the step is stored
+/// as the change in the multiple since the last such element, zigzag encoded
+/// plus one as a LEB128 varint. Any other element is stored as a 0 byte and
+/// then its bytes.
+fn quantize(
+    bytes: Vec<u8>,
+    layout: &ChunkLayout,
+    bound: ErrorBound,
+) -> io::Result<Vec<u8>> {
+    if !layout.is_binary {
+        return Ok(bytes);
+    }
+    let (elems, rest) = bytes.split_at(layout.elem_size * layout.num_elems);
+    let values: Vec<f64> =
+        elems.chunks(layout.elem_size).map(read_float).collect();
+    let finite = values.iter().filter(|value| value.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, |a, b| a.min(*b));
+    let max = finite.fold(f64::NEG_INFINITY, |a, b| a.max(*b));
+    let absolute = bound.get_absolute(min, max);
+    let step = 2.0 * absolute;
+
+    // Multiples up to 2^53 are exact, and their changes fit in a u64 varint.
+    let mut codes = Vec::with_capacity(elems.len() / 2);
+    let mut prev = 0_i64;
+    for (value, elem) in values.iter().zip(elems.chunks(layout.elem_size)) {
+        let multiple = (value / step).round();
+        let restored = to_float(multiple * step, layout.elem_size);
+        if multiple.abs() < (1_u64 << 53) as f64
+            && (restored - value).abs() <= absolute
+        {
+            let change = (multiple as i64).wrapping_sub(prev);
+            write_varint(
+                &mut codes,
+                ((change << 1) ^ (change >> 63)) as u64 + 1,
+            );
+            prev = multiple as i64;
+        } else {
+            codes.push(0);
+            codes.extend_from_slice(elem);
+        }
+    }
+    codes.extend_from_slice(rest);
+
+    let mut quantized = step.to_le_bytes().to_vec();
+    quantized.extend(zstd::bulk::compress(&codes, QUANTIZE_ZSTD_LEVEL)?);
+    Ok(quantized)
+}
+
+/// Undoes quantize, within its bound, returning None if the bytes aren't
+/// valid.
+fn dequantize(bytes: Vec<u8>, layout: &ChunkLayout) -> Option<Vec<u8>> {
+    if !layout.is_binary {
+        return Some(bytes);
+    }
+    let step = f64::from_le_bytes(bytes.get(..8)?.try_into().ok()?);
+    let codes = zstd::decode_all(&bytes[8..]).ok()?;
+
+    let mut dequantized = Vec::with_capacity(codes.len() * 2);
+    let mut pos = 0;
+    let mut multiple = 0_i64;
+    for _ in 0..layout.num_elems {
+        match read_varint(&codes, &mut pos)? {
+            0 => {
+                let elem = codes.get(pos..pos + layout.elem_size)?;
+                dequantized.extend_from_slice(elem);
+                pos += layout.elem_size;
+            }
+            code => {
+                let zigzag = code - 1;
+                let change = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                multiple = multiple.wrapping_add(change);
+                let value = to_float(multiple as f64 * step, layout.elem_size);
+                write_float(&mut dequantized, value, layout.elem_size);
+            }
+        }
+    }
+    dequantized.extend_from_slice(&codes[pos..]);
+    Some(dequantized)
+}
+
+/// Reads a little-endian Float32 or Float64, depending on its size.
+fn read_float(bytes: &[u8]) -> f64 {
+    match bytes.len() {
+        4 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+        _ => f64::from_le_bytes(bytes.try_into().unwrap()),
+    }
+}
+
+/// Rounds the value to the nearest Float32 if elem_size is 4, so that it's
+/// the value that will be read back.
+fn to_float(value: f64, elem_size: usize) -> f64 {
+    match elem_size {
+        4 => value as f32 as f64,
+        _ => value,
+    }
+}
+
+/// Writes a little-endian Float32 or Float64, depending on elem_size.
+fn write_float(bytes: &mut Vec<u8>, value: f64, elem_size: usize) {
+    match elem_size {
+        4 => bytes.extend_from_slice(&(value as f32).to_le_bytes()),
+        _ => bytes.extend_from_slice(&value.to_le_bytes()),
+    }
+}
+
+/// Writes the value as a LEB128 varint: 7 bits at a time, least significant
+/// first, with the top bit of each byte set if more follow.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads a LEB128 varint starting at pos, moving pos past it. Returns None if
+/// the bytes run out or the varint is too long.
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte < 0x80 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every pipeline should give back exactly the bytes that went in,
+    /// including any bytes left over after the last whole element.
+    #[test]
+    fn test_filters_round_trip() {
+        let bytes: Vec<u8> = (0..1001_u32).map(|i| (i / 7) as u8).collect();
+        let layout = ChunkLayout::new(4, 250, 0, 0, true);
+        let pipelines: [&[Filter]; 4] = [
+            &[],
+            &[Filter::Shuffle, Filter::Lz4],
+            &[Filter::Delta, Filter::Shuffle, Filter::Zstd],
+            &[Filter::Delta, Filter::Delta, Filter::Shuffle, Filter::Lz4],
+        ];
+        for filters in pipelines {
+            let encoded = encode_filters(filters, bytes.clone(), &layout);
+            let decoded = decode_filters(filters, encoded.unwrap(), &layout);
+            assert_eq!(decoded.unwrap(), bytes);
+        }
+
+        let compressed =
+            encode_filters(&[Filter::Zstd], bytes.clone(), &layout);
+        assert!(compressed.unwrap().len() < bytes.len() / 4);
+        assert!(decode_filters(&[Filter::Lz4], vec![9; 8], &layout).is_err());
+
+        let bound = ErrorBound::Relative(0.01);
+        let all_filters = [
+            Filter::Shuffle,
+            Filter::Delta,
+            Filter::Lz4,
+            Filter::Zstd,
+            Filter::Gorilla,
+            Filter::Quantize(ErrorBound::Absolute(0.5)),
+            Filter::Quantize(bound),
+        ];
+        for filter in all_filters {
+            let bound = filter.get_error_bound();
+            let param = bound.map_or(0.0, |bound| bound.get_value());
+            let code = filter.to_code();
+            assert_eq!(Filter::from_code(code, param), Some(filter));
+        }
+        assert_eq!(Filter::from_code(0, 0.0), None);
+    }
+
+    /// Gorilla should shrink a slowly varying series with steady timestamps
+    /// several times over, and give back every bit of any series, including
+    /// NaNs, jumps and timestamps that wrap around.
+    #[test]
+    fn test_gorilla() {
+        // A sensor reading to a tenth of a degree every millisecond, with the
+        // odd late timestamp, followed by a clock source and a checksum.
+        let mut bytes = Vec::new();
+        for i in 0..1000 {
+            let value = 20.0 + ((i / 50) % 7) as f64 / 10.0;
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for i in 0..1000_i64 {
+            let nanos = 1_700_000_000_000_000_000 + i * 1_000_000;
+            let jitter = if i % 100 == 0 { 1_234 } else { 0 };
+            bytes.extend_from_slice(&(nanos + jitter).to_le_bytes());
+            bytes.push(2);
+        }
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+        let layout = ChunkLayout::new(8, 1000, 9, 1000, true);
+        let encoded =
+            encode_filters(&[Filter::Gorilla], bytes.clone(), &layout).unwrap();
+        assert!(encoded.len() * 10 < bytes.len());
+        let decoded = decode_filters(&[Filter::Gorilla], encoded, &layout);
+        assert_eq!(decoded.unwrap(), bytes);
+
+        // Awkward Float32s, without timestamps.
+        let values = [0.0, -0.0, f32::NAN, f32::MAX, f32::MIN_POSITIVE, 1.0];
+        let bytes: Vec<u8> =
+            values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let layout = ChunkLayout::new(4, values.len(), 0, 0, true);
+        let encoded =
+            encode_filters(&[Filter::Gorilla], bytes.clone(), &layout).unwrap();
+        let decoded = decode_filters(&[Filter::Gorilla], encoded, &layout);
+        assert_eq!(decoded.unwrap(), bytes);
+
+        // Timestamps at the extremes.
+        let mut bytes = 1.5_f64.to_le_bytes().repeat(3);
+        for nanos in [i64::MAX, i64::MIN, 0] {
+            bytes.extend_from_slice(&nanos.to_le_bytes());
+            bytes.push(0);
+        }
+        let layout = ChunkLayout::new(8, 3, 9, 3, true);
+        let encoded =
+            encode_filters(&[Filter::Gorilla], bytes.clone(), &layout).unwrap();
+        let decoded = decode_filters(&[Filter::Gorilla], encoded, &layout);
+        assert_eq!(decoded.unwrap(), bytes);
+        assert!(
+            decode_filters(&[Filter::Gorilla], vec![0xFF], &layout).is_err()
+        );
+
+        // Gorilla must come first, and only suits scalar floats.
+        let gorilla_zstd = [Filter::Gorilla, Filter::Zstd];
+        assert!(
+            check_filters(&gorilla_zstd, ArrayDataType::Float32, &[]).is_ok()
+        );
+        let zstd_gorilla = [Filter::Zstd, Filter::Gorilla];
+        assert!(
+            check_filters(&zstd_gorilla, ArrayDataType::Float32, &[]).is_err()
+        );
+        assert!(
+            check_filters(&gorilla_zstd, ArrayDataType::Int32, &[]).is_err()
+        );
+        assert!(
+            check_filters(&gorilla_zstd, ArrayDataType::Float64, &[2]).is_err()
+        );
+    }
+
+    /// Quantize should read back every value within its bound, keep values it
+    /// can't round exactly, shrink a smooth image several times over and
+    /// leave everything after the elements alone.
+    #[test]
+    fn test_quantize() {
+        // A smooth 64x64 Float32 image, then a checksum.
+        let image: Vec<f32> = (0..64 * 64)
+            .map(|i| ((i % 64) as f32 / 9.0).sin() * 100.0 + (i / 64) as f32)
+            .collect();
+        let mut bytes: Vec<u8> =
+            image.iter().flat_map(|v| v.to_le_bytes()).collect();
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+        let layout = ChunkLayout::new(4, image.len(), 0, 0, true);
+        for bound in [ErrorBound::Absolute(0.05), ErrorBound::Relative(0.001)] {
+            let filters = [Filter::Quantize(bound)];
+            let encoded =
+                encode_filters(&filters, bytes.clone(), &layout).unwrap();
+            assert!(encoded.len() * 3 < bytes.len());
+            let decoded = decode_filters(&filters, encoded, &layout).unwrap();
+            assert_eq!(decoded.len(), bytes.len());
+            assert_eq!(decoded[decoded.len() - 4..], [1, 2, 3, 4]);
+
+            let min = image.iter().fold(f32::INFINITY, |a, b| a.min(*b));
+            let max = image.iter().fold(f32::NEG_INFINITY, |a, b| a.max(*b));
+            let absolute = bound.get_absolute(min as f64, max as f64);
+            for (value, elem) in image.iter().zip(decoded.chunks(4)) {
+                let restored = f32::from_le_bytes(elem.try_into().unwrap());
+                assert!(((restored - value).abs() as f64) <= absolute);
+            }
+        }
+
+        // Values that can't be rounded come back exactly.
+        let values = [f64::NAN, f64::INFINITY, 1.0e300, -0.3, 2.0];
+        let bytes: Vec<u8> =
+            values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let layout = ChunkLayout::new(8, values.len(), 0, 0, true);
+        let filters = [Filter::Quantize(ErrorBound::Absolute(0.1))];
+        let encoded = encode_filters(&filters, bytes.clone(), &layout).unwrap();
+        let decoded = decode_filters(&filters, encoded.clone(), &layout);
+        let decoded = decoded.unwrap();
+        assert_eq!(decoded[..24], bytes[..24]);
+        let last = f64::from_le_bytes(decoded[32..].try_into().unwrap());
+        assert!((last - 2.0).abs() <= 0.1);
+        assert!(decode_filters(&filters, vec![0; 12], &layout).is_err());
+
+        let error = decode_error_bound(&filters, encoded, &layout).unwrap();
+        assert_eq!(error, 0.1);
+
+        // A relative bound on a constant chunk has to be exact.
+        let filters =
+            [Filter::Quantize(ErrorBound::Relative(0.1)), Filter::Shuffle];
+        let bytes = 7.5_f64.to_le_bytes().repeat(5);
+        let encoded = encode_filters(&filters, bytes.clone(), &layout).unwrap();
+        let decoded = decode_filters(&filters, encoded.clone(), &layout);
+        assert_eq!(decoded.unwrap(), bytes);
+        let error = decode_error_bound(&filters, encoded, &layout).unwrap();
+        assert_eq!(error, 0.0);
+
+        // Quantize must come first, and only suits floats with a positive,
+        // finite bound.
+        let check = |filters: &[Filter], data_type| {
+            check_filters(filters, data_type, &[2, 2]).is_ok()
+        };
+        let quantize = Filter::Quantize(ErrorBound::Absolute(0.1));
+        assert!(check(&[quantize, Filter::Zstd], ArrayDataType::Float32));
+        assert!(!check(&[Filter::Zstd, quantize], ArrayDataType::Float32));
+        assert!(!check(&[quantize], ArrayDataType::Int16));
+        for bad in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let filters = [Filter::Quantize(ErrorBound::Relative(bad))];
+            assert!(!check(&filters, ArrayDataType::Float64));
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// The hash functions that can be used to place keys in distributed
+/// dictionaries. The algorithm is chosen when a file is created, and stored in
+/// the file's header so that readers hash keys the same way.
+#[derive(
+    Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq,
+)]
+pub enum HashAlgorithm {
+    /// The standard library's DefaultHasher, which is SipHash-1-3. Every file
+    /// written before the algorithm could be chosen uses this. The standard
+    /// library doesn't promise that DefaultHasher will never change, so files
+    /// that need to outlive the toolchain should use Fnv1a.
+    #[default]
+    SipHash13,
+
+    /// 64 bit FNV-1a, which is fixed by its specification.
+    Fnv1a,
+}
@@ -1,7 +1,10 @@
 use crate::core::structs::{array::Array, dir::Dir};
 
-pub(crate) enum HighLevelObject {
-    Array(Array),
-    Dir(Dir),
+/// Anything that can be looked up by name in a Dir.
+pub enum HighLevelObject<'a, 'b> {
+    Array(Array<'a, 'b>),
+    Dir(Dir<'a, 'b>),
+
+    /// The value of a metadata tag.
     MetadataTag(String),
 }
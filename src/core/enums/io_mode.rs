@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use super::{ReadMode, WriteMode};
 
 /// Enum for the different IO modes.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum IoMode {
     /// Read the file. This may or may not lock the file, depending on which
     /// ReadMode is being used.
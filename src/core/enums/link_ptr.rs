@@ -1,6 +1,6 @@
 use crate::core::{
     structs::Addr,
-    traits::{FileSerializable, FixedSizeOnDisk},
+    traits::{parse_text, to_text, FileSerializable, FixedSizeOnDisk},
 };
 
 /// The main link pointer enum, which can either be an address or a null
@@ -19,10 +19,16 @@ impl LinkPtr {
     /// from 0 if the LinkPtr is Null.
     fn to_addr(&self) -> &Addr {
         match self {
-            LinkPtr::Addr(addr) => &addr,
-            LinkPtr::Null(addr) => &addr,
+            LinkPtr::Addr(addr) => addr,
+            LinkPtr::Null(addr) => addr,
         }
     }
+
+    /// Returns the fields of the LinkPtr in the Text format. Whether it's
+    /// null is implied by its location, just like in the Binary format.
+    fn text_fields(&self) -> [(String, String); 1] {
+        [("loc".to_string(), self.to_addr().get_loc().to_string())]
+    }
 }
 
 impl FixedSizeOnDisk for LinkPtr {
@@ -31,11 +37,13 @@ impl FixedSizeOnDisk for LinkPtr {
     }
 
     fn get_json_size_on_disk() -> u64 {
-        Addr::get_json_size_on_disk()
+        LinkPtr::Addr(Addr::new(u64::MAX)).get_text_len()
     }
 }
 
 impl FileSerializable for LinkPtr {
+    const TEXT_MARKER: &'static str = "LinkPtr";
+
     fn null() -> Self {
         LinkPtr::Null(Addr::null())
     }
@@ -45,7 +53,15 @@ impl FileSerializable for LinkPtr {
     }
 
     fn to_json(&self) -> String {
-        self.to_addr().to_json()
+        to_text(
+            Self::TEXT_MARKER,
+            &self.text_fields(),
+            Self::get_json_size_on_disk(),
+        )
+    }
+
+    fn get_text_len(&self) -> u64 {
+        to_text(Self::TEXT_MARKER, &self.text_fields(), 0).len() as u64
     }
 
     fn from_bin(bytes: &[u8]) -> Self {
@@ -60,7 +76,12 @@ impl FileSerializable for LinkPtr {
     }
 
     fn from_json(json: String) -> Self {
-        let addr = Addr::from_json(json);
+        // Anything that isn't a single number is as good as null.
+        let loc = match parse_text(Self::TEXT_MARKER, &json).as_deref() {
+            Ok([(_, value)]) => value.parse().unwrap_or(0),
+            _ => 0,
+        };
+        let addr = Addr::new(loc);
 
         // Now return the appropriate LinkPtr depending on what we found.
         if addr.get_loc() == 0 {
@@ -120,20 +141,20 @@ mod tests {
         );
     }
 
-    /// Make sure that the json string of a LinkPtr is exactly what we expect.
+    /// Make sure that the text of a LinkPtr is exactly what we expect.
     #[test]
     fn test_link_ptr_json() {
         let addr = Addr::new(123);
         let link_ptr = LinkPtr::Addr(addr);
         let json = link_ptr.to_json();
 
-        // Note that we need to get the padding right because the json string
-        // is fixed width, and the maximum value of the address is 2^64 - 1,
+        // Note that we need to get the padding right because the text is
+        // fixed width, and the maximum value of the address is 2^64 - 1,
         // which is 20 characters long.
-        assert_eq!(json, "{\"loc\":123}                 ");
+        assert_eq!(json, "LinkPtr loc: 123                 \n");
 
         let null_link_ptr = LinkPtr::Null(Addr::new(0));
         let json = null_link_ptr.to_json();
-        assert_eq!(json, "{\"loc\":0}                   ");
+        assert_eq!(json, "LinkPtr loc: 0                   \n");
     }
 }
@@ -1,18 +1,47 @@
+pub mod aggregation;
+pub mod alignment;
+pub mod array_data_type;
+pub mod clock_source;
+pub mod durability;
+pub mod error_bound;
 pub mod file_format;
+pub mod filter;
+pub mod hash_algorithm;
+pub mod high_level_object;
 pub mod io_mode;
+pub mod predicate;
 pub mod read_mode;
+pub mod time_axis;
 pub mod write_mode;
 
-pub(crate) mod array_data_type;
-pub(crate) mod high_level_object;
+pub(crate) mod crash_mode;
 pub(crate) mod link_ptr;
+pub(crate) mod object_kind;
+pub(crate) mod storage_op;
 
 // Export the enums.
+pub use self::aggregation::Aggregation;
+pub use self::alignment::Alignment;
+pub use self::array_data_type::ArrayDataType;
+pub use self::clock_source::ClockSource;
+pub use self::durability::Durability;
+pub use self::error_bound::ErrorBound;
 pub use self::file_format::FileFormat;
+pub use self::filter::Filter;
+pub use self::hash_algorithm::HashAlgorithm;
+pub use self::high_level_object::HighLevelObject;
 pub use self::io_mode::IoMode;
+pub use self::predicate::Predicate;
 pub use self::read_mode::ReadMode;
+pub use self::time_axis::TimeAxis;
 pub use self::write_mode::WriteMode;
 
-pub(crate) use self::array_data_type::ArrayDataType;
-pub(crate) use self::high_level_object::HighLevelObject;
+pub(crate) use self::array_data_type::with_element_type;
+pub(crate) use self::crash_mode::CrashMode;
+pub(crate) use self::filter::{
+    check_filters, decode_error_bound, decode_filters, encode_filters,
+    ChunkLayout, MAX_FILTERS,
+};
 pub(crate) use self::link_ptr::LinkPtr;
+pub(crate) use self::object_kind::ObjectKind;
+pub(crate) use self::storage_op::StorageOp;
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// The kinds of object that can be stored in a Dir.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ObjectKind {
+    Dir,
+    Array,
+}
+
+impl ObjectKind {
+    /// Returns the single byte code used to store this kind in binary files.
+    pub(crate) fn to_code(self) -> u8 {
+        match self {
+            ObjectKind::Dir => 0,
+            ObjectKind::Array => 1,
+        }
+    }
+
+    /// Returns the kind with the given binary code, or None if the code
+    /// doesn't correspond to a kind.
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(ObjectKind::Dir),
+            1 => Some(ObjectKind::Array),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::core::{structs::ChunkStats, traits::Condition};
+
+/// A comparison against fixed bounds, used as a Condition to find the frames
+/// of an array that meet it (see Array::find). NaN meets none of the
+/// comparisons except IsNan.
+///
+/// Unlike an arbitrary closure, a Predicate can be checked against a chunk's
+/// ChunkStats, so that chunks that can't hold a match are never read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Predicate {
+    /// Values greater than the threshold.
+    GreaterThan(f64),
+
+    /// Values greater than or equal to the threshold.
+    AtLeast(f64),
+
+    /// Values less than the threshold.
+    LessThan(f64),
+
+    /// Values less than or equal to the threshold.
+    AtMost(f64),
+
+    /// Values equal to the given value.
+    Equal(f64),
+
+    /// Values from the first bound to the second, inclusive.
+    Between(f64, f64),
+
+    /// Values that are NaN.
+    IsNan,
+}
+
+impl Predicate {
+    /// Returns whether the value meets the condition.
+    pub fn matches(&self, value: f64) -> bool {
+        match *self {
+            Predicate::GreaterThan(threshold) => value > threshold,
+            Predicate::AtLeast(threshold) => value >= threshold,
+            Predicate::LessThan(threshold) => value < threshold,
+            Predicate::AtMost(threshold) => value <= threshold,
+            Predicate::Equal(other) => value == other,
+            Predicate::Between(low, high) => low <= value && value <= high,
+            Predicate::IsNan => value.is_nan(),
+        }
+    }
+}
+
+impl Condition for Predicate {
+    fn matches(&self, value: f64) -> bool {
+        Predicate::matches(self, value)
+    }
+
+    fn may_match(&self, stats: &ChunkStats) -> bool {
+        let (min, max) = (stats.get_min(), stats.get_max());
+        match *self {
+            Predicate::GreaterThan(threshold) => max > threshold,
+            Predicate::AtLeast(threshold) => max >= threshold,
+            Predicate::LessThan(threshold) => min < threshold,
+            Predicate::AtMost(threshold) => min <= threshold,
+            Predicate::Equal(other) => min <= other && other <= max,
+            Predicate::Between(low, high) => low <= max && min <= high,
+            Predicate::IsNan => stats.get_nan_count() > 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A chunk can only be ruled out if none of its values match, and a chunk
+    /// of nothing but NaNs only matches IsNan.
+    #[test]
+    fn test_may_match() {
+        let stats = ChunkStats::of(&[1.0, 4.0, f64::NAN]);
+        let all_nan = ChunkStats::of(&[f64::NAN]);
+        let predicates = [
+            Predicate::GreaterThan(4.0),
+            Predicate::AtLeast(4.0),
+            Predicate::LessThan(1.0),
+            Predicate::AtMost(1.0),
+            Predicate::Equal(2.5),
+            Predicate::Between(4.5, 9.0),
+            Predicate::IsNan,
+        ];
+        let may_match: Vec<bool> =
+            predicates.iter().map(|p| p.may_match(&stats)).collect();
+        assert_eq!(may_match, [false, true, false, true, true, false, true]);
+        for predicate in predicates {
+            let is_nan = predicate == Predicate::IsNan;
+            assert_eq!(predicate.may_match(&all_nan), is_nan);
+            assert_eq!(predicate.matches(f64::NAN), is_nan);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Enum for the different IO modes.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ReadMode {
     /// Read the file. This never places a lock on the file.
     LocklessRead,
@@ -0,0 +1,13 @@
+/// A single operation that was issued against a StorageBackend. The
+/// FaultInjectingBackend records these so that the CrashHarness can replay
+/// any subset of them.
+// Recorded by the FaultInjectingBackend, which only the CrashHarness uses.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StorageOp {
+    /// The given bytes were written starting at the given offset.
+    Write { offset: u64, bytes: Vec<u8> },
+
+    /// Every write issued so far was flushed to the storage medium.
+    Sync,
+}
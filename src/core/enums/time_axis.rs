@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// How an array knows when each of its frames was taken. The time axis is
+/// chosen when the array is created, and stored in its header.
+#[derive(
+    Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq,
+)]
+pub enum TimeAxis {
+    /// Frames have no times.
+    #[default]
+    None,
+
+    /// Every frame is stored with a Timestamp, which is written along with
+    /// the frame's data.
+    Timestamped,
+
+    /// Frames are taken at a fixed rate, so their timestamps are computed
+    /// from a start time and a period, plus a record of every gap where the
+    /// rate was broken. See RegularTimeAxis.
+    Regular,
+}
+
+impl TimeAxis {
+    /// Returns the single byte code used to store this time axis in binary
+    /// files.
+    pub(crate) fn to_code(self) -> u8 {
+        match self {
+            TimeAxis::None => 0,
+            TimeAxis::Timestamped => 1,
+            TimeAxis::Regular => 2,
+        }
+    }
+
+    /// Returns the time axis with the given binary code, or None if the code
+    /// doesn't correspond to a time axis.
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(TimeAxis::None),
+            1 => Some(TimeAxis::Timestamped),
+            2 => Some(TimeAxis::Regular),
+            _ => None,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Enum for the different IO modes.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum WriteMode {
     /// Write to the file without locking it. Using this mode removes some
     /// functionality, such as data deletion. Otherwise, data can be written to
@@ -0,0 +1,345 @@
+use std::{collections::HashMap, fs::File, io, path::Path, sync::Arc};
+
+use arrow_array::{
+    ArrayRef, FixedSizeListArray, Float32Array, Float64Array, Int16Array,
+    Int32Array, Int64Array, Int8Array, RecordBatch, RecordBatchOptions,
+    UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::{
+    arrow::ArrowWriter, file::properties::WriterProperties, format::KeyValue,
+};
+
+use crate::core::{
+    enums::{ArrayDataType, HighLevelObject},
+    structs::{Array, Dir},
+    traits::{DirTrait, HasMetadataTags},
+};
+
+/// Parquet files are written this many rows at a time, so that exporting a
+/// long array doesn't need the whole array in memory.
+const PARQUET_BATCH_LEN: usize = 65_536;
+
+/// The name of the only column when a single array is exported.
+const ARRAY_COLUMN: &str = "value";
+
+/// Returns frames start..end of the array as a record batch with a single
+/// column called "value". The array's tags are the schema's metadata.
+///
+/// Arrays of scalar frames become a column of the matching Arrow type. Other
+/// frames become fixed size lists, nested one level per frame dimension, so a
+/// frame of shape (2, 3) is a list of 2 lists of 3 values.
+pub(crate) fn array_record_batch(
+    array: &Array,
+    start: usize,
+    end: usize,
+) -> io::Result<RecordBatch> {
+    let schema = schema_of(&[(ARRAY_COLUMN, array)], array)?;
+    record_batch(schema, &[array], start, end)
+}
+
+/// Returns the arrays directly in the dir as a record batch, with one column
+/// per array, named after the array. Columns are described in
+/// array_record_batch. The dir's tags are the schema's metadata, and each
+/// array's tags are its field's metadata.
+///
+/// Arrays in a file that's being written to can be a frame or two apart, so
+/// the batch only has as many rows as the shortest array has frames.
+pub(crate) fn dir_record_batch(dir: &Dir) -> io::Result<RecordBatch> {
+    let columns = dir_columns(dir)?;
+    let (schema, arrays, len) = dir_table(dir, &columns)?;
+    record_batch(schema, &arrays, 0, len)
+}
+
+/// Writes frames start..end of the array to a Parquet file at the given path,
+/// with the same schema as array_record_batch.
+pub(crate) fn write_array_parquet(
+    array: &Array,
+    start: usize,
+    end: usize,
+    path: &Path,
+) -> io::Result<()> {
+    if start > end || end > array.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Frames {}..{} are out of bounds.", start, end),
+        ));
+    }
+    let schema = schema_of(&[(ARRAY_COLUMN, array)], array)?;
+    write_parquet(path, schema, &[array], start, end)
+}
+
+/// Writes the arrays directly in the dir to a Parquet file at the given path,
+/// with the same schema as dir_record_batch.
+pub(crate) fn write_dir_parquet(dir: &Dir, path: &Path) -> io::Result<()> {
+    let columns = dir_columns(dir)?;
+    let (schema, arrays, len) = dir_table(dir, &columns)?;
+    write_parquet(path, schema, &arrays, 0, len)
+}
+
+/// Returns the Arrow type of the given data type.
+fn arrow_data_type(data_type: ArrayDataType) -> DataType {
+    match data_type {
+        ArrayDataType::Int8 => DataType::Int8,
+        ArrayDataType::Int16 => DataType::Int16,
+        ArrayDataType::Int32 => DataType::Int32,
+        ArrayDataType::Int64 => DataType::Int64,
+        ArrayDataType::UInt8 => DataType::UInt8,
+        ArrayDataType::UInt16 => DataType::UInt16,
+        ArrayDataType::UInt32 => DataType::UInt32,
+        ArrayDataType::UInt64 => DataType::UInt64,
+        ArrayDataType::Float32 => DataType::Float32,
+        ArrayDataType::Float64 => DataType::Float64,
+    }
+}
+
+/// Returns the Arrow type of a column holding the array's frames.
+fn column_type(array: &Array) -> DataType {
+    let mut data_type = arrow_data_type(array.get_data_type());
+    for dim in array.get_frame_shape().iter().rev() {
+        let item = Field::new_list_field(data_type, false);
+        data_type = DataType::FixedSizeList(Arc::new(item), *dim as i32);
+    }
+    data_type
+}
+
+/// Returns the schema of a table with the given named columns, whose metadata
+/// is the tags of the given object.
+fn schema_of(
+    columns: &[(&str, &Array)],
+    object: &dyn HasMetadataTags,
+) -> io::Result<SchemaRef> {
+    let fields = columns
+        .iter()
+        .map(|(name, array)| {
+            Ok(Field::new(*name, column_type(array), false)
+                .with_metadata(tag_map(*array)?))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(Arc::new(
+        Schema::new(fields).with_metadata(tag_map(object)?),
+    ))
+}
+
+/// Returns the object's tags as Arrow metadata.
+fn tag_map(
+    object: &dyn HasMetadataTags,
+) -> io::Result<HashMap<String, String>> {
+    Ok(object.get_tags()?.into_iter().collect())
+}
+
+/// Returns the arrays directly in the dir, with their names, in name order.
+fn dir_columns<'d>(dir: &'d Dir) -> io::Result<Vec<(String, Array<'d, 'd>)>> {
+    let mut columns = Vec::new();
+    for name in dir.list_dir()? {
+        if let HighLevelObject::Array(array) = DirTrait::get(dir, &name)? {
+            columns.push((name, array));
+        }
+    }
+    Ok(columns)
+}
+
+/// Returns the schema, the arrays and the number of rows of the table made
+/// from the dir's columns.
+fn dir_table<'c>(
+    dir: &Dir,
+    columns: &'c [(String, Array)],
+) -> io::Result<(SchemaRef, Vec<&'c Array<'c, 'c>>, usize)> {
+    let named = columns
+        .iter()
+        .map(|(name, array)| (name.as_str(), array))
+        .collect::<Vec<_>>();
+    let schema = schema_of(&named, dir)?;
+    let arrays = columns.iter().map(|(_, array)| array).collect::<Vec<_>>();
+    let len = arrays.iter().map(|array| array.len()).min().unwrap_or(0);
+    Ok((schema, arrays, len))
+}
+
+/// Returns rows start..end of the table whose columns hold the given arrays.
+fn record_batch(
+    schema: SchemaRef,
+    arrays: &[&Array],
+    start: usize,
+    end: usize,
+) -> io::Result<RecordBatch> {
+    let columns = arrays
+        .iter()
+        .map(|array| column(array, start, end))
+        .collect::<io::Result<Vec<_>>>()?;
+    let options = RecordBatchOptions::new().with_row_count(Some(end - start));
+    RecordBatch::try_new_with_options(schema, columns, &options)
+        .map_err(io::Error::other)
+}
+
+/// Returns frames start..end of the array as a column.
+fn column(array: &Array, start: usize, end: usize) -> io::Result<ArrayRef> {
+    let mut column: ArrayRef = match array.get_data_type() {
+        ArrayDataType::Int8 => {
+            Arc::new(Int8Array::from(array.get_frames::<i8>(start, end)?))
+        }
+        ArrayDataType::Int16 => {
+            Arc::new(Int16Array::from(array.get_frames::<i16>(start, end)?))
+        }
+        ArrayDataType::Int32 => {
+            Arc::new(Int32Array::from(array.get_frames::<i32>(start, end)?))
+        }
+        ArrayDataType::Int64 => {
+            Arc::new(Int64Array::from(array.get_frames::<i64>(start, end)?))
+        }
+        ArrayDataType::UInt8 => {
+            Arc::new(UInt8Array::from(array.get_frames::<u8>(start, end)?))
+        }
+        ArrayDataType::UInt16 => {
+            Arc::new(UInt16Array::from(array.get_frames::<u16>(start, end)?))
+        }
+        ArrayDataType::UInt32 => {
+            Arc::new(UInt32Array::from(array.get_frames::<u32>(start, end)?))
+        }
+        ArrayDataType::UInt64 => {
+            Arc::new(UInt64Array::from(array.get_frames::<u64>(start, end)?))
+        }
+        ArrayDataType::Float32 => {
+            Arc::new(Float32Array::from(array.get_frames::<f32>(start, end)?))
+        }
+        ArrayDataType::Float64 => {
+            Arc::new(Float64Array::from(array.get_frames::<f64>(start, end)?))
+        }
+    };
+
+    // Wrap the innermost dimension first, so the outermost ends up on top.
+    for dim in array.get_frame_shape().iter().rev() {
+        let item =
+            Arc::new(Field::new_list_field(column.data_type().clone(), false));
+        column = Arc::new(
+            FixedSizeListArray::try_new(item, *dim as i32, column, None)
+                .map_err(io::Error::other)?,
+        );
+    }
+    Ok(column)
+}
+
+/// Writes rows start..end of the table whose columns hold the given arrays
+/// to a Parquet file, PARQUET_BATCH_LEN rows at a time. The schema's metadata
+/// is also written as the file's key-value metadata, since that's what tools
+/// that don't read the embedded Arrow schema, like DuckDB, look at.
+fn write_parquet(
+    path: &Path,
+    schema: SchemaRef,
+    arrays: &[&Array],
+    start: usize,
+    end: usize,
+) -> io::Result<()> {
+    let key_values = schema
+        .metadata()
+        .iter()
+        .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
+        .collect();
+    let properties = WriterProperties::builder()
+        .set_key_value_metadata(Some(key_values))
+        .build();
+    let file = File::create(path)?;
+    let mut writer =
+        ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+    for first in (start..end).step_by(PARQUET_BATCH_LEN) {
+        let last = (first + PARQUET_BATCH_LEN).min(end);
+        writer.write(&record_batch(schema.clone(), arrays, first, last)?)?;
+    }
+    writer.into_inner()?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use arrow_array::{cast::AsArray, types::UInt16Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::core::structs::TsdfFile;
+
+    /// Columns should have the right types and values, and tags should end up
+    /// in the schema's metadata, both in batches and in Parquet files.
+    #[test]
+    fn test_dir_record_batch_and_parquet() {
+        let dir = tempdir().unwrap();
+        let file =
+            TsdfFile::new_writer(dir.path().join("run.tsdf"), None, None)
+                .unwrap();
+        let root = file.get_root_dir().unwrap();
+        root.set_tag("sample", "silicon").unwrap();
+        let time = root
+            .create_array("time", ArrayDataType::Float64, &[], 2)
+            .unwrap();
+        time.append_frames(&[0.0, 0.5, 1.0]).unwrap();
+        time.set_tag("units", "s").unwrap();
+        let counts = root
+            .create_array("counts", ArrayDataType::UInt16, &[2], 2)
+            .unwrap();
+        counts.append_frames(&[1_u16, 2, 3, 4]).unwrap();
+        root.create_dir("detector").unwrap();
+
+        let batch = dir_record_batch(&root).unwrap();
+        let schema = batch.schema();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(schema.metadata()["sample"], "silicon");
+        assert_eq!(schema.field(1).name(), "time");
+        assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+        assert_eq!(schema.field(1).metadata()["units"], "s");
+        let values = batch.column(0).as_fixed_size_list().values();
+        assert_eq!(values.as_primitive::<UInt16Type>().values(), &[1, 2, 3, 4]);
+
+        let path = dir.path().join("run.parquet");
+        write_dir_parquet(&root, &path).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(
+            File::open(&path).unwrap(),
+        )
+        .unwrap();
+        let key_values = builder
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap();
+        assert!(key_values
+            .iter()
+            .any(|kv| kv.key == "sample"
+                && kv.value.as_deref() == Some("silicon")));
+        assert_eq!(builder.schema(), &schema);
+        let batches = builder
+            .build()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].columns(), batch.columns());
+    }
+
+    /// A single array is a single column, and frames with several dimensions
+    /// are nested lists.
+    #[test]
+    fn test_array_record_batch() {
+        let dir = tempdir().unwrap();
+        let file =
+            TsdfFile::new_writer(dir.path().join("run.tsdf"), None, None)
+                .unwrap();
+        let root = file.get_root_dir().unwrap();
+        let images = root
+            .create_array("images", ArrayDataType::Int8, &[2, 3], 4)
+            .unwrap();
+        images.append_frames(&[0_i8; 12]).unwrap();
+
+        let batch = array_record_batch(&images, 1, 2).unwrap();
+        let inner = Field::new_list_field(DataType::Int8, false);
+        let middle = Field::new_list_field(
+            DataType::FixedSizeList(Arc::new(inner), 3),
+            false,
+        );
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.schema().field(0).name(), "value");
+        assert_eq!(
+            batch.schema().field(0).data_type(),
+            &DataType::FixedSizeList(Arc::new(middle), 2)
+        );
+        assert!(array_record_batch(&images, 1, 3).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use chrono::DateTime;
+use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+
+use crate::core::{
+    enums::{with_element_type, ArrayDataType, HighLevelObject},
+    structs::{Array, CsvImportOptions, Dir},
+    traits::DirTrait,
+};
+
+/// Rows are read and written this many at a time, so that files of any length
+/// can be streamed.
+const CSV_BATCH_LEN: usize = 4096;
+
+/// Writes the arrays directly in the dir to a CSV file at the given path, one
+/// column per array, named after the array, and one row per frame. Every
+/// array must have scalar frames.
+///
+/// The column names are preceded by a comment block of tags, one per line.
+/// The dir's tags are written as `# name = value`, and each array's tags as
+/// `# column.name = value`, with line breaks and backslashes in names and
+/// values escaped as `\n` and `\\`.
+///
+/// Like Dir::to_record_batch, there are as many rows as the shortest array
+/// has frames.
+pub(crate) fn write_csv(dir: &Dir, path: &Path) -> io::Result<()> {
+    let mut columns = Vec::new();
+    for name in dir.list_dir()? {
+        if let HighLevelObject::Array(array) = DirTrait::get(dir, &name)? {
+            if !array.get_frame_shape().is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{:?} doesn't have scalar frames.", name),
+                ));
+            }
+            columns.push((name, array));
+        }
+    }
+    let len = columns.iter().map(|(_, array)| array.len()).min();
+
+    let mut out = BufWriter::new(File::create(path)?);
+    for (name, value) in dir.get_tags()? {
+        write_tag(&mut out, &name, &value)?;
+    }
+    for (column, array) in &columns {
+        for (name, value) in array.get_tags()? {
+            write_tag(&mut out, &format!("{}.{}", column, name), &value)?;
+        }
+    }
+
+    let mut writer = WriterBuilder::new().from_writer(&mut out);
+    writer.write_record(columns.iter().map(|(name, _)| name))?;
+    for first in (0..len.unwrap_or(0)).step_by(CSV_BATCH_LEN) {
+        let last = (first + CSV_BATCH_LEN).min(len.unwrap_or(0));
+        let fields = columns
+            .iter()
+            .map(|(_, array)| format_column(array, first, last))
+            .collect::<io::Result<Vec<_>>>()?;
+        for row in 0..last - first {
+            writer.write_record(fields.iter().map(|column| &column[row]))?;
+        }
+    }
+    writer.flush()?;
+    drop(writer);
+    out.into_inner()?.sync_all()
+}
+
+/// Streams the CSV file at the given path into new arrays in the dir. See
+/// CsvImportOptions for how columns become arrays.
+pub(crate) fn read_csv(
+    dir: &Dir,
+    path: &Path,
+    options: &CsvImportOptions,
+) -> io::Result<()> {
+    // The tags come first, so read them before handing over to the csv
+    // reader, which doesn't give us comments.
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut tags = Vec::new();
+    while reader.fill_buf()?.first() == Some(&b'#') {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        tags.push(parse_tag(&line)?);
+    }
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(options.get_delimiter())
+        .from_reader(reader);
+    let names = reader
+        .headers()?
+        .iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let timestamp_column = options.get_timestamp_column();
+    let configured = options.get_column_types().keys().map(String::as_str);
+    for name in timestamp_column.into_iter().chain(configured) {
+        if !names.iter().any(|column| column == name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} has no column called {:?}.", path.display(), name),
+            ));
+        }
+    }
+
+    let arrays = names
+        .iter()
+        .map(|name| {
+            dir.create_array(
+                name,
+                options.get_column_type(name),
+                &[],
+                options.get_frames_per_chunk(),
+            )
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    for (key, value) in tags {
+        let column = key.split_once('.').and_then(|(column, name)| {
+            let i = names.iter().position(|n| n == column)?;
+            Some((&arrays[i], name))
+        });
+        match column {
+            Some((array, name)) => array.set_tag(name, &value)?,
+            None => dir.set_tag(&key, &value)?,
+        }
+    }
+
+    let mut batch = Vec::with_capacity(CSV_BATCH_LEN);
+    for record in reader.records() {
+        batch.push(record?);
+        if batch.len() == CSV_BATCH_LEN {
+            append_rows(&arrays, &names, timestamp_column, &batch)?;
+            batch.clear();
+        }
+    }
+    append_rows(&arrays, &names, timestamp_column, &batch)
+}
+
+/// Writes a tag to the comment block at the top of a CSV file.
+fn write_tag(out: &mut dyn Write, name: &str, value: &str) -> io::Result<()> {
+    writeln!(out, "# {} = {}", escape(name), escape(value))
+}
+
+/// Parses a line of the comment block at the top of a CSV file into a tag's
+/// key and value.
+fn parse_tag(line: &str) -> io::Result<(String, String)> {
+    let body = line.trim_end_matches(['\r', '\n']);
+    let body = body.strip_prefix("# ").unwrap_or(&body[1..]);
+    let (key, value) = body.split_once(" = ").ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{:?} isn't a tag of the form \"name = value\".", body),
+        )
+    })?;
+    Ok((unescape(key), unescape(value)))
+}
+
+/// Escapes line breaks and backslashes, so the string fits on one line.
+fn escape(string: &str) -> String {
+    string.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Undoes escape.
+fn unescape(string: &str) -> String {
+    let mut unescaped = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Formats frames start..end of an array of scalar frames, one string per
+/// frame. Floats use their Debug representation, which round trips.
+fn format_column(
+    array: &Array,
+    start: usize,
+    end: usize,
+) -> io::Result<Vec<String>> {
+    with_element_type!(array.get_data_type(), T => {
+        Ok(array
+            .get_frames::<T>(start, end)?
+            .iter()
+            .map(|value| format!("{:?}", value))
+            .collect())
+    })
+}
+
+/// Appends a batch of rows to the arrays, one frame per row per array.
+fn append_rows(
+    arrays: &[Array],
+    names: &[String],
+    timestamp_column: Option<&str>,
+    batch: &[StringRecord],
+) -> io::Result<()> {
+    for (i, array) in arrays.iter().enumerate() {
+        if Some(names[i].as_str()) == timestamp_column {
+            let values = batch
+                .iter()
+                .map(|record| parse_timestamp(record, i))
+                .collect::<io::Result<Vec<_>>>()?;
+            array.append_frames(&values)?;
+            continue;
+        }
+        with_element_type!(array.get_data_type(), T => {
+            let values = batch
+                .iter()
+                .map(|record| {
+                    let field = record[i].trim();
+                    field.parse::<T>().map_err(|_| {
+                        bad_field(record, field, array.get_data_type())
+                    })
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            array.append_frames(&values)?;
+        });
+    }
+    Ok(())
+}
+
+/// Parses field i of the record as a timestamp, in nanoseconds since the Unix
+/// epoch. See CsvImportOptions::timestamp_column.
+fn parse_timestamp(record: &StringRecord, i: usize) -> io::Result<i64> {
+    let field = record[i].trim();
+    if let Ok(nanos) = field.parse::<i64>() {
+        return Ok(nanos);
+    }
+    DateTime::parse_from_rfc3339(field)
+        .ok()
+        .and_then(|time| time.timestamp_nanos_opt())
+        .ok_or_else(|| bad_field(record, field, ArrayDataType::Int64))
+}
+
+/// Makes the error returned when a field can't be parsed.
+fn bad_field(
+    record: &StringRecord,
+    field: &str,
+    data_type: ArrayDataType,
+) -> io::Error {
+    let line = record.position().map_or(0, |position| position.line());
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Line {}: can't read {:?} as {:?}.", line, field, data_type),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::core::structs::TsdfFile;
+
+    /// Import a CSV with a timestamp column and tags, then export it again.
+    #[test]
+    fn test_csv_round_trip() {
+        let dir = tempdir().unwrap();
+        let csv = dir.path().join("scan.csv");
+        fs::write(
+            &csv,
+            "# sample = silicon\n\
+             # counts.units = counts\\nper second\n\
+             time,counts,temperature\n\
+             2024-01-01T00:00:00Z,1,290.5\n\
+             1704067200000000001,2,-3e-5\n",
+        )
+        .unwrap();
+
+        let file =
+            TsdfFile::new_writer(dir.path().join("run.tsdf"), None, None)
+                .unwrap();
+        let root = file.get_root_dir().unwrap();
+        let scan = root.create_dir("scan").unwrap();
+        CsvImportOptions::new()
+            .timestamp_column("time")
+            .column_type("counts", ArrayDataType::UInt8)
+            .frames_per_chunk(1)
+            .import(&csv, &scan)
+            .unwrap();
+
+        let time = scan.get_array("time").unwrap();
+        assert_eq!(
+            time.get_frames::<i64>(0, 2).unwrap(),
+            vec![1_704_067_200_000_000_000, 1_704_067_200_000_000_001]
+        );
+        let counts = scan.get_array("counts").unwrap();
+        assert_eq!(counts.get_frames::<u8>(0, 2).unwrap(), vec![1, 2]);
+        assert_eq!(
+            counts.get_tag("units").unwrap().unwrap(),
+            "counts\nper second"
+        );
+        assert_eq!(scan.get_tag("sample").unwrap().unwrap(), "silicon");
+        let temperature = scan.get_array("temperature").unwrap();
+        assert_eq!(temperature.get_data_type(), ArrayDataType::Float64);
+
+        let exported = dir.path().join("exported.csv");
+        scan.export_csv(&exported).unwrap();
+        assert_eq!(
+            fs::read_to_string(&exported).unwrap(),
+            "# sample = silicon\n\
+             # counts.units = counts\\nper second\n\
+             counts,temperature,time\n\
+             1,290.5,1704067200000000000\n\
+             2,-3e-5,1704067200000000001\n"
+        );
+    }
+
+    /// Unknown columns and unparseable fields are errors.
+    #[test]
+    fn test_csv_errors() {
+        let dir = tempdir().unwrap();
+        let csv = dir.path().join("scan.csv");
+        fs::write(&csv, "a,b\n1,x\n").unwrap();
+        let file =
+            TsdfFile::new_writer(dir.path().join("run.tsdf"), None, None)
+                .unwrap();
+        let root = file.get_root_dir().unwrap();
+
+        let missing = CsvImportOptions::new()
+            .timestamp_column("time")
+            .import(&csv, &root.create_dir("missing").unwrap());
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        let bad = CsvImportOptions::new()
+            .import(&csv, &root.create_dir("bad").unwrap());
+        assert_eq!(bad.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let images = root.create_dir("images").unwrap();
+        images
+            .create_array("image", ArrayDataType::UInt8, &[2], 4)
+            .unwrap();
+        assert!(images.export_csv(dir.path().join("images.csv")).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+//! Exporters that write tsdf data out in the formats of other tools, and
+//! importers that read it back in.
+#[cfg(feature = "arrow")]
+pub(crate) mod arrow;
+pub(crate) mod csv;
+pub(crate) mod npy;
+pub(crate) mod npz;
+
+#[cfg(feature = "arrow")]
+pub(crate) use arrow::{
+    array_record_batch, dir_record_batch, write_array_parquet,
+    write_dir_parquet,
+};
+pub(crate) use csv::{read_csv, write_csv};
+pub(crate) use npy::write_npy;
+pub(crate) use npz::write_npz;
@@ -0,0 +1,184 @@
+use std::io::{self, Write};
+
+use crate::core::{
+    enums::{with_element_type, ArrayDataType},
+    structs::Array,
+    traits::FileSerializable,
+};
+
+/// The magic string that every .npy file starts with.
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// The headers of .npy files are padded so that the data starts on a multiple
+/// of this many bytes.
+const NPY_ALIGNMENT: usize = 64;
+
+/// Returns the NumPy dtype string of the given data type. Everything is little
+/// endian, since that's how tsdf stores array elements.
+pub(crate) fn npy_descr(data_type: ArrayDataType) -> &'static str {
+    match data_type {
+        ArrayDataType::Int8 => "|i1",
+        ArrayDataType::Int16 => "<i2",
+        ArrayDataType::Int32 => "<i4",
+        ArrayDataType::Int64 => "<i8",
+        ArrayDataType::UInt8 => "|u1",
+        ArrayDataType::UInt16 => "<u2",
+        ArrayDataType::UInt32 => "<u4",
+        ArrayDataType::UInt64 => "<u8",
+        ArrayDataType::Float32 => "<f4",
+        ArrayDataType::Float64 => "<f8",
+    }
+}
+
+/// Returns the header of a version 1.0 .npy file holding a C ordered array of
+/// the given data type and shape.
+///
+/// # Structure
+/// | magic (6 bytes) | major (u8) | minor (u8) | header_len (u16) | header |
+///
+/// The header is a Python dict literal, padded with spaces and a final line
+/// break so that the data after it is aligned to NPY_ALIGNMENT bytes.
+pub(crate) fn npy_header(data_type: ArrayDataType, shape: &[u64]) -> Vec<u8> {
+    // Python needs the trailing comma to know a 1-tuple is a tuple.
+    let shape = match shape {
+        [len] => format!("({},)", len),
+        _ => {
+            let dims = shape.iter().map(u64::to_string).collect::<Vec<_>>();
+            format!("({})", dims.join(", "))
+        }
+    };
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        npy_descr(data_type),
+        shape
+    );
+    let prefix_len = NPY_MAGIC.len() + 4;
+    let total_len =
+        (prefix_len + dict.len() + 1).next_multiple_of(NPY_ALIGNMENT);
+    dict.push_str(&" ".repeat(total_len - prefix_len - dict.len() - 1));
+    dict.push('\n');
+
+    let mut header = NPY_MAGIC.to_vec();
+    header.extend([1, 0]);
+    header.extend((dict.len() as u16).to_le_bytes());
+    header.extend(dict.into_bytes());
+    header
+}
+
+/// Returns the size in bytes of the .npy file that write_npy would write for
+/// the given frames of the array.
+pub(crate) fn npy_len(array: &Array, start: usize, end: usize) -> u64 {
+    let shape = npy_shape(array, start, end);
+    let num_elements = shape.iter().product::<u64>();
+    let element_size = with_element_type!(array.get_data_type(), T => {
+        std::mem::size_of::<T>() as u64
+    });
+    npy_header(array.get_data_type(), &shape).len() as u64
+        + num_elements * element_size
+}
+
+/// Writes frames start (inclusive) to end (exclusive) of the array as a .npy
+/// file, whose first axis runs over the frames and whose other axes are the
+/// frame shape. Frames are read and written a chunk at a time, so arrays of
+/// any length can be exported.
+pub(crate) fn write_npy(
+    array: &Array,
+    start: usize,
+    end: usize,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    if start > end || end > array.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Frames {}..{} are out of bounds.", start, end),
+        ));
+    }
+    out.write_all(&npy_header(
+        array.get_data_type(),
+        &npy_shape(array, start, end),
+    ))?;
+
+    let batch = array.get_frames_per_chunk() as usize;
+    with_element_type!(array.get_data_type(), T => {
+        for first in (start..end).step_by(batch) {
+            let last = (first + batch).min(end);
+            let bytes = array
+                .get_frames::<T>(first, last)?
+                .iter()
+                .flat_map(|element| element.to_bin())
+                .collect::<Vec<_>>();
+            out.write_all(&bytes)?;
+        }
+    });
+    Ok(())
+}
+
+/// Returns the shape of the NumPy array holding the given frames of the array.
+fn npy_shape(array: &Array, start: usize, end: usize) -> Vec<u64> {
+    let mut shape = vec![end.saturating_sub(start) as u64];
+    shape.extend(array.get_frame_shape());
+    shape
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        enums::FileFormat,
+        structs::{Addr, ArrayOptions, MemoryBackend},
+        test_utils::make_io_metadata,
+    };
+
+    /// Headers must match what NumPy writes, down to the padding.
+    #[test]
+    fn test_npy_header() {
+        let header = npy_header(ArrayDataType::UInt16, &[3, 2]);
+        assert_eq!(header.len() % NPY_ALIGNMENT, 0);
+        assert_eq!(&header[..8], b"\x93NUMPY\x01\x00");
+        let dict_len = u16::from_le_bytes([header[8], header[9]]) as usize;
+        assert_eq!(dict_len, header.len() - 10);
+        let dict = std::str::from_utf8(&header[10..]).unwrap();
+        assert_eq!(
+            dict.trim_end(),
+            "{'descr': '<u2', 'fortran_order': False, 'shape': (3, 2), }"
+        );
+        assert!(dict.ends_with(" \n"));
+
+        let header = npy_header(ArrayDataType::Int8, &[7]);
+        let dict = std::str::from_utf8(&header[10..]).unwrap();
+        assert!(dict.contains("'descr': '|i1'"));
+        assert!(dict.contains("'shape': (7,)"));
+    }
+
+    /// Exporting a slice writes the header followed by the slice's elements,
+    /// little endian, and out of bounds slices are rejected.
+    #[test]
+    fn test_write_npy() {
+        let io_metadata = make_io_metadata(FileFormat::Text);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Int16)
+                .frame_shape(&[2])
+                .frames_per_chunk(2),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        array.append_frames(&[1_i16, -2, 3, -4, 5, -6]).unwrap();
+
+        let mut bytes = Vec::new();
+        write_npy(&array, 1, 3, &mut bytes).unwrap();
+        let header = npy_header(ArrayDataType::Int16, &[2, 2]);
+        assert_eq!(bytes.len() as u64, npy_len(&array, 1, 3));
+        assert_eq!(&bytes[..header.len()], &header[..]);
+        let data = bytes[header.len()..]
+            .chunks(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(data, vec![3, -4, 5, -6]);
+
+        assert!(write_npy(&array, 2, 4, &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs::File,
+    io::{self, Seek, Write},
+    path::Path,
+};
+
+use serde_json::{Map, Value};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::core::{
+    enums::HighLevelObject,
+    structs::Dir,
+    traits::{DirTrait, HasMetadataTags},
+};
+
+use super::npy::{npy_len, write_npy};
+
+/// Writes every array in the dir, and in the dirs below it, to a .npz file at
+/// the given path, and every object's tags to a JSON sidecar next to it.
+///
+/// Like NumPy's savez, the .npz file is an uncompressed zip holding one .npy
+/// file per array, named by the array's path relative to the dir, so the
+/// array at detector/counts is loaded as `np.load(path)["detector/counts"]`.
+/// The sidecar has the same path with a .json extension, and maps the same
+/// relative paths to objects of tag names and values. The dir itself is "".
+pub(crate) fn write_npz(dir: &Dir, path: &Path) -> io::Result<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let mut tags = Map::new();
+    add_dir(dir, "", &mut zip, &mut tags)?;
+    zip.finish()?.sync_all()?;
+
+    let mut sidecar = File::create(path.with_extension("json"))?;
+    serde_json::to_writer_pretty(&mut sidecar, &tags)?;
+    sidecar.write_all(b"\n")?;
+    sidecar.sync_all()
+}
+
+/// Adds the arrays below the dir to the zip and their tags to the map, naming
+/// everything with the given prefix, which is empty or ends in a slash.
+fn add_dir<W: Write + Seek>(
+    dir: &Dir,
+    prefix: &str,
+    zip: &mut ZipWriter<W>,
+    tags: &mut Map<String, Value>,
+) -> io::Result<()> {
+    tags.insert(prefix.trim_end_matches('/').to_string(), tags_of(dir)?);
+
+    for name in dir.list_dir()? {
+        let path = format!("{}{}", prefix, name);
+        match DirTrait::get(dir, &name)? {
+            HighLevelObject::Dir(child) => {
+                add_dir(&child, &format!("{}/", path), zip, tags)?
+            }
+            HighLevelObject::Array(array) => {
+                let len = array.len();
+                let options = SimpleFileOptions::default()
+                    .compression_method(CompressionMethod::Stored)
+                    .large_file(npy_len(&array, 0, len) > u32::MAX as u64);
+                zip.start_file(format!("{}.npy", path), options)?;
+                write_npy(&array, 0, len, zip)?;
+                tags.insert(path, tags_of(&array)?);
+            }
+            HighLevelObject::MetadataTag(_) => unreachable!(),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the object's tags as a JSON object.
+fn tags_of(object: &dyn HasMetadataTags) -> io::Result<Value> {
+    Ok(Value::Object(
+        object
+            .get_tags()?
+            .into_iter()
+            .map(|(name, value)| (name, Value::String(value)))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use tempfile::tempdir;
+    use zip::ZipArchive;
+
+    use crate::core::{enums::ArrayDataType, structs::TsdfFile};
+
+    use super::super::npy::npy_header;
+
+    /// Export a small tree, and check the zip's entries and the sidecar.
+    #[test]
+    fn test_export_npz() {
+        let dir = tempdir().unwrap();
+        let file =
+            TsdfFile::new_writer(dir.path().join("run.tsdf"), None, None)
+                .unwrap();
+        let root = file.get_root_dir().unwrap();
+        root.set_tag("sample", "silicon").unwrap();
+        let detector = root.create_dir("detector").unwrap();
+        let counts = detector
+            .create_array("counts", ArrayDataType::UInt8, &[3], 2)
+            .unwrap();
+        counts.append_frames(&[1_u8, 2, 3, 4, 5, 6]).unwrap();
+        counts.set_tag("units", "counts").unwrap();
+        root.create_array("time", ArrayDataType::Float64, &[], 4)
+            .unwrap();
+
+        let path = dir.path().join("run.npz");
+        root.export_npz(&path).unwrap();
+
+        let mut zip =
+            ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut names = zip.file_names().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["detector/counts.npy", "time.npy"]);
+
+        let mut bytes = Vec::new();
+        zip.by_name("detector/counts.npy")
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        let mut expected = npy_header(ArrayDataType::UInt8, &[2, 3]);
+        expected.extend([1, 2, 3, 4, 5, 6]);
+        assert_eq!(bytes, expected);
+
+        let sidecar = std::fs::read_to_string(path.with_extension("json"));
+        let tags: serde_json::Value =
+            serde_json::from_str(&sidecar.unwrap()).unwrap();
+        assert_eq!(
+            tags,
+            serde_json::json!({
+                "": {"sample": "silicon"},
+                "detector": {},
+                "detector/counts": {"units": "counts"},
+                "time": {},
+            })
+        );
+    }
+}
@@ -1,5 +1,8 @@
 /// This module contains the core functionality of the library.
 pub(crate) mod enums;
+pub(crate) mod export;
 pub(crate) mod structs;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub(crate) mod traits;
 pub(crate) mod well_known_values;
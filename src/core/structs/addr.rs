@@ -29,24 +29,22 @@ impl FixedSizeOnDisk for Addr {
     }
 
     fn get_json_size_on_disk() -> u64 {
-        // To get the size of the json string, we make the largest possible
-        // Addr, convert it to json, and get the length of the json string. This
-        // is far from optimized, but the whole point of the json representation
-        // is to be debug-friendly, not performant.
-        let addr = Addr { loc: u64::MAX };
-        let json = serde_json::to_string(&addr).unwrap();
-        json.len() as u64
+        // To get the size of the text, we make the largest possible Addr and
+        // lay it out. This is far from optimized, but the whole point of the
+        // text representation is to be debug-friendly, not performant.
+        Addr { loc: u64::MAX }.get_text_len()
     }
 }
 
 impl FileSerializable for Addr {
+    const TEXT_MARKER: &'static str = "Addr";
+
     fn null() -> Self {
         Self { loc: NULL_LOC }
     }
 
     fn to_bin(&self) -> Vec<u8> {
-        let bytes = self.loc.to_le_bytes().to_vec();
-        bytes
+        self.loc.to_le_bytes().to_vec()
     }
 
     fn from_bin(bytes: &[u8]) -> Self {
@@ -1,8 +1,1402 @@
-// use super::HighLevelObjectMetadata;
-// use crate::core::enums::ArrayDataType;
+use std::{
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+};
 
-pub struct Array {
-    //     shape: Vec<i32>,
-    //     data_type: ArrayDataType,
-    //     metadata: HighLevelObjectMetadata,
+use crate::core::{
+    enums::{
+        check_filters, Aggregation, Alignment, ArrayDataType, ErrorBound,
+        Filter, TimeAxis,
+    },
+    export::write_npy,
+    traits::{
+        ArrayElement, ArrayQueries, ArrayTrait, Bucket, Condition,
+        DecimationLevels, DistDictTrait, DistListTrait, FileSerializable,
+        FixedSizeOnDisk, HasMetadataTags, Locatable, Resampling,
+        StorageBackend,
+    },
+};
+
+#[cfg(feature = "arrow")]
+use crate::core::export::{array_record_batch, write_array_parquet};
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+
+use super::{
+    Addr, ArrayHeader, ArrayOptions, ChunkStats, DistDict, DistList,
+    IoMetadata, RegularTimeAxis, TagEntry, TimeGap, Timestamp,
+};
+
+/// An Array stored in a tsdf file. See the ArrayTrait for the structure of an
+/// array on disk.
+pub struct Array<'a, 'b> {
+    /// The address of this Array in the file.
+    loc: Addr,
+
+    /// The array's header, which never changes once the array is created.
+    header: ArrayHeader,
+
+    /// The start time, period and gap list address of an array with a
+    /// Regular time axis, which also never change.
+    regular_axis: Option<RegularTimeAxis>,
+
+    /// The address of the dictionary holding the array's metadata tags.
+    tags: Addr,
+
+    /// The address of the list of the array's decimation levels, which is
+    /// null unless the array's frames are scalars.
+    levels: Addr,
+
+    /// The address of the list of stats of the array's full chunks.
+    stats: Addr,
+
+    /// The address of the open chunk of an array with filters, which is null
+    /// for any other array.
+    open_chunk: Addr,
+
+    /// The metadata needed to carry out I/O operations.
+    io_metadata: &'a IoMetadata,
+
+    /// The storage backend that the Array is stored in.
+    backend: &'b dyn StorageBackend,
+}
+
+impl Array<'_, '_> {
+    /// Creates a new, empty array at the given location in the file, laid out
+    /// as the options say. Arrays with a Regular time axis must have a
+    /// positive period. The filters are applied to every chunk once it's
+    /// full, and must suit the array (see Filter).
+    pub(crate) fn create<'a, 'b>(
+        loc: Addr,
+        options: &ArrayOptions,
+        io_metadata: &'a IoMetadata,
+        backend: &'b dyn StorageBackend,
+    ) -> io::Result<Array<'a, 'b>> {
+        let data_type = options.get_data_type();
+        let frame_shape = options.get_frame_shape();
+        let frames_per_chunk = options.get_frames_per_chunk();
+        let time_axis = options.get_time_axis();
+        let regular_axis = options.get_regular_axis();
+        let filters = options.get_filters();
+        check_filters(filters, data_type, frame_shape)?;
+        let header = ArrayHeader::new(
+            data_type,
+            frame_shape,
+            frames_per_chunk,
+            time_axis,
+        )
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid frame shape or frames per chunk.",
+            )
+        })?
+        .with_filters(filters)
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Too many filters.")
+        })?;
+        let is_regular = time_axis == TimeAxis::Regular;
+        match regular_axis {
+            Some(axis) if is_regular && axis.get_dt() > 0 => {}
+            None if !is_regular => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Regular arrays need a start time and a positive period.",
+                ))
+            }
+        }
+
+        // Write the header, null levels, stats and open chunk addresses and
+        // any RegularTimeAxis, then initialize the chunk list, any gap list,
+        // any list of levels, the list of stats, any open chunk and the tags
+        // dictionary. The tags address is written last, as it's what marks the
+        // array as complete.
+        header.write(loc, backend, io_metadata);
+        let mut array = Array {
+            loc,
+            header,
+            regular_axis,
+            tags: Addr::null(),
+            levels: Addr::null(),
+            stats: Addr::null(),
+            open_chunk: Addr::null(),
+            io_metadata,
+            backend,
+        };
+        let levels_ptr_addr = array.get_levels_ptr_addr();
+        Addr::null().write(levels_ptr_addr, backend, io_metadata);
+        let stats_ptr_addr = array.get_stats_ptr_addr();
+        Addr::null().write(stats_ptr_addr, backend, io_metadata);
+        let open_chunk_ptr_addr = array.get_open_chunk_ptr_addr();
+        Addr::null().write(open_chunk_ptr_addr, backend, io_metadata);
+        let axis_addr = array.get_regular_axis_addr();
+        if let Some(axis) = &array.regular_axis {
+            axis.write(axis_addr, backend, io_metadata);
+        }
+        array.get_chunk_list().init();
+        if let Some(axis) = &mut array.regular_axis {
+            let gaps = Addr::new(backend.len()?);
+            DistList::<TimeGap>::new(gaps, io_metadata, backend, false).init();
+            axis.set_gaps_addr(gaps);
+            axis.write(axis_addr, backend, io_metadata);
+        }
+        if frame_shape.is_empty() {
+            array.levels = Addr::new(backend.len()?);
+            DistList::<Addr>::new(array.levels, io_metadata, backend, false)
+                .init();
+            array.levels.write(levels_ptr_addr, backend, io_metadata);
+        }
+        array.stats = Addr::new(backend.len()?);
+        DistList::<ChunkStats>::new(array.stats, io_metadata, backend, false)
+            .init();
+        array.stats.write(stats_ptr_addr, backend, io_metadata);
+        if array.is_filtered() {
+            // The open chunk starts out empty, holding the first chunk.
+            array.open_chunk = Addr::new(backend.len()?);
+            let chunk_size = u64::get_size_on_disk(io_metadata)
+                + array.get_chunk_size_on_disk();
+            backend.write_all_at(
+                &vec![0; chunk_size as usize],
+                array.open_chunk.get_loc(),
+            )?;
+            array.write_open_chunk_index(0)?;
+            array
+                .open_chunk
+                .write(open_chunk_ptr_addr, backend, io_metadata);
+        }
+        array.tags = Addr::new(backend.len()?);
+        DistDict::<String, TagEntry>::new(
+            array.tags,
+            io_metadata,
+            backend,
+            false,
+        )
+        .init();
+        array
+            .tags
+            .write(array.get_tags_ptr_addr(), backend, io_metadata);
+
+        Ok(array)
+    }
+
+    /// Opens an array that has already been written to the file.
+    pub(crate) fn open<'a, 'b>(
+        loc: Addr,
+        io_metadata: &'a IoMetadata,
+        backend: &'b dyn StorageBackend,
+    ) -> io::Result<Array<'a, 'b>> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("No valid array at {}.", loc.get_loc()),
+            )
+        };
+        let header = ArrayHeader::from_addr(loc, backend, io_metadata);
+        if !header.is_valid() {
+            return Err(invalid());
+        }
+
+        let mut array = Array {
+            loc,
+            header,
+            regular_axis: None,
+            tags: Addr::null(),
+            levels: Addr::null(),
+            stats: Addr::null(),
+            open_chunk: Addr::null(),
+            io_metadata,
+            backend,
+        };
+        if array.header.get_time_axis() == TimeAxis::Regular {
+            let axis = RegularTimeAxis::from_addr(
+                array.get_regular_axis_addr(),
+                backend,
+                io_metadata,
+            );
+            if !axis.is_valid()
+                || axis.get_gaps_addr().get_loc() >= backend.len()?
+            {
+                return Err(invalid());
+            }
+            array.regular_axis = Some(axis);
+        }
+        array.tags =
+            Addr::from_addr(array.get_tags_ptr_addr(), backend, io_metadata);
+        if array.tags == Addr::null()
+            || array.tags.get_loc() >= backend.len()?
+        {
+            return Err(invalid());
+        }
+        if array.header.get_frame_shape().is_empty() {
+            array.levels = Addr::from_addr(
+                array.get_levels_ptr_addr(),
+                backend,
+                io_metadata,
+            );
+            if array.levels == Addr::null()
+                || array.levels.get_loc() >= backend.len()?
+            {
+                return Err(invalid());
+            }
+        }
+        array.stats =
+            Addr::from_addr(array.get_stats_ptr_addr(), backend, io_metadata);
+        if array.stats == Addr::null()
+            || array.stats.get_loc() >= backend.len()?
+        {
+            return Err(invalid());
+        }
+        if array.is_filtered() {
+            array.open_chunk = Addr::from_addr(
+                array.get_open_chunk_ptr_addr(),
+                backend,
+                io_metadata,
+            );
+            if array.open_chunk == Addr::null()
+                || array.open_chunk.get_loc() >= backend.len()?
+            {
+                return Err(invalid());
+            }
+        }
+
+        Ok(array)
+    }
+}
+
+// Implement the public API of Array, which forwards to ArrayTrait,
+// ArrayQueries, DecimationLevels, Resampling and HasMetadataTags.
+impl Array<'_, '_> {
+    /// Returns the type of every element in the array.
+    pub fn get_data_type(&self) -> ArrayDataType {
+        ArrayTrait::get_data_type(self)
+    }
+
+    /// Returns how the array knows when each of its frames was taken.
+    pub fn get_time_axis(&self) -> TimeAxis {
+        ArrayTrait::get_time_axis(self)
+    }
+
+    /// Returns when the first frame of an array with a Regular time axis was
+    /// taken, or None for any other array.
+    pub fn get_t0(&self) -> Option<Timestamp> {
+        self.regular_axis.map(|axis| axis.get_t0())
+    }
+
+    /// Returns the time between frames of an array with a Regular time axis,
+    /// in nanoseconds, or None for any other array.
+    pub fn get_dt(&self) -> Option<i64> {
+        self.regular_axis.map(|axis| axis.get_dt())
+    }
+
+    /// Returns the shape of a single frame.
+    pub fn get_frame_shape(&self) -> &[u64] {
+        self.header.get_frame_shape()
+    }
+
+    /// Returns the number of frames stored in each chunk of the array.
+    pub fn get_frames_per_chunk(&self) -> u64 {
+        self.header.get_frames_per_chunk()
+    }
+
+    /// Returns the filters that are applied to each full chunk of the array,
+    /// in order.
+    pub fn get_filters(&self) -> Vec<Filter> {
+        self.header.get_filters()
+    }
+
+    /// Returns how far the values read back from the array may be from the
+    /// values that were appended, or None if the array's filters are
+    /// lossless. Frames are exact until their chunk is full. A Relative bound
+    /// gives a different distance for each chunk; see get_absolute_error.
+    pub fn get_error_bound(&self) -> Option<ErrorBound> {
+        self.header.get_error_bound()
+    }
+
+    /// Returns the distance that the values of frames start..end are
+    /// guaranteed to be read back within, which is the largest of the
+    /// distances used for their chunks. It's 0 if the frames are exact, as
+    /// they are in arrays with lossless filters, chunks that aren't full yet
+    /// and chunks stored exactly, like those whose values are all the same
+    /// under a Relative bound.
+    pub fn get_absolute_error(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<f64> {
+        ArrayTrait::get_absolute_error(self, start, end)
+    }
+
+    /// Returns the shape of the whole array: the number of frames, followed
+    /// by the shape of a frame.
+    pub fn shape(&self) -> Vec<u64> {
+        ArrayTrait::shape(self)
+    }
+
+    /// Returns the number of frames in the array.
+    pub fn len(&self) -> usize {
+        ArrayTrait::len(self)
+    }
+
+    /// Returns true if no frames have been written to the array.
+    pub fn is_empty(&self) -> bool {
+        ArrayTrait::is_empty(self)
+    }
+
+    /// Reads the frame with the given index. T must match the array's data
+    /// type.
+    pub fn get_frame<T: ArrayElement>(
+        &self,
+        index: usize,
+    ) -> io::Result<Vec<T>> {
+        ArrayTrait::get_frame(self, index)
+    }
+
+    /// Reads frames start..end, one after another. T must match the array's
+    /// data type.
+    pub fn get_frames<T: ArrayElement>(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<T>> {
+        ArrayTrait::get_frames(self, start, end)
+    }
+
+    /// Reads the frame with the given index, along with its timestamp. The
+    /// array must have a time axis.
+    pub fn get_timestamped_frame<T: ArrayElement>(
+        &self,
+        index: usize,
+    ) -> io::Result<(Timestamp, Vec<T>)> {
+        let mut frames = self.get_timestamped_frames(index, index + 1)?;
+        Ok(frames.remove(0))
+    }
+
+    /// Reads frames start..end as (timestamp, frame) pairs. The array must
+    /// have a time axis.
+    pub fn get_timestamped_frames<T: ArrayElement>(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<(Timestamp, Vec<T>)>> {
+        self.check_has_time_axis()?;
+        let (timestamps, frames) = self.read_frames::<T>(start, end)?;
+        let frame_len = (self.header.get_frame_len() as usize).max(1);
+        Ok(timestamps
+            .into_iter()
+            .zip(frames.chunks(frame_len).map(<[T]>::to_vec))
+            .collect())
+    }
+
+    /// Reads the timestamp of the frame with the given index, without reading
+    /// the frame itself. The array must have a time axis.
+    pub fn get_timestamp(&self, index: usize) -> io::Result<Timestamp> {
+        Ok(self.read_timestamps(index, index + 1)?[0])
+    }
+
+    /// Reads the timestamps of frames start..end, without reading the frames
+    /// themselves. The array must have a time axis.
+    pub fn get_timestamps(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<Timestamp>> {
+        self.read_timestamps(start, end)
+    }
+
+    /// Reads the frames taken from t_start (inclusive) to t_end (exclusive),
+    /// in nanoseconds since the Unix epoch, as (timestamp, frame) pairs. The
+    /// frames are found with a binary search of the timestamps, so only the
+    /// frames in the range are read in full. The array must have a
+    /// time axis.
+    pub fn range<T: ArrayElement>(
+        &self,
+        t_start: i64,
+        t_end: i64,
+    ) -> io::Result<Vec<(Timestamp, Vec<T>)>> {
+        let (start, end) = self.find_time_range(t_start, t_end)?;
+        self.get_timestamped_frames(start, end)
+    }
+
+    /// Returns the indices of the frames taken from t_start (inclusive) to
+    /// t_end (exclusive), in nanoseconds since the Unix epoch. See range.
+    pub fn find_time_range(
+        &self,
+        t_start: i64,
+        t_end: i64,
+    ) -> io::Result<(usize, usize)> {
+        ArrayTrait::find_time_range(self, t_start, t_end)
+    }
+
+    /// Returns the index of the frame taken closest to the given time, in
+    /// nanoseconds since the Unix epoch, or None if the array is empty. Ties,
+    /// including frames that share a timestamp, go to the earliest frame.
+    pub fn find_nearest(&self, nanos: i64) -> io::Result<Option<usize>> {
+        ArrayTrait::find_nearest(self, nanos)
+    }
+
+    /// Reads the frame taken closest to the given time, along with its
+    /// timestamp, or None if the array is empty. See find_nearest.
+    pub fn get_nearest_frame<T: ArrayElement>(
+        &self,
+        nanos: i64,
+    ) -> io::Result<Option<(Timestamp, Vec<T>)>> {
+        match self.find_nearest(nanos)? {
+            Some(index) => self.get_timestamped_frame(index).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the indices of the frames whose values meet the condition, in
+    /// order. The condition is a Predicate, a closure such as
+    /// `|v| v > 5.0`, or a (Predicate, closure) pair; see Condition. If a
+    /// time range is given, as (t_start, t_end) in nanoseconds since the Unix
+    /// epoch, only the frames taken from t_start (inclusive) to t_end
+    /// (exclusive) are searched, and the array must have a time axis. Full
+    /// chunks whose stats show that they can't hold a match are skipped
+    /// without being read, which needs a Predicate.
+    pub fn find<C: Condition>(
+        &self,
+        condition: C,
+        time_range: Option<(i64, i64)>,
+    ) -> io::Result<Vec<usize>> {
+        let (start, end) = self.get_search_range(time_range)?;
+        self.find_frames(&condition, start, end)
+    }
+
+    /// Finds frames like find, but returns each run of consecutive matching
+    /// frames as the timestamps of its first and last frame. The array must
+    /// have a time axis.
+    pub fn find_intervals<C: Condition>(
+        &self,
+        condition: C,
+        time_range: Option<(i64, i64)>,
+    ) -> io::Result<Vec<(Timestamp, Timestamp)>> {
+        let (start, end) = self.get_search_range(time_range)?;
+        ArrayQueries::find_intervals(self, &condition, start, end)
+    }
+
+    /// Returns the frames taken in the given time range, or every frame if
+    /// there's no range.
+    fn get_search_range(
+        &self,
+        time_range: Option<(i64, i64)>,
+    ) -> io::Result<(usize, usize)> {
+        match time_range {
+            Some((t_start, t_end)) => self.find_time_range(t_start, t_end),
+            None => Ok((0, self.len())),
+        }
+    }
+
+    /// Aggregates the frames taken from t_start (inclusive) to t_end
+    /// (exclusive) into buckets of the given width in nanoseconds, such as
+    /// the mean of each second. Buckets start at t_start, and each element of
+    /// the frames is aggregated on its own. Returns the start time of each
+    /// bucket along with its aggregated frame, leaving out buckets without
+    /// any frames. The frames are read a chunk at a time, and the array must
+    /// have a time axis.
+    pub fn resample(
+        &self,
+        aggregation: Aggregation,
+        t_start: i64,
+        t_end: i64,
+        width: i64,
+    ) -> io::Result<Vec<(i64, Vec<f64>)>> {
+        Resampling::resample(self, aggregation, t_start, t_end, width)
+    }
+
+    /// Aggregates a trailing window of the given length in nanoseconds over
+    /// each of the frames taken from t_start (inclusive) to t_end
+    /// (exclusive). The window of a frame taken at t holds every frame taken
+    /// after t - window, up to and including t. Returns the time of each
+    /// frame along with the aggregated frame. The array must have a time
+    /// axis.
+    pub fn rolling(
+        &self,
+        aggregation: Aggregation,
+        window: i64,
+        t_start: i64,
+        t_end: i64,
+    ) -> io::Result<Vec<(i64, Vec<f64>)>> {
+        Resampling::rolling(self, aggregation, window, t_start, t_end)
+    }
+
+    /// Works out the array's frame at each of the given times, in nanoseconds
+    /// since the Unix epoch, which must not go backwards. Times without a
+    /// value, like those before the first frame, get None. Aligning several
+    /// arrays onto the same times, such as the timestamps of one of them,
+    /// joins them onto a common time base. The array must have a time axis.
+    pub fn align(
+        &self,
+        times: &[i64],
+        alignment: Alignment,
+    ) -> io::Result<Vec<Option<Vec<f64>>>> {
+        Resampling::align(self, times, alignment)
+    }
+
+    /// Appends a single frame to the end of the array.
+    pub fn append<T: ArrayElement>(&self, frame: &[T]) -> io::Result<()> {
+        self.append_frames(frame)
+    }
+
+    /// Appends any whole number of frames, laid out one after another, to the
+    /// end of the array.
+    pub fn append_frames<T: ArrayElement>(
+        &self,
+        frames: &[T],
+    ) -> io::Result<()> {
+        let old_len = self.len();
+        ArrayTrait::append_frames(self, frames)?;
+        self.update_levels(old_len)
+    }
+
+    /// Appends a single frame, stamped with the given timestamp. The
+    /// timestamp is written along with the frame, so readers never see one
+    /// without the other. The array must have a time axis. Plain append
+    /// stamps frames of a Timestamped array with the current system time.
+    ///
+    /// A Regular array only stores the timestamp if it breaks the array's
+    /// rate, as a gap that the following frames carry on from. Its clock
+    /// source is always the one of the array's t0.
+    ///
+    /// Timestamps must never go backwards, so that they can be searched by
+    /// range and find_nearest.
+    pub fn append_at<T: ArrayElement>(
+        &self,
+        timestamp: Timestamp,
+        frame: &[T],
+    ) -> io::Result<()> {
+        self.append_frames_at(&[timestamp], frame)
+    }
+
+    /// Appends any whole number of frames, stamped with the given timestamps,
+    /// one per frame. See append_at.
+    pub fn append_frames_at<T: ArrayElement>(
+        &self,
+        timestamps: &[Timestamp],
+        frames: &[T],
+    ) -> io::Result<()> {
+        let old_len = self.len();
+        self.append_frames_with(Some(timestamps), frames)?;
+        self.update_levels(old_len)
+    }
+
+    /// Returns the number of decimation levels above the array itself. Arrays
+    /// of scalar frames keep levels of min, max and mean buckets, each
+    /// covering 10 times as many frames as the level below, up to as many
+    /// levels as have a whole bucket. They're brought up to date by every
+    /// append.
+    pub fn get_num_levels(&self) -> usize {
+        DecimationLevels::get_num_levels(self)
+    }
+
+    /// Reads buckets start..end of the given decimation level as (min, max,
+    /// mean) triples. Bucket i of level n covers frames 10^n * i up to
+    /// 10^n * (i + 1), and level 0 is the array itself. Min and max ignore
+    /// NaNs, but the mean doesn't. The array's frames must be scalars.
+    pub fn get_decimated(
+        &self,
+        level: usize,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<Bucket>> {
+        self.read_level(level, start, end)
+    }
+
+    /// Reads frames start..end at the finest decimation level that needs no
+    /// more than max_points buckets, or the coarsest level if none is coarse
+    /// enough. Returns the number of frames in each bucket, followed by the
+    /// buckets, starting with the one holding frame start. Frames at the end
+    /// that don't fill a whole bucket yet are left out. This is meant for
+    /// drawing any stretch of a long array with a few thousand points.
+    pub fn get_overview(
+        &self,
+        start: usize,
+        end: usize,
+        max_points: usize,
+    ) -> io::Result<(u64, Vec<Bucket>)> {
+        DecimationLevels::get_overview(self, start, end, max_points)
+    }
+
+    /// Returns the stats of every full chunk, in order: the min, max, sum and
+    /// count of its values, leaving out NaNs, and the number of NaNs. Chunk i
+    /// holds frames i * frames_per_chunk up to (i + 1) * frames_per_chunk.
+    /// A chunk's stats are written when its last frame is, so the frames in
+    /// the last chunk, if it isn't full yet, have none.
+    pub fn get_chunk_stats(&self) -> io::Result<Vec<ChunkStats>> {
+        Ok(ArrayTrait::get_chunk_stats(self))
+    }
+
+    /// Writes the whole array to a NumPy .npy file at the given path. See
+    /// export_npy_frames.
+    pub fn export_npy<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.export_npy_frames(path, 0, self.len())
+    }
+
+    /// Writes frames start..end to a NumPy .npy file at the given path. The
+    /// NumPy array has the array's data type, and its shape is the number of
+    /// frames followed by the frame shape.
+    pub fn export_npy_frames<P: AsRef<Path>>(
+        &self,
+        path: P,
+        start: usize,
+        end: usize,
+    ) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        write_npy(self, start, end, &mut out)?;
+        out.into_inner()?.sync_all()
+    }
+
+    /// Returns frames start..end as an Arrow record batch with one column,
+    /// called "value", and the array's tags as the schema's metadata. Scalar
+    /// frames are a column of the matching Arrow type, and other frames are
+    /// fixed size lists nested one level per frame dimension.
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<RecordBatch> {
+        array_record_batch(self, start, end)
+    }
+
+    /// Writes frames start..end to a Parquet file at the given path, with the
+    /// same schema as to_record_batch.
+    #[cfg(feature = "arrow")]
+    pub fn export_parquet<P: AsRef<Path>>(
+        &self,
+        path: P,
+        start: usize,
+        end: usize,
+    ) -> io::Result<()> {
+        write_array_parquet(self, start, end, path.as_ref())
+    }
+
+    /// Returns the value of the metadata tag with the given name, or None if
+    /// there's no such tag.
+    pub fn get_tag(&self, name: &str) -> io::Result<Option<String>> {
+        HasMetadataTags::get_tag(self, name)
+    }
+
+    /// Sets the metadata tag with the given name, adding it if it's new.
+    pub fn set_tag(&self, name: &str, value: &str) -> io::Result<()> {
+        HasMetadataTags::set_tag(self, name, value)
+    }
+
+    /// Returns every metadata tag as (name, value) pairs, sorted by name.
+    pub fn get_tags(&self) -> io::Result<Vec<(String, String)>> {
+        HasMetadataTags::get_tags(self)
+    }
+}
+
+impl Locatable for Array<'_, '_> {
+    fn get_addr(&self) -> &Addr {
+        &self.loc
+    }
+
+    fn get_backend(&self) -> &dyn StorageBackend {
+        self.backend
+    }
+
+    fn get_io_metadata(&self) -> &IoMetadata {
+        self.io_metadata
+    }
+}
+
+impl ArrayTrait for Array<'_, '_> {
+    fn get_header(&self) -> &ArrayHeader {
+        &self.header
+    }
+
+    fn get_regular_axis(&self) -> Option<&RegularTimeAxis> {
+        self.regular_axis.as_ref()
+    }
+
+    fn get_stats_addr(&self) -> Addr {
+        self.stats
+    }
+
+    fn get_open_chunk_addr(&self) -> Addr {
+        self.open_chunk
+    }
+}
+
+impl DecimationLevels for Array<'_, '_> {
+    fn get_levels_addr(&self) -> Addr {
+        self.levels
+    }
+}
+
+impl HasMetadataTags for Array<'_, '_> {
+    fn get_tags_addr(&self) -> Addr {
+        self.tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempfile;
+
+    use super::*;
+    use crate::core::{
+        enums::{ClockSource, FileFormat, IoMode, StorageOp, WriteMode},
+        structs::{
+            FaultInjectingBackend, FileBackend, MemoryBackend, TsdfMetadata,
+        },
+        test_utils::make_io_metadata,
+        traits::DistListTrait,
+    };
+
+    /// Append scalar frames to a 1D array, spanning several chunks, and read
+    /// them back. This uses the Text file format.
+    #[test]
+    fn test_append_get_text() {
+        let io_metadata = make_io_metadata(FileFormat::Text);
+        let backend = FileBackend::new(tempfile().unwrap());
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Float64)
+                .frames_per_chunk(4),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+
+        for i in 0..10 {
+            array.append(&[i as f64 * 0.5]).unwrap();
+        }
+
+        assert_eq!(array.shape(), vec![10]);
+        assert_eq!(array.get_frame::<f64>(3).unwrap(), vec![1.5]);
+        let expected: Vec<f64> = (2..9).map(|i| i as f64 * 0.5).collect();
+        assert_eq!(array.get_frames::<f64>(2, 9).unwrap(), expected);
+        array.verify().unwrap();
+    }
+
+    /// Append 2D frames to an array and read them back. This uses the Binary
+    /// file format.
+    #[test]
+    fn test_append_get_bin() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = FileBackend::new(tempfile().unwrap());
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::UInt16)
+                .frame_shape(&[2, 3])
+                .frames_per_chunk(8),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+
+        let frames: Vec<Vec<u16>> =
+            (0..100).map(|i| (i * 6..i * 6 + 6).collect()).collect();
+        for frame in &frames {
+            array.append(frame).unwrap();
+        }
+
+        assert_eq!(array.shape(), vec![100, 2, 3]);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(&array.get_frame::<u16>(i).unwrap(), frame);
+        }
+        let all: Vec<u16> = frames.concat();
+        assert_eq!(array.get_frames::<u16>(0, 100).unwrap(), all);
+        array.verify().unwrap();
+    }
+
+    /// Make sure that we get errors, rather than garbage, when using the wrong
+    /// type or shape, or reading out of bounds.
+    #[test]
+    fn test_errors() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Int32)
+                .frame_shape(&[2])
+                .frames_per_chunk(8),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+
+        assert!(array.append(&[1.0_f32, 2.0]).is_err());
+        assert!(array.append(&[1_i32]).is_err());
+        array.append(&[1_i32, 2]).unwrap();
+        assert!(array.get_frame::<i32>(1).is_err());
+        assert!(array.get_frame::<i64>(0).is_err());
+
+        // Opening an array where there isn't one should fail.
+        assert!(Array::open(Addr::new(10_000), &io_metadata, &backend).is_err());
+    }
+
+    /// Make sure that a reader that opens the array from a shared backend sees
+    /// the frames that the writer appends.
+    #[test]
+    fn test_reader_sees_writes() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let reader_backend = backend.clone();
+        let writer = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Int64)
+                .frames_per_chunk(4),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        let reader =
+            Array::open(Addr::new(0), &io_metadata, &reader_backend).unwrap();
+
+        for i in 0..20_i64 {
+            writer.append(&[i]).unwrap();
+            assert_eq!(reader.len(), i as usize + 1);
+            assert_eq!(reader.get_frame::<i64>(i as usize).unwrap(), vec![i]);
+        }
+    }
+
+    /// Make sure that a batch of frames is written with one data write and
+    /// one flag write per chunk, with the flags going last.
+    #[test]
+    fn test_append_frames_batches_writes() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = FaultInjectingBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::UInt8)
+                .frame_shape(&[2])
+                .frames_per_chunk(4),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        array.append(&[0_u8, 1]).unwrap();
+        let num_setup_ops = backend.get_ops().len();
+
+        // Three frames fill the first chunk, and the last two go in a new one.
+        let frames: Vec<u8> = (2..12).collect();
+        array.append_frames(&frames).unwrap();
+        let ops = backend.get_ops().split_off(num_setup_ops);
+        let writes: Vec<(u64, usize)> = ops
+            .iter()
+            .map(|op| match op {
+                StorageOp::Write { offset, bytes } => (*offset, bytes.len()),
+                StorageOp::Sync => panic!("Unexpected sync."),
+            })
+            .collect();
+        let chunk_0 = writes[1].0 - 1;
+        assert_eq!(writes[0], (chunk_0 + 4 + 2, 6));
+        assert_eq!(writes[1], (chunk_0 + 1, 3));
+
+        // The new chunk is initialized, added to the chunk list (data then
+        // flag) and then filled (data then flags). Finally the stats of the
+        // full first chunk are added to the stats list (data then flag).
+        assert_eq!(writes.len(), 2 + 1 + 2 + 2 + 2);
+        assert_eq!(writes[6].1, 2);
+
+        assert_eq!(array.len(), 6);
+        let expected: Vec<u8> = (0..12).collect();
+        assert_eq!(array.get_frames::<u8>(0, 6).unwrap(), expected);
+        assert!(array.append_frames(&[1_u8, 2, 3]).is_err());
+    }
+
+    /// A torn batch of flags can leave flags set after the first unset one.
+    /// Readers ignore them, and the next append must clear them.
+    #[test]
+    fn test_stray_flags_are_cleared() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Int16)
+                .frames_per_chunk(4),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        array.append_frames(&[1_i16, 2, 3]).unwrap();
+
+        // Unset the first flag, as if only the end of the flags made it.
+        let chunk = array.get_chunk_list().get(0).unwrap();
+        backend.write_all_at(&[0], chunk.get_loc()).unwrap();
+        assert_eq!(array.len(), 0);
+        array.verify().unwrap();
+
+        array.append(&[7_i16]).unwrap();
+        assert_eq!(array.len(), 1);
+        assert_eq!(array.get_frames::<i16>(0, 1).unwrap(), vec![7]);
+    }
+
+    /// Append frames with timestamps to an array with checksums, in both file
+    /// formats, and read them back with and without their frames.
+    #[test]
+    fn test_timestamped_frames() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let mut metadata =
+                TsdfMetadata::new("no_version".to_string(), file_format);
+            metadata.set_checksums(true);
+            let io_metadata = IoMetadata::new(
+                metadata,
+                IoMode::Write(WriteMode::LocklessWrite),
+            );
+            let backend = MemoryBackend::new();
+            let array = Array::create(
+                Addr::new(0),
+                ArrayOptions::new()
+                    .data_type(ArrayDataType::UInt8)
+                    .frame_shape(&[2])
+                    .frames_per_chunk(2)
+                    .timestamped(),
+                &io_metadata,
+                &backend,
+            )
+            .unwrap();
+
+            let stamp = |i: i64| Timestamp::new(i * 1_000, ClockSource::Ptp);
+            array.append_at(stamp(-1), &[0_u8, 1]).unwrap();
+            let timestamps: Vec<Timestamp> = (0..4).map(stamp).collect();
+            let frames: Vec<u8> = (2..10).collect();
+            array.append_frames_at(&timestamps, &frames).unwrap();
+            array.append(&[10_u8, 11]).unwrap();
+
+            assert_eq!(array.get_time_axis(), TimeAxis::Timestamped);
+            assert_eq!(array.shape(), vec![6, 2]);
+            assert_eq!(
+                array.get_timestamped_frame::<u8>(2).unwrap(),
+                (stamp(1), vec![4, 5])
+            );
+            let pairs = array.get_timestamped_frames::<u8>(0, 2).unwrap();
+            assert_eq!(
+                pairs,
+                vec![(stamp(-1), vec![0, 1]), (stamp(0), vec![2, 3])]
+            );
+            assert_eq!(array.get_timestamps(1, 5).unwrap(), timestamps);
+            let now = array.get_timestamp(5).unwrap();
+            assert_eq!(now.get_clock_source(), ClockSource::System);
+            assert!(now.get_nanos() > stamp(3).get_nanos());
+            let expected: Vec<u8> = (0..12).collect();
+            assert_eq!(array.get_frames::<u8>(0, 6).unwrap(), expected);
+            array.verify().unwrap();
+
+            // There must be one timestamp per frame.
+            let err = array.append_frames_at(&timestamps[..1], &frames);
+            assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        }
+
+        // Arrays without timestamps don't take or give them.
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::UInt8)
+                .frames_per_chunk(2),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        assert!(array.append_at(Timestamp::now(), &[1_u8]).is_err());
+        array.append(&[1_u8]).unwrap();
+        assert!(array.get_timestamp(0).is_err());
+        assert!(array.get_timestamped_frame::<u8>(0).is_err());
+    }
+
+    /// Find frames by time, over several chunks and with repeated timestamps.
+    #[test]
+    fn test_time_range_and_nearest() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Int32)
+                .frames_per_chunk(3)
+                .timestamped(),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        assert_eq!(array.find_nearest(5).unwrap(), None);
+        assert!(array.range::<i32>(0, 10).unwrap().is_empty());
+
+        let nanos = [10, 20, 20, 30, 40, 50, 60, 70];
+        let timestamps: Vec<Timestamp> = nanos
+            .iter()
+            .map(|n| Timestamp::new(*n, ClockSource::Ntp))
+            .collect();
+        let frames: Vec<i32> = (0..8).collect();
+        array.append_frames_at(&timestamps, &frames).unwrap();
+
+        assert_eq!(array.find_time_range(20, 50).unwrap(), (1, 5));
+        assert_eq!(array.find_time_range(21, 21).unwrap(), (3, 3));
+        assert_eq!(array.find_time_range(50, 20).unwrap(), (5, 5));
+        assert_eq!(array.find_time_range(0, 1000).unwrap(), (0, 8));
+        let range = array.range::<i32>(15, 31).unwrap();
+        let values: Vec<i32> = range.iter().map(|(_, f)| f[0]).collect();
+        assert_eq!(values, vec![1, 2, 3]);
+        assert_eq!(range[0].0, timestamps[1]);
+
+        assert_eq!(array.find_nearest(-5).unwrap(), Some(0));
+        assert_eq!(array.find_nearest(24).unwrap(), Some(1));
+        assert_eq!(array.find_nearest(25).unwrap(), Some(1));
+        assert_eq!(array.find_nearest(26).unwrap(), Some(3));
+        assert_eq!(array.find_nearest(1000).unwrap(), Some(7));
+        assert_eq!(
+            array.get_nearest_frame::<i32>(44).unwrap(),
+            Some((timestamps[4], vec![4]))
+        );
+
+        // Timestamps can't go backwards, within a batch or across batches.
+        let early = Timestamp::new(69, ClockSource::Ntp);
+        assert!(array.append_at(early, &[8_i32]).is_err());
+        let late = Timestamp::new(80, ClockSource::Ntp);
+        assert!(array.append_frames_at(&[late, early], &[8_i32, 9]).is_err());
+        assert_eq!(array.len(), 8);
+    }
+
+    /// Work out the timestamps of a Regular array from its rate and gaps, and
+    /// make sure that gaps left by a crash are overridden.
+    #[test]
+    fn test_regular_time_axis() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_io_metadata(file_format);
+            let backend = MemoryBackend::new();
+            let t0 = Timestamp::new(1_000, ClockSource::Gps);
+            let array = Array::create(
+                Addr::new(0),
+                ArrayOptions::new()
+                    .data_type(ArrayDataType::UInt8)
+                    .frames_per_chunk(2)
+                    .regular(t0, 10),
+                &io_metadata,
+                &backend,
+            )
+            .unwrap();
+
+            array.append_frames(&[0_u8, 1, 2]).unwrap();
+            let at = |nanos| Timestamp::new(nanos, ClockSource::Ntp);
+            array.append_at(at(2_000), &[3_u8]).unwrap();
+            array.append(&[4_u8]).unwrap();
+            array
+                .append_frames_at(
+                    &[at(2_020), at(2_030), at(5_000)],
+                    &[5_u8, 6, 7],
+                )
+                .unwrap();
+            assert_eq!(array.get_gap_list().unwrap().len(), 2);
+            assert!(array.append_at(at(4_999), &[8_u8]).is_err());
+
+            let array =
+                Array::open(Addr::new(0), &io_metadata, &backend).unwrap();
+            assert_eq!(array.get_t0(), Some(t0));
+            assert_eq!(array.get_dt(), Some(10));
+            let nanos: Vec<i64> = array
+                .get_timestamps(0, 8)
+                .unwrap()
+                .iter()
+                .map(|t| t.get_nanos())
+                .collect();
+            assert_eq!(
+                nanos,
+                vec![1_000, 1_010, 1_020, 2_000, 2_010, 2_020, 2_030, 5_000]
+            );
+            assert_eq!(
+                array.get_timestamped_frame::<u8>(4).unwrap(),
+                (Timestamp::new(2_010, ClockSource::Gps), vec![4])
+            );
+            assert_eq!(array.find_time_range(1_015, 2_020).unwrap(), (2, 5));
+            assert_eq!(array.find_nearest(3_000).unwrap(), Some(6));
+            let range = array.range::<u8>(2_030, 10_000).unwrap();
+            assert_eq!(range.len(), 2);
+            array.verify().unwrap();
+
+            // Gaps written for frames that never made it must not apply to
+            // the frames appended after them.
+            let mut gap_list = array.get_gap_list().unwrap();
+            gap_list.add(&TimeGap::new(8, 9_000));
+            gap_list.add(&TimeGap::new(9, 9_500));
+            assert_eq!(array.len(), 8);
+            array.append_frames(&[8_u8, 9]).unwrap();
+            let nanos: Vec<i64> = array
+                .get_timestamps(7, 10)
+                .unwrap()
+                .iter()
+                .map(|t| t.get_nanos())
+                .collect();
+            assert_eq!(nanos, vec![5_000, 5_010, 5_020]);
+        }
+
+        // Regular arrays need a positive period.
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::UInt8)
+                .frames_per_chunk(2)
+                .regular(Timestamp::now(), 0),
+            &io_metadata,
+            &backend,
+        );
+        assert!(array.is_err());
+    }
+
+    /// Every full chunk should get stats, whether it's filled from memory in
+    /// one append or across several appends, and the last chunk shouldn't
+    /// until it's full.
+    #[test]
+    fn test_chunk_stats() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_io_metadata(file_format);
+            let backend = MemoryBackend::new();
+            let array = Array::create(
+                Addr::new(0),
+                ArrayOptions::new()
+                    .data_type(ArrayDataType::Float32)
+                    .frame_shape(&[2])
+                    .frames_per_chunk(3),
+                &io_metadata,
+                &backend,
+            )
+            .unwrap();
+
+            let values: Vec<f32> = (0..20).map(|i| i as f32).collect();
+            array.append_frames(&values[0..4]).unwrap();
+            assert!(array.get_chunk_stats().unwrap().is_empty());
+            array.append_frames(&[4.0, f32::NAN, -6.0, 7.0]).unwrap();
+            array.append_frames(&values[8..18]).unwrap();
+
+            let array =
+                Array::open(Addr::new(0), &io_metadata, &backend).unwrap();
+            let stats = array.get_chunk_stats().unwrap();
+            assert_eq!(stats.len(), 3);
+            assert_eq!(stats[0].get_min(), 0.0);
+            assert_eq!(stats[0].get_max(), 4.0);
+            assert_eq!(stats[0].get_nan_count(), 1);
+            assert_eq!(stats[1].get_min(), -6.0);
+            assert_eq!(stats[1].get_count(), 6);
+            for (i, chunk_stats) in stats.iter().enumerate() {
+                let frames = array.get_frames::<f32>(i * 3, i * 3 + 3).unwrap();
+                assert_eq!(
+                    chunk_stats.to_bin(),
+                    ChunkStats::of(&frames).to_bin()
+                );
+            }
+            array.verify().unwrap();
+        }
+    }
+
+    /// Frames of a filtered array should read back the same from sealed
+    /// chunks and the open chunk, in both formats and with checksums, and an
+    /// open chunk left behind by a crash part way through sealing should be
+    /// reset by the next append.
+    #[test]
+    fn test_filtered_array() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let mut metadata =
+                TsdfMetadata::new("no_version".to_string(), file_format);
+            metadata.set_checksums(true);
+            let io_metadata = IoMetadata::new(
+                metadata,
+                IoMode::Write(WriteMode::LocklessWrite),
+            );
+            let backend = MemoryBackend::new();
+            let filters = [Filter::Delta, Filter::Shuffle, Filter::Zstd];
+            let array = Array::create(
+                Addr::new(0),
+                ArrayOptions::new()
+                    .data_type(ArrayDataType::Float64)
+                    .frame_shape(&[2])
+                    .frames_per_chunk(4)
+                    .timestamped()
+                    .filters(&filters),
+                &io_metadata,
+                &backend,
+            )
+            .unwrap();
+            assert_eq!(array.get_filters(), filters);
+
+            let values: Vec<f64> = (0..24).map(|i| i as f64 / 4.0).collect();
+            let stamp = |i: i64| Timestamp::new(i * 10, ClockSource::Ptp);
+            let timestamps: Vec<Timestamp> = (0..12).map(stamp).collect();
+            array
+                .append_frames_with(Some(&timestamps[..3]), &values[..6])
+                .unwrap();
+            array
+                .append_frames_with(Some(&timestamps[3..10]), &values[6..20])
+                .unwrap();
+            assert_eq!(array.get_chunk_list().len(), 2);
+            assert_eq!(array.read_open_chunk_index(), 2);
+
+            let array =
+                Array::open(Addr::new(0), &io_metadata, &backend).unwrap();
+            assert_eq!(array.len(), 10);
+            assert_eq!(array.get_frames::<f64>(0, 10).unwrap(), values[..20]);
+            assert_eq!(array.get_frames::<f64>(3, 9).unwrap(), values[6..18]);
+            let nanos: Vec<i64> = array
+                .get_timestamps(2, 10)
+                .unwrap()
+                .iter()
+                .map(|t| t.get_nanos())
+                .collect();
+            assert_eq!(nanos, (2..10).map(|i| i * 10).collect::<Vec<_>>());
+            assert_eq!(array.get_chunk_stats().unwrap().len(), 2);
+            array.verify().unwrap();
+
+            // Fill the open chunk, then wind its index back, as if the writer
+            // crashed after sealing it but before resetting it.
+            array
+                .append_frames_with(Some(&timestamps[10..]), &values[20..])
+                .unwrap();
+            assert_eq!(array.get_chunk_list().len(), 3);
+            array.write_open_chunk_index(2).unwrap();
+            assert_eq!(array.len(), 12);
+            array.verify().unwrap();
+            array.append_at(stamp(12), &[6.0, 6.25]).unwrap();
+            assert_eq!(array.len(), 13);
+            assert_eq!(array.read_open_chunk_index(), 3);
+            assert_eq!(
+                array.get_frames::<f64>(11, 13).unwrap(),
+                [5.5, 5.75, 6.0, 6.25]
+            );
+            array.verify().unwrap();
+
+            // An open chunk whose index is damaged doesn't hold the chunk
+            // after the sealed ones, which can't be found anywhere.
+            array.write_open_chunk_index(9).unwrap();
+            assert_eq!(array.len(), 12);
+            let err = array.read_chunk_frames(3, 0, 1).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            array.write_open_chunk_index(3).unwrap();
+
+            // A sealed chunk that's been damaged can't be decoded.
+            let sealed = array.get_chunk_list().get(1).unwrap();
+            let len_size = u64::get_size_on_disk(&io_metadata);
+            backend
+                .write_all_at(&[0xFF; 4], sealed.get_loc() + len_size)
+                .unwrap();
+            assert!(array.get_frames::<f64>(4, 5).is_err());
+            assert!(array.verify().is_err());
+        }
+
+        // Gorilla encodes scalar floats and their timestamps.
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Float32)
+                .frames_per_chunk(8)
+                .timestamped()
+                .filters(&[Filter::Gorilla, Filter::Zstd]),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        let values: Vec<f32> = (0..20).map(|i| (i / 3) as f32).collect();
+        let timestamps: Vec<Timestamp> = (0..20)
+            .map(|i| Timestamp::new(i * 1_000, ClockSource::Ptp))
+            .collect();
+        array
+            .append_frames_with(Some(&timestamps), &values)
+            .unwrap();
+        assert_eq!(array.get_chunk_list().len(), 2);
+        assert_eq!(array.get_frames::<f32>(0, 20).unwrap(), values);
+        assert_eq!(array.get_timestamp(9).unwrap().get_nanos(), 9_000);
+        array.verify().unwrap();
+
+        // No more than four filters fit in the header.
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::UInt8)
+                .frames_per_chunk(2)
+                .filters(&[Filter::Delta; 5]),
+            &io_metadata,
+            &backend,
+        );
+        assert!(array.is_err());
+    }
+
+    /// Frames of a lossy array should come back within the array's bound once
+    /// their chunk is sealed, with checksums and stats that match the values
+    /// that come back.
+    #[test]
+    fn test_lossy_array() {
+        let mut metadata =
+            TsdfMetadata::new("no_version".to_string(), FileFormat::Binary);
+        metadata.set_checksums(true);
+        let io_metadata =
+            IoMetadata::new(metadata, IoMode::Write(WriteMode::LocklessWrite));
+        let backend = MemoryBackend::new();
+        let bound = ErrorBound::Absolute(0.01);
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Float32)
+                .frame_shape(&[2, 3])
+                .frames_per_chunk(4)
+                .filters(&[Filter::Quantize(bound), Filter::Shuffle]),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        assert_eq!(array.get_error_bound(), Some(bound));
+
+        let values: Vec<f32> = (0..60).map(|i| i as f32 / 7.0).collect();
+        array.append_frames(&values).unwrap();
+        let read = array.get_frames::<f32>(0, 10).unwrap();
+        assert!(read.iter().zip(&values).any(|(a, b)| a != b));
+        for (read, value) in read.iter().zip(&values) {
+            assert!((read - value).abs() <= 0.01);
+        }
+
+        // The open chunk is still exact.
+        assert_eq!(read[48..], values[48..]);
+        for (i, stats) in array.get_chunk_stats().unwrap().iter().enumerate() {
+            let frames = array.get_frames::<f32>(i * 4, i * 4 + 4).unwrap();
+            assert_eq!(stats.to_bin(), ChunkStats::of(&frames).to_bin());
+        }
+        array.verify().unwrap();
+        let array = Array::open(Addr::new(0), &io_metadata, &backend).unwrap();
+        assert_eq!(array.get_error_bound(), Some(bound));
+        assert_eq!(array.get_frames::<f32>(0, 10).unwrap(), read);
+        assert_eq!(array.get_absolute_error(0, 10).unwrap(), 0.01);
+        assert_eq!(array.get_absolute_error(8, 10).unwrap(), 0.0);
+
+        // A Relative bound gives each chunk its own distance, and stores a
+        // chunk of one repeated value exactly.
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Float64)
+                .frames_per_chunk(4)
+                .filters(&[Filter::Quantize(ErrorBound::Relative(0.01))]),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        let values = [0.1, 1.7, 3.1, 2.2, 9.3, 9.3, 9.3, 9.3, 0.5, 0.25];
+        array.append_frames(&values).unwrap();
+        let read = array.get_frames::<f64>(0, 10).unwrap();
+        assert_eq!(read[4..], values[4..]);
+        let error = array.get_absolute_error(0, 4).unwrap();
+        assert!((error - 0.03).abs() < 1e-12);
+        for (read, value) in read[..4].iter().zip(&values) {
+            assert!((read - value).abs() <= error);
+        }
+        assert_eq!(array.get_absolute_error(0, 10).unwrap(), error);
+        assert_eq!(array.get_absolute_error(4, 10).unwrap(), 0.0);
+        assert!(array.get_absolute_error(0, 11).is_err());
+    }
 }
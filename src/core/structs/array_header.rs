@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    enums::{ArrayDataType, ErrorBound, Filter, TimeAxis, MAX_FILTERS},
+    traits::{FileSerializable, FixedSizeOnDisk},
+};
+
+/// The maximum number of dimensions that a single frame of an array can have.
+/// Arrays have one more dimension than their frames, as frames are stacked
+/// along the first (time) axis.
+pub(crate) const MAX_FRAME_DIMS: usize = 4;
+
+/// The ArrayHeader holds everything that we need to know to interpret the data
+/// stored in an Array. It is written once, when the array is created, and
+/// never changes afterwards.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct ArrayHeader {
+    /// The type of every element in the array.
+    data_type: ArrayDataType,
+
+    /// The number of dimensions of each frame in the array. A value of 0 means
+    /// that each frame is a single scalar.
+    ndim: u8,
+
+    /// The shape of each frame. Only the first ndim values are meaningful.
+    frame_shape: [u64; MAX_FRAME_DIMS],
+
+    /// How the array knows when each of its frames was taken.
+    time_axis: TimeAxis,
+
+    /// The number of frames stored in each chunk of the array. A value of 0
+    /// marks a null (invalid) header.
+    frames_per_chunk: u64,
+
+    /// The filters applied to every sealed chunk, in order. Only the filters
+    /// before the first None are meaningful.
+    filters: [Option<Filter>; MAX_FILTERS],
+}
+
+impl ArrayHeader {
+    /// Constructs a new ArrayHeader, without any filters. Returns None if the
+    /// frame has too many dimensions or frames_per_chunk is zero.
+    pub(crate) fn new(
+        data_type: ArrayDataType,
+        frame_shape: &[u64],
+        frames_per_chunk: u64,
+        time_axis: TimeAxis,
+    ) -> Option<Self> {
+        if frame_shape.len() > MAX_FRAME_DIMS || frames_per_chunk == 0 {
+            return None;
+        }
+
+        let mut padded_shape = [0; MAX_FRAME_DIMS];
+        padded_shape[..frame_shape.len()].copy_from_slice(frame_shape);
+        Some(Self {
+            data_type,
+            ndim: frame_shape.len() as u8,
+            frame_shape: padded_shape,
+            time_axis,
+            frames_per_chunk,
+            filters: [None; MAX_FILTERS],
+        })
+    }
+
+    /// Returns the header with the given filters. Returns None if there are
+    /// more than MAX_FILTERS of them.
+    pub(crate) fn with_filters(mut self, filters: &[Filter]) -> Option<Self> {
+        if filters.len() > MAX_FILTERS {
+            return None;
+        }
+        self.filters = [None; MAX_FILTERS];
+        for (slot, filter) in self.filters.iter_mut().zip(filters) {
+            *slot = Some(*filter);
+        }
+        Some(self)
+    }
+
+    /// Returns whether this is a valid header, rather than a null header (or
+    /// garbage read from an unwritten part of the file).
+    pub(crate) fn is_valid(&self) -> bool {
+        self.frames_per_chunk > 0 && self.ndim as usize <= MAX_FRAME_DIMS
+    }
+
+    /// Returns the type of every element in the array.
+    pub(crate) fn get_data_type(&self) -> ArrayDataType {
+        self.data_type
+    }
+
+    /// Returns the shape of a single frame of the array.
+    pub(crate) fn get_frame_shape(&self) -> &[u64] {
+        &self.frame_shape[..self.ndim as usize]
+    }
+
+    /// Returns the number of elements in a single frame of the array.
+    pub(crate) fn get_frame_len(&self) -> u64 {
+        self.get_frame_shape().iter().product()
+    }
+
+    /// Returns the number of frames stored in each chunk of the array.
+    pub(crate) fn get_frames_per_chunk(&self) -> u64 {
+        self.frames_per_chunk
+    }
+
+    /// Returns how the array knows when each of its frames was taken.
+    pub(crate) fn get_time_axis(&self) -> TimeAxis {
+        self.time_axis
+    }
+
+    /// Returns the filters applied to every sealed chunk, in order.
+    pub(crate) fn get_filters(&self) -> Vec<Filter> {
+        self.filters.iter().map_while(|filter| *filter).collect()
+    }
+
+    /// Returns the error bound of the array's lossy filter, or None if all of
+    /// its filters are lossless.
+    pub(crate) fn get_error_bound(&self) -> Option<ErrorBound> {
+        self.get_filters()
+            .into_iter()
+            .find_map(Filter::get_error_bound)
+    }
+}
+
+impl FixedSizeOnDisk for ArrayHeader {
+    fn get_bin_size_on_disk() -> u64 {
+        // One byte each for the data type code, ndim and the time axis code,
+        // then the frame shape, frames_per_chunk, a byte for each filter code
+        // and the value of the error bound of any Quantize filter.
+        1 + 1 + 1 + 8 * MAX_FRAME_DIMS as u64 + 8 + MAX_FILTERS as u64 + 8
+    }
+
+    fn get_json_size_on_disk() -> u64 {
+        // Serialize the largest possible header. No data type has a longer
+        // name than Float64, no time axis a longer name than Timestamped and
+        // no filter is longer than a Quantize filter with the longest f64.
+        let bound = ErrorBound::Relative(-1.2345678901234567e-308);
+        let header = ArrayHeader {
+            data_type: ArrayDataType::Float64,
+            ndim: u8::MAX,
+            frame_shape: [u64::MAX; MAX_FRAME_DIMS],
+            time_axis: TimeAxis::Timestamped,
+            frames_per_chunk: u64::MAX,
+            filters: [Some(Filter::Quantize(bound)); MAX_FILTERS],
+        };
+        header.get_text_len()
+    }
+}
+
+impl FileSerializable for ArrayHeader {
+    const TEXT_MARKER: &'static str = "ArrayHeader";
+
+    fn null() -> Self {
+        Self {
+            data_type: ArrayDataType::Int8,
+            ndim: 0,
+            frame_shape: [0; MAX_FRAME_DIMS],
+            time_axis: TimeAxis::None,
+            frames_per_chunk: 0,
+            filters: [None; MAX_FILTERS],
+        }
+    }
+
+    fn to_bin(&self) -> Vec<u8> {
+        let mut bytes = vec![
+            self.data_type.to_code(),
+            self.ndim,
+            self.time_axis.to_code(),
+        ];
+        for dim in self.frame_shape {
+            bytes.extend_from_slice(&dim.to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.frames_per_chunk.to_le_bytes());
+        bytes.extend(self.filters.iter().map(|f| f.map_or(0, Filter::to_code)));
+        let param = self.get_error_bound().map_or(0.0, |b| b.get_value());
+        bytes.extend_from_slice(&param.to_le_bytes());
+        bytes
+    }
+
+    fn from_bin(bytes: &[u8]) -> Self {
+        let read_u64 = |start: usize| {
+            u64::from_le_bytes(bytes[start..start + 8].try_into().unwrap())
+        };
+
+        // If either code is garbage, so is the rest of the header.
+        let codes = (
+            ArrayDataType::from_code(bytes[0]),
+            TimeAxis::from_code(bytes[2]),
+        );
+        let (data_type, time_axis) = match codes {
+            (Some(data_type), Some(time_axis)) => (data_type, time_axis),
+            _ => return Self::null(),
+        };
+
+        let mut frame_shape = [0; MAX_FRAME_DIMS];
+        for (i, dim) in frame_shape.iter_mut().enumerate() {
+            *dim = read_u64(3 + 8 * i);
+        }
+        let filters_start = 3 + 8 * MAX_FRAME_DIMS + 8;
+        let param = f64::from_bits(read_u64(filters_start + MAX_FILTERS));
+        let mut filters = [None; MAX_FILTERS];
+        for (i, filter) in filters.iter_mut().enumerate() {
+            *filter = match bytes[filters_start + i] {
+                0 => None,
+                code => match Filter::from_code(code, param) {
+                    Some(filter) => Some(filter),
+                    None => return Self::null(),
+                },
+            };
+        }
+        Self {
+            data_type,
+            ndim: bytes[1],
+            frame_shape,
+            time_axis,
+            frames_per_chunk: read_u64(3 + 8 * MAX_FRAME_DIMS),
+            filters,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that we can convert an ArrayHeader to bytes and back.
+    #[test]
+    fn test_array_header_to_bin() {
+        let header = ArrayHeader::new(
+            ArrayDataType::UInt16,
+            &[480, 640],
+            32,
+            TimeAxis::Timestamped,
+        )
+        .unwrap()
+        .with_filters(&[
+            Filter::Quantize(ErrorBound::Absolute(0.25)),
+            Filter::Zstd,
+        ])
+        .unwrap();
+        let bytes = header.to_bin();
+        assert_eq!(bytes.len() as u64, ArrayHeader::get_bin_size_on_disk());
+        assert_eq!(ArrayHeader::from_bin(&bytes), header);
+        assert_eq!(header.get_frame_shape(), &[480, 640]);
+        assert_eq!(header.get_frame_len(), 480 * 640);
+        assert_eq!(
+            header.get_filters(),
+            [Filter::Quantize(ErrorBound::Absolute(0.25)), Filter::Zstd]
+        );
+        assert_eq!(header.get_error_bound(), Some(ErrorBound::Absolute(0.25)));
+    }
+
+    /// Test that we can convert an ArrayHeader to json and back.
+    #[test]
+    fn test_array_header_to_json() {
+        let header =
+            ArrayHeader::new(ArrayDataType::Float64, &[], 8, TimeAxis::None)
+                .unwrap()
+                .with_filters(&[Filter::Quantize(ErrorBound::Relative(0.1))])
+                .unwrap();
+        let json = header.to_json();
+        assert_eq!(json.len() as u64, ArrayHeader::get_json_size_on_disk());
+        assert_eq!(ArrayHeader::from_json(json), header);
+        assert_eq!(header.get_frame_len(), 1);
+    }
+
+    /// Make sure that invalid headers can't be constructed, and that a null
+    /// header is recognised as invalid.
+    #[test]
+    fn test_invalid_headers() {
+        let new = |frame_shape: &[u64], frames_per_chunk| {
+            ArrayHeader::new(
+                ArrayDataType::Int8,
+                frame_shape,
+                frames_per_chunk,
+                TimeAxis::None,
+            )
+        };
+        assert!(new(&[1; 5], 8).is_none());
+        assert!(new(&[1], 0).is_none());
+        assert!(!ArrayHeader::null().is_valid());
+        assert!(!ArrayHeader::from_bin(&[0; 55]).is_valid());
+        let filters = [Filter::Delta; MAX_FILTERS + 1];
+        assert!(new(&[1], 8).unwrap().with_filters(&filters).is_none());
+
+        // A garbage time axis or filter code makes the whole header invalid.
+        let mut bytes = new(&[1], 8).unwrap().to_bin();
+        bytes[2] = u8::MAX;
+        assert!(!ArrayHeader::from_bin(&bytes).is_valid());
+        let mut bytes = new(&[1], 8).unwrap().to_bin();
+        bytes[46] = u8::MAX;
+        assert!(!ArrayHeader::from_bin(&bytes).is_valid());
+    }
+}
@@ -0,0 +1,169 @@
+use std::io;
+
+use crate::core::{
+    enums::{ArrayDataType, Filter, TimeAxis},
+    traits::{ArrayTrait, DirTrait},
+};
+
+use super::{Array, Dir, RegularTimeAxis, Timestamp};
+
+/// Options for creating an array in a Dir, in the style of OpenOptions. This
+/// covers everything that Dir::create_array and its siblings do, along with
+/// the filters applied to the array's chunks.
+///
+/// ```no_run
+/// use tsdf::{ArrayDataType, ArrayOptions, Filter, TsdfFile};
+///
+/// # fn main() -> tsdf::Result<()> {
+/// let file = TsdfFile::new_writer("run.tsdf", None, None)?;
+/// let root = file.get_root_dir()?;
+/// let temperature = ArrayOptions::new()
+///     .data_type(ArrayDataType::Float32)
+///     .timestamped()
+///     .filters(&[Filter::Shuffle, Filter::Zstd])
+///     .create("temperature", &root)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrayOptions {
+    /// The type of every element in the array.
+    data_type: ArrayDataType,
+
+    /// The shape of a single frame.
+    frame_shape: Vec<u64>,
+
+    /// The number of frames stored in each chunk.
+    frames_per_chunk: u64,
+
+    /// How the array knows when each of its frames was taken.
+    time_axis: TimeAxis,
+
+    /// The start time and period of an array with a Regular time axis.
+    regular_axis: Option<RegularTimeAxis>,
+
+    /// The filters applied to each full chunk, in order.
+    filters: Vec<Filter>,
+}
+
+impl Default for ArrayOptions {
+    fn default() -> Self {
+        ArrayOptions {
+            data_type: ArrayDataType::Float64,
+            frame_shape: Vec::new(),
+            frames_per_chunk: 1024,
+            time_axis: TimeAxis::None,
+            regular_axis: None,
+            filters: Vec::new(),
+        }
+    }
+}
+
+impl ArrayOptions {
+    /// Constructs a new set of options, with everything left at its default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the type of every element in the array. This is Float64 by
+    /// default.
+    pub fn data_type(&mut self, data_type: ArrayDataType) -> &mut Self {
+        self.data_type = data_type;
+        self
+    }
+
+    /// Sets the shape of a single frame. Frames are scalars by default.
+    pub fn frame_shape(&mut self, frame_shape: &[u64]) -> &mut Self {
+        self.frame_shape = frame_shape.to_vec();
+        self
+    }
+
+    /// Sets the number of frames stored in each chunk. This is 1024 by
+    /// default.
+    pub fn frames_per_chunk(&mut self, frames_per_chunk: u64) -> &mut Self {
+        self.frames_per_chunk = frames_per_chunk;
+        self
+    }
+
+    /// Stores a Timestamp with every frame, as Dir::create_timestamped_array
+    /// does.
+    pub fn timestamped(&mut self) -> &mut Self {
+        self.time_axis = TimeAxis::Timestamped;
+        self.regular_axis = None;
+        self
+    }
+
+    /// Takes the first frame at t0 and each one after it dt nanoseconds later,
+    /// as Dir::create_regular_array does. dt must be positive.
+    pub fn regular(&mut self, t0: Timestamp, dt: i64) -> &mut Self {
+        self.time_axis = TimeAxis::Regular;
+        self.regular_axis = Some(RegularTimeAxis::new(t0, dt));
+        self
+    }
+
+    /// Sets the filters applied to each chunk once it's full, in order. There
+    /// are none by default, and there can be at most four. See Filter.
+    pub fn filters(&mut self, filters: &[Filter]) -> &mut Self {
+        self.filters = filters.to_vec();
+        self
+    }
+
+    /// Creates a new, empty array with the given name in the Dir.
+    pub fn create<'a, 'b>(
+        &self,
+        name: &str,
+        dir: &Dir<'a, 'b>,
+    ) -> io::Result<Array<'a, 'b>> {
+        DirTrait::create_array(dir, name, self)
+    }
+}
+
+// Implement private methods for ArrayOptions.
+impl ArrayOptions {
+    /// Returns the options that the array was created with.
+    pub(crate) fn like(array: &Array) -> Self {
+        let mut options = ArrayOptions::new();
+        options
+            .data_type(array.get_data_type())
+            .frame_shape(array.get_frame_shape())
+            .frames_per_chunk(array.get_frames_per_chunk())
+            .filters(&array.get_filters());
+        if let Some(axis) = array.get_regular_axis() {
+            options.regular(axis.get_t0(), axis.get_dt());
+        } else if array.get_time_axis() == TimeAxis::Timestamped {
+            options.timestamped();
+        }
+        options
+    }
+
+    /// Returns the type of every element in the array.
+    pub(crate) fn get_data_type(&self) -> ArrayDataType {
+        self.data_type
+    }
+
+    /// Returns the shape of a single frame.
+    pub(crate) fn get_frame_shape(&self) -> &[u64] {
+        &self.frame_shape
+    }
+
+    /// Returns the number of frames stored in each chunk.
+    pub(crate) fn get_frames_per_chunk(&self) -> u64 {
+        self.frames_per_chunk
+    }
+
+    /// Returns how the array knows when each of its frames was taken.
+    pub(crate) fn get_time_axis(&self) -> TimeAxis {
+        self.time_axis
+    }
+
+    /// Returns the start time and period of an array with a Regular time
+    /// axis.
+    pub(crate) fn get_regular_axis(&self) -> Option<RegularTimeAxis> {
+        self.regular_axis
+    }
+
+    /// Returns the filters applied to each full chunk, in order.
+    pub(crate) fn get_filters(&self) -> &[Filter] {
+        &self.filters
+    }
+}
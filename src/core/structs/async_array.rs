@@ -0,0 +1,111 @@
+use std::io;
+
+use crate::core::traits::{ArrayElement, TsdfFileTrait};
+
+use super::{Addr, Array, AsyncTsdfFile, TsdfFile};
+
+/// An async handle to an Array in an AsyncTsdfFile. See ArrayTrait for what
+/// each method does; the only difference is that the work happens on tokio's
+/// blocking thread pool.
+#[derive(Clone)]
+pub struct AsyncArray {
+    /// The file that the Array lives in.
+    file: AsyncTsdfFile,
+
+    /// The address of the Array in the file.
+    loc: Addr,
+}
+
+impl AsyncArray {
+    /// Constructs a new AsyncArray for the Array at the given location.
+    pub(crate) fn new(file: AsyncTsdfFile, loc: Addr) -> Self {
+        Self { file, loc }
+    }
+
+    /// Opens the Array on the blocking thread pool and runs the closure
+    /// against it. The closure must only read.
+    async fn with_array<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&Array) -> io::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.file.with_file(self.in_array(f)).await
+    }
+
+    /// Opens the Array on the blocking thread pool and runs the closure
+    /// against it, holding the file's write lock.
+    async fn write_array<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&Array) -> io::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.file.with_writer(self.in_array(f)).await
+    }
+
+    /// Wraps the closure in one that opens the Array in a TsdfFile first.
+    fn in_array<F, R>(
+        &self,
+        f: F,
+    ) -> impl FnOnce(&TsdfFile) -> io::Result<R> + Send + 'static
+    where
+        F: FnOnce(&Array) -> io::Result<R> + Send + 'static,
+    {
+        let loc = self.loc;
+        move |file: &TsdfFile| {
+            let array =
+                Array::open(loc, file.get_io_metadata(), file.get_backend())?;
+            f(&array)
+        }
+    }
+
+    /// Returns the number of frames in the array.
+    pub async fn len(&self) -> io::Result<usize> {
+        self.with_array(|array| Ok(array.len())).await
+    }
+
+    /// Returns whether the array has no frames.
+    pub async fn is_empty(&self) -> io::Result<bool> {
+        self.with_array(|array| Ok(array.is_empty())).await
+    }
+
+    /// Returns the shape of the array, starting with the number of frames.
+    pub async fn shape(&self) -> io::Result<Vec<u64>> {
+        self.with_array(|array| Ok(array.shape())).await
+    }
+
+    /// Gets the frames from start (inclusive) to end (exclusive), flattened
+    /// into a single vector.
+    pub async fn get_frames<T: ArrayElement + Send + 'static>(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<T>> {
+        self.with_array(move |array| array.get_frames(start, end))
+            .await
+    }
+
+    /// Appends any number of frames, flattened into one vector, to the end of
+    /// the array.
+    pub async fn append_frames<T: ArrayElement + Send + 'static>(
+        &self,
+        frames: Vec<T>,
+    ) -> io::Result<()> {
+        self.write_array(move |array| array.append_frames(&frames))
+            .await
+    }
+
+    /// Returns the value of the tag with the given name, or None if the array
+    /// has no such tag.
+    pub async fn get_tag(&self, name: &str) -> io::Result<Option<String>> {
+        let name = name.to_string();
+        self.with_array(move |array| array.get_tag(&name)).await
+    }
+
+    /// Sets the tag with the given name to the given value.
+    pub async fn set_tag(&self, name: &str, value: &str) -> io::Result<()> {
+        let name = name.to_string();
+        let value = value.to_string();
+        self.write_array(move |array| array.set_tag(&name, &value))
+            .await
+    }
+}
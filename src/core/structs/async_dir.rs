@@ -0,0 +1,152 @@
+use std::io;
+
+use crate::core::{
+    enums::ArrayDataType,
+    traits::{Locatable, TsdfFileTrait},
+};
+
+use super::{Addr, AsyncArray, AsyncTsdfFile, Dir, TsdfFile};
+
+/// An async handle to a Dir in an AsyncTsdfFile. See DirTrait for what each
+/// method does; the only difference is that the work happens on tokio's
+/// blocking thread pool.
+#[derive(Clone)]
+pub struct AsyncDir {
+    /// The file that the Dir lives in.
+    file: AsyncTsdfFile,
+
+    /// The address of the Dir in the file.
+    loc: Addr,
+
+    /// The name of the Dir within its parent.
+    name: String,
+}
+
+impl AsyncDir {
+    /// Constructs a new AsyncDir for the Dir at the given location.
+    pub(crate) fn new(file: AsyncTsdfFile, loc: Addr, name: String) -> Self {
+        Self { file, loc, name }
+    }
+
+    /// Returns the name of the Dir within its parent.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Opens the Dir on the blocking thread pool and runs the closure
+    /// against it. The closure must only read.
+    async fn with_dir<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&Dir) -> io::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.file.with_file(self.in_dir(f)).await
+    }
+
+    /// Opens the Dir on the blocking thread pool and runs the closure against
+    /// it, holding the file's write lock.
+    async fn write_dir<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&Dir) -> io::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        self.file.with_writer(self.in_dir(f)).await
+    }
+
+    /// Wraps the closure in one that opens the Dir in a TsdfFile first.
+    fn in_dir<F, R>(
+        &self,
+        f: F,
+    ) -> impl FnOnce(&TsdfFile) -> io::Result<R> + Send + 'static
+    where
+        F: FnOnce(&Dir) -> io::Result<R> + Send + 'static,
+    {
+        let loc = self.loc;
+        let name = self.name.clone();
+        move |file: &TsdfFile| {
+            let dir = Dir::open(
+                loc,
+                &name,
+                file.get_io_metadata(),
+                file.get_backend(),
+            )?;
+            f(&dir)
+        }
+    }
+
+    /// Returns the names of every Array and Dir in the directory, sorted.
+    pub async fn list_dir(&self) -> io::Result<Vec<String>> {
+        self.with_dir(|dir| dir.list_dir()).await
+    }
+
+    /// Returns the subdirectory with the given name.
+    pub async fn get_dir(&self, name: &str) -> io::Result<AsyncDir> {
+        let name = name.to_string();
+        let child = name.clone();
+        let loc = self
+            .with_dir(move |dir| Ok(*dir.get_dir(&child)?.get_addr()))
+            .await?;
+        Ok(AsyncDir::new(self.file.clone(), loc, name))
+    }
+
+    /// Returns the array with the given name.
+    pub async fn get_array(&self, name: &str) -> io::Result<AsyncArray> {
+        let name = name.to_string();
+        let loc = self
+            .with_dir(move |dir| Ok(*dir.get_array(&name)?.get_addr()))
+            .await?;
+        Ok(AsyncArray::new(self.file.clone(), loc))
+    }
+
+    /// Creates a new, empty subdirectory with the given name.
+    pub async fn create_dir(&self, name: &str) -> io::Result<AsyncDir> {
+        let name = name.to_string();
+        let child = name.clone();
+        let loc = self
+            .write_dir(move |dir| Ok(*dir.create_dir(&child)?.get_addr()))
+            .await?;
+        Ok(AsyncDir::new(self.file.clone(), loc, name))
+    }
+
+    /// Creates a new, empty array with the given name.
+    pub async fn create_array(
+        &self,
+        name: &str,
+        data_type: ArrayDataType,
+        frame_shape: Vec<u64>,
+        frames_per_chunk: u64,
+    ) -> io::Result<AsyncArray> {
+        let name = name.to_string();
+        let loc = self
+            .write_dir(move |dir| {
+                let array = dir.create_array(
+                    &name,
+                    data_type,
+                    &frame_shape,
+                    frames_per_chunk,
+                )?;
+                Ok(*array.get_addr())
+            })
+            .await?;
+        Ok(AsyncArray::new(self.file.clone(), loc))
+    }
+
+    /// Returns the value of the tag with the given name, or None if the Dir
+    /// has no such tag.
+    pub async fn get_tag(&self, name: &str) -> io::Result<Option<String>> {
+        let name = name.to_string();
+        self.with_dir(move |dir| dir.get_tag(&name)).await
+    }
+
+    /// Sets the tag with the given name to the given value.
+    pub async fn set_tag(&self, name: &str, value: &str) -> io::Result<()> {
+        let name = name.to_string();
+        let value = value.to_string();
+        self.write_dir(move |dir| dir.set_tag(&name, &value)).await
+    }
+
+    /// Returns every tag on the Dir as (name, value) pairs, sorted by name.
+    pub async fn get_tags(&self) -> io::Result<Vec<(String, String)>> {
+        self.with_dir(|dir| dir.get_tags()).await
+    }
+}
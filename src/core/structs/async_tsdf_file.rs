@@ -0,0 +1,251 @@
+use std::{
+    io,
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::core::{
+    enums::{FileFormat, WriteMode},
+    traits::Locatable,
+};
+
+use super::{AsyncDir, TsdfFile};
+
+/// An async facade over a TsdfFile, for use from inside a tokio runtime.
+///
+/// Every call is run on tokio's blocking thread pool, so slow reads and writes
+/// never stall the runtime's worker threads. The file is shared behind an Arc,
+/// which means that any number of tasks can read from it at once. Writes take
+/// a lock shared by every clone, so they run one at a time. The handles that
+/// it gives out (AsyncDir and AsyncArray) are cheap to clone and can be moved
+/// between tasks freely.
+#[derive(Clone)]
+pub struct AsyncTsdfFile {
+    /// The file that every call is forwarded to.
+    file: Arc<TsdfFile>,
+
+    /// Held by every write, as a TsdfFile only supports one writer at a time.
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl AsyncTsdfFile {
+    /// Wraps a TsdfFile that has already been opened, for example with
+    /// OpenOptions.
+    pub fn new(file: TsdfFile) -> Self {
+        Self {
+            file: file.into(),
+            write_lock: Arc::default(),
+        }
+    }
+
+    /// Opens a file for reading. See TsdfFile::new_reader.
+    pub async fn new_reader<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = spawn_blocking(move || TsdfFile::new_reader(path)).await?;
+        Ok(Self::new(file))
+    }
+
+    /// Opens a file for writing. See TsdfFile::new_writer.
+    pub async fn new_writer<P: AsRef<Path>>(
+        path: P,
+        write_mode: Option<WriteMode>,
+        file_format: Option<FileFormat>,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = spawn_blocking(move || {
+            TsdfFile::new_writer(path, write_mode, file_format)
+        })
+        .await?;
+        Ok(Self::new(file))
+    }
+
+    /// Creates a new file for writing, overwriting any existing file. See
+    /// TsdfFile::new_overwriting_writer.
+    pub async fn new_overwriting_writer<P: AsRef<Path>>(
+        path: P,
+        write_mode: Option<WriteMode>,
+        file_format: Option<FileFormat>,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = spawn_blocking(move || {
+            TsdfFile::new_overwriting_writer(path, write_mode, file_format)
+        })
+        .await?;
+        Ok(Self::new(file))
+    }
+
+    /// Returns the file that every call is forwarded to.
+    pub fn get_file(&self) -> &TsdfFile {
+        &self.file
+    }
+
+    /// Returns the root dir of the file.
+    pub async fn get_root_dir(&self) -> io::Result<AsyncDir> {
+        let loc = self
+            .with_file(|file| Ok(*file.get_root_dir()?.get_addr()))
+            .await?;
+        Ok(AsyncDir::new(self.clone(), loc, "/".to_string()))
+    }
+
+    /// Runs the closure against the file on tokio's blocking thread pool. The
+    /// closure may run at the same time as others, so it must only read; use
+    /// with_writer for anything that writes.
+    pub async fn with_file<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&TsdfFile) -> io::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let file = self.file.clone();
+        spawn_blocking(move || f(&file)).await
+    }
+
+    /// Runs the closure against the file on tokio's blocking thread pool,
+    /// holding the write lock so that no other write runs at the same time.
+    pub async fn with_writer<F, R>(&self, f: F) -> io::Result<R>
+    where
+        F: FnOnce(&TsdfFile) -> io::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let file = self.file.clone();
+        let write_lock = self.write_lock.clone();
+        spawn_blocking(move || {
+            // A writer that panicked has already been reported as an error,
+            // and leaves the file as a crash would, which readers cope with.
+            let _guard =
+                write_lock.lock().unwrap_or_else(PoisonError::into_inner);
+            f(&file)
+        })
+        .await
+    }
+}
+
+/// Runs the closure on tokio's blocking thread pool. A closure that panics is
+/// reported as an error, rather than taking the caller down with it.
+async fn spawn_blocking<F, R>(f: F) -> io::Result<R>
+where
+    F: FnOnce() -> io::Result<R> + Send + 'static,
+    R: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(io::Error::other)?
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::core::enums::ArrayDataType;
+
+    /// Write a file through the async facade, then read it back from several
+    /// concurrent tasks.
+    #[tokio::test]
+    async fn test_concurrent_readers() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("async.tsdf");
+
+        let writer = AsyncTsdfFile::new_overwriting_writer(&path, None, None)
+            .await
+            .unwrap();
+        let root = writer.get_root_dir().await.unwrap();
+        let detector = root.create_dir("detector").await.unwrap();
+        detector.set_tag("units", "counts").await.unwrap();
+        let array = detector
+            .create_array("counts", ArrayDataType::UInt16, vec![2], 4)
+            .await
+            .unwrap();
+        let frames: Vec<u16> = (0..20).collect();
+        array.append_frames(frames.clone()).await.unwrap();
+
+        let reader = AsyncTsdfFile::new_reader(&path).await.unwrap();
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let reader = reader.clone();
+                tokio::spawn(async move {
+                    let root = reader.get_root_dir().await.unwrap();
+                    let detector = root.get_dir("detector").await.unwrap();
+                    let array = detector.get_array("counts").await.unwrap();
+                    let start = i % 10;
+                    let frames = array.get_frames::<u16>(start, 10).await;
+                    (start, frames.unwrap())
+                })
+            })
+            .collect();
+        for task in tasks {
+            let (start, read) = task.await.unwrap();
+            assert_eq!(read, frames[start * 2..].to_vec());
+        }
+
+        let root = reader.get_root_dir().await.unwrap();
+        assert_eq!(root.list_dir().await.unwrap(), vec!["detector"]);
+        let detector = root.get_dir("detector").await.unwrap();
+        assert_eq!(detector.get_tag("units").await.unwrap().unwrap(), "counts");
+        let array = detector.get_array("counts").await.unwrap();
+        assert_eq!(array.shape().await.unwrap(), vec![10, 2]);
+    }
+
+    /// Many tasks appending to their own arrays at once, on several worker
+    /// threads, must leave a file that verifies.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_writers() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("writers.tsdf");
+
+        let file = AsyncTsdfFile::new_overwriting_writer(&path, None, None)
+            .await
+            .unwrap();
+        let root = file.get_root_dir().await.unwrap();
+        let tasks: Vec<_> = (0..8)
+            .map(|i: i64| {
+                let root = root.clone();
+                tokio::spawn(async move {
+                    let name = format!("array_{}", i);
+                    let array = root
+                        .create_array(&name, ArrayDataType::Int64, vec![], 1)
+                        .await
+                        .unwrap();
+                    for j in 0..300 {
+                        array.append_frames(vec![i * 1000 + j]).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        file.with_file(|file| file.verify()).await.unwrap();
+        for i in 0..8 {
+            let array = root.get_array(&format!("array_{}", i)).await.unwrap();
+            let expected: Vec<i64> = (0..300).map(|j| i * 1000 + j).collect();
+            assert_eq!(
+                array.get_frames::<i64>(0, 300).await.unwrap(),
+                expected
+            );
+        }
+    }
+
+    /// Errors from the blocking pool should come back as errors.
+    #[tokio::test]
+    async fn test_errors() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("missing.tsdf");
+        assert!(AsyncTsdfFile::new_reader(&path).await.is_err());
+
+        let path = dir.path().join("errors.tsdf");
+        let file = AsyncTsdfFile::new_overwriting_writer(&path, None, None)
+            .await
+            .unwrap();
+        let root = file.get_root_dir().await.unwrap();
+        let err = root.get_array("nope").await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let err = file
+            .with_file(|_| -> io::Result<()> { panic!("oops") })
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+}
@@ -0,0 +1,82 @@
+/// Writes values of any number of bits, most significant bit first, packed
+/// into bytes. Used by the Gorilla filter.
+pub(crate) struct BitWriter {
+    /// The bytes written so far, the last of which may be partly filled.
+    bytes: Vec<u8>,
+
+    /// The number of bits used in the last byte, from 0 to 7. 0 means the
+    /// next bit starts a new byte.
+    used: u32,
+}
+
+impl BitWriter {
+    /// Constructs an empty BitWriter.
+    pub(crate) fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            used: 0,
+        }
+    }
+
+    /// Writes the lowest num_bits bits of the value, which must be at most
+    /// 64.
+    pub(crate) fn write(&mut self, value: u64, num_bits: u32) {
+        for i in (0..num_bits).rev() {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.bytes.last_mut().unwrap() |= bit << (7 - self.used);
+            self.used = (self.used + 1) % 8;
+        }
+    }
+
+    /// Writes a single bit.
+    pub(crate) fn write_bit(&mut self, bit: bool) {
+        self.write(bit as u64, 1);
+    }
+
+    /// Returns the bytes written, with the last byte padded with zeros.
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads the values written by a BitWriter.
+pub(crate) struct BitReader<'a> {
+    /// The bytes being read.
+    bytes: &'a [u8],
+
+    /// The index of the next bit to read.
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Constructs a BitReader that starts at the first bit of the bytes.
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Reads a value of num_bits bits, which must be at most 64. Returns None
+    /// if the bytes run out.
+    pub(crate) fn read(&mut self, num_bits: u32) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..num_bits {
+            let byte = self.bytes.get(self.pos / 8)?;
+            let bit = (byte >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u64;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+
+    /// Reads a single bit. Returns None if the bytes run out.
+    pub(crate) fn read_bit(&mut self) -> Option<bool> {
+        Some(self.read(1)? == 1)
+    }
+
+    /// Returns the number of whole bytes read, counting a partly read byte.
+    pub(crate) fn get_bytes_read(&self) -> usize {
+        self.pos.div_ceil(8)
+    }
+}
@@ -0,0 +1,140 @@
+use std::io;
+
+use crate::core::{
+    enums::FileFormat,
+    traits::{FileSerializable, FixedSizeOnDisk, StorageBackend},
+};
+
+use super::{Addr, IoMetadata};
+
+/// A Blob is an immutable run of bytes of any length, stored at the end of the
+/// file. Blobs are used for anything whose size can't be known up front, like
+/// the names of objects and the values of metadata tags.
+///
+/// # Structure on disk
+/// | len (u64) | bytes (len bytes) |
+///
+/// In the Text format, the bytes are followed by a line break that isn't
+/// counted in len, so that whatever follows the blob starts on a new line.
+///
+/// Blobs are never changed once they're written, so it's always safe to
+/// publish the address of a blob as soon as the write returns.
+pub(crate) struct Blob;
+
+impl Blob {
+    /// Writes the bytes to a new blob at the end of the file, returning the
+    /// address of the blob.
+    pub(crate) fn append(
+        bytes: &[u8],
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) -> io::Result<Addr> {
+        let addr = Addr::new(backend.len()?);
+        let mut blob = (bytes.len() as u64).to_bytes(io_metadata);
+        blob.extend_from_slice(bytes);
+        if *io_metadata.get_tsdf_metadata().get_file_format()
+            == FileFormat::Text
+        {
+            blob.push(b'\n');
+        }
+        backend.write_all_at(&blob, addr.get_loc())?;
+
+        Ok(addr)
+    }
+
+    /// Writes the string to a new blob at the end of the file, returning the
+    /// address of the blob.
+    pub(crate) fn append_str(
+        string: &str,
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) -> io::Result<Addr> {
+        Self::append(string.as_bytes(), backend, io_metadata)
+    }
+
+    /// Reads the blob at the given address.
+    pub(crate) fn read(
+        addr: Addr,
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) -> io::Result<Vec<u8>> {
+        // Check the length before allocating, so that a corrupt length can't
+        // make us allocate an absurd amount of memory.
+        let len_size = u64::get_size_on_disk(io_metadata);
+        let file_len = backend.len()?;
+        let data_loc = addr.get_loc() + len_size;
+        if data_loc > file_len {
+            return Err(invalid_blob(addr));
+        }
+        let mut len_bytes = vec![0; len_size as usize];
+        backend.read_full_at(&mut len_bytes, addr.get_loc())?;
+        let len = u64::from_bytes(&len_bytes, io_metadata);
+        if len > file_len - data_loc {
+            return Err(invalid_blob(addr));
+        }
+
+        let mut bytes = vec![0; len as usize];
+        backend.read_full_at(&mut bytes, data_loc)?;
+        Ok(bytes)
+    }
+
+    /// Reads the blob at the given address as a utf-8 string.
+    pub(crate) fn read_string(
+        addr: Addr,
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) -> io::Result<String> {
+        let bytes = Self::read(addr, backend, io_metadata)?;
+        String::from_utf8(bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Makes the error returned when there's no valid blob at an address.
+fn invalid_blob(addr: Addr) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("No valid blob at {}.", addr.get_loc()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        enums::{FileFormat, IoMode, WriteMode},
+        structs::{MemoryBackend, TsdfMetadata},
+    };
+
+    /// Make sure that blobs round trip in both file formats, and that a blob
+    /// whose length runs past the end of the file is rejected.
+    #[test]
+    fn test_blob_round_trip() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = IoMetadata::new(
+                TsdfMetadata::new("no_version".to_string(), file_format),
+                IoMode::Write(WriteMode::LocklessWrite),
+            );
+            let backend = MemoryBackend::from_bytes(vec![0; 3]);
+            let empty = Blob::append(&[], &backend, &io_metadata).unwrap();
+            let name =
+                Blob::append_str("détecteur", &backend, &io_metadata).unwrap();
+
+            assert_eq!(empty.get_loc(), 3);
+            assert!(Blob::read(empty, &backend, &io_metadata)
+                .unwrap()
+                .is_empty());
+            assert_eq!(
+                Blob::read_string(name, &backend, &io_metadata).unwrap(),
+                "détecteur"
+            );
+
+            // Chop the end off the last blob, past the line break that
+            // follows it in the Text format.
+            let bytes = backend.to_bytes();
+            let backend =
+                MemoryBackend::from_bytes(bytes[..bytes.len() - 2].to_vec());
+            assert!(Blob::read(name, &backend, &io_metadata).is_err());
+        }
+    }
+}
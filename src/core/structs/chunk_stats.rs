@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::traits::{
+    parse_text, to_text, ArrayElement, FileSerializable, FixedSizeOnDisk,
+};
+
+/// A summary of the values in one chunk of an array, written when the chunk's
+/// last frame is. NaNs are counted separately, and are left out of everything
+/// else, so the mean of a chunk is its sum divided by its count.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ChunkStats {
+    /// The smallest value, or NaN if there were no values that weren't NaN.
+    min: f64,
+
+    /// The largest value, or NaN if there were no values that weren't NaN.
+    max: f64,
+
+    /// The sum of the values.
+    sum: f64,
+
+    /// The number of values, not including NaNs.
+    count: u64,
+
+    /// The number of NaNs.
+    nan_count: u64,
+}
+
+impl ChunkStats {
+    /// Summarizes the given values.
+    pub(crate) fn of<T: ArrayElement>(values: &[T]) -> Self {
+        let mut stats = Self::null();
+        for value in values {
+            let value = value.to_f64();
+            if value.is_nan() {
+                stats.nan_count += 1;
+                continue;
+            }
+
+            // f64::min and f64::max ignore NaN, so the first value replaces
+            // the NaNs that null starts with.
+            stats.min = stats.min.min(value);
+            stats.max = stats.max.max(value);
+            stats.sum += value;
+            stats.count += 1;
+        }
+        stats
+    }
+
+    /// Combines the stats of two sets of values into the stats of both.
+    pub fn merge(&self, other: &ChunkStats) -> ChunkStats {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            sum: self.sum + other.sum,
+            count: self.count + other.count,
+            nan_count: self.nan_count + other.nan_count,
+        }
+    }
+
+    /// Returns the smallest value, or NaN if every value was NaN.
+    pub fn get_min(&self) -> f64 {
+        self.min
+    }
+
+    /// Returns the largest value, or NaN if every value was NaN.
+    pub fn get_max(&self) -> f64 {
+        self.max
+    }
+
+    /// Returns the sum of the values that weren't NaN.
+    pub fn get_sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Returns the number of values that weren't NaN.
+    pub fn get_count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the number of NaNs.
+    pub fn get_nan_count(&self) -> u64 {
+        self.nan_count
+    }
+
+    /// Returns the mean of the values that weren't NaN, or NaN if there were
+    /// none.
+    pub fn get_mean(&self) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+        self.sum / self.count as f64
+    }
+
+    /// The fields of the stats in the Text format. Like f64 elements, the
+    /// floats are written with their Debug representation, as json can't
+    /// hold NaN.
+    fn text_fields(&self) -> [(String, String); 5] {
+        [
+            ("count".to_string(), self.count.to_string()),
+            ("max".to_string(), format!("{:?}", self.max)),
+            ("min".to_string(), format!("{:?}", self.min)),
+            ("nan_count".to_string(), self.nan_count.to_string()),
+            ("sum".to_string(), format!("{:?}", self.sum)),
+        ]
+    }
+}
+
+impl FixedSizeOnDisk for ChunkStats {
+    fn get_bin_size_on_disk() -> u64 {
+        8 * 5
+    }
+
+    fn get_json_size_on_disk() -> u64 {
+        // No f64 has a longer Debug representation than this one.
+        let longest_float = "-1.2345678901234567e-308".to_string();
+        let mut fields = ChunkStats::null().text_fields();
+        for (label, value) in fields.iter_mut() {
+            *value = match label.as_str() {
+                "count" | "nan_count" => u64::MAX.to_string(),
+                _ => longest_float.clone(),
+            };
+        }
+        to_text(Self::TEXT_MARKER, &fields, 0).len() as u64
+    }
+}
+
+impl FileSerializable for ChunkStats {
+    const TEXT_MARKER: &'static str = "ChunkStats";
+
+    fn null() -> Self {
+        Self {
+            min: f64::NAN,
+            max: f64::NAN,
+            sum: 0.0,
+            count: 0,
+            nan_count: 0,
+        }
+    }
+
+    fn to_bin(&self) -> Vec<u8> {
+        let mut bytes = self.min.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.max.to_le_bytes());
+        bytes.extend_from_slice(&self.sum.to_le_bytes());
+        bytes.extend_from_slice(&self.count.to_le_bytes());
+        bytes.extend_from_slice(&self.nan_count.to_le_bytes());
+        bytes
+    }
+
+    fn from_bin(bytes: &[u8]) -> Self {
+        let word = |i: usize| bytes[i * 8..i * 8 + 8].try_into().unwrap();
+        Self {
+            min: f64::from_le_bytes(word(0)),
+            max: f64::from_le_bytes(word(1)),
+            sum: f64::from_le_bytes(word(2)),
+            count: u64::from_le_bytes(word(3)),
+            nan_count: u64::from_le_bytes(word(4)),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        to_text(
+            Self::TEXT_MARKER,
+            &self.text_fields(),
+            Self::get_json_size_on_disk(),
+        )
+    }
+
+    fn get_text_len(&self) -> u64 {
+        to_text(Self::TEXT_MARKER, &self.text_fields(), 0).len() as u64
+    }
+
+    fn from_json(json: String) -> Self {
+        let fields = match parse_text(Self::TEXT_MARKER, &json) {
+            Ok(fields) => fields,
+            Err(_) => return Self::null(),
+        };
+
+        // If any field is garbage, so is the rest of the stats.
+        let mut stats = Self::null();
+        for (label, value) in fields {
+            let parsed = match label.as_str() {
+                "count" => value.parse().map(|v| stats.count = v).is_ok(),
+                "max" => value.parse().map(|v| stats.max = v).is_ok(),
+                "min" => value.parse().map(|v| stats.min = v).is_ok(),
+                "nan_count" => {
+                    value.parse().map(|v| stats.nan_count = v).is_ok()
+                }
+                "sum" => value.parse().map(|v| stats.sum = v).is_ok(),
+                _ => true,
+            };
+            if !parsed {
+                return Self::null();
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stats should leave NaNs out of everything but nan_count, and survive a
+    /// round trip through both formats even when their min and max are NaN.
+    #[test]
+    fn test_chunk_stats() {
+        let stats = ChunkStats::of(&[2.0, f64::NAN, -1.0, 5.0]);
+        assert_eq!(stats.get_min(), -1.0);
+        assert_eq!(stats.get_max(), 5.0);
+        assert_eq!(stats.get_sum(), 6.0);
+        assert_eq!(stats.get_count(), 3);
+        assert_eq!(stats.get_nan_count(), 1);
+        assert_eq!(stats.get_mean(), 2.0);
+
+        let all_nan = ChunkStats::of(&[f32::NAN, f32::NAN]);
+        assert!(all_nan.get_min().is_nan() && all_nan.get_mean().is_nan());
+        let merged = all_nan.merge(&stats);
+        assert_eq!((merged.get_min(), merged.get_nan_count()), (-1.0, 3));
+
+        for stats in [stats, all_nan, ChunkStats::of(&[u64::MAX])] {
+            let bytes = stats.to_bin();
+            assert_eq!(bytes.len() as u64, ChunkStats::get_bin_size_on_disk());
+            assert_eq!(ChunkStats::from_bin(&bytes).to_bin(), bytes);
+
+            let json = stats.to_json();
+            assert_eq!(json.len() as u64, ChunkStats::get_json_size_on_disk());
+            assert_eq!(ChunkStats::from_json(json).to_bin(), bytes);
+        }
+    }
+}
@@ -0,0 +1,819 @@
+use std::{
+    any::Any,
+    io,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::core::{
+    enums::{CrashMode, StorageOp},
+    traits::StorageBackend,
+};
+
+use super::{CrashReport, FaultInjectingBackend, MemoryBackend};
+
+/// The maximum number of bytes returned by a single read while verifying a
+/// crash image. Keeping this small makes sure that every reader copes with
+/// short reads.
+#[allow(dead_code)]
+const VERIFY_MAX_READ_LEN: usize = 3;
+
+/// The CrashHarness proves that readers can always make sense of a file, no
+/// matter when the writer crashed.
+///
+/// A workload is recorded once, on a FaultInjectingBackend. The harness then
+/// rebuilds the bytes that storage could have held if the writer had crashed
+/// at each possible point, under each CrashMode, and runs a verifier against
+/// every one of these crash images. The verifier typically opens the
+/// structures written by the workload, calls their verify() methods and checks
+/// that everything a reader can see was really written.
+///
+/// Every crash image is a different file, so a verifier must not share an
+/// IoMetadata (and with it, a shard cache) between images.
+///
+/// Anything written by the setup closure is treated as durable, and is never
+/// crashed. This is the place to create the structures that the workload then
+/// writes to.
+// Shipped with the library, but only the crash tests run it for now.
+#[allow(dead_code)]
+pub(crate) struct CrashHarness {
+    /// The bytes in storage once setup had finished.
+    base: Vec<u8>,
+
+    /// Every write and sync issued by the workload, in order.
+    ops: Vec<StorageOp>,
+
+    /// How many writes back an unsynced write can be dropped in
+    /// CrashMode::Reordered.
+    reorder_window: usize,
+}
+
+#[allow(dead_code)]
+impl CrashHarness {
+    /// Runs setup and then the workload, recording everything that the
+    /// workload writes.
+    pub(crate) fn record<S, W>(setup: S, workload: W) -> Self
+    where
+        S: FnOnce(&dyn StorageBackend),
+        W: FnOnce(&dyn StorageBackend),
+    {
+        let setup_backend = MemoryBackend::new();
+        setup(&setup_backend);
+        let base = setup_backend.to_bytes();
+
+        let backend = FaultInjectingBackend::from_bytes(base.clone());
+        workload(&backend);
+
+        Self {
+            base,
+            ops: backend.get_ops(),
+            reorder_window: 8,
+        }
+    }
+
+    /// Sets how many writes back an unsynced write can be dropped in
+    /// CrashMode::Reordered.
+    pub(crate) fn with_reorder_window(mut self, reorder_window: usize) -> Self {
+        self.reorder_window = reorder_window;
+        self
+    }
+
+    /// Returns every write that the workload issued, in order, along with the
+    /// number of syncs that came before it.
+    fn get_writes(&self) -> Vec<(u64, &[u8], usize)> {
+        let mut num_syncs = 0;
+        let mut writes = Vec::new();
+        for op in &self.ops {
+            match op {
+                StorageOp::Write { offset, bytes } => {
+                    writes.push((*offset, bytes.as_slice(), num_syncs))
+                }
+                StorageOp::Sync => num_syncs += 1,
+            }
+        }
+
+        writes
+    }
+
+    /// Returns the number of writes issued by the workload.
+    pub(crate) fn get_num_writes(&self) -> usize {
+        self.get_writes().len()
+    }
+
+    /// Returns every crash image for the given mode, along with a description
+    /// of how each one was made.
+    pub(crate) fn crash_images(
+        &self,
+        mode: CrashMode,
+    ) -> Vec<(String, Vec<u8>)> {
+        let writes = self.get_writes();
+
+        // Builds an image by applying the given writes on top of the base.
+        let apply = |indices: &mut dyn Iterator<Item = usize>| {
+            let backend = MemoryBackend::from_bytes(self.base.clone());
+            for i in indices {
+                let (offset, bytes, _) = writes[i];
+                backend.write_all_at(bytes, offset).unwrap();
+            }
+            backend
+        };
+
+        let mut images = Vec::new();
+        match mode {
+            CrashMode::Prefix => {
+                for n in 0..=writes.len() {
+                    let image = apply(&mut (0..n)).to_bytes();
+                    images.push((format!("first {} writes", n), image));
+                }
+            }
+            CrashMode::TornWrite => {
+                for (n, (offset, bytes, _)) in writes.iter().enumerate() {
+                    let mut splits = vec![1, bytes.len() / 2, bytes.len() - 1];
+                    splits.retain(|split| *split > 0 && *split < bytes.len());
+                    splits.dedup();
+
+                    for split in splits {
+                        // Only the start of write n made it to storage.
+                        let backend = apply(&mut (0..n));
+                        backend.write_all_at(&bytes[..split], *offset).unwrap();
+                        images.push((
+                            format!(
+                                "first {} writes, {} byte head of next",
+                                n, split
+                            ),
+                            backend.to_bytes(),
+                        ));
+
+                        // Only the end of write n made it to storage.
+                        let backend = apply(&mut (0..n));
+                        let tail_offset = offset + split as u64;
+                        backend
+                            .write_all_at(&bytes[split..], tail_offset)
+                            .unwrap();
+                        images.push((
+                            format!(
+                                "first {} writes, {} byte tail of next",
+                                n, split
+                            ),
+                            backend.to_bytes(),
+                        ));
+                    }
+                }
+            }
+            CrashMode::Reordered => {
+                for (description, image, _) in self.reordered_images() {
+                    images.push((description, image));
+                }
+            }
+        }
+
+        images
+    }
+
+    /// Returns every CrashMode::Reordered image, along with a description of
+    /// how each one was made and the number of syncs that completed before
+    /// the crash. Every write issued before the last of those syncs is in the
+    /// image.
+    fn reordered_images(&self) -> Vec<(String, Vec<u8>, usize)> {
+        let writes = self.get_writes();
+        let mut images = Vec::new();
+
+        // Write k made it to storage, but an earlier write j didn't. This is
+        // only possible if there was no sync between them.
+        for (k, (_, _, k_syncs)) in writes.iter().enumerate() {
+            let first = k.saturating_sub(self.reorder_window);
+            let unsynced = writes[first..k]
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, _, j_syncs))| j_syncs == k_syncs)
+                .map(|(i, _)| first + i);
+            for j in unsynced {
+                let backend = MemoryBackend::from_bytes(self.base.clone());
+                for (i, (offset, bytes, _)) in writes[..=k].iter().enumerate() {
+                    if i != j {
+                        backend.write_all_at(bytes, *offset).unwrap();
+                    }
+                }
+                images.push((
+                    format!("first {} writes, without write {}", k + 1, j),
+                    backend.to_bytes(),
+                    *k_syncs,
+                ));
+            }
+        }
+
+        images
+    }
+
+    /// Runs the verifier against every crash image for the given mode, and
+    /// returns a report for every image that failed. The verifier sees the
+    /// image through a backend that only ever returns short reads. A verifier
+    /// that panics counts as a failure.
+    pub(crate) fn check<V>(
+        &self,
+        mode: CrashMode,
+        verifier: V,
+    ) -> Vec<CrashReport>
+    where
+        V: Fn(&dyn StorageBackend) -> io::Result<()>,
+    {
+        let mut reports = Vec::new();
+        for (description, image) in self.crash_images(mode) {
+            let backend = FaultInjectingBackend::from_bytes(image)
+                .with_max_read_len(VERIFY_MAX_READ_LEN);
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| verifier(&backend)));
+
+            if let Some(error) = get_failure(result) {
+                reports.push(CrashReport::new(mode, description, error));
+            }
+        }
+
+        reports
+    }
+
+    /// Runs the verifier against every CrashMode::Reordered image, and returns
+    /// a report for every image that failed. Along with the image, the
+    /// verifier is given the number of syncs that completed before the crash,
+    /// so that it can check that everything written before them survived.
+    pub(crate) fn check_synced<V>(&self, verifier: V) -> Vec<CrashReport>
+    where
+        V: Fn(&dyn StorageBackend, usize) -> io::Result<()>,
+    {
+        let mut reports = Vec::new();
+        for (description, image, num_syncs) in self.reordered_images() {
+            let backend = FaultInjectingBackend::from_bytes(image)
+                .with_max_read_len(VERIFY_MAX_READ_LEN);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                verifier(&backend, num_syncs)
+            }));
+            if let Some(error) = get_failure(result) {
+                reports.push(CrashReport::new(
+                    CrashMode::Reordered,
+                    description,
+                    error,
+                ));
+            }
+        }
+
+        reports
+    }
+
+    /// Runs the verifier against every crash image for every mode.
+    pub(crate) fn check_all<V>(&self, verifier: V) -> Vec<CrashReport>
+    where
+        V: Fn(&dyn StorageBackend) -> io::Result<()>,
+    {
+        CrashMode::ALL
+            .iter()
+            .flat_map(|mode| self.check(*mode, &verifier))
+            .collect()
+    }
+}
+
+/// Returns why a verifier failed, or None if it succeeded.
+#[allow(dead_code)]
+fn get_failure(
+    result: Result<io::Result<()>, Box<dyn Any + Send>>,
+) -> Option<String> {
+    match result {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(payload) => {
+            Some(format!("verifier panicked: {}", panic_message(payload)))
+        }
+    }
+}
+
+/// Extracts the message from a panic payload, if there is one.
+#[allow(dead_code)]
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::core::{
+        enums::{ArrayDataType, Durability, FileFormat},
+        structs::{
+            Addr, Array, ArrayOptions, Dir, DistDict, DistList, IoMetadata,
+        },
+        test_utils::make_io_metadata,
+        traits::{ArrayTrait, DistDictTrait, DistListTrait},
+    };
+
+    /// Make the IoMetadata used by the tests, syncing at the end of every high
+    /// level write.
+    fn make_synced_io_metadata(file_format: FileFormat) -> IoMetadata {
+        let mut io_metadata = make_io_metadata(file_format);
+        io_metadata.set_durability(Durability::Synced);
+        io_metadata
+    }
+
+    /// Returns an InvalidData error with the given message.
+    fn invalid(msg: String) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    /// Panics with every report, if there are any.
+    fn assert_no_reports(reports: Vec<CrashReport>) {
+        let msgs: Vec<String> = reports.iter().map(|r| r.to_string()).collect();
+        assert!(
+            msgs.is_empty(),
+            "{} crash images failed:\n{}",
+            msgs.len(),
+            msgs.join("\n")
+        );
+    }
+
+    /// Records a workload that appends to a DistList of i64, syncing after
+    /// every element if asked to.
+    fn record_list_workload(
+        io_metadata: &IoMetadata,
+        expected: &[i64],
+        sync: bool,
+    ) -> CrashHarness {
+        CrashHarness::record(
+            |backend| {
+                let mut list: DistList<'_, '_, i64> =
+                    DistList::new(Addr::new(0), io_metadata, backend, false);
+                list.init();
+            },
+            |backend| {
+                let mut list: DistList<'_, '_, i64> =
+                    DistList::new(Addr::new(0), io_metadata, backend, true);
+                for elem in expected {
+                    list.add(elem);
+                    if sync {
+                        backend.sync().unwrap();
+                    }
+                }
+            },
+        )
+    }
+
+    /// Checks that a DistList of i64 is well formed and holds a prefix of the
+    /// expected elements.
+    fn verify_list(
+        backend: &dyn StorageBackend,
+        file_format: FileFormat,
+        expected: &[i64],
+    ) -> io::Result<()> {
+        let io_metadata = make_io_metadata(file_format);
+        let list: DistList<'_, '_, i64> =
+            DistList::new(Addr::new(0), &io_metadata, backend, true);
+        list.verify()?;
+
+        let elems = list.to_vec();
+        if list.len() != elems.len() || !expected.starts_with(&elems) {
+            return Err(invalid(format!(
+                "Unexpected list contents {:?}.",
+                elems
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that the first num_synced elements of a DistList of i64 are
+    /// the expected ones.
+    fn verify_synced_list(
+        backend: &dyn StorageBackend,
+        file_format: FileFormat,
+        expected: &[i64],
+        num_synced: usize,
+    ) -> io::Result<()> {
+        let io_metadata = make_io_metadata(file_format);
+        let list: DistList<'_, '_, i64> =
+            DistList::new(Addr::new(0), &io_metadata, backend, true);
+        for (i, elem) in expected[..num_synced].iter().enumerate() {
+            if list.get(i) != Some(*elem) {
+                return Err(invalid(format!(
+                    "Synced element {} is {:?}.",
+                    i,
+                    list.get(i)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Crash a workload that adds to and updates a DistDict at every possible
+    /// point, in both file formats. The workload syncs after every change, and
+    /// every change synced before a crash must survive it.
+    #[test]
+    fn test_dist_dict_survives_crashes() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_io_metadata(file_format);
+            let harness = CrashHarness::record(
+                |backend| {
+                    let mut dict: DistDict<'_, '_, String, Addr> =
+                        DistDict::new(
+                            Addr::new(0),
+                            &io_metadata,
+                            backend,
+                            false,
+                        );
+                    dict.init();
+                },
+                |backend| {
+                    let mut dict: DistDict<'_, '_, String, Addr> =
+                        DistDict::new(
+                            Addr::new(0),
+                            &io_metadata,
+                            backend,
+                            true,
+                        );
+                    for i in 0..20 {
+                        dict.add(&format!("key_{}", i), &Addr::new(i + 1));
+                        backend.sync().unwrap();
+                    }
+                    // Update a few values in place.
+                    for i in 0..5 {
+                        dict.add(&format!("key_{}", i), &Addr::new(100 + i));
+                        backend.sync().unwrap();
+                    }
+                },
+            );
+
+            // Every key's value must be one of the values written for it.
+            let mut allowed: HashMap<String, Vec<Addr>> = HashMap::new();
+            for i in 0..20 {
+                allowed.insert(format!("key_{}", i), vec![Addr::new(i + 1)]);
+            }
+            for i in 0..5 {
+                allowed
+                    .get_mut(&format!("key_{}", i))
+                    .unwrap()
+                    .push(Addr::new(100 + i));
+            }
+
+            let verifier = |backend: &dyn StorageBackend| {
+                let io_metadata = make_io_metadata(file_format);
+                let dict: DistDict<'_, '_, String, Addr> =
+                    DistDict::new(Addr::new(0), &io_metadata, backend, true);
+                dict.verify()?;
+                for (key, vals) in &allowed {
+                    if let Some(val) = dict.get(key) {
+                        if !vals.contains(&val) {
+                            return Err(invalid(format!(
+                                "{} has value {:?}.",
+                                key, val
+                            )));
+                        }
+                    }
+                }
+                Ok(())
+            };
+
+            // Every change synced must be visible, unless a later change to
+            // the same key replaced it.
+            let changes: Vec<(String, Addr)> = (0..20)
+                .map(|i| (format!("key_{}", i), Addr::new(i + 1)))
+                .chain(
+                    (0..5).map(|i| (format!("key_{}", i), Addr::new(100 + i))),
+                )
+                .collect();
+            let synced_verifier =
+                |backend: &dyn StorageBackend, num_synced: usize| {
+                    let io_metadata = make_io_metadata(file_format);
+                    let dict: DistDict<'_, '_, String, Addr> = DistDict::new(
+                        Addr::new(0),
+                        &io_metadata,
+                        backend,
+                        true,
+                    );
+                    for (n, (key, val)) in
+                        changes[..num_synced].iter().enumerate()
+                    {
+                        let later = changes[n + 1..]
+                            .iter()
+                            .filter(|(k, _)| k == key)
+                            .map(|(_, v)| *v);
+                        let found = dict.get(key);
+                        if !found.is_some_and(|found| {
+                            found == *val || later.clone().any(|v| v == found)
+                        }) {
+                            return Err(invalid(format!(
+                                "Synced {} has value {:?}.",
+                                key, found
+                            )));
+                        }
+                    }
+                    Ok(())
+                };
+
+            assert_no_reports(harness.check(CrashMode::Prefix, verifier));
+            assert_no_reports(harness.check(CrashMode::TornWrite, verifier));
+            assert_no_reports(harness.check_synced(synced_verifier));
+        }
+    }
+
+    /// Crash a workload that appends to a DistList at every possible point.
+    /// The workload syncs after every element, and every element synced
+    /// before a crash must survive it.
+    #[test]
+    fn test_dist_list_survives_crashes() {
+        let expected: Vec<i64> = (1..=30).collect();
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_io_metadata(file_format);
+            let harness = record_list_workload(&io_metadata, &expected, true);
+            let verifier = |backend: &dyn StorageBackend| {
+                verify_list(backend, file_format, &expected)
+            };
+            let synced_verifier =
+                |backend: &dyn StorageBackend, num_synced: usize| {
+                    verify_synced_list(
+                        backend,
+                        file_format,
+                        &expected,
+                        num_synced,
+                    )
+                };
+
+            assert_no_reports(harness.check(CrashMode::Prefix, verifier));
+            assert_no_reports(harness.check(CrashMode::TornWrite, verifier));
+            assert_no_reports(harness.check_synced(synced_verifier));
+        }
+    }
+
+    /// Crash a workload that appends frames to an Array at every possible
+    /// point. The array is synced after every append, and every frame synced
+    /// before a crash must survive it.
+    #[test]
+    fn test_array_survives_crashes() {
+        let frames: Vec<Vec<f32>> = (0..20)
+            .map(|i| vec![i as f32 + 0.5, -(i as f32), 1e3 * i as f32])
+            .collect();
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_synced_io_metadata(file_format);
+            let harness = CrashHarness::record(
+                |backend| {
+                    Array::create(
+                        Addr::new(0),
+                        ArrayOptions::new()
+                            .data_type(ArrayDataType::Float32)
+                            .frame_shape(&[3])
+                            .frames_per_chunk(4),
+                        &io_metadata,
+                        backend,
+                    )
+                    .unwrap();
+                },
+                |backend| {
+                    let array =
+                        Array::open(Addr::new(0), &io_metadata, backend)
+                            .unwrap();
+                    for frame in &frames {
+                        array.append(frame).unwrap();
+                    }
+                },
+            );
+
+            let expected = frames.concat();
+            let verifier = |backend: &dyn StorageBackend| {
+                let io_metadata = make_io_metadata(file_format);
+                let array = Array::open(Addr::new(0), &io_metadata, backend)?;
+                array.verify()?;
+                let data = array.get_frames::<f32>(0, array.len())?;
+                if !expected.starts_with(&data) {
+                    return Err(invalid(format!(
+                        "Unexpected array data {:?}.",
+                        data
+                    )));
+                }
+                Ok(())
+            };
+
+            // Every frame synced must read back as it was appended.
+            let synced_verifier =
+                |backend: &dyn StorageBackend, num_synced: usize| {
+                    let io_metadata = make_io_metadata(file_format);
+                    let array =
+                        Array::open(Addr::new(0), &io_metadata, backend)?;
+                    let data = array.get_frames::<f32>(0, num_synced)?;
+                    if data != frames[..num_synced].concat() {
+                        return Err(invalid(format!(
+                            "Unexpected synced data {:?}.",
+                            data
+                        )));
+                    }
+                    Ok(())
+                };
+
+            assert_no_reports(harness.check(CrashMode::Prefix, verifier));
+            assert_no_reports(harness.check(CrashMode::TornWrite, verifier));
+            assert_no_reports(harness.check_synced(synced_verifier));
+        }
+    }
+
+    /// Crash a workload that appends batches of frames to an Array, so that
+    /// both frame data and flags are written many at a time.
+    #[test]
+    fn test_batched_array_survives_crashes() {
+        let data: Vec<u16> = (0..60).collect();
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let harness = CrashHarness::record(
+            |backend| {
+                Array::create(
+                    Addr::new(0),
+                    ArrayOptions::new()
+                        .data_type(ArrayDataType::UInt16)
+                        .frame_shape(&[2])
+                        .frames_per_chunk(8),
+                    &io_metadata,
+                    backend,
+                )
+                .unwrap();
+            },
+            |backend| {
+                let array =
+                    Array::open(Addr::new(0), &io_metadata, backend).unwrap();
+                for batch in data.chunks(14) {
+                    array.append_frames(batch).unwrap();
+                }
+            },
+        );
+
+        let verifier = |backend: &dyn StorageBackend| {
+            let io_metadata = make_io_metadata(FileFormat::Binary);
+            let array = Array::open(Addr::new(0), &io_metadata, backend)?;
+            array.verify()?;
+            let read = array.get_frames::<u16>(0, array.len())?;
+            if !data.starts_with(&read) {
+                return Err(invalid(format!(
+                    "Unexpected array data {:?}.",
+                    read
+                )));
+            }
+            Ok(())
+        };
+
+        assert_no_reports(harness.check(CrashMode::Prefix, verifier));
+        assert_no_reports(harness.check(CrashMode::TornWrite, verifier));
+    }
+
+    /// Crash a workload that adds children and tags to a Dir. Every child
+    /// that a reader can see must open cleanly, and the children must be
+    /// those created first. The Dir is synced after every change, and every
+    /// change synced before a crash must survive it.
+    #[test]
+    fn test_dir_survives_crashes() {
+        let names = ["a", "b", "c", "d"];
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_synced_io_metadata(file_format);
+            let harness = CrashHarness::record(
+                |backend| {
+                    Dir::create(Addr::new(0), "/", &io_metadata, backend)
+                        .unwrap();
+                },
+                |backend| {
+                    let root =
+                        Dir::open(Addr::new(0), "/", &io_metadata, backend)
+                            .unwrap();
+                    for name in names {
+                        root.create_dir(name).unwrap();
+                        root.set_tag("last", name).unwrap();
+                    }
+                },
+            );
+
+            let verifier = |backend: &dyn StorageBackend| {
+                let io_metadata = make_io_metadata(file_format);
+                let root = Dir::open(Addr::new(0), "/", &io_metadata, backend)?;
+                let children = root.list_dir()?;
+                if children.len() > names.len()
+                    || !children.iter().zip(names).all(|(c, n)| c == n)
+                {
+                    return Err(invalid(format!(
+                        "Unexpected children {:?}.",
+                        children
+                    )));
+                }
+                for child in &children {
+                    root.get_dir(child)?.list_dir()?;
+                }
+                if let Some(last) = root.get_tag("last")? {
+                    if !names.contains(&last.as_str()) {
+                        return Err(invalid(format!(
+                            "Unexpected tag {}.",
+                            last
+                        )));
+                    }
+                }
+                Ok(())
+            };
+
+            // The workload alternates between creating a child and tagging
+            // the Dir with its name. create_dir syncs once, and set_tag syncs
+            // both before and after it publishes the new value. Every child
+            // synced must open cleanly, and the tag must be the last one
+            // synced or a later one.
+            let synced_verifier =
+                |backend: &dyn StorageBackend, num_synced: usize| {
+                    let io_metadata = make_io_metadata(file_format);
+                    let root =
+                        Dir::open(Addr::new(0), "/", &io_metadata, backend)?;
+                    for name in &names[..num_synced.div_ceil(3)] {
+                        root.get_dir(name)?.list_dir()?;
+                    }
+
+                    let num_synced_tags = num_synced / 3;
+                    if num_synced_tags > 0 {
+                        let tag = root.get_tag("last")?;
+                        let later = &names[num_synced_tags - 1..];
+                        if !tag.as_ref().is_some_and(|t| later.contains(&&**t))
+                        {
+                            return Err(invalid(format!(
+                                "Unexpected synced tag {:?}.",
+                                tag
+                            )));
+                        }
+                    }
+                    Ok(())
+                };
+
+            assert_no_reports(harness.check(CrashMode::Prefix, verifier));
+            assert_no_reports(harness.check(CrashMode::TornWrite, verifier));
+            assert_no_reports(harness.check_synced(synced_verifier));
+        }
+    }
+
+    /// Nothing orders the writes of an unsynced workload, so the harness must
+    /// be able to find a reordering that a reader can't make sense of.
+    #[test]
+    fn test_reordered_detects_unsynced_writes() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let expected: Vec<i64> = (1..=10).collect();
+        let harness = record_list_workload(&io_metadata, &expected, false);
+        let verifier = |backend: &dyn StorageBackend| {
+            verify_list(backend, FileFormat::Binary, &expected)
+        };
+
+        let reports = harness.check(CrashMode::Reordered, verifier);
+        assert!(!reports.is_empty());
+        assert!(reports.iter().all(|r| r.get_mode() == CrashMode::Reordered));
+    }
+
+    /// A sync stops the harness from dropping the writes that came before it.
+    #[test]
+    fn test_reordered_respects_syncs() {
+        let harness = CrashHarness::record(
+            |_| {},
+            |backend| {
+                backend.write_all_at(&[1], 0).unwrap();
+                backend.sync().unwrap();
+                backend.write_all_at(&[2], 1).unwrap();
+                backend.write_all_at(&[3], 2).unwrap();
+            },
+        );
+
+        assert_eq!(harness.get_num_writes(), 3);
+        let images = harness.crash_images(CrashMode::Reordered);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].1, vec![1, 0, 3]);
+
+        // With no window, nothing can be reordered.
+        let harness = harness.with_reorder_window(0);
+        assert!(harness.crash_images(CrashMode::Reordered).is_empty());
+    }
+
+    /// Make sure that the prefix and torn images are what we expect, and that
+    /// a failing verifier is reported.
+    #[test]
+    fn test_prefix_and_torn_images() {
+        let harness = CrashHarness::record(
+            |backend| backend.write_all_at(b"base", 0).unwrap(),
+            |backend| {
+                backend.write_all_at(b"wxyz", 0).unwrap();
+            },
+        );
+
+        let prefix = harness.crash_images(CrashMode::Prefix);
+        let prefix: Vec<Vec<u8>> = prefix.into_iter().map(|(_, b)| b).collect();
+        assert_eq!(prefix, vec![b"base".to_vec(), b"wxyz".to_vec()]);
+
+        let torn = harness.crash_images(CrashMode::TornWrite);
+        let torn: Vec<Vec<u8>> = torn.into_iter().map(|(_, b)| b).collect();
+        assert!(torn.contains(&b"wase".to_vec()));
+        assert!(torn.contains(&b"bayz".to_vec()));
+        assert!(torn.contains(&b"bxyz".to_vec()));
+
+        let reports = harness.check_all(|backend| {
+            let mut buf = [0; 4];
+            backend.read_full_at(&mut buf, 0)?;
+            assert!(&buf == b"base" || &buf == b"wxyz");
+            Ok(())
+        });
+        assert!(!reports.is_empty());
+        assert!(reports.iter().all(|r| r.get_error().contains("panicked")));
+        assert!(!reports[0].get_description().is_empty());
+    }
+}
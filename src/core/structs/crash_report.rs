@@ -0,0 +1,55 @@
+use std::fmt;
+
+use crate::core::enums::CrashMode;
+
+/// A single crash image that failed verification.
+// Only the CrashHarness makes these.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CrashReport {
+    /// The kind of crash that produced the image.
+    mode: CrashMode,
+
+    /// A human readable description of which writes made it to storage.
+    description: String,
+
+    /// Why the image failed verification.
+    error: String,
+}
+
+#[allow(dead_code)]
+impl CrashReport {
+    /// Constructs a new CrashReport.
+    pub(crate) fn new(
+        mode: CrashMode,
+        description: String,
+        error: String,
+    ) -> Self {
+        Self {
+            mode,
+            description,
+            error,
+        }
+    }
+
+    /// Returns the kind of crash that produced the image.
+    pub(crate) fn get_mode(&self) -> CrashMode {
+        self.mode
+    }
+
+    /// Returns a description of which writes made it to storage.
+    pub(crate) fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns why the image failed verification.
+    pub(crate) fn get_error(&self) -> &str {
+        &self.error
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} ({}): {}", self.mode, self.description, self.error)
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, io, path::Path};
+
+use crate::core::{enums::ArrayDataType, export::read_csv};
+
+use super::Dir;
+
+/// Options for importing a CSV file into a Dir, in the style of OpenOptions.
+///
+/// ```no_run
+/// use tsdf::{ArrayDataType, CsvImportOptions, TsdfFile};
+///
+/// # fn main() -> tsdf::Result<()> {
+/// let file = TsdfFile::new_writer("run.tsdf", None, None)?;
+/// let root = file.get_root_dir()?;
+/// let scan = root.create_dir("scan")?;
+/// CsvImportOptions::new()
+///     .timestamp_column("time")
+///     .column_type("counts", ArrayDataType::UInt32)
+///     .import("scan.csv", &scan)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Every column of the CSV becomes an array of scalar frames in the Dir,
+/// named after the column, with one frame per row. The first row must be the
+/// column names. Any lines starting with # before it are tags, written the
+/// way Dir::export_csv writes them.
+#[derive(Debug, Clone)]
+pub struct CsvImportOptions {
+    /// The column holding each row's timestamp, if there is one.
+    timestamp_column: Option<String>,
+
+    /// The data types of the columns that don't have the default type.
+    column_types: HashMap<String, ArrayDataType>,
+
+    /// The data type of every other column.
+    default_type: ArrayDataType,
+
+    /// The byte that separates fields.
+    delimiter: u8,
+
+    /// The frames_per_chunk of every array that's created.
+    frames_per_chunk: u64,
+}
+
+impl Default for CsvImportOptions {
+    fn default() -> Self {
+        CsvImportOptions {
+            timestamp_column: None,
+            column_types: HashMap::new(),
+            default_type: ArrayDataType::Float64,
+            delimiter: b',',
+            frames_per_chunk: 1024,
+        }
+    }
+}
+
+impl CsvImportOptions {
+    /// Constructs a new set of options, with everything left at its default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the column holding each row's timestamp. Timestamps can be
+    /// integers, which are taken to be nanoseconds since the Unix epoch, or
+    /// RFC 3339 date-times, and are stored as Int64 nanoseconds since the
+    /// epoch.
+    pub fn timestamp_column(&mut self, name: &str) -> &mut Self {
+        self.timestamp_column = Some(name.to_string());
+        self
+    }
+
+    /// Sets the data type of a column.
+    pub fn column_type(
+        &mut self,
+        name: &str,
+        data_type: ArrayDataType,
+    ) -> &mut Self {
+        self.column_types.insert(name.to_string(), data_type);
+        self
+    }
+
+    /// Sets the data type of columns without a type of their own. This is
+    /// Float64 by default.
+    pub fn default_type(&mut self, data_type: ArrayDataType) -> &mut Self {
+        self.default_type = data_type;
+        self
+    }
+
+    /// Sets the byte that separates fields. This is a comma by default.
+    pub fn delimiter(&mut self, delimiter: u8) -> &mut Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the frames_per_chunk of the arrays that are created. This is 1024
+    /// by default.
+    pub fn frames_per_chunk(&mut self, frames_per_chunk: u64) -> &mut Self {
+        self.frames_per_chunk = frames_per_chunk;
+        self
+    }
+
+    /// Streams the CSV file at the given path into new arrays in the Dir.
+    pub fn import<P: AsRef<Path>>(&self, path: P, dir: &Dir) -> io::Result<()> {
+        read_csv(dir, path.as_ref(), self)
+    }
+}
+
+// Implement the methods that read_csv uses to apply the options.
+impl CsvImportOptions {
+    /// Returns the column holding each row's timestamp, if there is one.
+    pub(crate) fn get_timestamp_column(&self) -> Option<&str> {
+        self.timestamp_column.as_deref()
+    }
+
+    /// Returns the columns that have been given data types.
+    pub(crate) fn get_column_types(&self) -> &HashMap<String, ArrayDataType> {
+        &self.column_types
+    }
+
+    /// Returns the data type of the column with the given name.
+    pub(crate) fn get_column_type(&self, name: &str) -> ArrayDataType {
+        match self.timestamp_column.as_deref() == Some(name) {
+            true => ArrayDataType::Int64,
+            false => *self.column_types.get(name).unwrap_or(&self.default_type),
+        }
+    }
+
+    /// Returns the byte that separates fields.
+    pub(crate) fn get_delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    /// Returns the frames_per_chunk of the arrays that are created.
+    pub(crate) fn get_frames_per_chunk(&self) -> u64 {
+        self.frames_per_chunk
+    }
+}
@@ -1,4 +1,450 @@
-pub struct Dir {
-    // contents: Vec<HighLevelObject>,
-    // metadata: HighLevelObjectMetadata,
+use std::{io, path::Path};
+
+use crate::core::{
+    enums::{ArrayDataType, HighLevelObject},
+    export::{write_csv, write_npz},
+    traits::{
+        DirTrait, DistDictTrait, FileSerializable, FixedSizeOnDisk,
+        HasMetadataTags, HasName, Locatable, StorageBackend,
+    },
+};
+
+#[cfg(feature = "arrow")]
+use crate::core::export::{dir_record_batch, write_dir_parquet};
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+
+use super::{
+    Addr, Array, ArrayOptions, DirEntry, DistDict, IoMetadata, TagEntry,
+    Timestamp,
+};
+
+/// A Dir stored in a tsdf file. See the DirTrait for the structure of a Dir on
+/// disk.
+pub struct Dir<'a, 'b> {
+    /// The address of this Dir in the file.
+    loc: Addr,
+
+    /// The name of this Dir within its parent.
+    name: String,
+
+    /// The address of the dictionary holding the Dir's children.
+    entries: Addr,
+
+    /// The address of the dictionary holding the Dir's metadata tags.
+    tags: Addr,
+
+    /// The metadata needed to carry out I/O operations.
+    io_metadata: &'a IoMetadata,
+
+    /// The storage backend that the Dir is stored in.
+    backend: &'b dyn StorageBackend,
+}
+
+impl Dir<'_, '_> {
+    /// Creates a new, empty Dir at the given location in the file, which
+    /// should be the end of the file.
+    pub(crate) fn create<'a, 'b>(
+        loc: Addr,
+        name: &str,
+        io_metadata: &'a IoMetadata,
+        backend: &'b dyn StorageBackend,
+    ) -> io::Result<Dir<'a, 'b>> {
+        // The entries dictionary lives immediately after the Dir, and the tags
+        // dictionary goes wherever the file ends once that's been written.
+        let addr_size = Addr::get_size_on_disk(io_metadata);
+        let entries = Addr::new(loc.get_loc() + 2 * addr_size);
+        DistDict::<String, DirEntry>::new(entries, io_metadata, backend, false)
+            .init();
+        let tags = Addr::new(backend.len()?);
+        DistDict::<String, TagEntry>::new(tags, io_metadata, backend, false)
+            .init();
+
+        // Only now that both dictionaries exist can we point at them.
+        entries.write(loc, backend, io_metadata);
+        tags.write(Addr::new(loc.get_loc() + addr_size), backend, io_metadata);
+
+        Ok(Dir {
+            loc,
+            name: name.to_string(),
+            entries,
+            tags,
+            io_metadata,
+            backend,
+        })
+    }
+
+    /// Opens a Dir that has already been written to the file.
+    pub(crate) fn open<'a, 'b>(
+        loc: Addr,
+        name: &str,
+        io_metadata: &'a IoMetadata,
+        backend: &'b dyn StorageBackend,
+    ) -> io::Result<Dir<'a, 'b>> {
+        let addr_size = Addr::get_size_on_disk(io_metadata);
+        let file_len = backend.len()?;
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("No valid dir at {}.", loc.get_loc()),
+            )
+        };
+        if loc.get_loc() + 2 * addr_size > file_len {
+            return Err(invalid());
+        }
+
+        let entries = Addr::from_addr(loc, backend, io_metadata);
+        let tags = Addr::from_addr(
+            Addr::new(loc.get_loc() + addr_size),
+            backend,
+            io_metadata,
+        );
+        for addr in [entries, tags] {
+            if addr == Addr::null() || addr.get_loc() >= file_len {
+                return Err(invalid());
+            }
+        }
+
+        Ok(Dir {
+            loc,
+            name: name.to_string(),
+            entries,
+            tags,
+            io_metadata,
+            backend,
+        })
+    }
+}
+
+// Implement the public API of Dir, which forwards to DirTrait and
+// HasMetadataTags. Children borrow the file rather than this Dir, so they can
+// outlive it.
+impl<'a, 'b> Dir<'a, 'b> {
+    /// Returns the name of this Dir within its parent.
+    pub fn name(&self) -> String {
+        HasName::name(self)
+    }
+
+    /// Returns the names of every Array and Dir in this Dir, sorted.
+    pub fn list_dir(&self) -> io::Result<Vec<String>> {
+        DirTrait::list_dir(self)
+    }
+
+    /// Returns the Array, Dir or metadata tag with the given name. Children
+    /// take precedence over tags with the same name.
+    pub fn get(&self, name: &str) -> io::Result<HighLevelObject<'a, 'b>> {
+        DirTrait::get(self, name)
+    }
+
+    /// Returns the child Dir with the given name.
+    pub fn get_dir(&self, name: &str) -> io::Result<Dir<'a, 'b>> {
+        DirTrait::get_dir(self, name)
+    }
+
+    /// Returns the child Array with the given name.
+    pub fn get_array(&self, name: &str) -> io::Result<Array<'a, 'b>> {
+        DirTrait::get_array(self, name)
+    }
+
+    /// Creates a new, empty child Dir.
+    pub fn create_dir(&self, name: &str) -> io::Result<Dir<'a, 'b>> {
+        DirTrait::create_dir(self, name)
+    }
+
+    /// Creates a new, empty child Array. Every frame appended to the array
+    /// has the given frame shape, and frames are stored in chunks of
+    /// frames_per_chunk.
+    pub fn create_array(
+        &self,
+        name: &str,
+        data_type: ArrayDataType,
+        frame_shape: &[u64],
+        frames_per_chunk: u64,
+    ) -> io::Result<Array<'a, 'b>> {
+        ArrayOptions::new()
+            .data_type(data_type)
+            .frame_shape(frame_shape)
+            .frames_per_chunk(frames_per_chunk)
+            .create(name, self)
+    }
+
+    /// Creates a new, empty child Array like create_array, except that every
+    /// frame is stored with a Timestamp, written along with the frame. See
+    /// Array::append_at.
+    pub fn create_timestamped_array(
+        &self,
+        name: &str,
+        data_type: ArrayDataType,
+        frame_shape: &[u64],
+        frames_per_chunk: u64,
+    ) -> io::Result<Array<'a, 'b>> {
+        ArrayOptions::new()
+            .data_type(data_type)
+            .frame_shape(frame_shape)
+            .frames_per_chunk(frames_per_chunk)
+            .timestamped()
+            .create(name, self)
+    }
+
+    /// Creates a new, empty child Array like create_array, for frames taken
+    /// at a fixed rate. The first frame is taken at t0 and each one after it
+    /// dt nanoseconds later, so no timestamps are stored. Appending a frame
+    /// with Array::append_at records a gap in the rate instead. dt must be
+    /// positive.
+    pub fn create_regular_array(
+        &self,
+        name: &str,
+        data_type: ArrayDataType,
+        frame_shape: &[u64],
+        frames_per_chunk: u64,
+        t0: Timestamp,
+        dt: i64,
+    ) -> io::Result<Array<'a, 'b>> {
+        ArrayOptions::new()
+            .data_type(data_type)
+            .frame_shape(frame_shape)
+            .frames_per_chunk(frames_per_chunk)
+            .regular(t0, dt)
+            .create(name, self)
+    }
+
+    /// Returns the value of the metadata tag with the given name, or None if
+    /// there's no such tag.
+    pub fn get_tag(&self, name: &str) -> io::Result<Option<String>> {
+        HasMetadataTags::get_tag(self, name)
+    }
+
+    /// Sets the metadata tag with the given name, adding it if it's new.
+    pub fn set_tag(&self, name: &str, value: &str) -> io::Result<()> {
+        HasMetadataTags::set_tag(self, name, value)
+    }
+
+    /// Returns every metadata tag as (name, value) pairs, sorted by name.
+    pub fn get_tags(&self) -> io::Result<Vec<(String, String)>> {
+        HasMetadataTags::get_tags(self)
+    }
+
+    /// Writes every array below this Dir to a NumPy .npz file at the given
+    /// path, keyed by their paths relative to this Dir, and every object's
+    /// tags to a JSON file with the same path but a .json extension.
+    pub fn export_npz<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_npz(self, path.as_ref())
+    }
+
+    /// Writes the arrays directly in this Dir, which must all have scalar
+    /// frames, to a CSV file at the given path, with one column per array and
+    /// one row per frame. The tags of the Dir and its arrays go in a block of
+    /// comments before the column names, which CsvImportOptions reads back.
+    pub fn export_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_csv(self, path.as_ref())
+    }
+
+    /// Returns the arrays directly in this Dir as an Arrow record batch, with
+    /// one column per array (see Array::to_record_batch), named after the
+    /// array. This Dir's tags are the schema's metadata, and each array's tags
+    /// are its field's metadata. There are as many rows as the shortest array
+    /// has frames.
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> io::Result<RecordBatch> {
+        dir_record_batch(self)
+    }
+
+    /// Writes the arrays directly in this Dir to a Parquet file at the given
+    /// path, with the same schema as to_record_batch.
+    #[cfg(feature = "arrow")]
+    pub fn export_parquet<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        write_dir_parquet(self, path.as_ref())
+    }
+}
+
+impl Locatable for Dir<'_, '_> {
+    fn get_addr(&self) -> &Addr {
+        &self.loc
+    }
+
+    fn get_backend(&self) -> &dyn StorageBackend {
+        self.backend
+    }
+
+    fn get_io_metadata(&self) -> &IoMetadata {
+        self.io_metadata
+    }
+}
+
+impl HasName for Dir<'_, '_> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl HasMetadataTags for Dir<'_, '_> {
+    fn get_tags_addr(&self) -> Addr {
+        self.tags
+    }
+}
+
+impl<'a, 'b> DirTrait<'a, 'b> for Dir<'a, 'b> {
+    fn get_entries_addr(&self) -> Addr {
+        self.entries
+    }
+
+    fn get_file_io_metadata(&self) -> &'a IoMetadata {
+        self.io_metadata
+    }
+
+    fn get_file_backend(&self) -> &'b dyn StorageBackend {
+        self.backend
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        enums::{
+            ArrayDataType, ClockSource, FileFormat, HighLevelObject, TimeAxis,
+        },
+        structs::MemoryBackend,
+        test_utils::make_io_metadata,
+    };
+
+    /// Build a small tree of dirs and arrays, and find everything again from a
+    /// freshly opened root.
+    #[test]
+    fn test_create_and_get() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_io_metadata(file_format);
+            let backend = MemoryBackend::new();
+            let root =
+                Dir::create(Addr::new(0), "/", &io_metadata, &backend).unwrap();
+            let detector = root.create_dir("detector").unwrap();
+            let array = detector
+                .create_array("counts", ArrayDataType::UInt32, &[2], 4)
+                .unwrap();
+            array.append(&[1_u32, 2]).unwrap();
+            root.create_array("time", ArrayDataType::Float64, &[], 8)
+                .unwrap();
+
+            let root =
+                Dir::open(Addr::new(0), "/", &io_metadata, &backend).unwrap();
+            assert_eq!(root.list_dir().unwrap(), vec!["detector", "time"]);
+            let detector = root.get_dir("detector").unwrap();
+            assert_eq!(detector.name(), "detector");
+            let array = detector.get_array("counts").unwrap();
+            assert_eq!(array.get_frame::<u32>(0).unwrap(), vec![1, 2]);
+            assert!(matches!(
+                root.get("time").unwrap(),
+                HighLevelObject::Array(_)
+            ));
+        }
+    }
+
+    /// Copying a dir must keep each array's time axis and timestamps, whether
+    /// they're stored or computed.
+    #[test]
+    fn test_copy_keeps_timestamps() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let root =
+            Dir::create(Addr::new(0), "/", &io_metadata, &backend).unwrap();
+        let array = root
+            .create_timestamped_array("counts", ArrayDataType::Int8, &[], 2)
+            .unwrap();
+        let timestamps: Vec<Timestamp> = (0..3)
+            .map(|i| Timestamp::new(i, ClockSource::Gps))
+            .collect();
+        array.append_frames_at(&timestamps, &[1_i8, 2, 3]).unwrap();
+        let regular = root
+            .create_regular_array(
+                "temperature",
+                ArrayDataType::Float32,
+                &[],
+                2,
+                timestamps[0],
+                100,
+            )
+            .unwrap();
+        regular
+            .append_frames_at(&timestamps, &[1.0_f32, 2.0, 3.0])
+            .unwrap();
+        regular.append(&[4.0_f32]).unwrap();
+
+        let copy_backend = MemoryBackend::new();
+        let copy = Dir::create(Addr::new(0), "/", &io_metadata, &copy_backend)
+            .unwrap();
+        root.copy_into(&copy).unwrap();
+        let array = copy.get_array("counts").unwrap();
+        assert_eq!(array.get_time_axis(), TimeAxis::Timestamped);
+        assert_eq!(
+            array.get_timestamped_frame::<i8>(2).unwrap(),
+            (timestamps[2], vec![3])
+        );
+        assert_eq!(array.get_timestamps(0, 3).unwrap(), timestamps);
+        let regular = copy.get_array("temperature").unwrap();
+        assert_eq!(regular.get_time_axis(), TimeAxis::Regular);
+        assert_eq!(regular.get_dt(), Some(100));
+        let nanos: Vec<i64> = regular
+            .get_timestamps(0, 4)
+            .unwrap()
+            .iter()
+            .map(|t| t.get_nanos())
+            .collect();
+        assert_eq!(nanos, vec![0, 1, 2, 102]);
+    }
+
+    /// Make sure that bad names, duplicate names, missing names and the wrong
+    /// kind of object are all reported as errors.
+    #[test]
+    fn test_errors() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let root =
+            Dir::create(Addr::new(0), "/", &io_metadata, &backend).unwrap();
+        root.create_dir("a").unwrap();
+
+        let kind = |result: io::Result<Dir>| result.err().unwrap().kind();
+        assert_eq!(kind(root.create_dir("a")), io::ErrorKind::AlreadyExists);
+        assert_eq!(kind(root.create_dir("")), io::ErrorKind::InvalidInput);
+        assert_eq!(kind(root.create_dir("b/c")), io::ErrorKind::InvalidInput);
+        assert_eq!(kind(root.get_dir("b")), io::ErrorKind::NotFound);
+        assert!(root.get_array("a").is_err());
+        assert!(
+            Dir::open(Addr::new(10_000), "x", &io_metadata, &backend).is_err()
+        );
+    }
+
+    /// Make sure that tags can be added, updated and listed, and that they're
+    /// returned by get when no child has the same name.
+    #[test]
+    fn test_tags() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let root =
+            Dir::create(Addr::new(0), "/", &io_metadata, &backend).unwrap();
+        let array = root
+            .create_array("counts", ArrayDataType::Int8, &[], 4)
+            .unwrap();
+
+        root.set_tag("sample", "silicon").unwrap();
+        root.set_tag("temperature", "300").unwrap();
+        root.set_tag("sample", "germanium").unwrap();
+        array.set_tag("units", "counts").unwrap();
+
+        assert_eq!(root.get_tag("sample").unwrap().unwrap(), "germanium");
+        assert_eq!(root.get_tag("units").unwrap(), None);
+        assert_eq!(
+            root.get_tags().unwrap(),
+            vec![
+                ("sample".to_string(), "germanium".to_string()),
+                ("temperature".to_string(), "300".to_string()),
+            ]
+        );
+        assert!(matches!(
+            root.get("temperature").unwrap(),
+            HighLevelObject::MetadataTag(value) if value == "300"
+        ));
+
+        let array = root.get_array("counts").unwrap();
+        assert_eq!(array.get_tag("units").unwrap().unwrap(), "counts");
+    }
 }
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    enums::ObjectKind,
+    traits::{FileSerializable, FixedSizeOnDisk},
+};
+
+use super::Addr;
+
+/// A DirEntry is the value stored in a Dir's entries dictionary for each of
+/// its children. The child's name is stored in a Blob, so that we can tell
+/// apart names whose hashes collide.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DirEntry {
+    /// Whether the child is a Dir or an Array.
+    kind: ObjectKind,
+
+    /// The address of the Blob holding the child's name.
+    name: Addr,
+
+    /// The address of the child itself.
+    addr: Addr,
+}
+
+impl DirEntry {
+    /// Constructs a new DirEntry.
+    pub(crate) fn new(kind: ObjectKind, name: Addr, addr: Addr) -> Self {
+        Self { kind, name, addr }
+    }
+
+    /// Returns whether the child is a Dir or an Array.
+    pub(crate) fn get_kind(&self) -> ObjectKind {
+        self.kind
+    }
+
+    /// Returns the address of the Blob holding the child's name.
+    pub(crate) fn get_name_addr(&self) -> Addr {
+        self.name
+    }
+
+    /// Returns the address of the child itself.
+    pub(crate) fn get_addr(&self) -> Addr {
+        self.addr
+    }
+}
+
+impl FixedSizeOnDisk for DirEntry {
+    fn get_bin_size_on_disk() -> u64 {
+        // One byte for the kind code, then the two addresses.
+        1 + 2 * Addr::get_bin_size_on_disk()
+    }
+
+    fn get_json_size_on_disk() -> u64 {
+        // Serialize the largest possible entry. No kind has a longer name
+        // than Array.
+        let entry = DirEntry {
+            kind: ObjectKind::Array,
+            name: Addr::new(u64::MAX),
+            addr: Addr::new(u64::MAX),
+        };
+        entry.get_text_len()
+    }
+}
+
+impl FileSerializable for DirEntry {
+    const TEXT_MARKER: &'static str = "DirEntry";
+
+    fn null() -> Self {
+        Self {
+            kind: ObjectKind::Dir,
+            name: Addr::null(),
+            addr: Addr::null(),
+        }
+    }
+
+    fn to_bin(&self) -> Vec<u8> {
+        let mut bytes = vec![self.kind.to_code()];
+        bytes.extend_from_slice(&self.name.to_bin());
+        bytes.extend_from_slice(&self.addr.to_bin());
+        bytes
+    }
+
+    fn from_bin(bytes: &[u8]) -> Self {
+        // If the kind code is garbage, so is the rest of the entry.
+        let kind = match ObjectKind::from_code(bytes[0]) {
+            Some(kind) => kind,
+            None => return Self::null(),
+        };
+
+        Self {
+            kind,
+            name: Addr::from_bin(&bytes[1..9]),
+            addr: Addr::from_bin(&bytes[9..17]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a DirEntry survives a round trip through binary and json.
+    #[test]
+    fn test_dir_entry_round_trip() {
+        let entry =
+            DirEntry::new(ObjectKind::Array, Addr::new(12), Addr::new(345));
+        let bytes = entry.to_bin();
+        assert_eq!(bytes.len() as u64, DirEntry::get_bin_size_on_disk());
+        assert_eq!(DirEntry::from_bin(&bytes), entry);
+
+        let json = entry.to_json();
+        assert_eq!(json.len() as u64, DirEntry::get_json_size_on_disk());
+        assert_eq!(DirEntry::from_json(json), entry);
+        assert_eq!(DirEntry::from_bin(&[7; 17]), DirEntry::null());
+    }
+
+    /// The Text layout should put every field on its own labelled line, with
+    /// only the last line padded.
+    #[test]
+    fn test_dir_entry_text_layout() {
+        let entry =
+            DirEntry::new(ObjectKind::Dir, Addr::new(12), Addr::new(345));
+        let text = entry.to_json();
+        assert_eq!(text.len() as u64, DirEntry::get_json_size_on_disk());
+        assert!(text.ends_with(" \n"));
+        assert_eq!(
+            text.lines().map(str::trim_end).collect::<Vec<_>>(),
+            vec![
+                "DirEntry addr.loc: 345",
+                "DirEntry kind: \"Dir\"",
+                "DirEntry name.loc: 12",
+            ]
+        );
+    }
+}
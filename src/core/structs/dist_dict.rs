@@ -1,7 +1,8 @@
-use std::{fs::File, marker::PhantomData};
+use std::marker::PhantomData;
 
 use crate::core::traits::{
-    DistDictTrait, FileSerializable, FixedSizeOnDisk, Locatable, TsdfHashable,
+    DistDictTrait, FileSerializable, FixedSizeOnDisk, Locatable,
+    StorageBackend, TsdfHashable,
 };
 
 use super::{Addr, IoMetadata};
@@ -17,13 +18,37 @@ pub(crate) struct DistDict<'a, 'b, TKey, TVal> {
     /// The metadata needed to carry out I/O operations.
     io_metadata: &'a IoMetadata,
 
-    /// The file that the DistDict is stored in.
-    file: &'b File,
+    /// The storage backend that the DistDict is stored in.
+    backend: &'b dyn StorageBackend,
 
     /// Whether the distributed dictionary has been initialized.
     initialized: bool,
 }
 
+impl<TKey, TVal> DistDict<'_, '_, TKey, TVal>
+where
+    TKey: TsdfHashable,
+    TVal: FileSerializable,
+{
+    /// Constructs a new DistDict. If the DistDict has already been written to
+    /// the file, is_initialized should be true.
+    pub(crate) fn new<'a, 'b>(
+        loc: Addr,
+        io_metadata: &'a IoMetadata,
+        backend: &'b dyn StorageBackend,
+        is_initialized: bool,
+    ) -> DistDict<'a, 'b, TKey, TVal> {
+        DistDict {
+            key: PhantomData,
+            val: PhantomData,
+            loc,
+            io_metadata,
+            backend,
+            initialized: is_initialized,
+        }
+    }
+}
+
 impl<TKey, TVal> Locatable for DistDict<'_, '_, TKey, TVal>
 where
     TKey: TsdfHashable,
@@ -33,8 +58,8 @@ where
         &self.loc
     }
 
-    fn get_file(&self) -> &File {
-        self.file
+    fn get_backend(&self) -> &dyn StorageBackend {
+        self.backend
     }
 
     fn get_io_metadata(&self) -> &IoMetadata {
@@ -74,26 +99,24 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::io::{Read, Seek};
-
     use super::*;
     use crate::core::traits::{
         DistDictShardReader, ShardTrait, VariableSizeOnDisk,
     };
     use crate::core::{
         enums::{IoMode, WriteMode},
-        structs::TsdfMetadata,
+        structs::{
+            FaultInjectingBackend, FileBackend, MemoryBackend, TsdfMetadata,
+        },
     };
 
     use tempfile::tempfile;
 
     macro_rules! print_file {
-        ($file:expr) => {{
-            let mut file_clone = $file.try_clone().unwrap();
-            let mut file_contents = String::new();
-            file_clone.read_to_string(&mut file_contents).unwrap();
-            file_clone.seek(std::io::SeekFrom::Start(0)).unwrap();
-            println!("{}", file_contents);
+        ($backend:expr) => {{
+            let mut file_contents = vec![0; $backend.len().unwrap() as usize];
+            $backend.read_full_at(&mut file_contents, 0).unwrap();
+            println!("{}", String::from_utf8_lossy(&file_contents));
             println!("\n\n\n\n\n\n\n\n");
         }};
     }
@@ -111,10 +134,10 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make sure that the file is empty.
-        assert_eq!(file.metadata().unwrap().len(), 0);
+        assert_eq!(backend.len().unwrap(), 0);
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -122,7 +145,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -138,14 +161,11 @@ mod tests {
             DistDict::<'_, '_, String, Addr>::get_size_on_disk(&io_metadata);
 
         // Print the file.
-        print_file!(file);
+        print_file!(backend);
 
         // Make sure that the file contains the correct number of bytes (meaning
         // that the entire distributed dictionary has been written to the file).
-        assert_eq!(
-            file.metadata().unwrap().len(),
-            dist_dict_size + first_shard_size
-        );
+        assert_eq!(backend.len().unwrap(), dist_dict_size + first_shard_size);
     }
 
     /// This test is the same as the above test, but this time making sure that
@@ -160,10 +180,10 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make sure that the file is empty.
-        assert_eq!(file.metadata().unwrap().len(), 0);
+        assert_eq!(backend.len().unwrap(), 0);
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -171,7 +191,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -187,14 +207,11 @@ mod tests {
             DistDict::<'_, '_, String, Addr>::get_size_on_disk(&io_metadata);
 
         // Print the file.
-        print_file!(file);
+        print_file!(backend);
 
         // Make sure that the file contains the correct number of bytes (meaning
         // that the entire distributed dictionary has been written to the file).
-        assert_eq!(
-            file.metadata().unwrap().len(),
-            dist_dict_size + first_shard_size
-        );
+        assert_eq!(backend.len().unwrap(), dist_dict_size + first_shard_size);
     }
 
     /// Test that we can add a single key value pair to the distributed
@@ -213,7 +230,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -221,7 +238,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -236,10 +253,10 @@ mod tests {
         let shard = dist_dict.get_first_shard();
 
         // Print the file.
-        print_file!(file);
+        print_file!(backend);
 
         // Hash the key.
-        let hashed_key = key.hash();
+        let hashed_key = key.hash(&io_metadata);
 
         // Make sure that the shard contains the key.
         assert!(shard.contains(&hashed_key));
@@ -258,7 +275,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -266,7 +283,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -281,7 +298,7 @@ mod tests {
         let shard = dist_dict.get_first_shard();
 
         // Hash the key.
-        let hashed_key = key.hash();
+        let hashed_key = key.hash(&io_metadata);
 
         // Make sure that the shard contains the key.
         assert!(shard.contains(&hashed_key));
@@ -299,7 +316,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -307,7 +324,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -329,7 +346,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -337,7 +354,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -359,7 +376,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -367,7 +384,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -393,7 +410,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -401,7 +418,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -427,7 +444,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -435,7 +452,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -466,7 +483,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -474,7 +491,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -506,7 +523,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -514,7 +531,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -536,7 +553,7 @@ mod tests {
 
         // Make sure that the shard contains the second value.
         let shard = dist_dict.get_first_shard();
-        let hashed_key = key.hash();
+        let hashed_key = key.hash(&io_metadata);
         let hash_idx =
             hashed_key.get_hash_table_idx(shard.get_capacity() as u64);
         assert_eq!(shard.get_val(hash_idx as usize), val2);
@@ -554,7 +571,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -562,7 +579,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -584,7 +601,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -592,7 +609,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -614,7 +631,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -622,7 +639,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -651,7 +668,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -659,7 +676,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -692,7 +709,7 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDict.
         let mut dist_dict: DistDict<'_, '_, String, Addr> = DistDict {
@@ -700,7 +717,7 @@ mod tests {
             val: PhantomData,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             initialized: false,
         };
 
@@ -710,7 +727,7 @@ mod tests {
         for i in 0..num_keys {
             let key = format!("key_{}", i);
             let val = Addr::new(i as u64);
-            key_vals.push((key.clone(), val.clone()));
+            key_vals.push((key.clone(), val));
             dist_dict.add(&key, &val);
         }
 
@@ -729,4 +746,104 @@ mod tests {
             assert!(!dist_dict.contains(&key));
         }
     }
+
+    /// Make sure that the distributed dictionary works with an in-memory
+    /// backend, and that a reader sharing that backend sees the writer's
+    /// changes.
+    #[test]
+    fn test_memory_backend_reader_sees_writes() {
+        // The necessary setup.
+        let io_metadata = IoMetadata::new(
+            TsdfMetadata::new(
+                "no_version".to_string(),
+                crate::core::enums::FileFormat::Binary,
+            ),
+            IoMode::Write(WriteMode::LocklessWrite),
+        );
+        let backend = MemoryBackend::new();
+        let reader_backend = backend.clone();
+
+        // Make a DistDict to write with.
+        let mut writer: DistDict<'_, '_, String, Addr> = DistDict {
+            key: PhantomData,
+            val: PhantomData,
+            loc: Addr::new(0),
+            io_metadata: &io_metadata,
+            backend: &backend,
+            initialized: false,
+        };
+
+        // Write enough keys to force the creation of a few shards.
+        for i in 0..100 {
+            writer.add(&format!("key_{}", i), &Addr::new(i));
+        }
+
+        // Make a second DistDict, reading from a clone of the backend.
+        let reader: DistDict<'_, '_, String, Addr> = DistDict {
+            key: PhantomData,
+            val: PhantomData,
+            loc: Addr::new(0),
+            io_metadata: &io_metadata,
+            backend: &reader_backend,
+            initialized: true,
+        };
+        for i in 0..100 {
+            assert_eq!(reader.get(&format!("key_{}", i)), Some(Addr::new(i)));
+        }
+        assert_eq!(reader.get(&"missing".to_string()), None);
+    }
+
+    /// Make sure that the shard cache saves reads on repeated lookups, and
+    /// that a reader with a warm cache still sees shards that the writer adds
+    /// later on.
+    #[test]
+    fn test_shard_cache() {
+        // The necessary setup. The writer and reader have their own metadata,
+        // so their caches are separate.
+        let make_io_metadata = || {
+            IoMetadata::new(
+                TsdfMetadata::new(
+                    "no_version".to_string(),
+                    crate::core::enums::FileFormat::Binary,
+                ),
+                IoMode::Write(WriteMode::LocklessWrite),
+            )
+        };
+        let writer_io_metadata = make_io_metadata();
+        let reader_io_metadata = make_io_metadata();
+        let backend = FaultInjectingBackend::new();
+
+        let mut writer: DistDict<'_, '_, String, Addr> =
+            DistDict::new(Addr::new(0), &writer_io_metadata, &backend, false);
+        for i in 0..100 {
+            writer.add(&format!("key_{}", i), &Addr::new(i));
+        }
+
+        // Count the reads needed to look up a missing key, which has to visit
+        // every shard.
+        let reader: DistDict<'_, '_, String, Addr> =
+            DistDict::new(Addr::new(0), &reader_io_metadata, &backend, true);
+        let count_reads = || {
+            let num_reads = backend.get_num_reads();
+            assert_eq!(reader.get(&"missing".to_string()), None);
+            backend.get_num_reads() - num_reads
+        };
+        let cold_reads = count_reads();
+        let warm_reads = count_reads();
+        assert!(warm_reads < cold_reads);
+
+        // Disabling the cache takes us back to reading every next pointer.
+        reader_io_metadata.get_shard_cache().set_capacity(0);
+        assert_eq!(count_reads(), cold_reads);
+        reader_io_metadata.get_shard_cache().set_capacity(8);
+        count_reads();
+
+        // New shards must still be found after the cache is warm.
+        for i in 100..1000 {
+            writer.add(&format!("key_{}", i), &Addr::new(i));
+        }
+        for i in 0..1000 {
+            assert_eq!(reader.get(&format!("key_{}", i)), Some(Addr::new(i)));
+        }
+    }
 }
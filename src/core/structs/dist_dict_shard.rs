@@ -1,8 +1,8 @@
-use std::fs::File;
 use std::marker::PhantomData;
 
 use crate::core::traits::{
-    DistDictShardWriter, Locatable, ShardTrait, VariableSizeOnDisk,
+    DistDictShardWriter, Locatable, ShardTrait, StorageBackend,
+    VariableSizeOnDisk,
 };
 use crate::core::{
     enums::LinkPtr,
@@ -56,8 +56,8 @@ where
     /// All metadata that is needed to read/write to the file.
     io_metadata: &'a IoMetadata,
 
-    /// A reference to the file that the shard is stored in.
-    file: &'b File,
+    /// A reference to the storage backend that the shard is stored in.
+    backend: &'b dyn StorageBackend,
 
     /// Whether the shard has been initialized.
     initialized: bool,
@@ -71,7 +71,7 @@ where
         link_number: i32,
         loc: Addr,
         io_metadata: &'a IoMetadata,
-        file: &'b File,
+        backend: &'b dyn StorageBackend,
         is_initialized: bool,
     ) -> DistDictShard<'a, 'b, TVal> {
         DistDictShard {
@@ -79,7 +79,7 @@ where
             link_number,
             loc,
             io_metadata,
-            file,
+            backend,
             initialized: is_initialized,
         }
     }
@@ -94,8 +94,8 @@ where
         &self.loc
     }
 
-    fn get_file(&self) -> &File {
-        self.file
+    fn get_backend(&self) -> &dyn StorageBackend {
+        self.backend
     }
 
     fn get_io_metadata(&self) -> &IoMetadata {
@@ -162,11 +162,10 @@ where
     DistDictShard<'a, 'b, TVal>: DistDictShardReader<TVal>,
 {
     fn get_capacity(&self) -> usize {
-        // The number of keys in the shard should be equal to 8 times the link
-        // number raised to the power of 2. The number of keys that the shard
-        // can hold is equal to its capacity.
-        let num_keys = 8 * (2 as usize).pow(self.link_number as u32);
-        num_keys as usize
+        // Each shard has twice the capacity of the one before it, starting
+        // from the file's shard capacity.
+        let first = self.io_metadata.get_tsdf_metadata().get_shard_capacity();
+        (first as usize) << self.link_number
     }
 
     fn get_count(&self) -> usize {
@@ -206,25 +205,21 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::io::{Read, Seek};
-
     use tempfile::tempfile;
 
     use crate::core::{
-        enums::{IoMode, WriteMode},
-        structs::TsdfMetadata,
+        enums::{IoMode, StorageOp, WriteMode},
+        structs::{FaultInjectingBackend, FileBackend, TsdfMetadata},
+        traits::TsdfHashable,
     };
 
-    use super::super::TsdfHash;
     use super::*;
 
     macro_rules! print_file {
-        ($file:expr) => {{
-            let mut file_clone = $file.try_clone().unwrap();
-            let mut file_contents = String::new();
-            file_clone.read_to_string(&mut file_contents).unwrap();
-            file_clone.seek(std::io::SeekFrom::Start(0)).unwrap();
-            println!("{}", file_contents);
+        ($backend:expr) => {{
+            let mut file_contents = vec![0; $backend.len().unwrap() as usize];
+            $backend.read_full_at(&mut file_contents, 0).unwrap();
+            println!("{}", String::from_utf8_lossy(&file_contents));
             println!("\n\n\n\n\n\n\n\n");
         }};
     }
@@ -242,25 +237,25 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDictShard.
         let mut shard: DistDictShard<'_, '_, Addr> = DistDictShard {
             link_number: 1,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             val: std::marker::PhantomData,
             initialized: false,
         };
 
         // Initialize the shard.
         shard.init();
-        print_file!(file);
+        print_file!(backend);
 
         // Check that the size of the shard on disk is as expected.
         let expected_size = shard.get_size_on_disk(&io_metadata);
-        let actual_size = file.metadata().unwrap().len();
+        let actual_size = backend.len().unwrap();
         assert_eq!(expected_size, actual_size);
     }
 
@@ -277,25 +272,25 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDictShard.
         let mut shard: DistDictShard<'_, '_, Addr> = DistDictShard {
             link_number: 1,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             val: std::marker::PhantomData,
             initialized: false,
         };
 
         // Initialize the shard.
         shard.init();
-        print_file!(file);
+        print_file!(backend);
 
         // Check that the size of the shard on disk is as expected.
         let expected_size = shard.get_size_on_disk(&io_metadata);
-        let actual_size = file.metadata().unwrap().len();
+        let actual_size = backend.len().unwrap();
         assert_eq!(expected_size, actual_size);
     }
 
@@ -311,37 +306,37 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDictShard.
         let mut shard: DistDictShard<'_, '_, Addr> = DistDictShard {
             link_number: 1,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             val: std::marker::PhantomData,
             initialized: false,
         };
 
         // Initialize the shard.
         shard.init();
-        print_file!(file);
+        print_file!(backend);
 
         // Create a key-value pair.
         let key = "test_key".to_string();
-        let hashed_key = TsdfHash::new(&key);
+        let hashed_key = key.hash(&io_metadata);
         let val = Addr::new(123);
 
         // Add the key-value pair to the shard.
         shard.add(&hashed_key, &val);
-        print_file!(file);
+        print_file!(backend);
 
         // Check that the shard contains the key.
         assert!(shard.contains(&hashed_key));
 
         // Remove the key-value pair from the shard.
         shard.remove(&hashed_key);
-        print_file!(file);
+        print_file!(backend);
 
         // Check that the shard no longer contains the key.
         assert!(!shard.contains(&hashed_key));
@@ -359,39 +354,93 @@ mod tests {
             ),
             IoMode::Write(WriteMode::LocklessWrite),
         );
-        let file = tempfile().unwrap();
+        let backend = FileBackend::new(tempfile().unwrap());
 
         // Make a DistDictShard.
         let mut shard: DistDictShard<'_, '_, Addr> = DistDictShard {
             link_number: 1,
             loc: Addr::new(0),
             io_metadata: &io_metadata,
-            file: &file,
+            backend: &backend,
             val: std::marker::PhantomData,
             initialized: false,
         };
 
         // Initialize the shard.
         shard.init();
-        print_file!(file);
+        print_file!(backend);
 
         // Create a key-value pair.
         let key = "test_key".to_string();
-        let hashed_key = TsdfHash::new(&key);
+        let hashed_key = key.hash(&io_metadata);
         let val = Addr::new(123);
 
         // Add the key-value pair to the shard.
         shard.add(&hashed_key, &val);
-        // print_file!(file);
+        // print_file!(backend);
 
         // Check that the shard contains the key.
         assert!(shard.contains(&hashed_key));
 
         // Remove the key-value pair from the shard.
         shard.remove(&hashed_key);
-        print_file!(file);
+        print_file!(backend);
 
         // Check that the shard no longer contains the key.
         assert!(!shard.contains(&hashed_key));
     }
+
+    /// Make sure that initializing a shard takes a single write, and that
+    /// adding to it takes one write for the hash and value and one for the
+    /// is_hash_written boolean.
+    #[test]
+    fn test_writes_are_coalesced() {
+        // Define the io metadata.
+        let io_metadata = IoMetadata::new(
+            TsdfMetadata::new(
+                "no_version".to_string(),
+                crate::core::enums::FileFormat::Binary,
+            ),
+            IoMode::Write(WriteMode::LocklessWrite),
+        );
+        let backend = FaultInjectingBackend::new();
+
+        // Make a DistDictShard.
+        let mut shard: DistDictShard<'_, '_, Addr> = DistDictShard {
+            link_number: 3,
+            loc: Addr::new(0),
+            io_metadata: &io_metadata,
+            backend: &backend,
+            val: std::marker::PhantomData,
+            initialized: false,
+        };
+
+        // The whole shard should be written in one go.
+        shard.init();
+        assert_eq!(backend.get_num_writes(), 1);
+        assert_eq!(
+            backend.len().unwrap(),
+            shard.get_size_on_disk(&io_metadata)
+        );
+
+        // Adding a key should write the hash and value, then the boolean.
+        let hash = "key".to_string().hash(&io_metadata);
+        shard.add(&hash, &Addr::new(42));
+        let ops = backend.get_ops();
+        assert_eq!(ops.len(), 3);
+        let is_written_loc = shard
+            .get_is_hash_written_addr(hash.get_hash_table_idx(64) as usize)
+            .get_loc();
+        assert_eq!(
+            ops[2],
+            StorageOp::Write {
+                offset: is_written_loc,
+                bytes: vec![1]
+            }
+        );
+        assert_eq!(
+            shard.get_val(hash.get_hash_table_idx(64) as usize),
+            Addr::new(42)
+        );
+    }
 }
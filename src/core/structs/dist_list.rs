@@ -0,0 +1,176 @@
+use std::marker::PhantomData;
+
+use crate::core::traits::{
+    DistListTrait, FileSerializable, FixedSizeOnDisk, Locatable, StorageBackend,
+};
+
+use super::{Addr, IoMetadata};
+
+/// A list that is spread over a chain of DistListShards. The DistList itself
+/// only stores the address of its first shard on disk, and the first shard is
+/// always located immediately after it.
+pub(crate) struct DistList<'a, 'b, T> {
+    /// Throwaway variable used to store the type of the elements.
+    elem: PhantomData<T>,
+
+    /// The address of this DistList in the file.
+    loc: Addr,
+
+    /// The metadata needed to carry out I/O operations.
+    io_metadata: &'a IoMetadata,
+
+    /// The storage backend that the DistList is stored in.
+    backend: &'b dyn StorageBackend,
+
+    /// Whether the distributed list has been initialized.
+    initialized: bool,
+}
+
+impl<T> DistList<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    /// Constructs a new DistList. If the DistList has already been written to
+    /// the file, is_initialized should be true.
+    pub(crate) fn new<'a, 'b>(
+        loc: Addr,
+        io_metadata: &'a IoMetadata,
+        backend: &'b dyn StorageBackend,
+        is_initialized: bool,
+    ) -> DistList<'a, 'b, T> {
+        DistList {
+            elem: PhantomData,
+            loc,
+            io_metadata,
+            backend,
+            initialized: is_initialized,
+        }
+    }
+}
+
+impl<T> Locatable for DistList<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    fn get_addr(&self) -> &Addr {
+        &self.loc
+    }
+
+    fn get_backend(&self) -> &dyn StorageBackend {
+        self.backend
+    }
+
+    fn get_io_metadata(&self) -> &IoMetadata {
+        self.io_metadata
+    }
+}
+
+impl<T> FixedSizeOnDisk for DistList<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    fn get_bin_size_on_disk() -> u64 {
+        // The size of the DistList is the size of the address.
+        Addr::get_bin_size_on_disk()
+    }
+
+    fn get_json_size_on_disk() -> u64 {
+        // The size of the DistList is the size of the address.
+        Addr::get_json_size_on_disk()
+    }
+}
+
+impl<T> DistListTrait<T> for DistList<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    fn set_initialization_state(&mut self, initialized: bool) {
+        self.initialized = initialized;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempfile;
+
+    use super::*;
+    use crate::core::{
+        enums::FileFormat,
+        structs::{FileBackend, MemoryBackend},
+        test_utils::make_io_metadata,
+    };
+
+    /// Test that an uninitialized list is empty.
+    #[test]
+    fn test_empty() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let list: DistList<'_, '_, Addr> =
+            DistList::new(Addr::new(0), &io_metadata, &backend, false);
+
+        assert!(list.is_empty());
+        assert_eq!(list.get(0), None);
+        assert!(list.to_vec().is_empty());
+    }
+
+    /// Add enough elements to need several shards, and make sure that they
+    /// all come back in order. This uses the Text file format.
+    #[test]
+    fn test_add_get_text() {
+        let io_metadata = make_io_metadata(FileFormat::Text);
+        let backend = FileBackend::new(tempfile().unwrap());
+        let mut list: DistList<'_, '_, Addr> =
+            DistList::new(Addr::new(0), &io_metadata, &backend, false);
+
+        for i in 0..30 {
+            list.add(&Addr::new(i));
+        }
+
+        assert_eq!(list.len(), 30);
+        assert_eq!(list.get(29), Some(Addr::new(29)));
+        assert_eq!(list.get(30), None);
+        list.verify().unwrap();
+    }
+
+    /// As above, but with the Binary file format and a lot more elements.
+    #[test]
+    fn test_add_get_bin() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = FileBackend::new(tempfile().unwrap());
+        let mut list: DistList<'_, '_, Addr> =
+            DistList::new(Addr::new(0), &io_metadata, &backend, false);
+
+        let elems: Vec<Addr> = (0..1000).map(Addr::new).collect();
+        list.add_all(&elems);
+
+        assert_eq!(list.len(), 1000);
+        assert_eq!(list.to_vec(), elems);
+        for (i, elem) in elems.iter().enumerate() {
+            assert_eq!(list.get(i).as_ref(), Some(elem));
+        }
+        list.verify().unwrap();
+    }
+
+    /// Make sure that a reader sharing the writer's backend sees the elements
+    /// that the writer adds.
+    #[test]
+    fn test_reader_sees_writes() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let reader_backend = backend.clone();
+        let mut writer: DistList<'_, '_, Addr> =
+            DistList::new(Addr::new(0), &io_metadata, &backend, false);
+        let reader: DistList<'_, '_, Addr> =
+            DistList::new(Addr::new(0), &io_metadata, &reader_backend, true);
+
+        for i in 0..20 {
+            writer.add(&Addr::new(i));
+            assert_eq!(reader.len(), i as usize + 1);
+            assert_eq!(reader.get(i as usize), Some(Addr::new(i)));
+        }
+    }
+}
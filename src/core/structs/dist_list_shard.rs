@@ -1,29 +1,214 @@
-use std::fs::File;
+use std::marker::PhantomData;
 
-use super::Addr;
-use super::IoMetadata;
+use crate::core::traits::{
+    DistListShardReader, DistListShardWriter, Locatable, ShardTrait,
+    StorageBackend, VariableSizeOnDisk,
+};
+use crate::core::{
+    enums::LinkPtr,
+    traits::{FileSerializable, Link},
+};
 
-use crate::core::enums::LinkPtr;
-use crate::core::traits::Link;
-use crate::core::traits::Locatable;
+use super::{Addr, IoMetadata};
 
-pub(crate) struct DistListShard<'a, 'b> {
+/// The DistListShard struct is a shard of a distributed list. Like the
+/// DistDictShard, it doesn't hold any of its elements in memory. Instead, it
+/// knows where its elements live on disk, and reads them on demand.
+///
+/// # Serialized structure
+/// The shard looks like this on disk:
+///
+/// | is_next_written | next: LinkPtr | is_written_1 | ... | is_written_N |
+/// | elem1: T | ... | elemN: T |
+///
+/// where T is the type of the elements, and N is the capacity of the shard.
+pub(crate) struct DistListShard<'a, 'b, T>
+where
+    T: FileSerializable,
+{
+    /// A throwaway variable to store the type of the elements. See the
+    /// equivalent field in DistDictShard for why this is necessary.
+    elem: PhantomData<T>,
+
+    /// The link number of this shard.
     link_number: i32,
+
+    /// The location of this shard in the file.
     loc: Addr,
-    file: &'a File,
-    metadata: &'b IoMetadata,
+
+    /// All metadata that is needed to read/write to the file.
+    io_metadata: &'a IoMetadata,
+
+    /// A reference to the storage backend that the shard is stored in.
+    backend: &'b dyn StorageBackend,
+
+    /// Whether the shard has been initialized.
+    initialized: bool,
 }
 
-impl Locatable for DistListShard<'_, '_> {
+impl<T> DistListShard<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    pub(crate) fn new<'a, 'b>(
+        link_number: i32,
+        loc: Addr,
+        io_metadata: &'a IoMetadata,
+        backend: &'b dyn StorageBackend,
+        is_initialized: bool,
+    ) -> DistListShard<'a, 'b, T> {
+        DistListShard {
+            elem: PhantomData::<T>,
+            link_number,
+            loc,
+            io_metadata,
+            backend,
+            initialized: is_initialized,
+        }
+    }
+}
+
+// Implement the locatable trait for DistListShard.
+impl<T> Locatable for DistListShard<'_, '_, T>
+where
+    T: FileSerializable,
+{
     fn get_addr(&self) -> &Addr {
         &self.loc
     }
 
-    fn get_file(&self) -> &File {
-        self.file
+    fn get_backend(&self) -> &dyn StorageBackend {
+        self.backend
     }
 
     fn get_io_metadata(&self) -> &IoMetadata {
-        self.metadata
+        self.io_metadata
+    }
+}
+
+// Implement the Link trait for DistListShard.
+impl<T> Link for DistListShard<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    fn get_next(&self) -> LinkPtr {
+        self.get_next_ptr()
+    }
+
+    fn get_link_number(&self) -> i32 {
+        self.link_number
+    }
+}
+
+impl<T> VariableSizeOnDisk for DistListShard<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    fn get_bin_size_on_disk(&self) -> u64 {
+        // The very last part of the shard is the final element, so the shard
+        // ends where the element after the final element would start.
+        let first_byte_following_shard =
+            self.get_element_addr(self.get_capacity()).get_loc();
+        first_byte_following_shard - self.get_addr().get_loc()
+    }
+
+    fn get_json_size_on_disk(&self) -> u64 {
+        // The element addresses already account for the file format.
+        self.get_bin_size_on_disk()
+    }
+}
+
+impl<T> ShardTrait<T> for DistListShard<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    fn get_capacity(&self) -> usize {
+        // Each shard has twice the capacity of the one before it, starting
+        // from the file's shard capacity.
+        let first = self.io_metadata.get_tsdf_metadata().get_shard_capacity();
+        (first as usize) << self.link_number
+    }
+
+    fn get_count(&self) -> usize {
+        self.get_num_written()
+    }
+}
+
+// Implement the DistListShardReader trait for DistListShard.
+impl<T> DistListShardReader<T> for DistListShard<'_, '_, T> where
+    T: FileSerializable
+{
+}
+
+// Implement the DistListShardWriter trait for DistListShard.
+impl<T> DistListShardWriter<T> for DistListShard<'_, '_, T>
+where
+    T: FileSerializable,
+{
+    fn set_initialization_state(&mut self, initialized: bool) {
+        self.initialized = initialized;
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempfile;
+
+    use crate::core::{
+        enums::{FileFormat, IoMode, WriteMode},
+        structs::{FileBackend, TsdfMetadata},
+    };
+
+    use super::*;
+
+    /// Make sure that the size of an initialized shard is what we expect, in
+    /// both file formats.
+    #[test]
+    fn test_size_on_disk() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = IoMetadata::new(
+                TsdfMetadata::new("no_version".to_string(), file_format),
+                IoMode::Write(WriteMode::LocklessWrite),
+            );
+            let backend = FileBackend::new(tempfile().unwrap());
+
+            // Make and initialize a shard.
+            let mut shard: DistListShard<'_, '_, Addr> = DistListShard::new(
+                1,
+                Addr::new(0),
+                &io_metadata,
+                &backend,
+                false,
+            );
+            shard.init();
+
+            let expected_size = shard.get_size_on_disk(&io_metadata);
+            assert_eq!(backend.len().unwrap(), expected_size);
+        }
+    }
+
+    /// Test that we can add elements to a shard, and that the number of
+    /// written elements is tracked correctly.
+    #[test]
+    fn test_add() {
+        let io_metadata = IoMetadata::new(
+            TsdfMetadata::new("no_version".to_string(), FileFormat::Binary),
+            IoMode::Write(WriteMode::LocklessWrite),
+        );
+        let backend = FileBackend::new(tempfile().unwrap());
+        let mut shard: DistListShard<'_, '_, Addr> =
+            DistListShard::new(0, Addr::new(0), &io_metadata, &backend, false);
+
+        for i in 0..shard.get_capacity() {
+            assert_eq!(shard.get_num_written(), i);
+            assert!(!shard.is_full());
+            shard.add(i, &Addr::new(i as u64 + 100));
+        }
+        assert!(shard.is_full());
+        assert_eq!(shard.get_element(3), Addr::new(103));
     }
 }
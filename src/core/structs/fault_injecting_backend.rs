@@ -0,0 +1,171 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::core::{enums::StorageOp, traits::StorageBackend};
+
+use super::MemoryBackend;
+
+/// A StorageBackend for crash testing. It behaves exactly like a
+/// MemoryBackend, but it also records every write and sync that it sees, so
+/// that the CrashHarness can later rebuild the state of storage at any point
+/// during a workload. It can also be told to return short reads, which makes
+/// sure that nothing assumes a single read_at call fills its buffer.
+///
+/// As with the MemoryBackend, clones share the same bytes and the same log.
+// The CrashHarness is the only thing that drives it, and the library never
+// runs the harness itself.
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+pub(crate) struct FaultInjectingBackend {
+    /// The backend that actually stores the bytes.
+    inner: MemoryBackend,
+
+    /// Every write and sync issued against the backend, in order.
+    ops: Arc<Mutex<Vec<StorageOp>>>,
+
+    /// If set, no single read_at call returns more than this many bytes.
+    max_read_len: Option<usize>,
+
+    /// The number of read_at calls made so far.
+    num_reads: Arc<AtomicUsize>,
+}
+
+#[allow(dead_code)]
+impl FaultInjectingBackend {
+    /// Constructs a new, empty FaultInjectingBackend.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a new FaultInjectingBackend that starts out containing
+    /// `bytes`. These bytes aren't part of the recorded log.
+    pub(crate) fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            inner: MemoryBackend::from_bytes(bytes),
+            ..Self::default()
+        }
+    }
+
+    /// Makes every read_at call return at most `max_read_len` bytes.
+    pub(crate) fn with_max_read_len(mut self, max_read_len: usize) -> Self {
+        self.max_read_len = Some(max_read_len.max(1));
+        self
+    }
+
+    /// Returns a copy of every operation recorded so far.
+    pub(crate) fn get_ops(&self) -> Vec<StorageOp> {
+        self.ops.lock().unwrap().clone()
+    }
+
+    /// Returns the number of writes recorded so far.
+    pub(crate) fn get_num_writes(&self) -> usize {
+        self.ops
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|op| matches!(op, StorageOp::Write { .. }))
+            .count()
+    }
+
+    /// Returns the number of read_at calls made so far.
+    pub(crate) fn get_num_reads(&self) -> usize {
+        self.num_reads.load(Ordering::Relaxed)
+    }
+
+    /// Returns a copy of every byte currently stored in the backend.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+}
+
+impl StorageBackend for FaultInjectingBackend {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.num_reads.fetch_add(1, Ordering::Relaxed);
+        let len = match self.max_read_len {
+            Some(max_read_len) => buf.len().min(max_read_len),
+            None => buf.len(),
+        };
+        self.inner.read_at(&mut buf[..len], offset)
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        // Carry out the write and then record the bytes that actually landed,
+        // holding the log's lock throughout so that the log order always
+        // matches the order the bytes landed in.
+        let mut ops = self.ops.lock().unwrap();
+        let num_written = self.inner.write_at(buf, offset)?;
+        ops.push(StorageOp::Write {
+            offset,
+            bytes: buf[..num_written].to_vec(),
+        });
+
+        Ok(num_written)
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        self.inner.len()
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.ops.lock().unwrap().push(StorageOp::Sync);
+        self.inner.sync()
+    }
+
+    fn lock(&self) -> io::Result<()> {
+        self.inner.lock()
+    }
+
+    fn unlock(&self) -> io::Result<()> {
+        self.inner.unlock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Make sure that writes and syncs are recorded in order.
+    #[test]
+    fn test_records_ops() {
+        let backend = FaultInjectingBackend::from_bytes(b"base".to_vec());
+        backend.write_all_at(b"ab", 4).unwrap();
+        backend.sync().unwrap();
+        backend.write_all_at(b"c", 0).unwrap();
+
+        assert_eq!(backend.to_bytes(), b"caseab");
+        assert_eq!(backend.get_num_writes(), 2);
+        assert_eq!(
+            backend.get_ops(),
+            vec![
+                StorageOp::Write {
+                    offset: 4,
+                    bytes: b"ab".to_vec()
+                },
+                StorageOp::Sync,
+                StorageOp::Write {
+                    offset: 0,
+                    bytes: b"c".to_vec()
+                },
+            ]
+        );
+    }
+
+    /// Make sure that short reads are short, but that read_full_at still
+    /// fills the whole buffer.
+    #[test]
+    fn test_short_reads() {
+        let backend = FaultInjectingBackend::from_bytes(b"abcdefg".to_vec())
+            .with_max_read_len(2);
+
+        let mut buf = [0; 5];
+        assert_eq!(backend.read_at(&mut buf, 0).unwrap(), 2);
+        assert_eq!(backend.read_full_at(&mut buf, 1).unwrap(), 5);
+        assert_eq!(&buf, b"bcdef");
+        assert_eq!(backend.get_num_reads(), 4);
+    }
+}
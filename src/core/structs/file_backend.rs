@@ -0,0 +1,162 @@
+use std::{
+    fs::File,
+    io,
+    os::unix::{fs::FileExt, io::AsRawFd},
+};
+
+use crate::core::traits::StorageBackend;
+
+/// The most buffers that we hand to a single pwritev call. POSIX only
+/// guarantees that IOV_MAX is at least 16, but every platform we care about
+/// allows 1024.
+const MAX_IOVECS: usize = 1024;
+
+/// A StorageBackend that stores everything in a file on the local file system.
+pub(crate) struct FileBackend {
+    /// The open file handle.
+    file: File,
+}
+
+impl FileBackend {
+    /// Constructs a new FileBackend from an open file handle.
+    pub(crate) fn new(file: File) -> Self {
+        Self { file }
+    }
+
+    /// Truncates or extends the file to the given length.
+    pub(crate) fn set_len(&self, len: u64) -> io::Result<()> {
+        self.file.set_len(len)
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        self.file.read_at(buf, offset)
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.file.write_at(buf, offset)
+    }
+
+    fn write_vectored_at(
+        &self,
+        bufs: &[&[u8]],
+        offset: u64,
+    ) -> io::Result<usize> {
+        // Hand the buffers straight to pwritev, so that the kernel gathers
+        // them without us copying them into one buffer first. Any buffers
+        // beyond MAX_IOVECS are left for the caller's next call.
+        let iovecs: Vec<libc::iovec> = bufs
+            .iter()
+            .take(MAX_IOVECS)
+            .map(|buf| libc::iovec {
+                iov_base: buf.as_ptr() as *mut libc::c_void,
+                iov_len: buf.len(),
+            })
+            .collect();
+
+        // SAFETY: every iovec points into a buffer that outlives this call,
+        // and pwritev only reads from them.
+        let num_written = unsafe {
+            libc::pwritev(
+                self.file.as_raw_fd(),
+                iovecs.as_ptr(),
+                iovecs.len() as libc::c_int,
+                offset as libc::off_t,
+            )
+        };
+        if num_written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(num_written as usize)
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    fn lock(&self) -> io::Result<()> {
+        // File::try_lock reports contention with its own error type, which we
+        // map onto the WouldBlock error kind promised by StorageBackend.
+        self.file.try_lock().map_err(|e| match e {
+            std::fs::TryLockError::WouldBlock => io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "The file is locked by another handle.",
+            ),
+            std::fs::TryLockError::Error(e) => e,
+        })
+    }
+
+    fn unlock(&self) -> io::Result<()> {
+        self.file.unlock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::{tempfile, NamedTempFile};
+
+    use super::*;
+
+    /// Test that bytes written to a FileBackend can be read back.
+    #[test]
+    fn test_write_read() {
+        let backend = FileBackend::new(tempfile().unwrap());
+        backend.write_all_at(b"hello", 3).unwrap();
+
+        // The gap before the write should have been filled with zeros.
+        assert_eq!(backend.len().unwrap(), 8);
+        let mut buf = [1; 8];
+        assert_eq!(backend.read_full_at(&mut buf, 0).unwrap(), 8);
+        assert_eq!(&buf, b"\0\0\0hello");
+    }
+
+    /// Make sure that vectored writes land one after another, even when there
+    /// are more buffers than a single pwritev call can take.
+    #[test]
+    fn test_write_vectored() {
+        let backend = FileBackend::new(tempfile().unwrap());
+        let bufs: Vec<Vec<u8>> =
+            (0..3000).map(|i| vec![(i % 251) as u8; 2]).collect();
+        let slices: Vec<&[u8]> =
+            bufs.iter().map(|buf| buf.as_slice()).collect();
+        backend.write_all_vectored_at(&slices, 5).unwrap();
+
+        assert_eq!(backend.len().unwrap(), 5 + 6000);
+        let mut buf = vec![0; 6000];
+        backend.read_full_at(&mut buf, 5).unwrap();
+        assert_eq!(buf, bufs.concat());
+    }
+
+    /// Make sure that reading past the end of the file is a short read, rather
+    /// than an error.
+    #[test]
+    fn test_read_past_end() {
+        let backend = FileBackend::new(tempfile().unwrap());
+        backend.write_all_at(b"abc", 0).unwrap();
+
+        let mut buf = [0; 5];
+        assert_eq!(backend.read_full_at(&mut buf, 1).unwrap(), 2);
+        assert_eq!(&buf, b"bc\0\0\0");
+    }
+
+    /// Make sure that two handles to the same file can't both hold the lock.
+    #[test]
+    fn test_lock() {
+        let named = NamedTempFile::new().unwrap();
+        let first = FileBackend::new(named.reopen().unwrap());
+        let second = FileBackend::new(named.reopen().unwrap());
+
+        first.lock().unwrap();
+        let err = second.lock().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        first.unlock().unwrap();
+        second.lock().unwrap();
+    }
+}
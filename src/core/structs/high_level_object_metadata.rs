@@ -1,6 +0,0 @@
-use super::DistDict;
-
-// pub(super) struct HighLevelObjectMetadata {
-//     name: String,
-//     metadata: DistDict<String, String>,
-// }
@@ -1,6 +1,9 @@
-use crate::core::enums::IoMode;
+use crate::core::{
+    enums::{Durability, IoMode},
+    well_known_values::shard_limits::DEFAULT_SHARD_CACHE_CAPACITY,
+};
 
-use super::TsdfMetadata;
+use super::{ShardCache, TsdfMetadata};
 
 /// All metadata required to carry out an I/O operation in the tsdf library. This includes the
 /// TsdfMetadata stored in the file, as well as the IoMode that is being used to interact with the
@@ -11,6 +14,13 @@ pub(crate) struct IoMetadata {
 
     /// The IoMode used to interact with the file.
     io_mode: IoMode,
+
+    /// How hard writes work to get changes onto the disk.
+    durability: Durability,
+
+    /// Immutable facts about the layout of shard chains in the file, shared
+    /// by every structure that is read through this IoMetadata.
+    shard_cache: ShardCache,
 }
 
 impl IoMetadata {
@@ -19,6 +29,8 @@ impl IoMetadata {
         Self {
             tsdf_metadata,
             io_mode,
+            durability: Durability::default(),
+            shard_cache: ShardCache::new(DEFAULT_SHARD_CACHE_CAPACITY),
         }
     }
 
@@ -31,4 +43,19 @@ impl IoMetadata {
     pub fn get_io_mode(&self) -> &IoMode {
         &self.io_mode
     }
+
+    /// Returns how hard writes work to get changes onto the disk.
+    pub(crate) fn get_durability(&self) -> Durability {
+        self.durability
+    }
+
+    /// Sets how hard writes work to get changes onto the disk.
+    pub(crate) fn set_durability(&mut self, durability: Durability) {
+        self.durability = durability;
+    }
+
+    /// Returns the cache of shard chain layouts.
+    pub(crate) fn get_shard_cache(&self) -> &ShardCache {
+        &self.shard_cache
+    }
 }
@@ -0,0 +1,171 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use crate::core::traits::StorageBackend;
+
+/// A StorageBackend that keeps all of its bytes in memory. This is mostly
+/// useful for tests and bindings, where touching the file system would be
+/// slow or undesirable.
+///
+/// Cloning a MemoryBackend is cheap, and the clone refers to the same buffer.
+/// This means that a writer and any number of readers can share one in-memory
+/// "file", exactly as they would share a file on disk.
+// Only tests build one so far. It's kept in the library for the bindings and
+// future backends to reuse.
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+pub(crate) struct MemoryBackend {
+    /// The bytes stored in the backend.
+    data: Arc<RwLock<Vec<u8>>>,
+
+    /// Whether some handle currently holds the lock on the backend.
+    locked: Arc<AtomicBool>,
+}
+
+#[allow(dead_code)]
+impl MemoryBackend {
+    /// Constructs a new, empty MemoryBackend.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a new MemoryBackend that starts out containing `bytes`.
+    pub(crate) fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            data: Arc::new(RwLock::new(bytes)),
+            locked: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns a copy of every byte currently stored in the backend.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        self.data.read().unwrap().clone()
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let data = self.data.read().unwrap();
+
+        // Reading from past the end of the buffer is a zero byte read.
+        let start = (offset as usize).min(data.len());
+        let end = (start + buf.len()).min(data.len());
+        let num_read = end - start;
+        buf[..num_read].copy_from_slice(&data[start..end]);
+
+        Ok(num_read)
+    }
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let mut data = self.data.write().unwrap();
+
+        // Writing past the end of the buffer grows it, filling any gap with
+        // zeros. This mirrors what happens with a sparse file on disk.
+        let start = offset as usize;
+        let end = start + buf.len();
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[start..end].copy_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn write_vectored_at(
+        &self,
+        bufs: &[&[u8]],
+        offset: u64,
+    ) -> io::Result<usize> {
+        // Copy every buffer in while holding the lock once, so that readers
+        // see the whole vectored write land at once.
+        let mut data = self.data.write().unwrap();
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let mut start = offset as usize;
+        if data.len() < start + total_len {
+            data.resize(start + total_len, 0);
+        }
+        for buf in bufs {
+            data[start..start + buf.len()].copy_from_slice(buf);
+            start += buf.len();
+        }
+
+        Ok(total_len)
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        Ok(self.data.read().unwrap().len() as u64)
+    }
+
+    fn sync(&self) -> io::Result<()> {
+        // There's nowhere to flush to.
+        Ok(())
+    }
+
+    fn lock(&self) -> io::Result<()> {
+        match self.locked.compare_exchange(
+            false,
+            true,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "The memory backend is already locked.",
+            )),
+        }
+    }
+
+    fn unlock(&self) -> io::Result<()> {
+        self.locked.store(false, Ordering::Release);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that bytes written to a MemoryBackend can be read back, and that
+    /// gaps are zero filled.
+    #[test]
+    fn test_write_read() {
+        let backend = MemoryBackend::new();
+        backend.write_all_at(b"hello", 3).unwrap();
+
+        assert_eq!(backend.len().unwrap(), 8);
+        assert_eq!(backend.to_bytes(), b"\0\0\0hello");
+    }
+
+    /// Make sure that reading past the end of the buffer is a short read.
+    #[test]
+    fn test_read_past_end() {
+        let backend = MemoryBackend::from_bytes(b"abc".to_vec());
+
+        let mut buf = [0; 5];
+        assert_eq!(backend.read_full_at(&mut buf, 1).unwrap(), 2);
+        assert_eq!(&buf, b"bc\0\0\0");
+        assert_eq!(backend.read_full_at(&mut buf, 10).unwrap(), 0);
+    }
+
+    /// Make sure that clones share both the data and the lock.
+    #[test]
+    fn test_clones_share_state() {
+        let writer = MemoryBackend::new();
+        let reader = writer.clone();
+
+        writer.write_all_at(b"shared", 0).unwrap();
+        assert_eq!(reader.to_bytes(), b"shared");
+
+        writer.lock().unwrap();
+        let err = reader.lock().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        writer.unlock().unwrap();
+        reader.lock().unwrap();
+    }
+}
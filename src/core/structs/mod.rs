@@ -1,23 +1,74 @@
 pub mod array;
+pub mod array_options;
+#[cfg(feature = "async")]
+pub mod async_array;
+#[cfg(feature = "async")]
+pub mod async_dir;
+#[cfg(feature = "async")]
+pub mod async_tsdf_file;
+pub mod chunk_stats;
+pub mod csv_import_options;
 pub mod dir;
+pub mod open_options;
+pub mod timestamp;
 pub mod tsdf_file;
 
 pub(crate) mod addr;
+pub(crate) mod array_header;
+pub(crate) mod bit_stream;
+pub(crate) mod blob;
+pub(crate) mod crash_harness;
+pub(crate) mod crash_report;
+pub(crate) mod dir_entry;
 pub(crate) mod dist_dict;
 pub(crate) mod dist_dict_shard;
+pub(crate) mod dist_list;
 pub(crate) mod dist_list_shard;
-pub(crate) mod high_level_object_metadata;
+pub(crate) mod fault_injecting_backend;
+pub(crate) mod file_backend;
 pub(crate) mod io_metadata;
+pub(crate) mod memory_backend;
+pub(crate) mod regular_time_axis;
+pub(crate) mod shard_cache;
+pub(crate) mod tag_entry;
+pub(crate) mod time_gap;
 pub(crate) mod tsdf_hash;
 pub(crate) mod tsdf_metadata;
+pub(crate) mod write_buffer;
+
+pub use array::Array;
+pub use array_options::ArrayOptions;
+#[cfg(feature = "async")]
+pub use async_array::AsyncArray;
+#[cfg(feature = "async")]
+pub use async_dir::AsyncDir;
+#[cfg(feature = "async")]
+pub use async_tsdf_file::AsyncTsdfFile;
+pub use chunk_stats::ChunkStats;
+pub use csv_import_options::CsvImportOptions;
+pub use dir::Dir;
+pub use open_options::OpenOptions;
+pub use timestamp::Timestamp;
+pub use tsdf_file::TsdfFile;
 
 pub(crate) use addr::Addr;
-pub(crate) use array::Array;
-pub(crate) use dir::Dir;
+pub(crate) use array_header::ArrayHeader;
+pub(crate) use bit_stream::{BitReader, BitWriter};
+pub(crate) use blob::Blob;
+pub(crate) use crash_report::CrashReport;
+pub(crate) use dir_entry::DirEntry;
 pub(crate) use dist_dict::DistDict;
 pub(crate) use dist_dict_shard::DistDictShard;
+pub(crate) use dist_list::DistList;
 pub(crate) use dist_list_shard::DistListShard;
+pub(crate) use fault_injecting_backend::FaultInjectingBackend;
+pub(crate) use file_backend::FileBackend;
 pub(crate) use io_metadata::IoMetadata;
-pub(crate) use tsdf_file::TsdfFile;
+pub(crate) use memory_backend::MemoryBackend;
+pub(crate) use regular_time_axis::RegularTimeAxis;
+pub(crate) use shard_cache::ShardCache;
+pub(crate) use tag_entry::TagEntry;
+pub(crate) use time_gap::TimeGap;
 pub(crate) use tsdf_hash::TsdfHash;
 pub(crate) use tsdf_metadata::TsdfMetadata;
+pub(crate) use write_buffer::WriteBuffer;
@@ -0,0 +1,412 @@
+use std::{io, path::Path, time::Duration};
+
+use crate::core::{
+    enums::{Durability, FileFormat, HashAlgorithm, ReadMode, WriteMode},
+    well_known_values::shard_limits::MAX_SHARD_CAPACITY,
+};
+
+use super::{TsdfFile, TsdfMetadata};
+
+/// Options for opening and creating a TsdfFile, in the style of
+/// std::fs::OpenOptions.
+///
+/// ```no_run
+/// use tsdf::{Durability, HashAlgorithm, OpenOptions};
+///
+/// # fn main() -> tsdf::Result<()> {
+/// let file = OpenOptions::new()
+///     .hash_algorithm(HashAlgorithm::Fnv1a)
+///     .checksums(true)
+///     .durability(Durability::Synced)
+///     .create("run.tsdf")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The file format, hash algorithm, shard capacity and checksums are part of
+/// the file's layout, and are stored in its header when the file is created.
+/// When an existing file is opened, any of these that are set must match the
+/// file, and those that aren't set are read from it.
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    /// The read mode used by open_reader.
+    read_mode: Option<ReadMode>,
+
+    /// The write mode used by open_writer and create.
+    write_mode: Option<WriteMode>,
+
+    /// How hard writes work to get changes onto the disk.
+    durability: Durability,
+
+    /// How long to keep trying to lock a file before giving up.
+    lock_timeout: Option<Duration>,
+
+    /// The format of the file.
+    file_format: Option<FileFormat>,
+
+    /// The hash algorithm used to place keys in distributed dictionaries.
+    hash_algorithm: Option<HashAlgorithm>,
+
+    /// The number of slots in the first shard of every distributed list and
+    /// dictionary.
+    shard_capacity: Option<u64>,
+
+    /// Whether every frame of every array is followed by a checksum.
+    checksums: Option<bool>,
+}
+
+impl OpenOptions {
+    /// Constructs a new set of options, with everything left at its default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the read mode. Defaults to ReadMode::LocklessRead.
+    pub fn read_mode(&mut self, read_mode: ReadMode) -> &mut Self {
+        self.read_mode = Some(read_mode);
+        self
+    }
+
+    /// Sets the write mode. Defaults to WriteMode::LocklessWrite.
+    pub fn write_mode(&mut self, write_mode: WriteMode) -> &mut Self {
+        self.write_mode = Some(write_mode);
+        self
+    }
+
+    /// Sets how hard writes work to get changes onto the disk. Defaults to
+    /// Durability::Buffered.
+    pub fn durability(&mut self, durability: Durability) -> &mut Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Sets how long a locking writer keeps trying to lock a file that's
+    /// locked by someone else. By default, it gives up straight away.
+    pub fn lock_timeout(&mut self, lock_timeout: Duration) -> &mut Self {
+        self.lock_timeout = Some(lock_timeout);
+        self
+    }
+
+    /// Sets the format of the file. New files default to FileFormat::Binary.
+    pub fn file_format(&mut self, file_format: FileFormat) -> &mut Self {
+        self.file_format = Some(file_format);
+        self
+    }
+
+    /// Sets the hash algorithm used to place keys in distributed
+    /// dictionaries. New files default to HashAlgorithm::SipHash13.
+    pub fn hash_algorithm(
+        &mut self,
+        hash_algorithm: HashAlgorithm,
+    ) -> &mut Self {
+        self.hash_algorithm = Some(hash_algorithm);
+        self
+    }
+
+    /// Sets the number of slots in the first shard of every distributed list
+    /// and dictionary, which must be between 1 and 4096. Larger shards mean
+    /// shorter chains for big dirs and arrays, at the cost of space in small
+    /// ones. New files default to 8.
+    pub fn shard_capacity(&mut self, shard_capacity: u64) -> &mut Self {
+        self.shard_capacity = Some(shard_capacity);
+        self
+    }
+
+    /// Sets whether every frame of every array is followed by a checksum,
+    /// which is checked whenever the frame is read. New files default to no
+    /// checksums.
+    pub fn checksums(&mut self, checksums: bool) -> &mut Self {
+        self.checksums = Some(checksums);
+        self
+    }
+
+    /// Opens an existing file for reading.
+    pub fn open_reader<P: AsRef<Path>>(&self, path: P) -> io::Result<TsdfFile> {
+        TsdfFile::open_reader(path.as_ref(), self)
+    }
+
+    /// Opens a file for writing, creating it if it doesn't exist.
+    pub fn open_writer<P: AsRef<Path>>(&self, path: P) -> io::Result<TsdfFile> {
+        TsdfFile::open_writer(path.as_ref(), self)
+    }
+
+    /// Creates a new file for writing, overwriting any existing file. A
+    /// locking writer leaves an existing file alone, and fails with
+    /// WouldBlock, if someone else holds its lock past the lock timeout.
+    pub fn create<P: AsRef<Path>>(&self, path: P) -> io::Result<TsdfFile> {
+        TsdfFile::create(path.as_ref(), self)
+    }
+}
+
+// Implement the methods that TsdfFile uses to apply the options.
+impl OpenOptions {
+    /// Returns the read mode.
+    pub(crate) fn get_read_mode(&self) -> ReadMode {
+        self.read_mode.unwrap_or(ReadMode::LocklessRead)
+    }
+
+    /// Returns the write mode.
+    pub(crate) fn get_write_mode(&self) -> WriteMode {
+        self.write_mode.unwrap_or(WriteMode::LocklessWrite)
+    }
+
+    /// Returns how hard writes work to get changes onto the disk.
+    pub(crate) fn get_durability(&self) -> Durability {
+        self.durability
+    }
+
+    /// Returns how long to keep trying to lock a file.
+    pub(crate) fn get_lock_timeout(&self) -> Duration {
+        self.lock_timeout.unwrap_or_default()
+    }
+
+    /// Makes the metadata for a new file with the given version.
+    pub(crate) fn make_metadata(
+        &self,
+        version: &str,
+    ) -> io::Result<TsdfMetadata> {
+        let file_format = self.file_format.unwrap_or(FileFormat::Binary);
+        let mut metadata = TsdfMetadata::new(version.to_string(), file_format);
+        if let Some(hash_algorithm) = self.hash_algorithm {
+            metadata.set_hash_algorithm(hash_algorithm);
+        }
+        if let Some(shard_capacity) = self.shard_capacity {
+            if !(1..=MAX_SHARD_CAPACITY).contains(&shard_capacity) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Shard capacity must be between 1 and {}, not {}.",
+                        MAX_SHARD_CAPACITY, shard_capacity
+                    ),
+                ));
+            }
+            metadata.set_shard_capacity(shard_capacity);
+        }
+        if let Some(checksums) = self.checksums {
+            metadata.set_checksums(checksums);
+        }
+
+        Ok(metadata)
+    }
+
+    /// Returns a copy of these options, with any layout option that isn't set
+    /// taken from the given metadata.
+    pub(crate) fn with_layout_of(&self, metadata: &TsdfMetadata) -> Self {
+        let mut options = self.clone();
+        options
+            .file_format
+            .get_or_insert(*metadata.get_file_format());
+        options
+            .hash_algorithm
+            .get_or_insert(metadata.get_hash_algorithm());
+        options
+            .shard_capacity
+            .get_or_insert(metadata.get_shard_capacity());
+        options.checksums.get_or_insert(metadata.has_checksums());
+        options
+    }
+
+    /// Returns an error if any layout option that has been set doesn't match
+    /// the metadata of an existing file.
+    pub(crate) fn check_layout(
+        &self,
+        metadata: &TsdfMetadata,
+    ) -> io::Result<()> {
+        let mismatch = |name: &str| {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} does not match the existing file's.", name),
+            ))
+        };
+        if self
+            .file_format
+            .is_some_and(|f| &f != metadata.get_file_format())
+        {
+            return mismatch("File format");
+        }
+        if self
+            .hash_algorithm
+            .is_some_and(|h| h != metadata.get_hash_algorithm())
+        {
+            return mismatch("Hash algorithm");
+        }
+        if self
+            .shard_capacity
+            .is_some_and(|c| c != metadata.get_shard_capacity())
+        {
+            return mismatch("Shard capacity");
+        }
+        if self
+            .checksums
+            .is_some_and(|c| c != metadata.has_checksums())
+        {
+            return mismatch("Checksums setting");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::core::{enums::ArrayDataType, traits::ArrayTrait};
+
+    /// Layout options given at creation should be stored in the header, and
+    /// read back when the file is reopened without them.
+    #[test]
+    fn test_layout_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("layout.tsdf");
+
+        let file = OpenOptions::new()
+            .hash_algorithm(HashAlgorithm::Fnv1a)
+            .shard_capacity(32)
+            .checksums(true)
+            .durability(Durability::Synced)
+            .create(&path)
+            .unwrap();
+        assert_eq!(file.get_durability(), Durability::Synced);
+        let root = file.get_root_dir().unwrap();
+        for i in 0..40 {
+            root.create_dir(&format!("dir_{}", i)).unwrap();
+        }
+        let array = root
+            .create_array("counts", ArrayDataType::UInt32, &[2], 4)
+            .unwrap();
+        array.append_frames(&[1_u32, 2, 3, 4, 5, 6]).unwrap();
+        drop(file);
+
+        let file = OpenOptions::new().open_reader(&path).unwrap();
+        assert_eq!(file.get_hash_algorithm(), HashAlgorithm::Fnv1a);
+        assert_eq!(file.get_shard_capacity(), 32);
+        assert!(file.has_checksums());
+        let root = file.get_root_dir().unwrap();
+        assert_eq!(root.list_dir().unwrap().len(), 41);
+        let array = root.get_array("counts").unwrap();
+        assert_eq!(array.get_frame::<u32>(2).unwrap(), vec![5, 6]);
+    }
+
+    /// Layout options that don't match an existing file, and shard capacities
+    /// that are out of range, are both invalid input.
+    #[test]
+    fn test_bad_options() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("bad.tsdf");
+        OpenOptions::new().create(&path).unwrap();
+
+        let kind = |result: io::Result<TsdfFile>| result.err().unwrap().kind();
+        assert_eq!(
+            kind(OpenOptions::new().checksums(true).open_writer(&path)),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            kind(
+                OpenOptions::new()
+                    .hash_algorithm(HashAlgorithm::Fnv1a)
+                    .open_reader(&path)
+            ),
+            io::ErrorKind::InvalidInput
+        );
+        for capacity in [0, MAX_SHARD_CAPACITY + 1] {
+            assert_eq!(
+                kind(OpenOptions::new().shard_capacity(capacity).create(&path)),
+                io::ErrorKind::InvalidInput
+            );
+        }
+
+        // The failed create mustn't have touched the existing file.
+        assert!(OpenOptions::new().open_reader(&path).is_ok());
+    }
+
+    /// A second locking writer should keep retrying until its timeout runs
+    /// out, and then give up.
+    #[test]
+    fn test_lock_timeout() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("locked.tsdf");
+        let mut options = OpenOptions::new();
+        options.write_mode(WriteMode::LockingWrite);
+        let _writer = options.create(&path).unwrap();
+
+        let timeout = Duration::from_millis(50);
+        let start = std::time::Instant::now();
+        let err = options
+            .lock_timeout(timeout)
+            .open_writer(&path)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert!(start.elapsed() >= timeout);
+    }
+
+    /// Overwriting a file that another writer has locked should fail, and
+    /// leave that writer's data alone.
+    #[test]
+    fn test_create_locked() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("locked.tsdf");
+        let mut options = OpenOptions::new();
+        options.write_mode(WriteMode::LockingWrite);
+        let writer = options.create(&path).unwrap();
+        writer.get_root_dir().unwrap().create_dir("kept").unwrap();
+        let len = fs::metadata(&path).unwrap().len();
+
+        let err = options
+            .lock_timeout(Duration::from_millis(20))
+            .create(&path)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+
+        // The first writer still owns the file it wrote to.
+        let root = writer.get_root_dir().unwrap();
+        root.create_dir("added").unwrap();
+        drop(writer);
+        let reader = OpenOptions::new().open_reader(&path).unwrap();
+        let root = reader.get_root_dir().unwrap();
+        assert_eq!(root.list_dir().unwrap(), vec!["added", "kept"]);
+    }
+
+    /// A corrupted frame should be caught both when it's read and when the
+    /// array is verified.
+    #[test]
+    fn test_checksum_mismatch() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("corrupt.tsdf");
+        let marker = 0x0123_4567_89ab_cdef_u64;
+        {
+            let file =
+                OpenOptions::new().checksums(true).create(&path).unwrap();
+            let root = file.get_root_dir().unwrap();
+            let array = root
+                .create_array("counts", ArrayDataType::UInt64, &[], 4)
+                .unwrap();
+            array.append(&[marker]).unwrap();
+        }
+
+        // Flip a bit in the middle of the frame.
+        let mut bytes = fs::read(&path).unwrap();
+        let marker_bytes = marker.to_le_bytes();
+        let loc = bytes
+            .windows(marker_bytes.len())
+            .position(|window| window == marker_bytes)
+            .unwrap();
+        bytes[loc + 4] ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        let file = OpenOptions::new().open_reader(&path).unwrap();
+        let root = file.get_root_dir().unwrap();
+        let array = root.get_array("counts").unwrap();
+        let kind = array.get_frame::<u64>(0).err().unwrap().kind();
+        assert_eq!(kind, io::ErrorKind::InvalidData);
+        assert_eq!(
+            ArrayTrait::verify(&array).err().unwrap().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
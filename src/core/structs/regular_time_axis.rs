@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    enums::ClockSource,
+    traits::{FileSerializable, FixedSizeOnDisk},
+};
+
+use super::{Addr, TimeGap, Timestamp};
+
+/// The RegularTimeAxis holds what's needed to work out when each frame of an
+/// array with a Regular time axis was taken. It's written right after the
+/// array's tags address when the array is created, and only the address of
+/// the gap list is filled in afterwards, before the array is complete.
+///
+/// Frame i was taken at t0 + i * dt, unless there's a TimeGap at or before i,
+/// in which case it was taken dt * (i - index) after the last such gap. Gaps
+/// are stored in a DistList, in the order they were written. A gap overrides
+/// every gap written before it whose index is at or after its own, which is
+/// how a writer undoes gaps left behind by a crash before their frames were
+/// written.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RegularTimeAxis {
+    /// When the first frame was taken.
+    t0: Timestamp,
+
+    /// The time between frames, in nanoseconds. Always positive.
+    dt: i64,
+
+    /// The address of the DistList of TimeGaps.
+    gaps: Addr,
+}
+
+impl RegularTimeAxis {
+    /// Constructs a new RegularTimeAxis, with no gap list yet.
+    pub(crate) fn new(t0: Timestamp, dt: i64) -> Self {
+        Self {
+            t0,
+            dt,
+            gaps: Addr::null(),
+        }
+    }
+
+    /// Returns whether this is a valid time axis, rather than a null one (or
+    /// garbage read from an unwritten part of the file).
+    pub(crate) fn is_valid(&self) -> bool {
+        self.dt > 0 && self.gaps != Addr::null()
+    }
+
+    /// Returns when the first frame was taken.
+    pub(crate) fn get_t0(&self) -> Timestamp {
+        self.t0
+    }
+
+    /// Returns the time between frames, in nanoseconds.
+    pub(crate) fn get_dt(&self) -> i64 {
+        self.dt
+    }
+
+    /// Returns the address of the DistList of TimeGaps.
+    pub(crate) fn get_gaps_addr(&self) -> Addr {
+        self.gaps
+    }
+
+    /// Sets the address of the DistList of TimeGaps.
+    pub(crate) fn set_gaps_addr(&mut self, gaps: Addr) {
+        self.gaps = gaps;
+    }
+
+    /// Returns when the frame with the given index was taken, in nanoseconds
+    /// since the Unix epoch. The gaps must be sorted by index, with none
+    /// overridden, as ArrayTrait::get_time_gaps returns them.
+    pub(crate) fn get_nanos(&self, gaps: &[TimeGap], index: u64) -> i64 {
+        let after = gaps.partition_point(|gap| gap.get_index() <= index);
+        let (start, nanos) = match after.checked_sub(1) {
+            Some(i) => (gaps[i].get_index(), gaps[i].get_nanos()),
+            None => (0, self.t0.get_nanos()),
+        };
+        nanos.saturating_add(self.dt.saturating_mul((index - start) as i64))
+    }
+}
+
+impl FixedSizeOnDisk for RegularTimeAxis {
+    fn get_bin_size_on_disk() -> u64 {
+        Timestamp::get_bin_size_on_disk() + 8 + Addr::get_bin_size_on_disk()
+    }
+
+    fn get_json_size_on_disk() -> u64 {
+        let axis = RegularTimeAxis {
+            t0: Timestamp::new(i64::MIN, ClockSource::Unknown),
+            dt: i64::MIN,
+            gaps: Addr::new(u64::MAX),
+        };
+        axis.get_text_len()
+    }
+}
+
+impl FileSerializable for RegularTimeAxis {
+    const TEXT_MARKER: &'static str = "RegularTimeAxis";
+
+    fn null() -> Self {
+        Self::new(Timestamp::null(), 0)
+    }
+
+    fn to_bin(&self) -> Vec<u8> {
+        let mut bytes = self.t0.to_bin();
+        bytes.extend_from_slice(&self.dt.to_le_bytes());
+        bytes.extend_from_slice(&self.gaps.to_bin());
+        bytes
+    }
+
+    fn from_bin(bytes: &[u8]) -> Self {
+        Self {
+            t0: Timestamp::from_bin(&bytes[0..9]),
+            dt: i64::from_le_bytes(bytes[9..17].try_into().unwrap()),
+            gaps: Addr::from_bin(&bytes[17..25]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The time axis should round trip in both formats, and compute times
+    /// from the right gap.
+    #[test]
+    fn test_regular_time_axis() {
+        let mut axis =
+            RegularTimeAxis::new(Timestamp::new(100, ClockSource::Gps), 10);
+        axis.set_gaps_addr(Addr::new(64));
+        let bytes = axis.to_bin();
+        assert_eq!(bytes.len() as u64, RegularTimeAxis::get_bin_size_on_disk());
+        assert_eq!(RegularTimeAxis::from_bin(&bytes), axis);
+        let json = axis.to_json();
+        assert_eq!(json.len() as u64, RegularTimeAxis::get_json_size_on_disk());
+        assert_eq!(RegularTimeAxis::from_json(json), axis);
+        assert!(!RegularTimeAxis::null().is_valid());
+
+        let gaps = [TimeGap::new(3, 1_000), TimeGap::new(5, 2_000)];
+        let nanos: Vec<i64> =
+            (0..7).map(|i| axis.get_nanos(&gaps, i)).collect();
+        assert_eq!(nanos, vec![100, 110, 120, 1_000, 1_010, 2_000, 2_010]);
+    }
+}
@@ -0,0 +1,230 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use super::Addr;
+
+/// Everything that we know about a single chain of shards.
+struct ChainEntry {
+    /// The address of every shard in the chain that we know of, indexed by
+    /// link number. Entry 0 is the first shard.
+    shards: Vec<Addr>,
+
+    /// The number of leading shards known to have every slot written.
+    num_full: usize,
+
+    /// When the chain was last used, for picking what to evict.
+    last_used: u64,
+}
+
+/// The mutable state of a ShardCache.
+#[derive(Default)]
+struct ShardCacheInner {
+    /// The maximum number of chains to remember.
+    capacity: usize,
+
+    /// The cached chains, keyed by the location of their first shard.
+    chains: HashMap<u64, ChainEntry>,
+
+    /// A counter that ticks on every access.
+    clock: u64,
+}
+
+/// The ShardCache remembers facts about chains of shards that can never
+/// change once they've been written, so that readers don't have to rediscover
+/// them on every lookup.
+///
+/// A shard's next pointer is never changed after its is_next_written boolean
+/// has been set, so the address of every shard in a chain, except for the
+/// tail, is immutable. The same goes for a shard of a distributed list whose
+/// slots have all been written, as list elements are never changed. The cache
+/// stores these facts, which means that only the tail of a chain has to be
+/// read from the file again. Slots of a distributed dictionary can be updated
+/// or removed, so they are never cached.
+///
+/// The cache is bounded: it remembers at most `capacity` chains, evicting the
+/// least recently used chain when it's full. A capacity of 0 disables it.
+pub(crate) struct ShardCache {
+    inner: Mutex<ShardCacheInner>,
+}
+
+impl ShardCache {
+    /// Constructs a new, empty ShardCache that remembers at most `capacity`
+    /// chains.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(ShardCacheInner {
+                capacity,
+                ..ShardCacheInner::default()
+            }),
+        }
+    }
+
+    /// Returns the maximum number of chains that the cache remembers.
+    pub(crate) fn get_capacity(&self) -> usize {
+        self.inner.lock().unwrap().capacity
+    }
+
+    /// Sets the maximum number of chains that the cache remembers, evicting
+    /// chains if there are now too many.
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.capacity = capacity;
+        while inner.chains.len() > capacity {
+            inner.evict();
+        }
+    }
+
+    /// Returns the number of chains currently in the cache.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.inner.lock().unwrap().chains.len()
+    }
+
+    /// Returns whether the cache is empty.
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the address of the shard with the given link number in the
+    /// chain that starts at `first_shard`, if we know it.
+    pub(crate) fn get_shard_addr(
+        &self,
+        first_shard: Addr,
+        link_number: i32,
+    ) -> Option<Addr> {
+        let mut inner = self.inner.lock().unwrap();
+        let entry = inner.touch(first_shard)?;
+        entry.shards.get(link_number as usize).copied()
+    }
+
+    /// Records the address of the shard with the given link number in the
+    /// chain that starts at `first_shard`. This must only be called once the
+    /// pointer to the shard has been published. Addresses are only recorded
+    /// in link order, so that the cache never has gaps.
+    pub(crate) fn insert_shard_addr(
+        &self,
+        first_shard: Addr,
+        link_number: i32,
+        addr: Addr,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(entry) = inner.touch_or_insert(first_shard) {
+            if entry.shards.len() == link_number as usize {
+                entry.shards.push(addr);
+            }
+        }
+    }
+
+    /// Returns the number of leading shards in the chain that starts at
+    /// `first_shard` that are known to have every slot written.
+    pub(crate) fn get_num_full(&self, first_shard: Addr) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        inner.touch(first_shard).map_or(0, |entry| entry.num_full)
+    }
+
+    /// Records that the first `num_full` shards in the chain that starts at
+    /// `first_shard` have every slot written.
+    pub(crate) fn set_num_full(&self, first_shard: Addr, num_full: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(entry) = inner.touch_or_insert(first_shard) {
+            entry.num_full = entry.num_full.max(num_full);
+        }
+    }
+}
+
+impl ShardCacheInner {
+    /// Returns the chain that starts at `first_shard`, marking it as used.
+    fn touch(&mut self, first_shard: Addr) -> Option<&mut ChainEntry> {
+        self.clock += 1;
+        let clock = self.clock;
+        let entry = self.chains.get_mut(&first_shard.get_loc())?;
+        entry.last_used = clock;
+        Some(entry)
+    }
+
+    /// Returns the chain that starts at `first_shard`, adding it if it isn't
+    /// there yet. Returns None if the cache is disabled.
+    fn touch_or_insert(
+        &mut self,
+        first_shard: Addr,
+    ) -> Option<&mut ChainEntry> {
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let key = first_shard.get_loc();
+        if !self.chains.contains_key(&key) {
+            if self.chains.len() >= self.capacity {
+                self.evict();
+            }
+            let entry = ChainEntry {
+                shards: vec![first_shard],
+                num_full: 0,
+                last_used: 0,
+            };
+            self.chains.insert(key, entry);
+        }
+        self.touch(first_shard)
+    }
+
+    /// Evicts the least recently used chain.
+    fn evict(&mut self) {
+        let oldest = self
+            .chains
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| *key);
+        if let Some(key) = oldest {
+            self.chains.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Make sure that shard addresses are only recorded in link order.
+    #[test]
+    fn test_shard_addrs() {
+        let cache = ShardCache::new(4);
+        let first = Addr::new(100);
+        assert_eq!(cache.get_shard_addr(first, 0), None);
+
+        // Link 2 can't be recorded before link 1.
+        cache.insert_shard_addr(first, 2, Addr::new(300));
+        assert_eq!(cache.get_shard_addr(first, 0), Some(first));
+        assert_eq!(cache.get_shard_addr(first, 2), None);
+
+        cache.insert_shard_addr(first, 1, Addr::new(200));
+        cache.insert_shard_addr(first, 2, Addr::new(300));
+        assert_eq!(cache.get_shard_addr(first, 1), Some(Addr::new(200)));
+        assert_eq!(cache.get_shard_addr(first, 2), Some(Addr::new(300)));
+
+        cache.set_num_full(first, 2);
+        cache.set_num_full(first, 1);
+        assert_eq!(cache.get_num_full(first), 2);
+    }
+
+    /// Make sure that the cache never holds more chains than its capacity, and
+    /// that it evicts the least recently used chain.
+    #[test]
+    fn test_bounded() {
+        let cache = ShardCache::new(2);
+        cache.insert_shard_addr(Addr::new(1), 1, Addr::new(10));
+        cache.insert_shard_addr(Addr::new(2), 1, Addr::new(20));
+
+        // Use chain 1, so that chain 2 is the one evicted.
+        assert!(cache.get_shard_addr(Addr::new(1), 1).is_some());
+        cache.insert_shard_addr(Addr::new(3), 1, Addr::new(30));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_shard_addr(Addr::new(1), 1).is_some());
+        assert!(cache.get_shard_addr(Addr::new(2), 1).is_none());
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        cache.set_capacity(0);
+        cache.insert_shard_addr(Addr::new(4), 1, Addr::new(40));
+        assert!(cache.is_empty());
+    }
+}
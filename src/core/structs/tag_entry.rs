@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::traits::{FileSerializable, FixedSizeOnDisk};
+
+use super::Addr;
+
+/// A TagEntry is the value stored in an object's tags dictionary for each of
+/// its metadata tags. Both the tag's name and its value are stored in Blobs.
+/// The name is kept so that we can tell apart names whose hashes collide.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TagEntry {
+    /// The address of the Blob holding the tag's name.
+    name: Addr,
+
+    /// The address of the Blob holding the tag's value.
+    value: Addr,
+}
+
+impl TagEntry {
+    /// Constructs a new TagEntry.
+    pub(crate) fn new(name: Addr, value: Addr) -> Self {
+        Self { name, value }
+    }
+
+    /// Returns the address of the Blob holding the tag's name.
+    pub(crate) fn get_name_addr(&self) -> Addr {
+        self.name
+    }
+
+    /// Returns the address of the Blob holding the tag's value.
+    pub(crate) fn get_value_addr(&self) -> Addr {
+        self.value
+    }
+}
+
+impl FixedSizeOnDisk for TagEntry {
+    fn get_bin_size_on_disk() -> u64 {
+        2 * Addr::get_bin_size_on_disk()
+    }
+
+    fn get_json_size_on_disk() -> u64 {
+        let entry = TagEntry {
+            name: Addr::new(u64::MAX),
+            value: Addr::new(u64::MAX),
+        };
+        entry.get_text_len()
+    }
+}
+
+impl FileSerializable for TagEntry {
+    const TEXT_MARKER: &'static str = "TagEntry";
+
+    fn null() -> Self {
+        Self {
+            name: Addr::null(),
+            value: Addr::null(),
+        }
+    }
+
+    fn to_bin(&self) -> Vec<u8> {
+        let mut bytes = self.name.to_bin();
+        bytes.extend_from_slice(&self.value.to_bin());
+        bytes
+    }
+
+    fn from_bin(bytes: &[u8]) -> Self {
+        Self {
+            name: Addr::from_bin(&bytes[0..8]),
+            value: Addr::from_bin(&bytes[8..16]),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::traits::{FileSerializable, FixedSizeOnDisk};
+
+/// A TimeGap records a break in the rate of an array with a Regular time axis.
+/// The frame with the given index was taken at the given time, and the frames
+/// after it carry on at the array's usual period from there.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TimeGap {
+    /// The index of the first frame after the gap.
+    index: u64,
+
+    /// When that frame was taken, in nanoseconds since the Unix epoch.
+    nanos: i64,
+}
+
+impl TimeGap {
+    /// Constructs a new TimeGap.
+    pub(crate) fn new(index: u64, nanos: i64) -> Self {
+        Self { index, nanos }
+    }
+
+    /// Returns the index of the first frame after the gap.
+    pub(crate) fn get_index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns when the first frame after the gap was taken.
+    pub(crate) fn get_nanos(&self) -> i64 {
+        self.nanos
+    }
+}
+
+impl FixedSizeOnDisk for TimeGap {
+    fn get_bin_size_on_disk() -> u64 {
+        8 + 8
+    }
+
+    fn get_json_size_on_disk() -> u64 {
+        TimeGap::new(u64::MAX, i64::MIN).get_text_len()
+    }
+}
+
+impl FileSerializable for TimeGap {
+    const TEXT_MARKER: &'static str = "TimeGap";
+
+    fn null() -> Self {
+        Self::new(0, 0)
+    }
+
+    fn to_bin(&self) -> Vec<u8> {
+        let mut bytes = self.index.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.nanos.to_le_bytes());
+        bytes
+    }
+
+    fn from_bin(bytes: &[u8]) -> Self {
+        Self {
+            index: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            nanos: i64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a TimeGap survives a round trip through binary and json.
+    #[test]
+    fn test_time_gap_round_trip() {
+        let gap = TimeGap::new(12, -345);
+        let bytes = gap.to_bin();
+        assert_eq!(bytes.len() as u64, TimeGap::get_bin_size_on_disk());
+        assert_eq!(TimeGap::from_bin(&bytes), gap);
+
+        let json = gap.to_json();
+        assert_eq!(json.len() as u64, TimeGap::get_json_size_on_disk());
+        assert_eq!(TimeGap::from_json(json), gap);
+    }
+}
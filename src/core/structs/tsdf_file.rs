@@ -1,47 +1,394 @@
 use std::io;
-use std::os::unix::fs::FileExt;
 use std::{
-    fs::{create_dir_all, File},
-    path::Path,
+    fs::{canonicalize, create_dir_all, File},
+    path::{Path, PathBuf},
+    thread::sleep,
+    time::{Duration, Instant},
 };
 
-use crate::core::enums::{FileFormat, IoMode, ReadMode, WriteMode};
-use crate::core::traits::TsdfFileTrait;
+use crate::core::enums::{
+    Durability, FileFormat, HashAlgorithm, HighLevelObject, IoMode, WriteMode,
+};
+use crate::core::traits::{DirTrait, StorageBackend, TsdfFileTrait};
+
+use super::{Addr, Dir, FileBackend, IoMetadata, OpenOptions, TsdfMetadata};
 
-use super::{Dir, IoMetadata, TsdfMetadata};
+/// How long a locking writer waits between attempts to lock a file.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
 
 /// The central TsdfFile struct. This struct is used to interact with tsdf
-/// files.
-pub struct TsdfFile<'a> {
+/// files. Files are opened with the constructors below, or with OpenOptions
+/// for more control.
+pub struct TsdfFile {
     /// The actual operating system path to the file.
-    path: &'a Path,
+    path: PathBuf,
 
     /// All metadata used in I/O operations.
     io_metadata: IoMetadata,
 
-    /// The open file handle.
-    file: File,
+    /// The storage backend that the file's bytes live in.
+    backend: Box<dyn StorageBackend>,
+}
+
+// Implement the public API of TsdfFile. Everything that's shared with the
+// TsdfFileTrait simply forwards to it.
+impl TsdfFile {
+    /// Opens an existing file for reading.
+    pub fn new_reader<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        OpenOptions::new().open_reader(path)
+    }
+
+    /// Opens a file for writing, creating it if it doesn't exist. The
+    /// write_mode and file_format default to those of the existing file, or to
+    /// WriteMode::LocklessWrite and FileFormat::Binary for a new file. If
+    /// they're given for an existing file, they must match it.
+    pub fn new_writer<P: AsRef<Path>>(
+        path: P,
+        write_mode: Option<WriteMode>,
+        file_format: Option<FileFormat>,
+    ) -> io::Result<Self> {
+        Self::options(write_mode, file_format).open_writer(path)
+    }
+
+    /// Creates a new file for writing, overwriting any existing file.
+    pub fn new_overwriting_writer<P: AsRef<Path>>(
+        path: P,
+        write_mode: Option<WriteMode>,
+        file_format: Option<FileFormat>,
+    ) -> io::Result<Self> {
+        Self::options(write_mode, file_format).create(path)
+    }
+
+    /// Returns the version of tsdf that created the file.
+    pub fn get_version(&self) -> &str {
+        TsdfFileTrait::get_version(self)
+    }
+
+    /// Returns the path to the file.
+    pub fn get_path(&self) -> &Path {
+        TsdfFileTrait::get_path(self)
+    }
+
+    /// Returns the io mode that the file was opened with.
+    pub fn get_io_mode(&self) -> &IoMode {
+        TsdfFileTrait::get_io_mode(self)
+    }
+
+    /// Returns the format of the file.
+    pub fn get_file_format(&self) -> &FileFormat {
+        TsdfFileTrait::get_file_format(self)
+    }
+
+    /// Returns the hash algorithm that the file places keys with.
+    pub fn get_hash_algorithm(&self) -> HashAlgorithm {
+        self.get_tsdf_metadata().get_hash_algorithm()
+    }
+
+    /// Returns the number of slots in the first shard of every distributed
+    /// list and dictionary in the file.
+    pub fn get_shard_capacity(&self) -> u64 {
+        self.get_tsdf_metadata().get_shard_capacity()
+    }
+
+    /// Returns whether every frame of every array is followed by a checksum.
+    pub fn has_checksums(&self) -> bool {
+        self.get_tsdf_metadata().has_checksums()
+    }
+
+    /// Returns how hard writes work to get changes onto the disk.
+    pub fn get_durability(&self) -> Durability {
+        self.io_metadata.get_durability()
+    }
+
+    /// Returns the size of the file, in bytes.
+    pub fn get_size(&self) -> u64 {
+        TsdfFileTrait::get_size(self)
+    }
+
+    /// Returns the root Dir of the file. Everything else in the file can be
+    /// reached from here.
+    pub fn get_root_dir(&self) -> io::Result<Dir<'_, '_>> {
+        TsdfFileTrait::get_root_dir(self)
+    }
+
+    /// Returns the Array, Dir or metadata tag at the given path, whose parts
+    /// are separated by slashes, like "/detector/counts". The leading slash
+    /// is optional, and "/" is the root Dir.
+    pub fn get(&self, path: &str) -> io::Result<HighLevelObject<'_, '_>> {
+        let mut object = HighLevelObject::Dir(self.get_root_dir()?);
+        for name in path.split('/').filter(|name| !name.is_empty()) {
+            object = match object {
+                HighLevelObject::Dir(dir) => dir.get(name)?,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("{:?} does not exist.", path),
+                    ))
+                }
+            };
+        }
+        Ok(object)
+    }
+
+    /// Checks that everything in the file is well formed, returning an
+    /// InvalidData error describing the first problem found. This reads the
+    /// whole file, checking every frame's checksum if the file has them.
+    pub fn verify(&self) -> io::Result<()> {
+        self.get_root_dir()?.verify_tree()
+    }
+
+    /// Copies everything in the file into a new file at the given path,
+    /// which is created with the given options. Any layout options that
+    /// aren't set are taken from this file, so this can be used both to
+    /// compact a file and to change its layout.
+    ///
+    /// The copy leaves behind anything that can't be reached from the root
+    /// Dir, like old tag values and the remains of interrupted writes.
+    pub fn repack<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &OpenOptions,
+    ) -> io::Result<TsdfFile> {
+        let path = path.as_ref();
+        if path.exists() && same_file(path, &self.path)? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A file can't be repacked into itself.",
+            ));
+        }
+
+        let options = options.with_layout_of(self.get_tsdf_metadata());
+        let repacked = options.create(path)?;
+        self.get_root_dir()?.copy_into(&repacked.get_root_dir()?)?;
+        repacked.get_backend().sync()?;
+        Ok(repacked)
+    }
+
+    /// Copies everything in the file into a new file at the given path, in
+    /// the given format. This is TsdfFile::repack with only the file format
+    /// changed, and is how a Binary file is turned into a readable Text one
+    /// (and back again).
+    pub fn convert<P: AsRef<Path>>(
+        &self,
+        path: P,
+        file_format: FileFormat,
+    ) -> io::Result<TsdfFile> {
+        self.repack(path, OpenOptions::new().file_format(file_format))
+    }
+
+    /// Returns the maximum number of shard chains whose layout is cached. See
+    /// ShardCache for details.
+    pub fn get_shard_cache_capacity(&self) -> usize {
+        self.io_metadata.get_shard_cache().get_capacity()
+    }
+
+    /// Sets the maximum number of shard chains whose layout is cached. Larger
+    /// caches make repeated lookups cheaper, at the cost of memory. A capacity
+    /// of 0 disables the cache.
+    pub fn set_shard_cache_capacity(&mut self, capacity: usize) {
+        self.io_metadata.get_shard_cache().set_capacity(capacity);
+    }
 }
 
 // Implement private methods for TsdfFile.
-impl TsdfFile<'_> {}
+impl TsdfFile {
+    /// Makes the OpenOptions used by the constructors that predate
+    /// OpenOptions.
+    fn options(
+        write_mode: Option<WriteMode>,
+        file_format: Option<FileFormat>,
+    ) -> OpenOptions {
+        let mut options = OpenOptions::new();
+        if let Some(write_mode) = write_mode {
+            options.write_mode(write_mode);
+        }
+        if let Some(file_format) = file_format {
+            options.file_format(file_format);
+        }
+        options
+    }
+
+    /// Opens an existing file for reading. See OpenOptions::open_reader.
+    pub(crate) fn open_reader(
+        path: &Path,
+        options: &OpenOptions,
+    ) -> io::Result<Self> {
+        let backend = FileBackend::new(File::open(path)?);
+
+        // Deserialize the metadata from the header of the file.
+        let metadata = TsdfMetadata::read_from_tsdf(&backend)?;
+        options.check_layout(&metadata)?;
+        let io_mode = IoMode::Read(options.get_read_mode());
+        let io_metadata = IoMetadata::new(metadata, io_mode);
+
+        Ok(TsdfFile {
+            path: path.to_path_buf(),
+            backend: Box::new(backend),
+            io_metadata,
+        })
+    }
+
+    /// Opens a file for writing, creating it if it doesn't exist. See
+    /// OpenOptions::open_writer.
+    pub(crate) fn open_writer(
+        path: &Path,
+        options: &OpenOptions,
+    ) -> io::Result<Self> {
+        // First of all, if the file doesn't exist, we can just create it, as
+        // the behaviour will be identical.
+        if !path.exists() {
+            return Self::create(path, options);
+        }
+
+        // If execution reaches here, we know that the file already exists.
+        // Deserialize the metadata from the file, and make sure that it
+        // matches the options we were given.
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        let backend = FileBackend::new(file);
+        let metadata = TsdfMetadata::read_from_tsdf(&backend)?;
+        options.check_layout(&metadata)?;
+
+        let io_mode = IoMode::Write(options.get_write_mode());
+        Self::lock_if_required(&backend, &io_mode, options)?;
+        let mut io_metadata = IoMetadata::new(metadata, io_mode);
+        io_metadata.set_durability(options.get_durability());
+        Self::create_root_dir_if_missing(&backend, &io_metadata)?;
+
+        Ok(TsdfFile {
+            path: path.to_path_buf(),
+            backend: Box::new(backend),
+            io_metadata,
+        })
+    }
+
+    /// Creates a new file for writing, overwriting any existing file. See
+    /// OpenOptions::create.
+    pub(crate) fn create(
+        path: &Path,
+        options: &OpenOptions,
+    ) -> io::Result<Self> {
+        // Check the options before anything on disk is touched.
+        let mut metadata = options.make_metadata(env!("CARGO_PKG_VERSION"))?;
+
+        // Create the parent directory if it doesn't exist, and open the file
+        // without truncating it. Any existing file is only emptied once we
+        // hold its lock, so a locked file is never touched by a second writer.
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let backend = FileBackend::new(file);
+        let io_mode = IoMode::Write(options.get_write_mode());
+        Self::lock_if_required(&backend, &io_mode, options)?;
+        backend.set_len(0)?;
+
+        // Write the metadata to the beginning of the file, followed by the
+        // root dir. The root dir is synced separately, so that the header is
+        // always on disk before anything else.
+        metadata.write_to_tsdf(&backend)?;
+        backend.sync()?;
+        let mut io_metadata = IoMetadata::new(metadata, io_mode);
+        io_metadata.set_durability(options.get_durability());
+        Self::create_root_dir_if_missing(&backend, &io_metadata)?;
+
+        Ok(TsdfFile {
+            path: path.to_path_buf(),
+            backend: Box::new(backend),
+            io_metadata,
+        })
+    }
+
+    /// Returns the storage backend that the file's bytes live in.
+    pub(crate) fn get_backend(&self) -> &dyn StorageBackend {
+        self.backend.as_ref()
+    }
+
+    /// Returns the location of the root dir, which lives immediately after
+    /// the header.
+    fn get_root_dir_addr(io_metadata: &IoMetadata) -> Addr {
+        Addr::new(io_metadata.get_tsdf_metadata().get_header_len())
+    }
+
+    /// Creates the root dir if the file ends at the header. This is the case
+    /// for brand new files, and for files whose writer died before the root
+    /// dir was written.
+    fn create_root_dir_if_missing(
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) -> io::Result<()> {
+        let loc = Self::get_root_dir_addr(io_metadata);
+        if backend.len()? <= loc.get_loc() {
+            Dir::create(loc, "/", io_metadata, backend)?;
+            backend.sync()?;
+        }
+        Ok(())
+    }
+
+    /// Takes the backend's lock if the given io mode requires it, retrying
+    /// until the options' lock timeout runs out.
+    fn lock_if_required(
+        backend: &dyn StorageBackend,
+        io_mode: &IoMode,
+        options: &OpenOptions,
+    ) -> io::Result<()> {
+        if *io_mode != IoMode::Write(WriteMode::LockingWrite) {
+            return Ok(());
+        }
+
+        let deadline = Instant::now() + options.get_lock_timeout();
+        loop {
+            match backend.lock() {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(err);
+                    }
+                    sleep(LOCK_RETRY_INTERVAL.min(deadline - now));
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Returns whether two existing paths point at the same file.
+fn same_file(a: &Path, b: &Path) -> io::Result<bool> {
+    Ok(canonicalize(a)? == canonicalize(b)?)
+}
+
+// Release the lock (if we hold one) when the TsdfFile is dropped.
+impl Drop for TsdfFile {
+    fn drop(&mut self) {
+        if let IoMode::Write(WriteMode::LockingWrite) = self.get_io_mode() {
+            let _ = self.backend.unlock();
+        }
+    }
+}
 
 // Implement the TsdfFileTrait for TsdfFile.
-impl TsdfFileTrait for TsdfFile<'_> {
+impl TsdfFileTrait for TsdfFile {
     fn get_version(&self) -> &str {
         self.io_metadata.get_tsdf_metadata().get_version()
     }
 
     fn get_path(&self) -> &Path {
-        self.path
+        &self.path
     }
 
     fn get_io_mode(&self) -> &IoMode {
-        &self.io_metadata.get_io_mode()
+        self.io_metadata.get_io_mode()
     }
 
     fn get_file_format(&self) -> &FileFormat {
-        &self.get_io_metadata().get_tsdf_metadata().get_file_format()
+        self.get_io_metadata().get_tsdf_metadata().get_file_format()
     }
 
     fn get_io_metadata(&self) -> &IoMetadata {
@@ -53,116 +400,268 @@ impl TsdfFileTrait for TsdfFile<'_> {
     }
 
     fn get_size(&self) -> u64 {
-        self.file.metadata().unwrap().len()
+        self.backend.len().unwrap()
     }
 
-    fn get_root_dir(&self) -> &Dir {
-        unimplemented!()
+    fn get_root_dir(&self) -> io::Result<Dir<'_, '_>> {
+        Dir::open(
+            Self::get_root_dir_addr(&self.io_metadata),
+            "/",
+            &self.io_metadata,
+            self.get_backend(),
+        )
     }
+}
 
-    fn new_reader(path: &'static Path) -> io::Result<Box<Self>> {
-        // Open the file. If the file doesn't exist, we're perfectly happy to
-        // panic - we can't read from a file that doesn't exist.
-        let mut file = File::open(path)?;
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
 
-        // Deserialize the metadata from the header of the file.
-        let metadata = TsdfMetadata::read_from_tsdf(&mut file)?;
-        let io_mode = IoMode::Read(ReadMode::LocklessRead);
-        let io_metadata = IoMetadata::new(metadata, io_mode);
+    use super::*;
+    use crate::core::{
+        enums::{ArrayDataType, Filter},
+        structs::ArrayOptions,
+    };
 
-        // Return the TsdfFile.
-        Ok(Box::new(TsdfFile {
-            path,
-            file,
-            io_metadata,
-        }))
+    /// A new file gets an empty root dir, and anything added to it can be
+    /// found again by a reader.
+    #[test]
+    fn test_root_dir() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("root.tsdf");
+
+        let writer =
+            TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        let root = writer.get_root_dir().unwrap();
+        assert!(root.list_dir().unwrap().is_empty());
+        root.create_dir("detector").unwrap();
+        root.set_tag("sample", "silicon").unwrap();
+
+        let reader = TsdfFile::new_reader(&path).unwrap();
+        let root = reader.get_root_dir().unwrap();
+        assert_eq!(root.list_dir().unwrap(), vec!["detector"]);
+        assert_eq!(root.get_tag("sample").unwrap().unwrap(), "silicon");
     }
 
-    fn new_writer(
-        path: &'static Path,
-        write_mode: Option<WriteMode>,
-        file_format: Option<FileFormat>,
-    ) -> io::Result<Box<Self>> {
-        // First of all, if the file doesn't exist, we can just use
-        // new_overwriting_writer, as the behaviour will be identical.
-        if !path.exists() {
-            return Self::new_overwriting_writer(path, write_mode, file_format);
-        }
+    /// Reopening a file as a writer shouldn't touch the existing root dir.
+    #[test]
+    fn test_reopen_writer() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("reopen.tsdf");
 
-        // If execution reaches here, we know that the file already exists.
-        // Deserialize the metadata from the file.
-        let mut file = File::open(path)?;
-        let metadata = TsdfMetadata::read_from_tsdf(&mut file)?;
+        let writer =
+            TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        writer.get_root_dir().unwrap().create_dir("a").unwrap();
+        drop(writer);
 
-        // Make sure that the file format in the metadata matches the file
-        // format passed in.
-        if let Some(file_format) = file_format {
-            if metadata.get_file_format() != &file_format {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "File format does not match existing file format.",
-                ));
-            }
+        let writer = TsdfFile::new_writer(&path, None, None).unwrap();
+        let root = writer.get_root_dir().unwrap();
+        root.create_dir("b").unwrap();
+        assert_eq!(root.list_dir().unwrap(), vec!["a", "b"]);
+    }
+
+    /// Paths should resolve to Dirs, Arrays and tags from the root, and
+    /// anything missing should be NotFound.
+    #[test]
+    fn test_get_path() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("paths.tsdf");
+        let file = TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        let root = file.get_root_dir().unwrap();
+        let detector = root.create_dir("detector").unwrap();
+        detector.set_tag("units", "counts").unwrap();
+        detector
+            .create_array("counts", ArrayDataType::UInt8, &[], 4)
+            .unwrap();
+
+        assert!(matches!(file.get("/").unwrap(), HighLevelObject::Dir(_)));
+        assert!(matches!(
+            file.get("/detector/counts").unwrap(),
+            HighLevelObject::Array(_)
+        ));
+        assert!(matches!(
+            file.get("detector/units").unwrap(),
+            HighLevelObject::MetadataTag(value) if value == "counts"
+        ));
+        for missing in ["/nothing", "/detector/counts/more"] {
+            let err = file.get(missing).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::NotFound);
         }
+    }
 
-        // If we weren't passed a write mode, default to lockless write.
-        let write_mode = write_mode.unwrap_or(WriteMode::LocklessWrite);
-        let io_mode = IoMode::Write(write_mode);
+    /// Children borrow the file rather than the Dir they came from, so
+    /// lookups can be chained without keeping every Dir along the way alive.
+    #[test]
+    fn test_chained_lookups() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("chained.tsdf");
+        let file = TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        let counts = file
+            .get_root_dir()
+            .unwrap()
+            .create_dir("detector")
+            .unwrap()
+            .create_array("counts", ArrayDataType::UInt8, &[], 4)
+            .unwrap();
+        counts.append(&[7_u8]).unwrap();
 
-        // If execution reaches here, we know that the write mode and file
-        // format match the existing file's write mode and file format. Return
-        // the TsdfFile.
-        Ok(Box::new(TsdfFile {
-            path,
-            file: File::open(path)?,
-            io_metadata: IoMetadata::new(metadata, io_mode),
-        }))
+        let detector =
+            file.get_root_dir().unwrap().get_dir("detector").unwrap();
+        let counts = detector.get_array("counts").unwrap();
+        drop(detector);
+        assert_eq!(counts.get_frame::<u8>(0).unwrap(), vec![7]);
     }
 
-    fn new_overwriting_writer(
-        path: &'static Path,
-        write_mode: Option<crate::core::enums::WriteMode>,
-        file_format: Option<FileFormat>,
-    ) -> io::Result<Box<Self>> {
-        // Get the parent directory of the file.
-        if let Some(parent) = path.parent() {
-            // Create the parent directory if it doesn't exist.
-            create_dir_all(parent)?;
+    /// A repacked file should verify, hold the same tree and data in its new
+    /// layout, and be smaller once tags have been overwritten.
+    #[test]
+    fn test_verify_and_repack() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("original.tsdf");
+        let file = TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        let root = file.get_root_dir().unwrap();
+        let detector = root.create_dir("detector").unwrap();
+        let array = detector
+            .create_array("counts", ArrayDataType::Int16, &[2], 3)
+            .unwrap();
+        array.append_frames(&[1_i16, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        array.set_tag("units", "counts").unwrap();
+        for i in 0..100 {
+            root.set_tag("progress", &i.to_string()).unwrap();
         }
+        file.verify().unwrap();
 
-        // Delete the file if it exists.
-        if path.exists() {
-            std::fs::remove_file(path)?;
-        }
+        let repacked_path = dir.path().join("repacked.tsdf");
+        let repacked = file
+            .repack(&repacked_path, OpenOptions::new().checksums(true))
+            .unwrap();
+        repacked.verify().unwrap();
+        assert!(repacked.has_checksums());
+        assert!(repacked.get_size() < file.get_size());
 
-        // Now create the file.
-        let file = File::create(path)?;
+        let root = repacked.get_root_dir().unwrap();
+        assert_eq!(root.get_tag("progress").unwrap().unwrap(), "99");
+        let array = match repacked.get("/detector/counts").unwrap() {
+            HighLevelObject::Array(array) => array,
+            _ => panic!("Expected an array."),
+        };
+        assert_eq!(array.get_frames_per_chunk(), 3);
+        assert_eq!(array.get_tag("units").unwrap().unwrap(), "counts");
+        assert_eq!(
+            array.get_frames::<i16>(0, 4).unwrap(),
+            vec![1, 2, 3, 4, 5, 6, 7, 8]
+        );
 
-        // Get the version from cargo.
-        let version = env!("CARGO_PKG_VERSION");
+        let err = file.repack(&path, &OpenOptions::new()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
 
-        // Make a new TsdfMetadata.
-        let metadata = {
-            let file_format = file_format.unwrap_or(FileFormat::Binary);
-            TsdfMetadata::new(version.to_string(), file_format)
-        };
+    /// Repacking or converting a file should rebuild the decimation levels of
+    /// its arrays, matching those of the original.
+    #[test]
+    fn test_repack_keeps_levels() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("original.tsdf");
+        let file = TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        let root = file.get_root_dir().unwrap();
+        let array = root
+            .create_array("values", ArrayDataType::Float64, &[], 64)
+            .unwrap();
+        let values: Vec<f64> = (0..1234).map(|i| (i % 97) as f64).collect();
+        array.append_frames(&values).unwrap();
+        assert_eq!(array.get_num_levels(), 3);
 
-        // Write the metadata to the beginning of the file.
-        let metadata_json = serde_json::to_vec(&metadata)?;
-        file.write_at(metadata_json.as_slice(), 0)?;
+        let repacked = file
+            .repack(dir.path().join("repacked.tsdf"), &OpenOptions::new())
+            .unwrap();
+        let text = file
+            .convert(dir.path().join("text.tsdf"), FileFormat::Text)
+            .unwrap();
+        for copy in [&repacked, &text] {
+            copy.verify().unwrap();
+            let root = copy.get_root_dir().unwrap();
+            let copied = root.get_array("values").unwrap();
+            assert_eq!(copied.get_num_levels(), 3);
+            for level in 1..=3 {
+                let num_buckets = 1234 / 10_usize.pow(level as u32);
+                assert_eq!(
+                    copied.get_decimated(level, 0, num_buckets).unwrap(),
+                    array.get_decimated(level, 0, num_buckets).unwrap()
+                );
+            }
+        }
+    }
 
-        // Flush the file to disk.
-        file.sync_all()?;
+    /// Converting a file to Text and back should preserve every data type
+    /// exactly, along with the tree and its tags.
+    #[test]
+    fn test_convert_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("binary.tsdf");
+        let file = TsdfFile::new_overwriting_writer(&path, None, None).unwrap();
+        let root = file.get_root_dir().unwrap();
+        root.set_tag("comment", "line one\nline \"two\"").unwrap();
+        let data = root.create_dir("data").unwrap();
+        data.create_array("i8", ArrayDataType::Int8, &[2], 2)
+            .unwrap()
+            .append_frames(&[i8::MIN, i8::MAX, -1, 0])
+            .unwrap();
+        data.create_array("u64", ArrayDataType::UInt64, &[], 2)
+            .unwrap()
+            .append_frames(&[u64::MAX, 0, 7])
+            .unwrap();
+        let floats = [f64::MIN_POSITIVE, -1.0e300, f64::INFINITY, 0.1];
+        data.create_array("f64", ArrayDataType::Float64, &[2], 8)
+            .unwrap()
+            .append_frames(&floats)
+            .unwrap();
+        let filters = [Filter::Delta, Filter::Shuffle, Filter::Lz4];
+        ArrayOptions::new()
+            .data_type(ArrayDataType::Int32)
+            .frames_per_chunk(2)
+            .filters(&filters)
+            .create("filtered", &data)
+            .unwrap()
+            .append_frames(&[-3_i32, 1, 4, 1, 5])
+            .unwrap();
 
-        // If we weren't passed a write mode, default to lockless write.
-        let write_mode = write_mode.unwrap_or(WriteMode::LocklessWrite);
-        let io_mode = IoMode::Write(write_mode);
+        let text_path = dir.path().join("text.tsdf");
+        let text = file.convert(&text_path, FileFormat::Text).unwrap();
+        assert_eq!(text.get_file_format(), &FileFormat::Text);
+        text.verify().unwrap();
+        let binary_path = dir.path().join("binary_again.tsdf");
+        let binary = text.convert(&binary_path, FileFormat::Binary).unwrap();
+        assert_eq!(binary.get_file_format(), &FileFormat::Binary);
 
-        // Return the TsdfFile.
-        Ok(Box::new(TsdfFile {
-            path,
-            file,
-            io_metadata: IoMetadata::new(metadata, io_mode),
-        }))
+        for file in [&text, &binary] {
+            let root = file.get_root_dir().unwrap();
+            assert_eq!(
+                root.get_tag("comment").unwrap().unwrap(),
+                "line one\nline \"two\""
+            );
+            let data = root.get_dir("data").unwrap();
+            assert_eq!(
+                data.list_dir().unwrap(),
+                vec!["f64", "filtered", "i8", "u64"]
+            );
+            let array = data.get_array("i8").unwrap();
+            assert_eq!(
+                array.get_frames::<i8>(0, 2).unwrap(),
+                vec![i8::MIN, i8::MAX, -1, 0]
+            );
+            let array = data.get_array("u64").unwrap();
+            assert_eq!(
+                array.get_frames::<u64>(0, 3).unwrap(),
+                vec![u64::MAX, 0, 7]
+            );
+            let array = data.get_array("f64").unwrap();
+            assert_eq!(array.get_frames::<f64>(0, 2).unwrap(), floats);
+            let array = data.get_array("filtered").unwrap();
+            assert_eq!(array.get_filters(), filters);
+            assert_eq!(
+                array.get_frames::<i32>(0, 5).unwrap(),
+                vec![-3, 1, 4, 1, 5]
+            );
+        }
     }
 }
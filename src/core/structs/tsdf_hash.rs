@@ -1,6 +1,9 @@
-use std::hash::{DefaultHasher, Hasher};
+use std::hash::{DefaultHasher, Hash, Hasher};
 
-use crate::core::traits::{FileSerializable, FixedSizeOnDisk};
+use crate::core::{
+    enums::HashAlgorithm,
+    traits::{FileSerializable, FixedSizeOnDisk},
+};
 
 /// The TsdfHash struct is a simple struct that holds a hash value. This hash
 
@@ -12,15 +15,46 @@ pub(crate) struct TsdfHash {
 /// The value that we use to represent a null TsdfHash.
 const NULL_HASH: u64 = 0;
 
-impl TsdfHash {
-    /// Creates a new TsdfHash from a hashable value.
-    pub(crate) fn new<T: std::hash::Hash>(hashable: &T) -> Self {
-        // Create a new hasher.
-        let mut hasher = DefaultHasher::new();
+/// The offset basis and prime of 64 bit FNV-1a.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A Hasher that computes 64 bit FNV-1a.
+struct Fnv1aHasher {
+    state: u64,
+}
+
+impl Hasher for Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= *byte as u64;
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state
+    }
+}
 
-        // Hash the value.
-        hashable.hash(&mut hasher);
-        let hash_value = hasher.finish();
+impl TsdfHash {
+    /// Creates a new TsdfHash from a hashable value, using the given hash
+    /// algorithm.
+    pub(crate) fn new<T: Hash>(hashable: &T, algorithm: HashAlgorithm) -> Self {
+        let hash_value = match algorithm {
+            HashAlgorithm::SipHash13 => {
+                let mut hasher = DefaultHasher::new();
+                hashable.hash(&mut hasher);
+                hasher.finish()
+            }
+            HashAlgorithm::Fnv1a => {
+                let mut hasher = Fnv1aHasher {
+                    state: FNV_OFFSET_BASIS,
+                };
+                hashable.hash(&mut hasher);
+                hasher.finish()
+            }
+        };
 
         Self { hash_value }
     }
@@ -43,19 +77,19 @@ impl FixedSizeOnDisk for TsdfHash {
     }
 
     fn get_json_size_on_disk() -> u64 {
-        // To get the size of the json string, we make the largest possible
-        // TsdfHash, convert it to json, and get the length of the json string.
-        // This is far from optimized, but the whole point of the json
-        // representation is to be debug-friendly, not performant.
+        // To get the size of the text, we make the largest possible TsdfHash
+        // and lay it out. This is far from optimized, but the whole point of
+        // the text representation is to be debug-friendly, not performant.
         let hash = TsdfHash {
             hash_value: u64::MAX,
         };
-        let json = serde_json::to_string(&hash).unwrap();
-        json.len() as u64
+        hash.get_text_len()
     }
 }
 
 impl FileSerializable for TsdfHash {
+    const TEXT_MARKER: &'static str = "TsdfHash";
+
     fn null() -> Self {
         Self {
             hash_value: NULL_HASH,
@@ -63,8 +97,7 @@ impl FileSerializable for TsdfHash {
     }
 
     fn to_bin(&self) -> Vec<u8> {
-        let bytes = self.hash_value.to_le_bytes().to_vec();
-        bytes
+        self.hash_value.to_le_bytes().to_vec()
     }
 
     fn from_bin(bytes: &[u8]) -> Self {
@@ -80,7 +113,7 @@ mod tests {
     /// Test that we can convert a TsdfHash to bytes and back.
     #[test]
     fn test_tsdf_hash_to_bytes() {
-        let hash = TsdfHash::new(&123);
+        let hash = TsdfHash::new(&123, HashAlgorithm::SipHash13);
         let bytes = hash.to_bin();
         let hash2 = TsdfHash::from_bin(&bytes);
         assert_eq!(hash, hash2);
@@ -89,9 +122,26 @@ mod tests {
     /// Test that we can convert a TsdfHash to json and back.
     #[test]
     fn test_tsdf_hash_to_json() {
-        let hash = TsdfHash::new(&123);
+        let hash = TsdfHash::new(&123, HashAlgorithm::SipHash13);
         let json = hash.to_json();
         let hash2 = TsdfHash::from_json(json);
         assert_eq!(hash, hash2);
     }
+
+    /// FNV-1a is fixed by its specification, so its hashes should never
+    /// change. The expected value is FNV-1a of the bytes that String's Hash
+    /// implementation feeds to the hasher: "tsdf" followed by 0xff.
+    #[test]
+    fn test_fnv1a_is_stable() {
+        let mut expected = FNV_OFFSET_BASIS;
+        for byte in b"tsdf\xff" {
+            expected = (expected ^ *byte as u64).wrapping_mul(FNV_PRIME);
+        }
+        let hash = TsdfHash::new(&"tsdf".to_string(), HashAlgorithm::Fnv1a);
+        assert_eq!(hash.get_hash_value(), expected);
+        assert_eq!(
+            TsdfHash::new(&(), HashAlgorithm::Fnv1a).get_hash_value(),
+            FNV_OFFSET_BASIS
+        );
+    }
 }
@@ -1,12 +1,17 @@
-use std::{
-    fs::File,
-    io::{self, Read, Seek},
-};
+use std::io;
 
 use serde::{Deserialize, Serialize};
 
-use crate::core::enums::FileFormat;
-use crate::core::well_known_values::metadata_strings::HEADER_END_STR;
+use crate::core::enums::{FileFormat, HashAlgorithm};
+use crate::core::traits::StorageBackend;
+use crate::core::well_known_values::{
+    metadata_strings::HEADER_END_STR, shard_limits::DEFAULT_SHARD_CAPACITY,
+};
+
+/// The version of the on-disk layout written by this version of tsdf. Version
+/// 1 lays out the Text format as labelled, one-field-per-line records; files
+/// written before that are version 0.
+const FORMAT_VERSION: u32 = 1;
 
 /// The core metadata for a tsdf file. This is written at the very beginning of
 /// every tsdf file as a json blob.
@@ -17,6 +22,32 @@ pub(crate) struct TsdfMetadata {
 
     /// The mode used to write the file.
     file_format: FileFormat,
+
+    /// The version of the on-disk layout. Files written before this was
+    /// stored are version 0.
+    #[serde(default)]
+    format_version: u32,
+
+    /// The hash algorithm used to place keys in distributed dictionaries.
+    /// Files written before this was stored use the default.
+    #[serde(default)]
+    hash_algorithm: HashAlgorithm,
+
+    /// The number of slots in the first shard of every distributed list and
+    /// dictionary. Each shard after the first has twice as many slots as the
+    /// one before it.
+    #[serde(default = "default_shard_capacity")]
+    shard_capacity: u64,
+
+    /// Whether every frame of every array is followed by a checksum.
+    #[serde(default)]
+    checksums: bool,
+
+    /// The length of the header in bytes, including the header end string.
+    /// This isn't stored in the header itself; it's filled in whenever the
+    /// header is read or written. The root dir lives right after the header.
+    #[serde(skip)]
+    header_len: u64,
 }
 
 impl TsdfMetadata {
@@ -25,35 +56,91 @@ impl TsdfMetadata {
         Self {
             version,
             file_format,
+            format_version: FORMAT_VERSION,
+            hash_algorithm: HashAlgorithm::default(),
+            shard_capacity: DEFAULT_SHARD_CAPACITY,
+            checksums: false,
+            header_len: 0,
         }
     }
 
     /// Deserializes a TsdfMetadata from the top of a tsdf file.
-    pub(crate) fn read_from_tsdf(file: &mut File) -> Result<Self, io::Error> {
-        // Seek to the beginning of the file.
-        file.seek(std::io::SeekFrom::Start(0))?;
-
-        // Read the file up until the header end string.
+    pub(crate) fn read_from_tsdf(
+        backend: &dyn StorageBackend,
+    ) -> Result<Self, io::Error> {
+        // Read the file, a block at a time, up until the header end string.
         let mut metadata_json = Vec::new();
-        let mut reader = std::io::BufReader::new(file);
+        let mut buffer = [0; 256];
         loop {
-            let mut buffer = [0; 1];
-            reader.read_exact(&mut buffer)?;
-            metadata_json.push(buffer[0]);
-            if metadata_json.ends_with(HEADER_END_STR.as_bytes()) {
+            let offset = metadata_json.len() as u64;
+            let num_read = backend.read_full_at(&mut buffer, offset)?;
+            if num_read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Failed to find the end of the tsdf header.",
+                ));
+            }
+            metadata_json.extend_from_slice(&buffer[..num_read]);
+
+            // Check to see if we've now read the header end string.
+            let header_end = HEADER_END_STR.as_bytes();
+            if let Some(idx) = metadata_json
+                .windows(header_end.len())
+                .position(|window| window == header_end)
+            {
+                // Remove the header end string (and anything after it) from
+                // the metadata json.
+                metadata_json.truncate(idx);
                 break;
             }
         }
 
-        // Remove the header end string from the metadata json.
-        metadata_json.truncate(metadata_json.len() - HEADER_END_STR.len());
-
         // Deserialize the metadata json.
-        let metadata: TsdfMetadata = serde_json::from_slice(&metadata_json)?;
+        let mut metadata: TsdfMetadata =
+            serde_json::from_slice(&metadata_json)?;
+        metadata.header_len =
+            (metadata_json.len() + HEADER_END_STR.len()) as u64;
+        metadata.check_format_version()?;
 
         Ok(metadata)
     }
 
+    /// Returns an error if this version of tsdf can't read the file's layout.
+    /// That's the case for files from a later version of tsdf, and for Text
+    /// files written before the Text layout changed in version 1.
+    fn check_format_version(&self) -> io::Result<()> {
+        let unreadable =
+            |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        if self.format_version > FORMAT_VERSION {
+            return unreadable(format!(
+                "The file has format version {}, but this version of tsdf \
+                 only reads up to {}.",
+                self.format_version, FORMAT_VERSION
+            ));
+        }
+        if self.file_format == FileFormat::Text && self.format_version < 1 {
+            return unreadable(format!(
+                "The file is in the Text format written by tsdf {}, which \
+                 lays out objects differently and can no longer be read.",
+                self.version
+            ));
+        }
+        Ok(())
+    }
+
+    /// Serializes the TsdfMetadata to the top of a tsdf file, including the
+    /// string that marks the end of the header.
+    pub(crate) fn write_to_tsdf(
+        &mut self,
+        backend: &dyn StorageBackend,
+    ) -> Result<(), io::Error> {
+        let mut header = serde_json::to_vec(self)?;
+        header.extend_from_slice(HEADER_END_STR.as_bytes());
+        backend.write_all_at(&header, 0)?;
+        self.header_len = header.len() as u64;
+        Ok(())
+    }
+
     /// Returns the version of the file.
     pub fn get_version(&self) -> &str {
         &self.version
@@ -63,4 +150,125 @@ impl TsdfMetadata {
     pub fn get_file_format(&self) -> &FileFormat {
         &self.file_format
     }
+
+    /// Returns the hash algorithm used to place keys in distributed
+    /// dictionaries.
+    pub(crate) fn get_hash_algorithm(&self) -> HashAlgorithm {
+        self.hash_algorithm
+    }
+
+    /// Sets the hash algorithm. This must only be done before the header is
+    /// written.
+    pub(crate) fn set_hash_algorithm(&mut self, hash_algorithm: HashAlgorithm) {
+        self.hash_algorithm = hash_algorithm;
+    }
+
+    /// Returns the number of slots in the first shard of every distributed
+    /// list and dictionary.
+    pub(crate) fn get_shard_capacity(&self) -> u64 {
+        self.shard_capacity
+    }
+
+    /// Sets the number of slots in the first shard of every distributed list
+    /// and dictionary. This must only be done before the header is written.
+    pub(crate) fn set_shard_capacity(&mut self, shard_capacity: u64) {
+        self.shard_capacity = shard_capacity;
+    }
+
+    /// Returns whether every frame of every array is followed by a checksum.
+    pub(crate) fn has_checksums(&self) -> bool {
+        self.checksums
+    }
+
+    /// Sets whether frames are followed by checksums. This must only be done
+    /// before the header is written.
+    pub(crate) fn set_checksums(&mut self, checksums: bool) {
+        self.checksums = checksums;
+    }
+
+    /// Returns the length of the header in bytes. This is 0 until the header
+    /// has been read or written.
+    pub(crate) fn get_header_len(&self) -> u64 {
+        self.header_len
+    }
+}
+
+/// The shard capacity of files written before it was stored in the header.
+fn default_shard_capacity() -> u64 {
+    DEFAULT_SHARD_CAPACITY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::structs::MemoryBackend;
+
+    /// Test that the metadata survives a round trip through a backend, even
+    /// when other data follows the header.
+    #[test]
+    fn test_write_read_round_trip() {
+        let backend = MemoryBackend::new();
+        let mut metadata =
+            TsdfMetadata::new("1.2.3".to_string(), FileFormat::Text);
+        metadata.write_to_tsdf(&backend).unwrap();
+
+        // Put some junk after the header to make sure it isn't consumed.
+        let len = backend.len().unwrap();
+        backend.write_all_at(&[7; 1000], len).unwrap();
+
+        let read_metadata = TsdfMetadata::read_from_tsdf(&backend).unwrap();
+        assert_eq!(read_metadata.get_version(), "1.2.3");
+        assert_eq!(read_metadata.get_file_format(), &FileFormat::Text);
+        assert_eq!(read_metadata.get_header_len(), len);
+        assert_eq!(metadata.get_header_len(), len);
+    }
+
+    /// Headers written before the hash algorithm, shard capacity and checksums
+    /// were stored should still be readable, with the old behaviour.
+    #[test]
+    fn test_read_old_header() {
+        let backend = MemoryBackend::new();
+        let header = format!(
+            "{{\"version\":\"0.1.0\",\"file_format\":\"Binary\"}}{}",
+            HEADER_END_STR
+        );
+        backend.write_all_at(header.as_bytes(), 0).unwrap();
+
+        let metadata = TsdfMetadata::read_from_tsdf(&backend).unwrap();
+        assert_eq!(metadata.get_hash_algorithm(), HashAlgorithm::SipHash13);
+        assert_eq!(metadata.get_shard_capacity(), 8);
+        assert!(!metadata.has_checksums());
+    }
+
+    /// Text files from before the Text layout changed, and files from later
+    /// versions, should be rejected with a clear error.
+    #[test]
+    fn test_reject_unreadable_format() {
+        for (header, msg) in [
+            (
+                r#"{"version":"0.1.0","file_format":"Text"}"#,
+                "can no longer be read",
+            ),
+            (
+                r#"{"version":"9.0.0","file_format":"Binary","format_version":99}"#,
+                "only reads up to 1",
+            ),
+        ] {
+            let backend = MemoryBackend::new();
+            let header = format!("{}{}", header, HEADER_END_STR);
+            backend.write_all_at(header.as_bytes(), 0).unwrap();
+
+            let err = TsdfMetadata::read_from_tsdf(&backend).err().unwrap();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains(msg), "{}", err);
+        }
+    }
+
+    /// Reading a header from an empty backend should fail cleanly.
+    #[test]
+    fn test_read_empty() {
+        let backend = MemoryBackend::new();
+        let err = TsdfMetadata::read_from_tsdf(&backend).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
 }
@@ -0,0 +1,133 @@
+use std::io;
+
+use crate::core::traits::{FileSerializable, StorageBackend};
+
+use super::{Addr, IoMetadata};
+
+/// A WriteBuffer collects writes in memory, so that they can be sent to the
+/// storage backend in as few calls as possible. When the buffer is flushed,
+/// writes that sit next to each other on disk are coalesced into a single
+/// vectored write.
+///
+/// The buffer keeps data and written flags apart. Every data write is flushed
+/// before any flag write, which means that batching never breaks the rule
+/// that readers rely on: a written flag is only ever set once the thing that
+/// it guards is completely on disk.
+///
+/// Writes pushed to the same buffer must not overlap.
+#[derive(Default)]
+pub(crate) struct WriteBuffer {
+    /// Data writes, as (offset, bytes) pairs.
+    data: Vec<(u64, Vec<u8>)>,
+
+    /// Written flag writes, as (offset, bytes) pairs.
+    flags: Vec<(u64, Vec<u8>)>,
+}
+
+impl WriteBuffer {
+    /// Constructs a new, empty WriteBuffer.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the bytes to be written at the given address.
+    pub(crate) fn push(&mut self, addr: Addr, bytes: Vec<u8>) {
+        self.data.push((addr.get_loc(), bytes));
+    }
+
+    /// Queues the on-disk representation of the object to be written at the
+    /// given address.
+    pub(crate) fn push_serializable<T: FileSerializable>(
+        &mut self,
+        addr: Addr,
+        obj: &T,
+        io_metadata: &IoMetadata,
+    ) {
+        self.push(addr, obj.to_bytes(io_metadata));
+    }
+
+    /// Queues written flags to be written at the given address. These are only
+    /// written once all of the data in the buffer has been written.
+    pub(crate) fn push_flags(&mut self, addr: Addr, flags: Vec<u8>) {
+        self.flags.push((addr.get_loc(), flags));
+    }
+
+    /// Returns whether nothing has been queued.
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.data.is_empty() && self.flags.is_empty()
+    }
+
+    /// Writes everything in the buffer to the backend. All of the data is
+    /// written before any of the flags.
+    pub(crate) fn flush(self, backend: &dyn StorageBackend) -> io::Result<()> {
+        write_coalesced(self.data, backend)?;
+        write_coalesced(self.flags, backend)
+    }
+}
+
+/// Writes the given (offset, bytes) pairs, using one vectored write for every
+/// run of writes that are contiguous on disk.
+fn write_coalesced(
+    mut writes: Vec<(u64, Vec<u8>)>,
+    backend: &dyn StorageBackend,
+) -> io::Result<()> {
+    // A stable sort keeps writes to the same offset in the order they were
+    // pushed.
+    writes.sort_by_key(|(offset, _)| *offset);
+
+    let mut idx = 0;
+    while idx < writes.len() {
+        let run_offset = writes[idx].0;
+        let mut run_end = run_offset;
+        let mut run = Vec::new();
+        while idx < writes.len() && writes[idx].0 == run_end {
+            run_end += writes[idx].1.len() as u64;
+            run.push(writes[idx].1.as_slice());
+            idx += 1;
+        }
+
+        backend.write_all_vectored_at(&run, run_offset)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{enums::StorageOp, structs::FaultInjectingBackend};
+
+    /// Make sure that contiguous writes are coalesced, and that flags are
+    /// written after all of the data.
+    #[test]
+    fn test_flush_coalesces() {
+        let backend = FaultInjectingBackend::new();
+        let mut buffer = WriteBuffer::new();
+        buffer.push_flags(Addr::new(0), vec![1]);
+        buffer.push(Addr::new(3), b"cd".to_vec());
+        buffer.push(Addr::new(1), b"ab".to_vec());
+        buffer.push(Addr::new(10), b"z".to_vec());
+        assert!(!buffer.is_empty());
+        buffer.flush(&backend).unwrap();
+
+        let write = |offset: u64, bytes: &[u8]| StorageOp::Write {
+            offset,
+            bytes: bytes.to_vec(),
+        };
+        assert_eq!(
+            backend.get_ops(),
+            vec![write(1, b"abcd"), write(10, b"z"), write(0, &[1])]
+        );
+    }
+
+    /// Flushing an empty buffer shouldn't touch the backend.
+    #[test]
+    fn test_flush_empty() {
+        let backend = FaultInjectingBackend::new();
+        let buffer = WriteBuffer::new();
+        assert!(buffer.is_empty());
+        buffer.flush(&backend).unwrap();
+        assert!(backend.get_ops().is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+use crate::core::{
+    enums::{FileFormat, IoMode, WriteMode},
+    structs::{IoMetadata, TsdfMetadata},
+};
+
+/// Make the IoMetadata used by the tests, for a lockless writer of a file in
+/// the given format.
+pub(crate) fn make_io_metadata(file_format: FileFormat) -> IoMetadata {
+    IoMetadata::new(
+        TsdfMetadata::new("no_version".to_string(), file_format),
+        IoMode::Write(WriteMode::LocklessWrite),
+    )
+}
@@ -0,0 +1,193 @@
+use crate::core::enums::ArrayDataType;
+
+use super::{parse_text, to_text, FileSerializable, FixedSizeOnDisk};
+
+/// The ArrayElement trait is implemented by every primitive type that can be
+/// stored in an Array. Each implementation corresponds to exactly one
+/// ArrayDataType, which is how arrays check that they're being read and
+/// written with the right type.
+///
+/// ArrayElement is sealed: its supertraits are private, so it can't be
+/// implemented outside of tsdf.
+#[allow(private_bounds)]
+pub trait ArrayElement: FileSerializable + Copy {
+    /// The ArrayDataType that corresponds to this type.
+    const DATA_TYPE: ArrayDataType;
+
+    /// Converts the element to an f64, rounding 64 bit integers that don't
+    /// fit exactly.
+    fn to_f64(self) -> f64;
+}
+
+/// Implements ArrayElement (and the traits it needs) for an integer type. The
+/// text representation of an integer is just the number, so we can use the
+/// default text methods.
+macro_rules! impl_int_element {
+    ($t:ty, $data_type:expr) => {
+        impl FixedSizeOnDisk for $t {
+            fn get_bin_size_on_disk() -> u64 {
+                std::mem::size_of::<$t>() as u64
+            }
+
+            fn get_json_size_on_disk() -> u64 {
+                // The longest text is either the most negative or the most
+                // positive value.
+                <$t>::MIN.get_text_len().max(<$t>::MAX.get_text_len())
+            }
+        }
+
+        impl FileSerializable for $t {
+            const TEXT_MARKER: &'static str = stringify!($t);
+
+            fn null() -> Self {
+                0
+            }
+
+            fn to_bin(&self) -> Vec<u8> {
+                self.to_le_bytes().to_vec()
+            }
+
+            fn from_bin(bytes: &[u8]) -> Self {
+                let size = std::mem::size_of::<$t>();
+                <$t>::from_le_bytes(bytes[0..size].try_into().unwrap())
+            }
+        }
+
+        impl ArrayElement for $t {
+            const DATA_TYPE: ArrayDataType = $data_type;
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    };
+}
+
+/// Implements ArrayElement (and the traits it needs) for a floating point
+/// type. Json can't represent NaN or infinity, so rather than relying on
+/// serde_json we write floats using their Debug representation, which is the
+/// shortest string that round trips exactly (and handles NaN and infinity).
+macro_rules! impl_float_element {
+    ($t:ty, $data_type:expr, $max_sig_digits:expr, $max_exp_digits:expr) => {
+        impl FixedSizeOnDisk for $t {
+            fn get_bin_size_on_disk() -> u64 {
+                std::mem::size_of::<$t>() as u64
+            }
+
+            fn get_json_size_on_disk() -> u64 {
+                // The longest possible value is something like
+                // -1.2345678901234567e-308: a sign, the significant digits, a
+                // decimal point, "e-" and the exponent digits.
+                let width = 1 + $max_sig_digits + 1 + 2 + $max_exp_digits;
+                let longest = [(String::new(), "0".repeat(width))];
+                to_text(Self::TEXT_MARKER, &longest, 0).len() as u64
+            }
+        }
+
+        impl FileSerializable for $t {
+            const TEXT_MARKER: &'static str = stringify!($t);
+
+            fn null() -> Self {
+                0.0
+            }
+
+            fn to_bin(&self) -> Vec<u8> {
+                self.to_le_bytes().to_vec()
+            }
+
+            fn from_bin(bytes: &[u8]) -> Self {
+                let size = std::mem::size_of::<$t>();
+                <$t>::from_le_bytes(bytes[0..size].try_into().unwrap())
+            }
+
+            fn to_json(&self) -> String {
+                let fields = [(String::new(), format!("{:?}", self))];
+                let size = Self::get_json_size_on_disk();
+                to_text(Self::TEXT_MARKER, &fields, size)
+            }
+
+            fn get_text_len(&self) -> u64 {
+                let fields = [(String::new(), format!("{:?}", self))];
+                to_text(Self::TEXT_MARKER, &fields, 0).len() as u64
+            }
+
+            fn from_json(json: String) -> Self {
+                match parse_text(Self::TEXT_MARKER, &json).as_deref() {
+                    Ok([(_, value)]) => value.parse().unwrap_or(Self::null()),
+                    _ => Self::null(),
+                }
+            }
+        }
+
+        impl ArrayElement for $t {
+            const DATA_TYPE: ArrayDataType = $data_type;
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    };
+}
+
+impl_int_element!(i8, ArrayDataType::Int8);
+impl_int_element!(i16, ArrayDataType::Int16);
+impl_int_element!(i32, ArrayDataType::Int32);
+impl_int_element!(i64, ArrayDataType::Int64);
+impl_int_element!(u8, ArrayDataType::UInt8);
+impl_int_element!(u16, ArrayDataType::UInt16);
+impl_int_element!(u32, ArrayDataType::UInt32);
+impl_int_element!(u64, ArrayDataType::UInt64);
+impl_float_element!(f32, ArrayDataType::Float32, 9, 2);
+impl_float_element!(f64, ArrayDataType::Float64, 17, 3);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Make sure that awkward integers round trip through json, and that the
+    /// json is padded to the full width.
+    #[test]
+    fn test_int_json_round_trip() {
+        for val in [i64::MIN, -1, 0, i64::MAX] {
+            let json = val.to_json();
+            assert_eq!(json.len() as u64, i64::get_json_size_on_disk());
+            assert_eq!(i64::from_json(json), val);
+        }
+        let json = u64::MAX.to_json();
+        assert_eq!(u64::from_json(json), u64::MAX);
+    }
+
+    /// Make sure that awkward floats (including NaN and infinities) round trip
+    /// through json without growing past the json size on disk.
+    #[test]
+    fn test_float_json_round_trip() {
+        let vals = [
+            f64::MIN,
+            f64::MAX,
+            -f64::MIN_POSITIVE,
+            -1.2345678901234567e-5,
+            f64::NEG_INFINITY,
+            0.1,
+        ];
+        for val in vals {
+            let json = val.to_json();
+            assert_eq!(json.len() as u64, f64::get_json_size_on_disk());
+            assert_eq!(f64::from_json(json), val);
+        }
+        assert!(f64::from_json(f64::NAN.to_json()).is_nan());
+
+        let val = -1.0000001e-38_f32;
+        let json = val.to_json();
+        assert_eq!(json.len() as u64, f32::get_json_size_on_disk());
+        assert_eq!(f32::from_json(json), val);
+    }
+
+    /// Make sure that numbers round trip through their binary representation.
+    #[test]
+    fn test_bin_round_trip() {
+        assert_eq!(i16::from_bin(&(-1234_i16).to_bin()), -1234);
+        assert_eq!(u32::from_bin(&123456_u32.to_bin()), 123456);
+        assert_eq!(f32::from_bin(&1.5_f32.to_bin()), 1.5);
+        assert_eq!(f64::from_bin(&(-2.5_f64).to_bin()), -2.5);
+    }
+}
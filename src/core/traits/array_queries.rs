@@ -0,0 +1,159 @@
+use std::io;
+
+use crate::core::{
+    enums::with_element_type,
+    structs::{Array, Timestamp},
+};
+
+use super::{ArrayElement, ArrayTrait, Condition};
+
+/// Queries for the frames of an array whose values meet a Condition. Each
+/// full chunk's ChunkStats are checked first, and only the chunks that could
+/// hold a match are read.
+pub(crate) trait ArrayQueries: ArrayTrait {
+    /// Returns the indices of the frames start..end that meet the condition,
+    /// in order.
+    fn find_frames<C: Condition>(
+        &self,
+        condition: &C,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<usize>> {
+        self.check_bounds(start, end)?;
+        let chunk_len = self.get_header().get_frames_per_chunk() as usize;
+        let frame_len = self.get_header().get_frame_len() as usize;
+        let stats = self.get_chunk_stats();
+        let mut found = Vec::new();
+        let mut chunk_start = start;
+        while chunk_start < end {
+            let chunk = chunk_start / chunk_len;
+            let chunk_end = ((chunk + 1) * chunk_len).min(end);
+            let ruled_out =
+                stats.get(chunk).is_some_and(|s| !condition.may_match(s));
+            if !ruled_out {
+                with_element_type!(self.get_data_type(), T => {
+                    let frames =
+                        self.get_frames::<T>(chunk_start, chunk_end)?;
+                    for (i, frame) in frames.chunks(frame_len).enumerate() {
+                        let mut values = frame.iter().map(|v| v.to_f64());
+                        if values.any(|v| condition.matches(v)) {
+                            found.push(chunk_start + i);
+                        }
+                    }
+                });
+            }
+            chunk_start = chunk_end;
+        }
+        Ok(found)
+    }
+
+    /// Returns the runs of consecutive frames start..end that meet the
+    /// condition, as ranges of frame indices with exclusive ends.
+    fn find_runs<C: Condition>(
+        &self,
+        condition: &C,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<(usize, usize)>> {
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for index in self.find_frames(condition, start, end)? {
+            match runs.last_mut() {
+                Some(run) if run.1 == index => run.1 += 1,
+                _ => runs.push((index, index + 1)),
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Returns the runs of consecutive frames start..end that meet the
+    /// condition, as the timestamps of the first and last frame of each run.
+    /// The array must have a time axis.
+    fn find_intervals<C: Condition>(
+        &self,
+        condition: &C,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<(Timestamp, Timestamp)>> {
+        self.check_has_time_axis()?;
+        let mut intervals = Vec::new();
+        for (first, last) in self.find_runs(condition, start, end)? {
+            let first = self.read_timestamps(first, first + 1)?[0];
+            let last = self.read_timestamps(last - 1, last)?[0];
+            intervals.push((first, last));
+        }
+        Ok(intervals)
+    }
+}
+
+impl ArrayQueries for Array<'_, '_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        enums::{ArrayDataType, FileFormat, Predicate},
+        structs::{Addr, ArrayOptions, MemoryBackend},
+        test_utils::make_io_metadata,
+        traits::{DistListTrait, FileSerializable, Locatable},
+    };
+
+    /// Queries should find the same frames as a plain scan, without reading
+    /// chunks that their stats rule out.
+    #[test]
+    fn test_find_frames() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let t0 = Timestamp::new(0, Default::default());
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Float64)
+                .frames_per_chunk(4)
+                .regular(t0, 10),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        let values = [0.0, 1.0, 2.0, 3.0, 9.0, f64::NAN, 8.0, 0.0, 7.0, 6.0];
+        array.append_frames(&values).unwrap();
+
+        let predicate = &Predicate::GreaterThan(5.0);
+        assert_eq!(array.find_frames(predicate, 0, 10).unwrap(), [4, 6, 8, 9]);
+        assert_eq!(array.find_frames(predicate, 5, 9).unwrap(), [6, 8]);
+        assert_eq!(
+            array.find_runs(predicate, 0, 10).unwrap(),
+            [(4, 5), (6, 7), (8, 10)]
+        );
+        let nanos: Vec<(i64, i64)> = array
+            .find_intervals(Predicate::IsNan, None)
+            .unwrap()
+            .iter()
+            .map(|(first, last)| (first.get_nanos(), last.get_nanos()))
+            .collect();
+        assert_eq!(nanos, [(50, 50)]);
+        assert!(array.find_frames(predicate, 0, 11).is_err());
+        assert_eq!(array.find(*predicate, Some((45, 90))).unwrap(), [6, 8]);
+        assert_eq!(array.find(|v| v > 5.0, None).unwrap(), [4, 6, 8, 9]);
+        let odd = |v: f64| v % 2.0 == 1.0;
+        assert_eq!(array.find((*predicate, odd), None).unwrap(), [4, 8]);
+
+        // The first chunk tops out at 3, so it isn't read, even once a value
+        // in it has been changed behind the stats' back.
+        let chunk = array.get_chunk_list().get(0).unwrap();
+        let addr = array.get_frame_addr(chunk, 1);
+        let bytes = 100.0_f64.to_bytes(&io_metadata);
+        array
+            .get_backend()
+            .write_all_at(&bytes, addr.get_loc())
+            .unwrap();
+        assert!(array.find_frames(predicate, 0, 4).unwrap().is_empty());
+        let paired = &(*predicate, |v: f64| v > 5.0);
+        assert!(array.find_frames(paired, 0, 4).unwrap().is_empty());
+        let closure = &|v: f64| v > 5.0;
+        assert_eq!(array.find_frames(closure, 0, 4).unwrap(), [1]);
+        assert_eq!(
+            array.find_frames(&Predicate::LessThan(1.0), 0, 10).unwrap(),
+            [0, 7]
+        );
+    }
+}
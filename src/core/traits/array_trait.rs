@@ -1,17 +1,1314 @@
-use super::{FileSerializable, HasMetadataTags, HasName};
+use std::io;
+
+use crate::core::{
+    enums::{
+        decode_error_bound, decode_filters, encode_filters, with_element_type,
+        ArrayDataType, ChunkLayout, FileFormat, TimeAxis,
+    },
+    structs::{
+        Addr, Array, ArrayHeader, Blob, ChunkStats, DistList, RegularTimeAxis,
+        TimeGap, Timestamp, WriteBuffer,
+    },
+};
+
+use super::{
+    ArrayElement, DecimationLevels, DistListTrait, FileSerializable,
+    FixedSizeOnDisk, Locatable,
+};
+
+/// The most times that a reader looks for a chunk of a filtered array, or
+/// counts its frames, before giving up. Each retry means the writer sealed
+/// the open chunk during the read, so this is only reached if the writer
+/// seals chunks faster than they can be read, or the open chunk's index is
+/// damaged.
+const MAX_READ_ATTEMPTS: usize = 1000;
 
 /// The high level array trait. This is a generally multi-dimensional array that
 /// is stored in the tsdf file. This is where the bulk of your tsdf data will be
 /// stored.
-pub(crate) trait ArrayTrait<T: FileSerializable>:
-    HasMetadataTags + HasName
-{
-    fn shape(&self) -> Vec<i32>;
+///
+/// An array is a stack of frames, where every frame has the same shape. Frames
+/// can only be appended, which is what allows readers to read the array while
+/// a writer is adding to it.
+///
+/// # Structure on disk
+/// The array itself is an ArrayHeader, followed by the address of the array's
+/// tags dictionary (see HasMetadataTags), the address of its list of
+/// decimation levels (see DecimationLevels), the address of its list of
+/// ChunkStats, the address of its open chunk, a RegularTimeAxis if the array
+/// has a Regular time axis, and then a DistList of chunk addresses. Each chunk
+/// looks like this:
+///
+/// | is_frame_written_0 (1 byte) | ... | is_frame_written_n (1 byte) |
+/// | frame_0 (var bytes) | ... | frame_n (var bytes) |
+///
+/// As with shards of a distributed list, keeping the booleans next to each
+/// other lets us count the frames in a chunk with a single read. If the array
+/// has a Timestamped time axis, every frame starts with its Timestamp, so the
+/// timestamp is published by the same boolean as the frame's elements. If the
+/// file has checksums, every frame's timestamp and elements are followed by a
+/// CRC32 (u32) of their bytes on disk, which is checked whenever the frame is
+/// read.
+///
+/// Entry i of the list of ChunkStats summarizes chunk i. It's added once the
+/// chunk is full, so every chunk but the last has one, unless the writer
+/// crashed before adding it; the next append to fill a chunk catches up.
+///
+/// # Filtered arrays
+/// If the header has filters, the chunk list holds the addresses of Blobs
+/// instead, one for each sealed (full) chunk. The frames of a sealed chunk are
+/// split into their elements, timestamps and checksums, passed through the
+/// filters, and written as a Blob. Frames are appended to the open chunk,
+/// which is a chunk like any other, preceded by the index of the chunk it
+/// holds (u64):
+///
+/// | index (u64) | is_frame_written_0 (1 byte) | ... | frame_n (var bytes) |
+///
+/// Appending works exactly as it does for any other chunk. Once the open chunk
+/// is full, its Blob is added to the chunk list, and then the open chunk is
+/// reset for the next chunk: its index is set to u64::MAX, its booleans are
+/// cleared and its index is set to that of the next chunk. Readers check the
+/// index before and after reading the open chunk, and go back to the chunk
+/// list if it changed, so they never see a chunk being reset. An index that
+/// isn't the number of sealed chunks means the open chunk is empty.
+pub(crate) trait ArrayTrait: Locatable {
+    /// Returns the array's header.
+    fn get_header(&self) -> &ArrayHeader;
+
+    /// Returns the type of every element in the array.
+    fn get_data_type(&self) -> ArrayDataType {
+        self.get_header().get_data_type()
+    }
+
+    /// Returns how the array knows when each of its frames was taken.
+    fn get_time_axis(&self) -> TimeAxis {
+        self.get_header().get_time_axis()
+    }
+
+    /// Returns the start time, period and gap list address of an array with a
+    /// Regular time axis, or None for any other array.
+    fn get_regular_axis(&self) -> Option<&RegularTimeAxis>;
+
+    /// Returns the address of the array's list of ChunkStats.
+    fn get_stats_addr(&self) -> Addr;
+
+    /// Returns the address of the open chunk of a filtered array, which is
+    /// null for any other array.
+    fn get_open_chunk_addr(&self) -> Addr;
+
+    /// Returns whether every frame is stored with a Timestamp.
+    fn has_timestamps(&self) -> bool {
+        self.get_time_axis() == TimeAxis::Timestamped
+    }
+
+    /// Returns whether the array knows when its frames were taken, whether
+    /// the times are stored or computed.
+    fn has_time_axis(&self) -> bool {
+        self.get_time_axis() != TimeAxis::None
+    }
+
+    /// Returns the shape of the array. The first dimension is the number of
+    /// frames, and the rest are the shape of each frame.
+    fn shape(&self) -> Vec<u64> {
+        let mut shape = vec![self.len() as u64];
+        shape.extend_from_slice(self.get_header().get_frame_shape());
+        shape
+    }
+
+    /// Returns the location of the address of the array's tags dictionary,
+    /// which always lives immediately after the header.
+    fn get_tags_ptr_addr(&self) -> Addr {
+        let header_size = ArrayHeader::get_size_on_disk(self.get_io_metadata());
+        Addr::new(self.get_addr().get_loc() + header_size)
+    }
+
+    /// Returns the location of the address of the array's list of
+    /// decimation levels, which always lives immediately after the tags
+    /// address.
+    fn get_levels_ptr_addr(&self) -> Addr {
+        let addr_size = Addr::get_size_on_disk(self.get_io_metadata());
+        Addr::new(self.get_tags_ptr_addr().get_loc() + addr_size)
+    }
+
+    /// Returns the location of the address of the array's list of
+    /// ChunkStats, which always lives immediately after the levels address.
+    fn get_stats_ptr_addr(&self) -> Addr {
+        let addr_size = Addr::get_size_on_disk(self.get_io_metadata());
+        Addr::new(self.get_levels_ptr_addr().get_loc() + addr_size)
+    }
+
+    /// Returns the location of the address of the array's open chunk, which
+    /// always lives immediately after the stats address.
+    fn get_open_chunk_ptr_addr(&self) -> Addr {
+        let addr_size = Addr::get_size_on_disk(self.get_io_metadata());
+        Addr::new(self.get_stats_ptr_addr().get_loc() + addr_size)
+    }
+
+    /// Returns the location of the array's RegularTimeAxis, which lives
+    /// immediately after the open chunk address if the array has one.
+    fn get_regular_axis_addr(&self) -> Addr {
+        let addr_size = Addr::get_size_on_disk(self.get_io_metadata());
+        Addr::new(self.get_open_chunk_ptr_addr().get_loc() + addr_size)
+    }
+
+    /// Returns the list of chunk addresses.
+    fn get_chunk_list(&self) -> DistList<'_, '_, Addr> {
+        // The chunk list always lives immediately after the open chunk
+        // address, and the RegularTimeAxis if there is one.
+        let axis_size = match self.get_time_axis() {
+            TimeAxis::Regular => {
+                RegularTimeAxis::get_size_on_disk(self.get_io_metadata())
+            }
+            _ => 0,
+        };
+        let loc = self.get_regular_axis_addr().get_loc() + axis_size;
+        DistList::new(
+            Addr::new(loc),
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        )
+    }
+
+    /// Returns the list of gaps in the rate of an array with a Regular time
+    /// axis, in the order they were written.
+    fn get_gap_list(&self) -> Option<DistList<'_, '_, TimeGap>> {
+        let axis = self.get_regular_axis()?;
+        Some(DistList::new(
+            axis.get_gaps_addr(),
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        ))
+    }
+
+    /// Returns the gaps in the rate of an array with a Regular time axis,
+    /// sorted by index, without the gaps that later ones override (see
+    /// RegularTimeAxis). Arrays without a Regular time axis have no gaps.
+    fn get_time_gaps(&self) -> Vec<TimeGap> {
+        let mut gaps: Vec<TimeGap> = Vec::new();
+        let written = self.get_gap_list().map_or(Vec::new(), |l| l.to_vec());
+        for gap in written {
+            let overridden =
+                gaps.partition_point(|g| g.get_index() < gap.get_index());
+            gaps.truncate(overridden);
+            gaps.push(gap);
+        }
+        gaps
+    }
+
+    /// Returns the list of ChunkStats, one for each full chunk.
+    fn get_stats_list(&self) -> DistList<'_, '_, ChunkStats> {
+        DistList::new(
+            self.get_stats_addr(),
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        )
+    }
+
+    /// Returns the stats of every full chunk, in order. Chunk i holds frames
+    /// i * frames_per_chunk up to (i + 1) * frames_per_chunk.
+    fn get_chunk_stats(&self) -> Vec<ChunkStats> {
+        self.get_stats_list().to_vec()
+    }
+
+    /// Returns whether the array's sealed chunks are filtered.
+    fn is_filtered(&self) -> bool {
+        !self.get_header().get_filters().is_empty()
+    }
+
+    /// Returns the open chunk of a filtered array, which comes after the index
+    /// of the chunk that it holds.
+    fn get_open_chunk(&self) -> Addr {
+        let index_size = u64::get_size_on_disk(self.get_io_metadata());
+        Addr::new(self.get_open_chunk_addr().get_loc() + index_size)
+    }
+
+    /// Reads the index of the chunk that the open chunk holds, which is
+    /// u64::MAX while the open chunk is being reset.
+    fn read_open_chunk_index(&self) -> u64 {
+        u64::from_addr(
+            self.get_open_chunk_addr(),
+            self.get_backend(),
+            self.get_io_metadata(),
+        )
+    }
+
+    /// Writes the index of the chunk that the open chunk holds.
+    fn write_open_chunk_index(&self, index: u64) -> io::Result<()> {
+        let bytes = index.to_bytes(self.get_io_metadata());
+        let loc = self.get_open_chunk_addr().get_loc();
+        self.get_backend().write_all_at(&bytes, loc)
+    }
+
+    /// Returns the (offset, length) of each part of a frame on disk, in the
+    /// order that sealed chunks of filtered arrays store them: the elements,
+    /// then the timestamp, then the checksum.
+    fn get_frame_parts(&self) -> [(usize, usize); 3] {
+        let timestamp_size = self.get_timestamp_size_on_disk() as usize;
+        let data_size = self.get_frame_data_size_on_disk() as usize;
+        let frame_size = self.get_frame_size_on_disk() as usize;
+        [
+            (timestamp_size, data_size - timestamp_size),
+            (0, timestamp_size),
+            (data_size, frame_size - data_size),
+        ]
+    }
+
+    /// Splits frames, as they're laid out in a chunk, into their elements,
+    /// timestamps and checksums, in the order given by get_frame_parts.
+    fn split_frames(&self, frames: &[u8]) -> Vec<u8> {
+        let frame_size = self.get_frame_size_on_disk() as usize;
+        let mut split = Vec::with_capacity(frames.len());
+        for (offset, len) in self.get_frame_parts() {
+            for frame in frames.chunks(frame_size) {
+                split.extend_from_slice(&frame[offset..offset + len]);
+            }
+        }
+        split
+    }
+
+    /// Undoes split_frames.
+    fn join_frames(&self, split: &[u8]) -> Vec<u8> {
+        let frame_size = self.get_frame_size_on_disk() as usize;
+        let num_frames = split.len() / frame_size;
+        let mut frames = vec![0; split.len()];
+        let mut pos = 0;
+        for (offset, len) in self.get_frame_parts() {
+            for i in 0..num_frames {
+                let start = i * frame_size + offset;
+                frames[start..start + len]
+                    .copy_from_slice(&split[pos..pos + len]);
+                pos += len;
+            }
+        }
+        frames
+    }
+
+    /// Turns the frames of a full chunk, as they're laid out in a chunk, into
+    /// a sealed chunk of a filtered array.
+    fn encode_chunk(&self, frames: &[u8]) -> io::Result<Vec<u8>> {
+        let filters = self.get_header().get_filters();
+        let layout = self.get_chunk_layout();
+        let sealed =
+            encode_filters(&filters, self.split_frames(frames), &layout)?;
+        if !self.has_checksums()
+            || self.get_header().get_error_bound().is_none()
+        {
+            return Ok(sealed);
+        }
+
+        // A lossy filter changes the elements, so the checksums have to be
+        // those of the frames that readers will get back. Lossy filters never
+        // look at the checksums, so they're swapped without changing anything
+        // else.
+        let io_metadata = self.get_io_metadata();
+        let data_size = self.get_frame_data_size_on_disk() as usize;
+        let frame_size = self.get_frame_size_on_disk() as usize;
+        let decoded = decode_filters(&filters, sealed, &layout)?;
+        let mut frames = frames.to_vec();
+        let lossy_frames = self.join_frames(&decoded);
+        for (frame, lossy_frame) in frames
+            .chunks_mut(frame_size)
+            .zip(lossy_frames.chunks(frame_size))
+        {
+            let checksum = crc32fast::hash(&lossy_frame[..data_size]);
+            frame[data_size..].copy_from_slice(&checksum.to_bytes(io_metadata));
+        }
+        encode_filters(&filters, self.split_frames(&frames), &layout)
+    }
+
+    /// Returns the layout of a full chunk of a filtered array, once its frames
+    /// have been split up.
+    fn get_chunk_layout(&self) -> ChunkLayout {
+        let io_metadata = self.get_io_metadata();
+        let header = self.get_header();
+        let frames_per_chunk = header.get_frames_per_chunk() as usize;
+        let num_timestamps = match self.has_timestamps() {
+            true => frames_per_chunk,
+            false => 0,
+        };
+        ChunkLayout::new(
+            self.get_data_type().get_size_on_disk(io_metadata) as usize,
+            frames_per_chunk * header.get_frame_len() as usize,
+            self.get_timestamp_size_on_disk() as usize,
+            num_timestamps,
+            *io_metadata.get_tsdf_metadata().get_file_format()
+                == FileFormat::Binary,
+        )
+    }
+
+    /// Reads the sealed chunk of a filtered array with the given address,
+    /// returning its frames as they're laid out in a chunk.
+    fn read_sealed_chunk(&self, addr: Addr) -> io::Result<Vec<u8>> {
+        let io_metadata = self.get_io_metadata();
+        let blob = Blob::read(addr, self.get_backend(), io_metadata)?;
+        let filters = self.get_header().get_filters();
+        let split = decode_filters(&filters, blob, &self.get_chunk_layout())?;
+
+        let frame_size = self.get_frame_size_on_disk() as usize;
+        let num_frames = self.get_header().get_frames_per_chunk() as usize;
+        if split.len() != num_frames * frame_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The sealed chunk at {} has the wrong size.",
+                    addr.get_loc()
+                ),
+            ));
+        }
+        Ok(self.join_frames(&split))
+    }
+
+    /// Returns how far the values of frames start..end may be read back from
+    /// the values that were appended: the largest distance used by a lossy
+    /// filter on any of their chunks. Frames of chunks that haven't been
+    /// sealed are exact.
+    fn get_absolute_error(&self, start: usize, end: usize) -> io::Result<f64> {
+        self.check_bounds(start, end)?;
+        if start == end || self.get_header().get_error_bound().is_none() {
+            return Ok(0.0);
+        }
+        let filters = self.get_header().get_filters();
+        let frames_per_chunk =
+            self.get_header().get_frames_per_chunk() as usize;
+        let layout = self.get_chunk_layout();
+        let chunk_list = self.get_chunk_list();
+        let mut error = 0.0_f64;
+        for chunk in start / frames_per_chunk..=(end - 1) / frames_per_chunk {
+            let Some(addr) = chunk_list.get(chunk) else {
+                break;
+            };
+            let blob =
+                Blob::read(addr, self.get_backend(), self.get_io_metadata())?;
+            error = error.max(decode_error_bound(&filters, blob, &layout)?);
+        }
+        Ok(error)
+    }
+
+    /// Reads the bytes of frames first..last of the chunk with the given
+    /// index, wherever the chunk is. Returns a WouldBlock error if the open
+    /// chunk kept moving on while it was read, or an InvalidData error if the
+    /// chunk is neither sealed nor open.
+    fn read_chunk_frames(
+        &self,
+        chunk: usize,
+        first: usize,
+        last: usize,
+    ) -> io::Result<Vec<u8>> {
+        let frame_size = self.get_frame_size_on_disk() as usize;
+        let mut bytes = vec![0; (last - first) * frame_size];
+        if !self.is_filtered() {
+            let chunk = self.get_chunk_list().get(chunk).unwrap();
+            let loc = self.get_frame_addr(chunk, first as u64).get_loc();
+            self.get_backend().read_full_at(&mut bytes, loc)?;
+            return Ok(bytes);
+        }
+
+        // The chunk is either sealed or in the open chunk. If the open chunk
+        // moves on while we read it, the chunk has been sealed.
+        let mut moved_on = false;
+        for _ in 0..MAX_READ_ATTEMPTS {
+            if let Some(sealed) = self.get_chunk_list().get(chunk) {
+                let frames = self.read_sealed_chunk(sealed)?;
+                return Ok(
+                    frames[first * frame_size..last * frame_size].to_vec()
+                );
+            }
+            if self.read_open_chunk_index() == chunk as u64 {
+                let open = self.get_open_chunk();
+                let loc = self.get_frame_addr(open, first as u64).get_loc();
+                self.get_backend().read_full_at(&mut bytes, loc)?;
+                if self.read_open_chunk_index() == chunk as u64 {
+                    return Ok(bytes);
+                }
+                moved_on = true;
+            }
+            std::hint::spin_loop();
+        }
+        match moved_on {
+            true => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!("Chunk {} kept being sealed while it was read.", chunk),
+            )),
+            false => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Chunk {} is neither sealed nor open.", chunk),
+            )),
+        }
+    }
+
+    /// Returns whether every frame is followed by a checksum.
+    fn has_checksums(&self) -> bool {
+        self.get_io_metadata().get_tsdf_metadata().has_checksums()
+    }
+
+    /// Returns the size of a single frame's timestamp on disk, which is zero
+    /// if the array doesn't have timestamps.
+    fn get_timestamp_size_on_disk(&self) -> u64 {
+        match self.has_timestamps() {
+            true => Timestamp::get_size_on_disk(self.get_io_metadata()),
+            false => 0,
+        }
+    }
+
+    /// Returns the size of a single frame's timestamp and elements on disk.
+    fn get_frame_data_size_on_disk(&self) -> u64 {
+        let elem_size = self
+            .get_data_type()
+            .get_size_on_disk(self.get_io_metadata());
+        self.get_timestamp_size_on_disk()
+            + elem_size * self.get_header().get_frame_len()
+    }
+
+    /// Returns the size of a single frame on disk, including its checksum.
+    fn get_frame_size_on_disk(&self) -> u64 {
+        let checksum_size = match self.has_checksums() {
+            true => u32::get_size_on_disk(self.get_io_metadata()),
+            false => 0,
+        };
+        self.get_frame_data_size_on_disk() + checksum_size
+    }
+
+    /// Serializes a frame, preceded by its timestamp if the array has them
+    /// and followed by its checksum if the file has them.
+    fn frame_to_bytes<T: ArrayElement>(
+        &self,
+        timestamp: Timestamp,
+        frame: &[T],
+    ) -> Vec<u8> {
+        let io_metadata = self.get_io_metadata();
+        let mut bytes = match self.has_timestamps() {
+            true => timestamp.to_bytes(io_metadata),
+            false => Vec::new(),
+        };
+        bytes.extend(frame.iter().flat_map(|elem| elem.to_bytes(io_metadata)));
+        if self.has_checksums() {
+            let checksum = crc32fast::hash(&bytes);
+            bytes.extend(checksum.to_bytes(io_metadata));
+        }
+        bytes
+    }
+
+    /// Deserializes the frame with the given index from its bytes on disk,
+    /// checking its checksum if the file has them. The timestamp is None if
+    /// the array doesn't have timestamps.
+    fn frame_from_bytes<T: ArrayElement>(
+        &self,
+        bytes: &[u8],
+        index: usize,
+    ) -> io::Result<(Option<Timestamp>, Vec<T>)> {
+        let io_metadata = self.get_io_metadata();
+        let data_size = self.get_frame_data_size_on_disk() as usize;
+        let (data, checksum) = bytes.split_at(data_size);
+        if self.has_checksums()
+            && crc32fast::hash(data) != u32::from_bytes(checksum, io_metadata)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Frame {} doesn't match its checksum.", index),
+            ));
+        }
+
+        let (timestamp, data) =
+            data.split_at(self.get_timestamp_size_on_disk() as usize);
+        let timestamp = match self.has_timestamps() {
+            true => Some(Timestamp::from_bytes(timestamp, io_metadata)),
+            false => None,
+        };
+        let elem_size = T::get_size_on_disk(io_metadata) as usize;
+        let frame = data
+            .chunks(elem_size)
+            .map(|elem| T::from_bytes(elem, io_metadata))
+            .collect();
+        Ok((timestamp, frame))
+    }
+
+    /// Returns the size of a whole chunk on disk.
+    fn get_chunk_size_on_disk(&self) -> u64 {
+        // Every frame in the chunk has a one byte boolean and the frame data.
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        frames_per_chunk * (1 + self.get_frame_size_on_disk())
+    }
+
+    /// Gets the location of the nth is_frame_written boolean in a chunk.
+    fn get_is_frame_written_addr(&self, chunk: Addr, n: u64) -> Addr {
+        Addr::new(chunk.get_loc() + n)
+    }
+
+    /// Gets the location of the nth frame in a chunk.
+    fn get_frame_addr(&self, chunk: Addr, n: u64) -> Addr {
+        // The frames come after all of the is_frame_written booleans.
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let first_frame_loc = chunk.get_loc() + frames_per_chunk;
+        Addr::new(first_frame_loc + n * self.get_frame_size_on_disk())
+    }
+
+    /// Returns the number of frames that have been written to a chunk. Frames
+    /// are always written in order, so this is the number of booleans set
+    /// before the first one that isn't.
+    fn get_num_written_in_chunk(&self, chunk: Addr) -> u64 {
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let mut buf = vec![0; frames_per_chunk as usize];
+        let loc = self.get_is_frame_written_addr(chunk, 0).get_loc();
+        self.get_backend().read_full_at(&mut buf, loc).unwrap();
+
+        buf.iter()
+            .take_while(|is_written| **is_written == 1)
+            .count() as u64
+    }
+
+    /// Returns the number of frames in the array.
+    fn len(&self) -> usize {
+        if self.is_filtered() {
+            return self.get_filtered_len();
+        }
+
+        // Every chunk except the last is full, so we only need to count the
+        // frames in the last chunk.
+        let chunk_list = self.get_chunk_list();
+        let num_chunks = chunk_list.len();
+        let last_chunk = match num_chunks.checked_sub(1) {
+            Some(idx) => chunk_list.get(idx).unwrap(),
+            None => return 0,
+        };
+
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let num_in_last = self.get_num_written_in_chunk(last_chunk);
+        ((num_chunks as u64 - 1) * frames_per_chunk + num_in_last) as usize
+    }
+
+    /// Returns the number of frames in a filtered array: the frames of every
+    /// sealed chunk, plus those of the open chunk if it holds the next one.
+    /// If the open chunk keeps moving on while it's counted, only the frames
+    /// of the sealed chunks are counted, which every reader can read.
+    fn get_filtered_len(&self) -> usize {
+        let chunk_list = self.get_chunk_list();
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let mut num_sealed = chunk_list.len() as u64;
+        for _ in 0..MAX_READ_ATTEMPTS {
+            num_sealed = chunk_list.len() as u64;
+            let index = self.read_open_chunk_index();
+            if index != u64::MAX && index > num_sealed {
+                // A chunk was sealed after we counted them.
+                num_sealed = chunk_list.len() as u64;
+            }
+            if index != num_sealed {
+                return (num_sealed * frames_per_chunk) as usize;
+            }
+
+            let num_open = self.get_num_written_in_chunk(self.get_open_chunk());
+            if self.read_open_chunk_index() == index {
+                return (num_sealed * frames_per_chunk + num_open) as usize;
+            }
+        }
+        (num_sealed * frames_per_chunk) as usize
+    }
 
-    /// Gets the data at the given indices. The indices should be a list of
-    /// integers, one for each dimension of the array.
-    fn get(&self, indices: Vec<i32>) -> std::vec::Vec<T>;
+    /// Returns whether the array has no frames.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an error if T isn't the type stored in the array.
+    fn check_data_type<T: ArrayElement>(&self) -> io::Result<()> {
+        if T::DATA_TYPE != self.get_data_type() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Array stores {:?}, not {:?}.",
+                    self.get_data_type(),
+                    T::DATA_TYPE
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Gets the frame at the given index, flattened into a single vector.
+    fn get_frame<T: ArrayElement>(&self, index: usize) -> io::Result<Vec<T>> {
+        self.get_frames(index, index + 1)
+    }
+
+    /// Gets the frames from start (inclusive) to end (exclusive), flattened
+    /// into a single vector.
+    fn get_frames<T: ArrayElement>(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<T>> {
+        Ok(self.read_frames(start, end)?.1)
+    }
+
+    /// Gets the frames from start (inclusive) to end (exclusive), flattened
+    /// into a single vector, along with their timestamps. There are no
+    /// timestamps if the array doesn't have them.
+    fn read_frames<T: ArrayElement>(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<(Vec<Timestamp>, Vec<T>)> {
+        self.check_data_type::<T>()?;
+        self.check_bounds(start, end)?;
+
+        let frames_per_chunk =
+            self.get_header().get_frames_per_chunk() as usize;
+
+        // Read each chunk's share of the frames in a single read.
+        let mut timestamps = Vec::new();
+        let mut data = Vec::new();
+        let mut idx = start;
+        while idx < end {
+            let chunk = idx / frames_per_chunk;
+            let first = idx % frames_per_chunk;
+            let last = (end - idx + first).min(frames_per_chunk);
+
+            let frame_size = self.get_frame_size_on_disk() as usize;
+            let bytes = self.read_chunk_frames(chunk, first, last)?;
+            for (i, frame) in bytes.chunks(frame_size).enumerate() {
+                let (timestamp, frame) =
+                    self.frame_from_bytes::<T>(frame, idx + i)?;
+                timestamps.extend(timestamp);
+                data.extend(frame);
+            }
+
+            idx += last - first;
+        }
+
+        // Regular arrays don't store their timestamps, so work them out.
+        if self.get_time_axis() == TimeAxis::Regular {
+            timestamps = self.read_timestamps(start, end)?;
+        }
+        Ok((timestamps, data))
+    }
+
+    /// Gets the timestamps of the frames from start (inclusive) to end
+    /// (exclusive), without reading the frames' elements. Checksums aren't
+    /// checked, since they cover the whole frame. Returns an error if the
+    /// array has no time axis.
+    fn read_timestamps(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<Timestamp>> {
+        self.check_has_time_axis()?;
+        self.check_bounds(start, end)?;
+
+        // A frame's gap is always written before the frame, so reading the
+        // gaps after the bounds check gets every gap that the frames need.
+        if let Some(axis) = self.get_regular_axis() {
+            let gaps = self.get_time_gaps();
+            let clock_source = axis.get_t0().get_clock_source();
+            return Ok((start..end)
+                .map(|i| {
+                    let nanos = axis.get_nanos(&gaps, i as u64);
+                    Timestamp::new(nanos, clock_source)
+                })
+                .collect());
+        }
+
+        let io_metadata = self.get_io_metadata();
+        let frames_per_chunk =
+            self.get_header().get_frames_per_chunk() as usize;
+        let size = self.get_timestamp_size_on_disk() as usize;
+        let mut timestamps = Vec::with_capacity(end - start);
+
+        // The sealed chunks of filtered arrays have to be read whole, so read
+        // each chunk's share of the frames at once.
+        if self.is_filtered() {
+            let frame_size = self.get_frame_size_on_disk() as usize;
+            let mut idx = start;
+            while idx < end {
+                let first = idx % frames_per_chunk;
+                let last = (end - idx + first).min(frames_per_chunk);
+                let bytes = self.read_chunk_frames(
+                    idx / frames_per_chunk,
+                    first,
+                    last,
+                )?;
+                for frame in bytes.chunks(frame_size) {
+                    let timestamp = &frame[..size];
+                    timestamps
+                        .push(Timestamp::from_bytes(timestamp, io_metadata));
+                }
+                idx += last - first;
+            }
+            return Ok(timestamps);
+        }
+
+        let chunk_list = self.get_chunk_list();
+        let mut bytes = vec![0; size];
+        for idx in start..end {
+            let chunk = chunk_list.get(idx / frames_per_chunk).unwrap();
+            let slot = (idx % frames_per_chunk) as u64;
+            let loc = self.get_frame_addr(chunk, slot).get_loc();
+            self.get_backend().read_full_at(&mut bytes, loc)?;
+            timestamps.push(Timestamp::from_bytes(&bytes, io_metadata));
+        }
+        Ok(timestamps)
+    }
+
+    /// Returns the index of the first frame taken at or after the given time,
+    /// in nanoseconds since the Unix epoch, or the number of frames if there
+    /// is no such frame. Timestamps never go backwards, so this is a binary
+    /// search that reads O(log n) timestamps.
+    fn find_time(&self, nanos: i64) -> io::Result<usize> {
+        self.check_has_time_axis()?;
+        let (mut low, mut high) = (0, self.len());
+
+        // Regular arrays work their times out, so there's nothing to read.
+        let gaps = self.get_time_gaps();
+        let get_nanos = |index: usize| -> io::Result<i64> {
+            match self.get_regular_axis() {
+                Some(axis) => Ok(axis.get_nanos(&gaps, index as u64)),
+                None => {
+                    Ok(self.read_timestamps(index, index + 1)?[0].get_nanos())
+                }
+            }
+        };
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match get_nanos(mid)? < nanos {
+                true => low = mid + 1,
+                false => high = mid,
+            }
+        }
+        Ok(low)
+    }
+
+    /// Returns the frames taken from t_start (inclusive) to t_end (exclusive),
+    /// as a range of frame indices.
+    fn find_time_range(
+        &self,
+        t_start: i64,
+        t_end: i64,
+    ) -> io::Result<(usize, usize)> {
+        let start = self.find_time(t_start)?;
+        let end = match t_end > t_start {
+            true => self.find_time(t_end)?,
+            false => start,
+        };
+        Ok((start, end))
+    }
+
+    /// Returns the index of the frame taken closest to the given time, or
+    /// None if the array is empty. Ties, including frames that share a
+    /// timestamp, go to the earliest frame.
+    fn find_nearest(&self, nanos: i64) -> io::Result<Option<usize>> {
+        let after = self.find_time(nanos)?;
+        if after == self.len() {
+            return Ok(after.checked_sub(1));
+        }
+        if after == 0 {
+            return Ok(Some(0));
+        }
+        let timestamps = self.read_timestamps(after - 1, after + 1)?;
+        let before_gap = nanos.abs_diff(timestamps[0].get_nanos());
+        let after_gap = nanos.abs_diff(timestamps[1].get_nanos());
+        match before_gap <= after_gap {
+            // Frames can share a timestamp, so find the first of them.
+            true => Ok(Some(self.find_time(timestamps[0].get_nanos())?)),
+            false => Ok(Some(after)),
+        }
+    }
+
+    /// Returns an error unless start..end is a range of frames in the array.
+    fn check_bounds(&self, start: usize, end: usize) -> io::Result<()> {
+        if start > end || end > self.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Frames {}..{} are out of bounds.", start, end),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns an error unless the array knows when its frames were taken.
+    fn check_has_time_axis(&self) -> io::Result<()> {
+        if !self.has_time_axis() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The array has no time axis.",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Appends any number of frames to the end of the array. The frames
+    /// should be flattened into one slice, one after another.
+    ///
+    /// Frames that share a chunk are written together: all of their data goes
+    /// out in a single write, followed by all of their is_frame_written
+    /// booleans in a single write. This is much faster than appending the
+    /// frames one at a time, and readers still never see a partially written
+    /// frame.
+    fn append_frames<T: ArrayElement>(&self, frames: &[T]) -> io::Result<()> {
+        self.append_frames_with(None, frames)
+    }
+
+    /// Appends frames like append_frames, stamping them with the given
+    /// timestamps, one per frame. If there are no timestamps, frames of a
+    /// Timestamped array are stamped with the current system time, and frames
+    /// of a Regular array carry on at the array's rate.
+    fn append_frames_with<T: ArrayElement>(
+        &self,
+        timestamps: Option<&[Timestamp]>,
+        frames: &[T],
+    ) -> io::Result<()> {
+        self.check_data_type::<T>()?;
+        let frame_len = self.get_header().get_frame_len() as usize;
+        if frame_len == 0 || !frames.len().is_multiple_of(frame_len) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Frames must have {} elements, but got {} elements.",
+                    frame_len,
+                    frames.len()
+                ),
+            ));
+        }
+        let num_frames = frames.len() / frame_len;
+        if let Some(timestamps) = timestamps {
+            self.check_has_time_axis()?;
+            if timestamps.len() != num_frames {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Got {} timestamps for {} frames.",
+                        timestamps.len(),
+                        num_frames
+                    ),
+                ));
+            }
+        }
+        let timestamps = match self.get_regular_axis() {
+            Some(axis) => self.write_time_gaps(axis, timestamps, num_frames)?,
+            None => self.stamp_frames(timestamps, num_frames)?,
+        };
+
+        let old_len = self.len();
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let mut remaining = frames;
+        let mut written = 0;
+        while !remaining.is_empty() {
+            // Work out where the next frame should go, and how many of the
+            // remaining frames fit in the same chunk.
+            let (chunk, slot) = self.find_free_slot::<T>()?;
+            let num_remaining = (remaining.len() / frame_len) as u64;
+            let num_frames = num_remaining.min(frames_per_chunk - slot);
+            let (batch, rest) =
+                remaining.split_at(num_frames as usize * frame_len);
+
+            // Write the frames, then mark them as written. As always, the
+            // booleans must come last, so that readers never see a partially
+            // written frame.
+            let mut buffer = WriteBuffer::new();
+            for (i, frame) in batch.chunks(frame_len).enumerate() {
+                let addr = self.get_frame_addr(chunk, slot + i as u64);
+                let timestamp = timestamps[written + i];
+                buffer.push(addr, self.frame_to_bytes(timestamp, frame));
+            }
+            buffer.push_flags(
+                self.get_is_frame_written_addr(chunk, slot),
+                vec![1; num_frames as usize],
+            );
+            buffer.flush(self.get_backend())?;
+            if self.is_filtered() && slot + num_frames == frames_per_chunk {
+                self.seal_open_chunk()?;
+            }
+
+            remaining = rest;
+            written += num_frames as usize;
+        }
+
+        self.seal_chunks(old_len, frames)?;
+        self.sync_if_durable()
+    }
+
+    /// Adds the stats of every full chunk that doesn't have them yet, after
+    /// the given frames were appended to an array that held old_len frames.
+    /// Chunks that were filled by the append are summarized from the frames
+    /// in memory, and any others, which lost their stats to a crash, are read
+    /// back. The chunks of an array with a lossy filter are always read back,
+    /// so that the stats are of the values that readers get.
+    fn seal_chunks<T: ArrayElement>(
+        &self,
+        old_len: usize,
+        frames: &[T],
+    ) -> io::Result<()> {
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let frame_len = self.get_header().get_frame_len() as usize;
+        let chunk_len = frames_per_chunk as usize;
+        let num_full = self.len() / chunk_len;
+        let mut stats_list = self.get_stats_list();
+        let mut new_stats = Vec::new();
+        let is_lossy = self.get_header().get_error_bound().is_some();
+        for chunk in stats_list.len()..num_full {
+            let start = chunk * chunk_len;
+            let stats = match start.checked_sub(old_len).filter(|_| !is_lossy) {
+                Some(offset) => {
+                    let offset = offset * frame_len;
+                    ChunkStats::of(
+                        &frames[offset..offset + chunk_len * frame_len],
+                    )
+                }
+                None => ChunkStats::of(
+                    &self.get_frames::<T>(start, start + chunk_len)?,
+                ),
+            };
+            new_stats.push(stats);
+        }
+        stats_list.add_all(&new_stats);
+        Ok(())
+    }
+
+    /// Returns the timestamps to store with frames about to be appended to the
+    /// array, which are the given ones if there are any and the system time
+    /// otherwise. Timestamps must never go backwards, so that they can be
+    /// binary searched. Frames stamped with the system time are stamped no
+    /// earlier than the last frame, in case the system clock has stepped
+    /// back.
+    fn stamp_frames(
+        &self,
+        timestamps: Option<&[Timestamp]>,
+        num_frames: usize,
+    ) -> io::Result<Vec<Timestamp>> {
+        let last = match self.has_timestamps() && !self.is_empty() {
+            true => Some(self.read_timestamps(self.len() - 1, self.len())?[0]),
+            false => None,
+        };
+        if let Some(timestamps) = timestamps {
+            check_not_backwards(last, timestamps)?;
+            return Ok(timestamps.to_vec());
+        }
+
+        let mut now = Timestamp::now();
+        if let Some(last) = last {
+            if last.get_nanos() > now.get_nanos() {
+                now = Timestamp::new(last.get_nanos(), now.get_clock_source());
+            }
+        }
+        Ok(vec![now; num_frames])
+    }
+
+    /// Writes the gaps needed to give frames about to be appended to an array
+    /// with a Regular time axis the given timestamps, or the regular ones if
+    /// there aren't any, and returns the frames' timestamps. The gaps must be
+    /// written before the frames, so that readers never see a frame without
+    /// its gap.
+    fn write_time_gaps(
+        &self,
+        axis: &RegularTimeAxis,
+        timestamps: Option<&[Timestamp]>,
+        num_frames: usize,
+    ) -> io::Result<Vec<Timestamp>> {
+        let len = self.len() as u64;
+        let clock_source = axis.get_t0().get_clock_source();
+        let mut gaps = self.get_time_gaps();
+
+        // Gaps at or after the end of the array were left by a crash before
+        // their frames were written, so the first new frame needs a gap of its
+        // own to override them.
+        let stale = gaps.iter().any(|gap| gap.get_index() >= len);
+        gaps.retain(|gap| gap.get_index() < len);
+        if let Some(timestamps) = timestamps {
+            let last = len.checked_sub(1).map(|index| {
+                Timestamp::new(axis.get_nanos(&gaps, index), clock_source)
+            });
+            check_not_backwards(last, timestamps)?;
+        }
+
+        let mut new_gaps = Vec::new();
+        let mut stamps = Vec::with_capacity(num_frames);
+        for i in 0..num_frames {
+            let index = len + i as u64;
+            let regular = axis.get_nanos(&gaps, index);
+            let nanos = timestamps.map_or(regular, |t| t[i].get_nanos());
+            if nanos != regular || (i == 0 && stale) {
+                gaps.push(TimeGap::new(index, nanos));
+                new_gaps.push(TimeGap::new(index, nanos));
+            }
+            stamps.push(Timestamp::new(nanos, clock_source));
+        }
+        if let Some(mut gap_list) = self.get_gap_list() {
+            gap_list.add_all(&new_gaps);
+        }
+        Ok(stamps)
+    }
+
+    /// Appends every frame of this array to another array with the same data
+    /// type, frame shape and time axis, which is usually in another file.
+    /// Frames are copied a chunk at a time, along with their timestamps, and
+    /// then the destination's decimation levels are brought up to date.
+    fn copy_frames_into(&self, dest: &Array) -> io::Result<()> {
+        let len = self.len();
+        let old_len = dest.len();
+        let batch = self.get_header().get_frames_per_chunk() as usize;
+        with_element_type!(self.get_data_type(), T => {
+            for start in (0..len).step_by(batch) {
+                let end = (start + batch).min(len);
+                let (timestamps, frames) = self.read_frames::<T>(start, end)?;
+                let timestamps = match self.has_time_axis() {
+                    true => Some(timestamps.as_slice()),
+                    false => None,
+                };
+                dest.append_frames_with(timestamps, &frames)?;
+            }
+        });
+        dest.update_levels(old_len)
+    }
+
+    /// Returns the chunk and slot that the next frame should be written to,
+    /// creating a new chunk if the last chunk is full. Filtered arrays always
+    /// write to their open chunk, which is tidied up first if the writer
+    /// crashed part way through sealing it.
+    fn find_free_slot<T: ArrayElement>(&self) -> io::Result<(Addr, u64)> {
+        let mut chunk_list = self.get_chunk_list();
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        if self.is_filtered() {
+            let open = self.get_open_chunk();
+            let num_sealed = chunk_list.len() as u64;
+            if self.read_open_chunk_index() != num_sealed {
+                self.reset_open_chunk(num_sealed)?;
+                return Ok((open, 0));
+            }
+            let num_written = self.get_num_written_in_chunk(open);
+            if num_written == frames_per_chunk {
+                self.seal_open_chunk()?;
+                return Ok((open, 0));
+            }
+            self.clear_stray_flags(open, num_written)?;
+            return Ok((open, num_written));
+        }
+
+        if let Some(idx) = chunk_list.len().checked_sub(1) {
+            let chunk = chunk_list.get(idx).unwrap();
+            let num_written = self.get_num_written_in_chunk(chunk);
+            if num_written < frames_per_chunk {
+                self.clear_stray_flags(chunk, num_written)?;
+                return Ok((chunk, num_written));
+            }
+        }
+
+        // We need a new chunk at the end of the file. It must be fully
+        // initialized before it's added to the chunk list.
+        let chunk = Addr::new(self.get_backend().len()?);
+        let frame_len = self.get_header().get_frame_len() as usize;
+        let null_frame =
+            self.frame_to_bytes(Timestamp::null(), &vec![T::null(); frame_len]);
+        let mut buffer = WriteBuffer::new();
+        buffer.push(chunk, vec![0; frames_per_chunk as usize]);
+        for n in 0..frames_per_chunk {
+            buffer.push(self.get_frame_addr(chunk, n), null_frame.clone());
+        }
+        buffer.flush(self.get_backend())?;
+        chunk_list.add(&chunk);
+
+        Ok((chunk, 0))
+    }
+
+    /// Seals the full open chunk of a filtered array: its frames are filtered
+    /// and written to a Blob, which is added to the chunk list, and then the
+    /// open chunk is reset for the next chunk.
+    fn seal_open_chunk(&self) -> io::Result<()> {
+        let mut chunk_list = self.get_chunk_list();
+        let index = chunk_list.len() as u64;
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let frame_size = self.get_frame_size_on_disk();
+        let mut frames = vec![0; (frames_per_chunk * frame_size) as usize];
+        let loc = self.get_frame_addr(self.get_open_chunk(), 0).get_loc();
+        self.get_backend().read_full_at(&mut frames, loc)?;
+
+        let sealed = self.encode_chunk(&frames)?;
+        let io_metadata = self.get_io_metadata();
+        chunk_list.add(&Blob::append(
+            &sealed,
+            self.get_backend(),
+            io_metadata,
+        )?);
+        self.reset_open_chunk(index + 1)
+    }
+
+    /// Empties the open chunk of a filtered array, and gives it the index of
+    /// the chunk that it will hold. The index is invalidated while the
+    /// booleans are cleared, so that readers never mistake the old frames for
+    /// the new chunk's.
+    fn reset_open_chunk(&self, index: u64) -> io::Result<()> {
+        self.write_open_chunk_index(u64::MAX)?;
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let zeros = vec![0; frames_per_chunk as usize];
+        let loc = self.get_is_frame_written_addr(self.get_open_chunk(), 0);
+        self.get_backend().write_all_at(&zeros, loc.get_loc())?;
+        self.write_open_chunk_index(index)
+    }
+
+    /// Clears any is_frame_written booleans that are set after the first
+    /// unset one in a chunk. Readers ignore these, but they can be left behind
+    /// if the writer crashes part way through writing a batch of booleans.
+    /// They have to go before we append to the chunk, or the frames they
+    /// guard would reappear after the new frames.
+    fn clear_stray_flags(
+        &self,
+        chunk: Addr,
+        num_written: u64,
+    ) -> io::Result<()> {
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let mut flags = vec![0; (frames_per_chunk - num_written) as usize];
+        let loc = self.get_is_frame_written_addr(chunk, num_written);
+        self.get_backend().read_full_at(&mut flags, loc.get_loc())?;
+
+        if flags.iter().any(|flag| *flag != 0) {
+            let zeros = vec![0; flags.len()];
+            self.get_backend().write_all_at(&zeros, loc.get_loc())?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the array is well formed. The header must be valid, the
+    /// chunk list must be well formed, every chunk must be inside the file and
+    /// every chunk but the last must be full. The last chunk may have booleans
+    /// set after the first unset one, left by a torn batch append; these are
+    /// ignored by readers (see clear_stray_flags). Every sealed chunk of a
+    /// filtered array must decode, and its open chunk must be inside the file.
+    fn verify(&self) -> io::Result<()> {
+        let invalid_data =
+            |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        if !self.get_header().is_valid() {
+            return invalid_data(format!(
+                "Array at {} has an invalid header.",
+                self.get_addr().get_loc()
+            ));
+        }
+
+        let chunk_list = self.get_chunk_list();
+        chunk_list.verify()?;
+
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        let chunks = chunk_list.to_vec();
+        match self.is_filtered() {
+            true => self.verify_filtered_chunks(&chunks)?,
+            false => self.verify_chunks(&chunks)?,
+        }
+        if let Some(gap_list) = self.get_gap_list() {
+            gap_list.verify()?;
+        }
+        let stats_list = self.get_stats_list();
+        stats_list.verify()?;
+        if stats_list.len() > self.len() / frames_per_chunk as usize {
+            return invalid_data(format!(
+                "Array at {} has stats for more chunks than it's filled.",
+                self.get_addr().get_loc()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every chunk is inside the file and that every chunk but
+    /// the last is full, checking the checksums of their frames.
+    fn verify_chunks(&self, chunks: &[Addr]) -> io::Result<()> {
+        let invalid_data =
+            |msg: String| Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        let file_len = self.get_backend().len()?;
+        let frames_per_chunk = self.get_header().get_frames_per_chunk();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let chunk_end = chunk.get_loc() + self.get_chunk_size_on_disk();
+            if chunk_end > file_len {
+                return invalid_data(format!(
+                    "Chunk {} at {} runs past the end of the file.",
+                    i,
+                    chunk.get_loc()
+                ));
+            }
+
+            let num_written = self.get_num_written_in_chunk(*chunk);
+            if i + 1 < chunks.len() && num_written < frames_per_chunk {
+                return invalid_data(format!(
+                    "Chunk {} at {} isn't full, but isn't the last chunk.",
+                    i,
+                    chunk.get_loc()
+                ));
+            }
+            if self.has_checksums() {
+                let frame_size = self.get_frame_size_on_disk() as usize;
+                let mut bytes = vec![0; num_written as usize * frame_size];
+                let loc = self.get_frame_addr(*chunk, 0).get_loc();
+                self.get_backend().read_full_at(&mut bytes, loc)?;
+                self.verify_checksums(&bytes, *chunk)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every sealed chunk of a filtered array decodes, and that
+    /// its open chunk is inside the file, checking the checksums of all of
+    /// their frames.
+    fn verify_filtered_chunks(&self, chunks: &[Addr]) -> io::Result<()> {
+        for chunk in chunks {
+            let bytes = self.read_sealed_chunk(*chunk)?;
+            if self.has_checksums() {
+                self.verify_checksums(&bytes, *chunk)?;
+            }
+        }
+
+        let open = self.get_open_chunk();
+        let chunk_end = open.get_loc() + self.get_chunk_size_on_disk();
+        if chunk_end > self.get_backend().len()? {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The open chunk at {} runs past the end of the file.",
+                    open.get_loc()
+                ),
+            ));
+        }
+        if self.has_checksums()
+            && self.read_open_chunk_index() == chunks.len() as u64
+        {
+            let num_written = self.get_num_written_in_chunk(open) as usize;
+            let bytes = self.read_chunk_frames(chunks.len(), 0, num_written)?;
+            self.verify_checksums(&bytes, open)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the checksums of the frames read from the chunk at the given
+    /// address.
+    fn verify_checksums(&self, bytes: &[u8], chunk: Addr) -> io::Result<()> {
+        let io_metadata = self.get_io_metadata();
+        let data_size = self.get_frame_data_size_on_disk() as usize;
+        let frame_size = self.get_frame_size_on_disk() as usize;
+        for (n, frame) in bytes.chunks(frame_size).enumerate() {
+            let (data, checksum) = frame.split_at(data_size);
+            if crc32fast::hash(data) != u32::from_bytes(checksum, io_metadata) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Frame {} of the chunk at {} doesn't match its \
+                         checksum.",
+                        n,
+                        chunk.get_loc()
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
 
-    /// Appends the given data to the end of the array.
-    fn append(&self, data: std::vec::Vec<T>);
+/// Returns an error if the timestamps go backwards, or start before the last
+/// one already in the array.
+fn check_not_backwards(
+    last: Option<Timestamp>,
+    timestamps: &[Timestamp],
+) -> io::Result<()> {
+    let nanos = last.iter().chain(timestamps).map(|t| t.get_nanos());
+    if nanos.clone().zip(nanos.skip(1)).any(|(a, b)| a > b) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Timestamps must not go backwards.",
+        ));
+    }
+    Ok(())
 }
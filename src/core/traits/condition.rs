@@ -0,0 +1,59 @@
+use crate::core::{enums::Predicate, structs::ChunkStats};
+
+/// A condition on the values in an array, used to find the frames that meet
+/// it (see Array::find). Values are compared as f64s, and a frame with
+/// several elements meets a condition if any of its elements do.
+///
+/// Conditions are usually a Predicate, whose bounds can be checked against a
+/// chunk's ChunkStats so that chunks that can't hold a match are never read,
+/// or a closure such as `|v| v > 5.0`, which has to look at every value. A
+/// closure can be paired with a Predicate, as `(predicate, closure)`, to get
+/// the values that meet both, with chunks skipped as for the Predicate alone.
+pub trait Condition {
+    /// Returns whether the value meets the condition.
+    fn matches(&self, value: f64) -> bool;
+
+    /// Returns whether any of the values summarized by the stats could meet
+    /// the condition. This is false only if none of them can.
+    fn may_match(&self, _stats: &ChunkStats) -> bool {
+        true
+    }
+}
+
+impl<F: Fn(f64) -> bool> Condition for F {
+    fn matches(&self, value: f64) -> bool {
+        self(value)
+    }
+}
+
+impl<F: Fn(f64) -> bool> Condition for (Predicate, F) {
+    fn matches(&self, value: f64) -> bool {
+        self.0.matches(value) && (self.1)(value)
+    }
+
+    fn may_match(&self, stats: &ChunkStats) -> bool {
+        Condition::may_match(&self.0, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A closure can't rule out a chunk on its own, but paired with a
+    /// Predicate it's ruled out wherever the Predicate is.
+    #[test]
+    fn test_closure_conditions() {
+        let stats = ChunkStats::of(&[1.0, 4.0]);
+        let is_even = |v: f64| v % 2.0 == 0.0;
+        assert!(is_even.matches(4.0));
+        assert!(is_even.may_match(&stats));
+
+        let paired = (Predicate::GreaterThan(2.0), is_even);
+        assert!(paired.matches(4.0));
+        assert!(!paired.matches(2.0));
+        assert!(paired.may_match(&stats));
+        let paired = (Predicate::GreaterThan(4.0), is_even);
+        assert!(!paired.may_match(&stats));
+    }
+}
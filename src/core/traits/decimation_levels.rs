@@ -0,0 +1,336 @@
+use std::io;
+
+use crate::core::{
+    enums::{with_element_type, ArrayDataType},
+    structs::{Addr, Array, ArrayOptions, DistList},
+};
+
+use super::{
+    ArrayElement, ArrayTrait, DistListTrait, FileSerializable, Locatable,
+};
+
+/// Each decimation level has this many times fewer buckets than the level
+/// below it.
+pub(crate) const DECIMATION_FACTOR: u64 = 10;
+
+/// Levels are filled in this many buckets at a time.
+const LEVEL_BATCH_LEN: u64 = 4096;
+
+/// A bucket of a decimation level: the min, max and mean of the values it
+/// covers.
+pub(crate) type Bucket = (f64, f64, f64);
+
+/// The DecimationLevels trait is implemented by Array. Arrays of scalar
+/// frames keep a pyramid of decimated copies of themselves, so that readers
+/// can draw any stretch of a long array by reading a few thousand points.
+///
+/// Level 0 is the array itself, and every bucket of level n covers
+/// DECIMATION_FACTOR buckets of level n - 1, so DECIMATION_FACTOR^n frames.
+/// Each level above 0 is an Array of Float64 frames of shape [3], holding the
+/// min, max and mean of its bucket. Min and max ignore NaNs, but the mean
+/// doesn't.
+///
+/// # Structure on disk
+/// The addresses of the levels are kept in a DistList, whose address lives in
+/// the array right after its tags address. Arrays whose frames aren't scalars
+/// have a null address there instead. A level is only created once it has a
+/// whole bucket, and only whole buckets are written. The writer works out
+/// which buckets are missing from the lengths of the levels, so if it crashes
+/// part way through an update, the next update carries on where it left off.
+pub(crate) trait DecimationLevels: ArrayTrait {
+    /// Returns the address of the list of levels, which is null if the array
+    /// doesn't have levels.
+    fn get_levels_addr(&self) -> Addr;
+
+    /// Returns the list of level addresses, or None if the array doesn't have
+    /// levels.
+    fn get_level_list(&self) -> Option<DistList<'_, '_, Addr>> {
+        if self.get_levels_addr() == Addr::null() {
+            return None;
+        }
+        Some(DistList::new(
+            self.get_levels_addr(),
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        ))
+    }
+
+    /// Returns the number of levels above level 0 that have been written.
+    fn get_num_levels(&self) -> usize {
+        self.get_level_list().map_or(0, |list| list.len())
+    }
+
+    /// Opens the Array holding the given level, which must be above 0.
+    fn get_level(&self, level: usize) -> io::Result<Array<'_, '_>> {
+        let addr = level
+            .checked_sub(1)
+            .and_then(|i| self.get_level_list()?.get(i))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("The array has no decimation level {}.", level),
+                )
+            })?;
+        Array::open(addr, self.get_io_metadata(), self.get_backend())
+    }
+
+    /// Reads buckets start..end of the given level. The buckets of level 0
+    /// are the array's own frames, each of which is its own min, max and
+    /// mean.
+    fn read_level(
+        &self,
+        level: usize,
+        start: usize,
+        end: usize,
+    ) -> io::Result<Vec<Bucket>> {
+        if self.get_level_list().is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only arrays of scalar frames have decimation levels.",
+            ));
+        }
+        if level > 0 {
+            let values =
+                self.get_level(level)?.get_frames::<f64>(start, end)?;
+            return Ok(values.chunks(3).map(|b| (b[0], b[1], b[2])).collect());
+        }
+        with_element_type!(self.get_data_type(), T => {
+            Ok(self
+                .get_frames::<T>(start, end)?
+                .into_iter()
+                .map(|value| {
+                    let value = value.to_f64();
+                    (value, value, value)
+                })
+                .collect())
+        })
+    }
+
+    /// Writes any whole buckets that are missing from the levels, creating
+    /// levels as they get their first bucket. old_len is the length of the
+    /// array before the frames that were just appended; nothing can be
+    /// missing unless they crossed a multiple of DECIMATION_FACTOR, so the
+    /// update is skipped otherwise.
+    fn update_levels(&self, old_len: usize) -> io::Result<()> {
+        let mut level_list = match self.get_level_list() {
+            Some(level_list) => level_list,
+            None => return Ok(()),
+        };
+        let factor = DECIMATION_FACTOR as usize;
+        if old_len / factor == self.len() / factor {
+            return Ok(());
+        }
+
+        let mut below_len = self.len() as u64;
+        for level in 1.. {
+            let target = below_len / DECIMATION_FACTOR;
+            if target == 0 {
+                break;
+            }
+            let array = match level_list.get(level - 1) {
+                Some(addr) => Array::open(
+                    addr,
+                    self.get_io_metadata(),
+                    self.get_backend(),
+                )?,
+                None => {
+                    // The level must be fully created before it's added to
+                    // the list.
+                    let array = Array::create(
+                        Addr::new(self.get_backend().len()?),
+                        ArrayOptions::new()
+                            .data_type(ArrayDataType::Float64)
+                            .frame_shape(&[3])
+                            .frames_per_chunk(
+                                self.get_header().get_frames_per_chunk(),
+                            ),
+                        self.get_io_metadata(),
+                        self.get_backend(),
+                    )?;
+                    level_list.add(array.get_addr());
+                    array
+                }
+            };
+
+            let written = array.len() as u64;
+            for first in (written..target).step_by(LEVEL_BATCH_LEN as usize) {
+                let last = (first + LEVEL_BATCH_LEN).min(target);
+                let below = self.read_level(
+                    level - 1,
+                    (first * DECIMATION_FACTOR) as usize,
+                    (last * DECIMATION_FACTOR) as usize,
+                )?;
+                let buckets: Vec<f64> = below
+                    .chunks(factor)
+                    .flat_map(|buckets| {
+                        let (min, max, mean) = combine(buckets);
+                        [min, max, mean]
+                    })
+                    .collect();
+                ArrayTrait::append_frames(&array, &buckets)?;
+            }
+            below_len = target;
+        }
+        Ok(())
+    }
+
+    /// Returns the buckets of the finest level that covers frames start..end
+    /// in at most max_points buckets, along with the number of frames in each
+    /// of its buckets. If no level is coarse enough, the coarsest is used.
+    /// The buckets start with the one holding frame start, and stop before
+    /// the first bucket that isn't whole.
+    fn get_overview(
+        &self,
+        start: usize,
+        end: usize,
+        max_points: usize,
+    ) -> io::Result<(u64, Vec<Bucket>)> {
+        self.check_bounds(start, end)?;
+        let num_levels = self.get_num_levels();
+        let mut level = 0;
+        let mut bucket_len = 1;
+        let num_buckets =
+            |len: u64| end.div_ceil(len as usize) - start / len as usize;
+        while level < num_levels && num_buckets(bucket_len) > max_points {
+            level += 1;
+            bucket_len *= DECIMATION_FACTOR;
+        }
+
+        let level_len = match level {
+            0 => self.len(),
+            _ => self.get_level(level)?.len(),
+        };
+        let first = (start / bucket_len as usize).min(level_len);
+        let last = end.div_ceil(bucket_len as usize).min(level_len);
+        Ok((bucket_len, self.read_level(level, first, last)?))
+    }
+
+    /// Checks that every level is a well formed array.
+    fn verify_levels(&self) -> io::Result<()> {
+        if let Some(level_list) = self.get_level_list() {
+            level_list.verify()?;
+            for level in 1..=level_list.len() {
+                ArrayTrait::verify(&self.get_level(level)?)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Combines buckets of one level into a bucket of the level above.
+fn combine(buckets: &[Bucket]) -> Bucket {
+    let min = buckets.iter().map(|b| b.0).fold(f64::NAN, f64::min);
+    let max = buckets.iter().map(|b| b.1).fold(f64::NAN, f64::max);
+    let mean = buckets.iter().map(|b| b.2).sum::<f64>() / buckets.len() as f64;
+    (min, max, mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        enums::FileFormat, structs::MemoryBackend,
+        test_utils::make_io_metadata, traits::StorageBackend,
+    };
+
+    /// Build levels from appends of every size, and read them back.
+    #[test]
+    fn test_levels() {
+        for file_format in [FileFormat::Binary, FileFormat::Text] {
+            let io_metadata = make_io_metadata(file_format);
+            let backend = MemoryBackend::new();
+            let array = Array::create(
+                Addr::new(0),
+                ArrayOptions::new()
+                    .data_type(ArrayDataType::Int32)
+                    .frames_per_chunk(64),
+                &io_metadata,
+                &backend,
+            )
+            .unwrap();
+
+            let values: Vec<i32> = (0..1234).collect();
+            for value in &values[..15] {
+                array.append(&[*value]).unwrap();
+            }
+            array.append_frames(&values[15..1200]).unwrap();
+            // Frames appended without updating the levels, as if the writer
+            // crashed before it got to them, are caught up on by the next
+            // append to finish a bucket.
+            ArrayTrait::append_frames(&array, &values[1200..1225]).unwrap();
+            array.append_frames(&values[1225..]).unwrap();
+
+            let array =
+                Array::open(Addr::new(0), &io_metadata, &backend).unwrap();
+            assert_eq!(array.get_num_levels(), 3);
+            assert_eq!(array.get_level(1).unwrap().len(), 123);
+            assert_eq!(array.get_level(2).unwrap().len(), 12);
+            assert_eq!(array.get_level(3).unwrap().len(), 1);
+            assert_eq!(
+                array.get_decimated(1, 122, 123).unwrap(),
+                vec![(1220.0, 1229.0, 1224.5)]
+            );
+            assert_eq!(
+                array.get_decimated(3, 0, 1).unwrap(),
+                vec![(0.0, 999.0, 499.5)]
+            );
+            assert_eq!(
+                array.get_decimated(0, 5, 6).unwrap(),
+                vec![(5.0, 5.0, 5.0)]
+            );
+            assert!(array.get_decimated(4, 0, 1).is_err());
+            array.verify_levels().unwrap();
+
+            // Pick the finest level that fits, leaving out partial buckets.
+            let (bucket_len, buckets) =
+                array.get_overview(0, 1234, 200).unwrap();
+            assert_eq!((bucket_len, buckets.len()), (10, 123));
+            let (bucket_len, buckets) =
+                array.get_overview(105, 1234, 12).unwrap();
+            assert_eq!((bucket_len, buckets.len()), (100, 11));
+            assert_eq!(buckets[0], (100.0, 199.0, 149.5));
+            let (bucket_len, buckets) = array.get_overview(3, 9, 10).unwrap();
+            assert_eq!((bucket_len, buckets.len()), (1, 6));
+            let (bucket_len, _) = array.get_overview(0, 1234, 1).unwrap();
+            assert_eq!(bucket_len, 1000);
+        }
+    }
+
+    /// Min and max skip NaNs, and only scalar frames have levels.
+    #[test]
+    fn test_levels_nans_and_shapes() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Float32)
+                .frames_per_chunk(4),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        let mut values = vec![f32::NAN; 10];
+        values[3] = -2.0;
+        values[7] = 4.0;
+        array.append_frames(&values).unwrap();
+        let (min, max, mean) = array.get_decimated(1, 0, 1).unwrap()[0];
+        assert_eq!((min, max), (-2.0, 4.0));
+        assert!(mean.is_nan());
+
+        let images = Array::create(
+            Addr::new(backend.len().unwrap()),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::UInt8)
+                .frame_shape(&[2])
+                .frames_per_chunk(4),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        images.append_frames(&[0_u8; 40]).unwrap();
+        assert_eq!(images.get_num_levels(), 0);
+        assert!(images.get_decimated(0, 0, 1).is_err());
+    }
+}
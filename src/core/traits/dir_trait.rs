@@ -1,14 +1,300 @@
-use super::{HasMetadataTags, HasName};
-use crate::core::enums::HighLevelObject;
+use std::{collections::HashSet, io};
+
+use super::{
+    ArrayTrait, DecimationLevels, DistDictTrait, HasMetadataTags, HasName,
+    StorageBackend,
+};
+use crate::core::{
+    enums::{HighLevelObject, ObjectKind},
+    structs::{
+        Addr, Array, ArrayOptions, Blob, Dir, DirEntry, DistDict, IoMetadata,
+    },
+};
 
 /// The high level dir trait. This is like a directory in a file system, but in
 /// a tsdf file directories can contain Arrays, other Dirs and metadata tags.
-pub(crate) trait DirTrait: HasMetadataTags + HasName {
-    /// Returns a list of all the objects in the directory. This includes all
-    /// metadata tags, arrays, and subdirectories.
-    fn list_dir(&self) -> Vec<&String>;
+///
+/// # Structure on disk
+/// A Dir is a pair of addresses:
+///
+/// | entries_addr (Addr) | tags_addr (Addr) |
+///
+/// The entries dictionary maps the name of each child to a DirEntry, and the
+/// tags dictionary holds the Dir's metadata tags (see HasMetadataTags). Both
+/// dictionaries are initialized before the pair of addresses is written, and
+/// a child is always fully written before its DirEntry is added, so readers
+/// never find a half-made object.
+///
+/// The lifetimes are those of the file that the Dir is in. Children opened or
+/// created through the Dir borrow the file rather than the Dir, so they can
+/// outlive it.
+pub(crate) trait DirTrait<'a, 'b>: HasMetadataTags + HasName {
+    /// Returns the address of the dictionary holding the Dir's children.
+    fn get_entries_addr(&self) -> Addr;
+
+    /// Returns the metadata needed to read/write to the file, for as long as
+    /// the file is open.
+    fn get_file_io_metadata(&self) -> &'a IoMetadata;
+
+    /// Returns the storage backend that the file is stored in, for as long as
+    /// the file is open.
+    fn get_file_backend(&self) -> &'b dyn StorageBackend;
+
+    /// Returns the dictionary holding the Dir's children.
+    fn get_entries(&self) -> DistDict<'_, '_, String, DirEntry> {
+        DistDict::new(
+            self.get_entries_addr(),
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        )
+    }
+
+    /// Returns the entry for the child with the given name, along with whether
+    /// the entry's name really is the given name. If it isn't, the entry
+    /// belongs to a different child whose name hashes to the same value.
+    fn get_entry(&self, name: &str) -> io::Result<Option<(DirEntry, bool)>> {
+        let entry = match self.get_entries().get(&name.to_string()) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let entry_name = Blob::read_string(
+            entry.get_name_addr(),
+            self.get_backend(),
+            self.get_io_metadata(),
+        )?;
+
+        Ok(Some((entry, entry_name == name)))
+    }
+
+    /// Returns the names of every Array and Dir in the directory, sorted.
+    /// Metadata tags are listed by get_tags.
+    fn list_dir(&self) -> io::Result<Vec<String>> {
+        let mut names = self
+            .get_entries()
+            .values()
+            .into_iter()
+            .map(|entry| {
+                Blob::read_string(
+                    entry.get_name_addr(),
+                    self.get_backend(),
+                    self.get_io_metadata(),
+                )
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        names.sort();
+        Ok(names)
+    }
 
     /// Returns the object with the given name in the directory. This can be an
-    /// Array, another Dir, or a metadata tag.
-    fn get(&self, name: String) -> HighLevelObject;
+    /// Array, another Dir, or the value of a metadata tag. Children take
+    /// precedence over tags with the same name.
+    fn get(&self, name: &str) -> io::Result<HighLevelObject<'a, 'b>> {
+        if let Some((entry, true)) = self.get_entry(name)? {
+            return self.open_entry(name, entry);
+        }
+
+        match self.get_tag(name)? {
+            Some(value) => Ok(HighLevelObject::MetadataTag(value)),
+            None => Err(not_found(name)),
+        }
+    }
+
+    /// Opens the child described by the given entry.
+    fn open_entry(
+        &self,
+        name: &str,
+        entry: DirEntry,
+    ) -> io::Result<HighLevelObject<'a, 'b>> {
+        let io_metadata = self.get_file_io_metadata();
+        let backend = self.get_file_backend();
+        Ok(match entry.get_kind() {
+            ObjectKind::Dir => HighLevelObject::Dir(Dir::open(
+                entry.get_addr(),
+                name,
+                io_metadata,
+                backend,
+            )?),
+            ObjectKind::Array => HighLevelObject::Array(Array::open(
+                entry.get_addr(),
+                io_metadata,
+                backend,
+            )?),
+        })
+    }
+
+    /// Returns the subdirectory with the given name.
+    fn get_dir(&self, name: &str) -> io::Result<Dir<'a, 'b>> {
+        match self.get_entry(name)? {
+            Some((entry, true)) => match self.open_entry(name, entry)? {
+                HighLevelObject::Dir(dir) => Ok(dir),
+                _ => Err(wrong_kind(name, ObjectKind::Dir)),
+            },
+            _ => Err(not_found(name)),
+        }
+    }
+
+    /// Returns the array with the given name.
+    fn get_array(&self, name: &str) -> io::Result<Array<'a, 'b>> {
+        match self.get_entry(name)? {
+            Some((entry, true)) => match self.open_entry(name, entry)? {
+                HighLevelObject::Array(array) => Ok(array),
+                _ => Err(wrong_kind(name, ObjectKind::Array)),
+            },
+            _ => Err(not_found(name)),
+        }
+    }
+
+    /// Creates a new, empty subdirectory with the given name.
+    fn create_dir(&self, name: &str) -> io::Result<Dir<'a, 'b>> {
+        let io_metadata = self.get_file_io_metadata();
+        let backend = self.get_file_backend();
+        self.check_name_is_free(name)?;
+
+        let loc = Addr::new(backend.len()?);
+        let dir = Dir::create(loc, name, io_metadata, backend)?;
+        self.add_entry(name, ObjectKind::Dir, loc)?;
+        Ok(dir)
+    }
+
+    /// Creates a new, empty array with the given name, laid out as the
+    /// options say. See ArrayOptions and Array::create.
+    fn create_array(
+        &self,
+        name: &str,
+        options: &ArrayOptions,
+    ) -> io::Result<Array<'a, 'b>> {
+        let io_metadata = self.get_file_io_metadata();
+        let backend = self.get_file_backend();
+        self.check_name_is_free(name)?;
+
+        let loc = Addr::new(backend.len()?);
+        let array = Array::create(loc, options, io_metadata, backend)?;
+        self.add_entry(name, ObjectKind::Array, loc)?;
+        Ok(array)
+    }
+
+    /// Returns an error if the name can't be used for a new child of this
+    /// directory.
+    fn check_name_is_free(&self, name: &str) -> io::Result<()> {
+        if name.is_empty() || name.contains('/') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is not a valid name.", name),
+            ));
+        }
+
+        match self.get_entry(name)? {
+            None => Ok(()),
+            Some((_, true)) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{:?} already exists in {:?}.", name, self.name()),
+            )),
+            Some((_, false)) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{:?} collides with another name in {:?}.",
+                    name,
+                    self.name()
+                ),
+            )),
+        }
+    }
+
+    /// Publishes a child that has already been written to the file.
+    fn add_entry(
+        &self,
+        name: &str,
+        kind: ObjectKind,
+        addr: Addr,
+    ) -> io::Result<()> {
+        let name_addr =
+            Blob::append_str(name, self.get_backend(), self.get_io_metadata())?;
+        self.get_entries()
+            .add(&name.to_string(), &DirEntry::new(kind, name_addr, addr));
+        self.sync_if_durable()
+    }
+
+    /// Checks that the directory and everything below it is well formed.
+    /// Every dictionary must pass DistDictTrait::verify, every array must pass
+    /// ArrayTrait::verify, and every name and tag value must be readable.
+    fn verify_tree(&self) -> io::Result<()> {
+        self.verify_tree_from(&mut HashSet::new())
+    }
+
+    /// Does the work of verify_tree. The locations of the Dirs that have been
+    /// visited are tracked, so that a corrupt file whose Dirs form a cycle is
+    /// reported rather than recursing forever.
+    fn verify_tree_from(&self, visited: &mut HashSet<u64>) -> io::Result<()> {
+        if !visited.insert(self.get_addr().get_loc()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Dir at {} is reachable more than once.",
+                    self.get_addr().get_loc()
+                ),
+            ));
+        }
+
+        self.get_entries().verify()?;
+        self.get_metadata().verify()?;
+        self.get_tags()?;
+        for name in self.list_dir()? {
+            match self.get(&name)? {
+                HighLevelObject::Dir(dir) => dir.verify_tree_from(visited)?,
+                HighLevelObject::Array(array) => {
+                    array.get_metadata().verify()?;
+                    array.get_tags()?;
+                    ArrayTrait::verify(&array)?;
+                    array.verify_levels()?;
+                }
+                HighLevelObject::MetadataTag(_) => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies every tag and child of this directory into another directory,
+    /// which is usually in another file. Arrays are copied frame by frame,
+    /// so the copy is laid out according to the destination's file.
+    fn copy_into(&self, dest: &Dir) -> io::Result<()> {
+        for (name, value) in self.get_tags()? {
+            dest.set_tag(&name, &value)?;
+        }
+
+        for name in self.list_dir()? {
+            match self.get(&name)? {
+                HighLevelObject::Dir(dir) => {
+                    dir.copy_into(&dest.create_dir(&name)?)?
+                }
+                HighLevelObject::Array(array) => {
+                    let options = ArrayOptions::like(&array);
+                    let copy = DirTrait::create_array(dest, &name, &options)?;
+                    for (name, value) in array.get_tags()? {
+                        copy.set_tag(&name, &value)?;
+                    }
+                    array.copy_frames_into(&copy)?;
+                }
+                HighLevelObject::MetadataTag(_) => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Makes the error returned when a name isn't in a directory.
+fn not_found(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{:?} does not exist.", name),
+    )
+}
+
+/// Makes the error returned when a child isn't the kind of object asked for.
+fn wrong_kind(name: &str, expected: ObjectKind) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{:?} is not a {:?}.", name, expected),
+    )
 }
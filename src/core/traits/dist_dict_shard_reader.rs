@@ -1,5 +1,3 @@
-use std::os::unix::fs::FileExt;
-
 use crate::core::{
     enums::LinkPtr,
     structs::{Addr, TsdfHash},
@@ -103,41 +101,12 @@ pub(crate) trait DistDictShardReader<TVal: FileSerializable>:
         // Read the boolean from the file.
         let loc = self.get_is_hash_written_addr(n).get_loc();
         let mut bytes = vec![0; 1];
-        self.get_file().read_at(&mut bytes, loc).unwrap();
-
-        // Convert the bytes to a boolean.
-        bytes[0] == 1
-    }
-
-    /// Gets the boolean that says whether the next pointer has been written.
-    /// This is similar to the is_hash_written boolean, but for the next
-    /// pointer.
-    fn is_next_written(&self) -> bool {
-        // Read the boolean from the file.
-        let loc = self.get_is_next_written_addr().get_loc();
-        let mut bytes = vec![0; 1];
-        self.get_file().read_at(&mut bytes, loc).unwrap();
+        self.get_backend().read_full_at(&mut bytes, loc).unwrap();
 
         // Convert the bytes to a boolean.
         bytes[0] == 1
     }
 
-    /// Gets the next pointer in the shard.
-    fn get_next_ptr(&self) -> LinkPtr {
-        // If the next pointer has not been written, we return a null pointer.
-        if !self.is_next_written() {
-            return LinkPtr::Null(Addr::null());
-        }
-
-        // Since LinkPtr is guaranteed to be FileSerializable, we can use the
-        // from_addr method to read the next pointer from the file.
-        LinkPtr::from_addr(
-            self.get_next_addr(),
-            self.get_file(),
-            self.get_io_metadata(),
-        )
-    }
-
     /// Gets the hash of the nth key in the shard.
     fn get_hash(&self, n: usize) -> TsdfHash {
         // If the hash has not been written, we return a null hash.
@@ -149,7 +118,7 @@ pub(crate) trait DistDictShardReader<TVal: FileSerializable>:
         // from_addr method to read the hash from the file.
         TsdfHash::from_addr(
             self.get_hash_addr(n),
-            self.get_file(),
+            self.get_backend(),
             self.get_io_metadata(),
         )
     }
@@ -165,7 +134,7 @@ pub(crate) trait DistDictShardReader<TVal: FileSerializable>:
         // from_addr method to read the value from the file.
         TVal::from_addr(
             self.get_val_addr(n),
-            self.get_file(),
+            self.get_backend(),
             self.get_io_metadata(),
         )
     }
@@ -1,6 +1,7 @@
-use std::os::unix::fs::FileExt;
-
-use crate::core::{enums::LinkPtr, structs::TsdfHash};
+use crate::core::{
+    enums::LinkPtr,
+    structs::{Addr, TsdfHash, WriteBuffer},
+};
 
 use super::{DistDictShardReader, FileSerializable};
 
@@ -12,31 +13,30 @@ pub(crate) trait DistDictShardWriter<TVal: FileSerializable>:
     /// Initializes the DistDictShardWriter. This function should be called
     /// before any other functions are called on the DistDictShardWriter.
     fn init(&mut self) {
-        // Iterate from 0 to the number of keys in the shard.
+        // Build the whole shard in memory, so that it can be written with a
+        // single vectored write. A fresh shard has no next pointer, and a null
+        // hash and null value in every slot.
+        let io_metadata = self.get_io_metadata();
+        let mut buffer = WriteBuffer::new();
+        buffer.push(self.get_is_next_written_addr(), vec![0]);
+        buffer.push_serializable(
+            self.get_next_addr(),
+            &LinkPtr::Null(Addr::null()),
+            io_metadata,
+        );
+
+        let null_hash = TsdfHash::null().to_bytes(io_metadata);
+        let null_val = TVal::null().to_bytes(io_metadata);
         for i in 0..self.get_capacity() {
-            // Get the location of the hash and value in the shard.
-            let hash_loc = self.get_hash_addr(i);
-            let val_loc = self.get_val_addr(i);
-            let is_written_loc = self.get_is_hash_written_addr(i);
-
-            // Write a null hash and null value to the file.
-            TsdfHash::null().write(
-                hash_loc,
-                self.get_file(),
-                self.get_io_metadata(),
-            );
-            TVal::null().write(
-                val_loc,
-                self.get_file(),
-                self.get_io_metadata(),
-            );
-
-            // Finally, write a false boolean to the file to indicate that the
-            // hash and value are not yet written.
-            self.get_file()
-                .write_at(&[0], is_written_loc.get_loc())
-                .unwrap();
+            buffer.push(self.get_hash_addr(i), null_hash.clone());
+            buffer.push(self.get_val_addr(i), null_val.clone());
+
+            // A false boolean indicates that the hash and value are not yet
+            // written. We're not publishing anything here, so this is data as
+            // far as the buffer is concerned.
+            buffer.push(self.get_is_hash_written_addr(i), vec![0]);
         }
+        buffer.flush(self.get_backend()).unwrap();
 
         // Now that the shard is initialized, set the initialized flag to true.
         self.set_initialization_state(true);
@@ -44,14 +44,16 @@ pub(crate) trait DistDictShardWriter<TVal: FileSerializable>:
 
     /// Sets the next pointer of the shard to the given address.
     fn set_next(&mut self, next: &LinkPtr) {
-        let next_loc = self.get_next_addr();
-        next.write(next_loc, self.get_file(), self.get_io_metadata());
-
-        // Now that the next pointer has been set, we can set the
-        // is_next_written boolean to true.
-        self.get_file()
-            .write_at(&[1], self.get_is_next_written_addr().get_loc())
-            .unwrap();
+        // The is_next_written boolean is a flag, so the buffer writes it after
+        // the next pointer itself.
+        let mut buffer = WriteBuffer::new();
+        buffer.push_serializable(
+            self.get_next_addr(),
+            next,
+            self.get_io_metadata(),
+        );
+        buffer.push_flags(self.get_is_next_written_addr(), vec![1]);
+        buffer.flush(self.get_backend()).unwrap();
     }
 
     /// Checks whether the DistDictShardWriter has been initialized.
@@ -62,6 +64,8 @@ pub(crate) trait DistDictShardWriter<TVal: FileSerializable>:
 
     /// Removes a key-value pair from the shard. Note that we take the hash of
     /// the key as an argument to avoid recomputing it.
+    // Only called by DistDictTrait::remove, which nothing calls yet.
+    #[allow(dead_code)]
     fn remove(&self, hashed_key: &TsdfHash) {
         if !self.is_initialized() {
             // If the shard hasn't been initialized, we can't remove anything.
@@ -80,13 +84,13 @@ pub(crate) trait DistDictShardWriter<TVal: FileSerializable>:
         let val_loc = self.get_val_addr(hash_table_idx as usize);
 
         // Set the is_written boolean to false.
-        self.get_file()
-            .write_at(&[0], is_written_addr.get_loc())
+        self.get_backend()
+            .write_all_at(&[0], is_written_addr.get_loc())
             .unwrap();
 
         // Write the hash and value to the file.
-        TsdfHash::remove(hash_loc, self.get_file(), self.get_io_metadata());
-        TVal::remove(val_loc, self.get_file(), self.get_io_metadata());
+        TsdfHash::remove(hash_loc, self.get_backend(), self.get_io_metadata());
+        TVal::remove(val_loc, self.get_backend(), self.get_io_metadata());
     }
 
     /// Adds a key-value pair to the shard. Note that we take the hash of the
@@ -110,18 +114,27 @@ pub(crate) trait DistDictShardWriter<TVal: FileSerializable>:
         let hash_loc = self.get_hash_addr(hash_table_idx as usize);
         let val_loc = self.get_val_addr(hash_table_idx as usize);
 
-        // Write the hash and value to the file.
-        hashed_key.write(hash_loc, self.get_file(), self.get_io_metadata());
-        val.write(val_loc, self.get_file(), self.get_io_metadata());
+        // If we're overwriting an existing key-value pair, we have to mark it
+        // as unwritten before touching it. Otherwise, a reader (or a crash)
+        // could catch the value half way through being overwritten. Readers
+        // will briefly see the key as missing, but never see a torn value.
+        if self.is_hash_written(hash_table_idx as usize) {
+            self.get_backend()
+                .write_all_at(&[0], is_written_addr.get_loc())
+                .unwrap();
+        }
 
-        // Finally, write a true boolean to the file to indicate that the hash
-        // and value are written. This order of writing is absolutely
+        // Write the hash and value to the file (they're next to each other, so
+        // this is one write), and then write a true boolean to indicate that
+        // the hash and value are written. This order of writing is absolutely
         // fundamental to the whole file format, as boolean writes are atomic.
         // We can guarantee that, for any number of readers, the readers will
         // either see the hash and value as written or not written, but never
         // partially written.
-        self.get_file()
-            .write_at(&[1], is_written_addr.get_loc())
-            .unwrap();
+        let mut buffer = WriteBuffer::new();
+        buffer.push_serializable(hash_loc, hashed_key, self.get_io_metadata());
+        buffer.push_serializable(val_loc, val, self.get_io_metadata());
+        buffer.push_flags(is_written_addr, vec![1]);
+        buffer.flush(self.get_backend()).unwrap();
     }
 }
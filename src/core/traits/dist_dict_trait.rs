@@ -1,6 +1,9 @@
+use std::io;
+
 use crate::core::{
     enums::LinkPtr,
     structs::{addr::Addr, DistDictShard},
+    well_known_values::shard_limits::MAX_LINK_NUMBER,
 };
 
 use super::{
@@ -20,7 +23,7 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
             0, // The first shard has link number 0.
             self.get_first_shard_addr(),
             self.get_io_metadata(),
-            self.get_file(),
+            self.get_backend(),
             // If the distributed dictionary is initialized, the first shard is
             // initialized.
             self.is_initialized(),
@@ -36,14 +39,50 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
         Addr::new(first_shard_loc)
     }
 
+    /// Returns the shard that follows the given shard, or None if the given
+    /// shard is the last shard in the dictionary. Published next pointers
+    /// never change, so they're remembered in the shard cache and only the
+    /// tail of the chain is ever read from the file more than once.
+    fn get_next_shard<'a>(
+        &'a self,
+        shard: &DistDictShard<'_, '_, TVal>,
+    ) -> Option<DistDictShard<'a, 'a, TVal>> {
+        let cache = self.get_io_metadata().get_shard_cache();
+        let first_shard_addr = self.get_first_shard_addr();
+        let link_number = shard.get_link_number() + 1;
+
+        let addr = match cache.get_shard_addr(first_shard_addr, link_number) {
+            Some(addr) => addr,
+            None => match shard.get_next() {
+                LinkPtr::Null(_) => return None,
+                LinkPtr::Addr(addr) => {
+                    cache.insert_shard_addr(
+                        first_shard_addr,
+                        link_number,
+                        addr,
+                    );
+                    addr
+                }
+            },
+        };
+
+        Some(DistDictShard::new(
+            link_number,
+            addr,
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        ))
+    }
+
     /// Initializes the distributed dictionary.
     fn init(&mut self) {
         // First we write the distributed dictionary's internal data to the
         // file.
         // Currently, this is just the first shard's address.
         self.get_first_shard_addr().write(
-            self.get_addr().clone(),
-            self.get_file(),
+            *self.get_addr(),
+            self.get_backend(),
             self.get_io_metadata(),
         );
 
@@ -70,7 +109,7 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
         }
 
         // Start by hashing the key.
-        let hashed_key = key.hash();
+        let hashed_key = key.hash(self.get_io_metadata());
 
         // Now that we've got the key, we need to find the first shard in the
         // distributed dictionary that has space for the key.
@@ -102,48 +141,47 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
             }
 
             // If the key isn't in the shard, we need to move to the next shard.
-            let next = shard.get_next();
-            let link_number = shard.get_link_number();
+            shard = match self.get_next_shard(&shard) {
+                Some(next_shard) => next_shard,
 
-            match next {
-                // If the next pointer is null, we need to create a new shard.
-                LinkPtr::Null(_) => {
+                // If there is no next shard, we need to create a new shard.
+                None => {
                     // Create a new shard at the end of the file.
-                    let file_end = self.get_file().metadata().unwrap().len();
+                    let file_end = self.get_backend().len().unwrap();
                     let new_shard_addr = Addr::new(file_end);
-                    let new_shard = DistDictShard::<TVal>::new(
-                        link_number + 1,
+                    let link_number = shard.get_link_number() + 1;
+                    let mut new_shard = DistDictShard::<TVal>::new(
+                        link_number,
                         new_shard_addr,
                         self.get_io_metadata(),
-                        self.get_file(),
+                        self.get_backend(),
                         false,
                     );
 
+                    // The new shard must be fully initialized before anything
+                    // points to it, so that readers following the next
+                    // pointer never find uninitialized data.
+                    new_shard.init();
+
                     // Set the next pointer of the current shard to point to the
-                    // new shard.
+                    // new shard. Now that it's published, it can be cached.
                     shard.set_next(&LinkPtr::Addr(new_shard_addr));
+                    self.get_io_metadata().get_shard_cache().insert_shard_addr(
+                        self.get_first_shard_addr(),
+                        link_number,
+                        new_shard_addr,
+                    );
 
                     // Set the new shard as the current shard.
-                    shard = new_shard;
-                }
-
-                // If the next pointer is an address, we need to load the next
-                // shard, which is stored at that address.
-                LinkPtr::Addr(addr) => {
-                    // Load the next shard.
-                    shard = DistDictShard::new(
-                        link_number + 1,
-                        Addr::new(addr.get_loc()),
-                        self.get_io_metadata(),
-                        self.get_file(),
-                        true,
-                    )
+                    new_shard
                 }
-            }
+            };
         }
     }
 
     /// Removes a key-value pair from the dictionary.
+    // Tags can't be deleted yet, so nothing removes keys.
+    #[allow(dead_code)]
     fn remove(&self, key: &TKey) {
         // If the distributed dictionary hasn't been initialized, we there's no
         // need to remove anything.
@@ -152,7 +190,7 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
         }
 
         // Start by hashing the key.
-        let hashed_key = key.hash();
+        let hashed_key = key.hash(self.get_io_metadata());
 
         // Now that we've got the key, we need to find the first shard in the
         // distributed dictionary that has space for the key.
@@ -166,26 +204,11 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
             }
 
             // If the key isn't in the shard, we need to move to the next shard.
-            match shard.get_next() {
-                // If the next pointer is null, we failed to find the key. This
-                // is fine, it just means that we have nothing to delete.
-                LinkPtr::Null(_) => {
-                    return;
-                }
-
-                // If the next pointer is an address, we need to load the next
-                // shard, which is stored at that address.
-                LinkPtr::Addr(addr) => {
-                    // Load the next shard.
-                    shard = DistDictShard::new(
-                        shard.get_link_number() + 1,
-                        Addr::new(addr.get_loc()),
-                        self.get_io_metadata(),
-                        self.get_file(),
-                        true,
-                    )
-                }
-            }
+            // If there is no next shard, we failed to find the key.
+            shard = match self.get_next_shard(&shard) {
+                Some(next_shard) => next_shard,
+                None => return,
+            };
         }
     }
 
@@ -199,7 +222,7 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
         }
 
         // Start by hashing the key.
-        let hashed_key = key.hash();
+        let hashed_key = key.hash(self.get_io_metadata());
 
         // Now that we've got the key, we need to find the first shard in the
         // distributed dictionary that has space for the key.
@@ -214,28 +237,14 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
             }
 
             // If the key isn't in the shard, we need to move to the next shard.
-            match shard.get_next() {
-                // If the next pointer is null, we failed to find the key. In
-                // this case, we panic.
-                LinkPtr::Null(_) => return None,
-
-                // If the next pointer is an address, we need to load the next
-                // shard, which is stored at that address.
-                LinkPtr::Addr(addr) => {
-                    // Load the next shard.
-                    shard = DistDictShard::new(
-                        shard.get_link_number() + 1,
-                        Addr::new(addr.get_loc()),
-                        self.get_io_metadata(),
-                        self.get_file(),
-                        true,
-                    )
-                }
-            }
+            // If there is no next shard, we failed to find the key.
+            shard = self.get_next_shard(&shard)?;
         }
     }
 
     /// Returns whether the distributed dictionary contains the given key.
+    // Callers use get, which also returns the value.
+    #[allow(dead_code)]
     fn contains(&self, key: &TKey) -> bool {
         // If the distributed dictionary hasn't been initialized, we can't
         // contain anything.
@@ -244,7 +253,7 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
         }
 
         // Start by hashing the key.
-        let hashed_key = key.hash();
+        let hashed_key = key.hash(self.get_io_metadata());
 
         // Now that we've got the key, we need to find the first shard in the
         // distributed dictionary that has space for the key.
@@ -257,24 +266,94 @@ pub(crate) trait DistDictTrait<TKey: TsdfHashable, TVal: FileSerializable>:
             }
 
             // If the key isn't in the shard, we need to move to the next shard.
-            match shard.get_next() {
-                // If the next pointer is null, we failed to find the key. In
-                // this case, we return false.
-                LinkPtr::Null(_) => return false,
+            // If there is no next shard, we failed to find the key.
+            shard = match self.get_next_shard(&shard) {
+                Some(next_shard) => next_shard,
+                None => return false,
+            };
+        }
+    }
 
-                // If the next pointer is an address, we need to load the next
-                // shard, which is stored at that address.
-                LinkPtr::Addr(addr) => {
-                    // Load the next shard.
-                    shard = DistDictShard::new(
-                        shard.get_link_number() + 1,
-                        Addr::new(addr.get_loc()),
-                        self.get_io_metadata(),
-                        self.get_file(),
-                        true,
-                    )
+    /// Returns every value in the dictionary, in no particular order.
+    fn values(&self) -> Vec<TVal> {
+        let mut values = Vec::new();
+        if !self.is_initialized() {
+            return values;
+        }
+
+        let mut shard = self.get_first_shard();
+        loop {
+            for n in 0..shard.get_capacity() {
+                if shard.is_hash_written(n) {
+                    values.push(shard.get_val(n));
+                }
+            }
+
+            shard = match self.get_next_shard(&shard) {
+                Some(next_shard) => next_shard,
+                None => return values,
+            };
+        }
+    }
+
+    /// Checks that the distributed dictionary is well formed. Every written
+    /// hash must live in the slot that its value maps to, and the chain of
+    /// shards must be finite and stay inside the file.
+    fn verify(&self) -> io::Result<()> {
+        if !self.is_initialized() {
+            return Ok(());
+        }
+
+        let file_len = self.get_backend().len()?;
+        let mut shard = self.get_first_shard();
+        loop {
+            // Every written hash must be in the slot it hashes to, or we'd
+            // never be able to find it again.
+            let capacity = shard.get_capacity();
+            for n in 0..capacity {
+                if !shard.is_hash_written(n) {
+                    continue;
                 }
+                let hash = shard.get_hash(n);
+                if hash.get_hash_table_idx(capacity as u64) != n as u64 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Dict shard {} at {} has hash {} in slot {}.",
+                            shard.get_link_number(),
+                            shard.get_addr().get_loc(),
+                            hash.get_hash_value(),
+                            n
+                        ),
+                    ));
+                }
+            }
+
+            let next_loc = match shard.get_next() {
+                LinkPtr::Null(_) => return Ok(()),
+                LinkPtr::Addr(addr) => addr.get_loc(),
+            };
+
+            // The next shard must be inside the file, and the chain can't go
+            // on forever.
+            let link_number = shard.get_link_number() + 1;
+            if next_loc >= file_len || link_number > MAX_LINK_NUMBER {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Dict shard {} points to an invalid next shard at {}.",
+                        shard.get_link_number(),
+                        next_loc
+                    ),
+                ));
             }
+            shard = DistDictShard::new(
+                link_number,
+                Addr::new(next_loc),
+                self.get_io_metadata(),
+                self.get_backend(),
+                true,
+            );
         }
     }
 }
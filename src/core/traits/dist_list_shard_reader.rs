@@ -1,6 +1,4 @@
-use std::os::unix::fs::FileExt;
-
-use crate::core::structs::Addr;
+use crate::core::{enums::LinkPtr, structs::Addr};
 
 use super::{
     FileSerializable, FixedSizeOnDisk, ShardTrait, VariableSizeOnDisk,
@@ -23,14 +21,16 @@ pub(crate) trait DistListShardReader<T: FileSerializable>:
     /// Returns the nth element in the shard.
     fn get_element(&self, n: usize) -> T {
         let addr = self.get_element_addr(n);
-        T::from_addr(addr, self.get_file(), self.get_io_metadata())
+        T::from_addr(addr, self.get_backend(), self.get_io_metadata())
     }
 
     /// Returns the nth is_element_written boolean in the shard.
     fn is_element_written(&self, n: usize) -> bool {
         let addr = self.get_is_element_written_addr(n);
         let mut buf = [0];
-        self.get_file().read_at(&mut buf, addr.get_loc()).unwrap();
+        self.get_backend()
+            .read_full_at(&mut buf, addr.get_loc())
+            .unwrap();
 
         buf[0] == 1
     }
@@ -58,10 +58,10 @@ pub(crate) trait DistListShardReader<T: FileSerializable>:
     fn get_is_element_written_addr(&self, n: usize) -> Addr {
         // The shard starts with the is_next_written boolean, and then the
         // next pointer. After that, we have the is_element_written booleans.
-        let size_of_addr = Addr::get_size_on_disk(self.get_io_metadata());
+        let size_of_next = LinkPtr::get_size_on_disk(self.get_io_metadata());
         let size_of_bool = 1;
         let start_of_is_element_written =
-            self.get_addr().get_loc() + size_of_bool + size_of_addr;
+            self.get_addr().get_loc() + size_of_bool + size_of_next;
 
         // Then, all the is_element_written booleans come one after another.
         let loc = start_of_is_element_written + size_of_bool * n as u64;
@@ -69,7 +69,26 @@ pub(crate) trait DistListShardReader<T: FileSerializable>:
         Addr::new(loc)
     }
 
+    /// Returns the number of elements that have been written to the shard.
+    /// Elements are always written in order, so this is the number of
+    /// is_element_written booleans that are set before the first one that
+    /// isn't. We read all of the booleans in one go, which is why they're
+    /// stored next to each other.
+    fn get_num_written(&self) -> usize {
+        let addr = self.get_is_element_written_addr(0);
+        let mut buf = vec![0; self.get_capacity()];
+        self.get_backend()
+            .read_full_at(&mut buf, addr.get_loc())
+            .unwrap();
+
+        buf.iter()
+            .take_while(|is_written| **is_written == 1)
+            .count()
+    }
+
     /// Returns whether the shard is full.
+    // Appends compare the written count to the capacity instead.
+    #[allow(dead_code)]
     fn is_full(&self) -> bool {
         // To find out if the shard is full, we check the final
         // is_element_written boolean in the shard.
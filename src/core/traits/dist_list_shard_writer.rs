@@ -1,12 +1,85 @@
-use super::{FileSerializable, Link};
+use crate::core::{
+    enums::LinkPtr,
+    structs::{Addr, WriteBuffer},
+};
 
-pub(crate) trait DistListShardWriter<T: FileSerializable>: Link {
-    /// Adds an element to the shard.
-    fn add(&self, elem: T);
+use super::{DistListShardReader, FileSerializable};
 
-    /// Removes an element from the shard.
-    fn remove(&self, elem: T);
+/// A DistListShardWriter can do everything a DistListShardReader can do, but
+/// also has the ability to write to disk.
+pub(crate) trait DistListShardWriter<T: FileSerializable>:
+    DistListShardReader<T>
+{
+    /// Initializes the DistListShardWriter. This function should be called
+    /// before any other functions are called on the DistListShardWriter.
+    fn init(&mut self) {
+        // Build the whole shard in memory, so that it can be written with a
+        // single vectored write. A fresh shard has no next pointer, a false
+        // boolean for every element and a null element in every slot.
+        let io_metadata = self.get_io_metadata();
+        let mut buffer = WriteBuffer::new();
+        buffer.push(self.get_is_next_written_addr(), vec![0]);
+        buffer.push_serializable(
+            self.get_next_addr(),
+            &LinkPtr::Null(Addr::null()),
+            io_metadata,
+        );
+        buffer.push(
+            self.get_is_element_written_addr(0),
+            vec![0; self.get_capacity()],
+        );
 
-    /// Gets the element at the given index.
-    fn get(&self, index: i32) -> T;
+        let null_elem = T::null().to_bytes(io_metadata);
+        for i in 0..self.get_capacity() {
+            buffer.push(self.get_element_addr(i), null_elem.clone());
+        }
+        buffer.flush(self.get_backend()).unwrap();
+
+        // Now that the shard is initialized, set the initialized flag to true.
+        self.set_initialization_state(true);
+    }
+
+    /// Sets the next pointer of the shard to the given address.
+    fn set_next(&mut self, next: &LinkPtr) {
+        // The is_next_written boolean is a flag, so the buffer writes it after
+        // the next pointer itself.
+        let mut buffer = WriteBuffer::new();
+        buffer.push_serializable(
+            self.get_next_addr(),
+            next,
+            self.get_io_metadata(),
+        );
+        buffer.push_flags(self.get_is_next_written_addr(), vec![1]);
+        buffer.flush(self.get_backend()).unwrap();
+    }
+
+    /// Checks whether the DistListShardWriter has been initialized.
+    fn is_initialized(&self) -> bool;
+
+    /// Sets the initialized flag.
+    fn set_initialization_state(&mut self, initialized: bool);
+
+    /// Writes an element to the nth slot in the shard. Elements must be added
+    /// in order, as readers assume that every element before the first
+    /// unwritten element has been written.
+    fn add(&mut self, n: usize, elem: &T) {
+        if !self.is_initialized() {
+            // If the shard hasn't been initialized, we must initialize it
+            // before adding anything.
+            self.init();
+        }
+
+        // Write the element to the file, followed by a true boolean to indicate
+        // that the element has been written. As with the distributed
+        // dictionary, the boolean must come last, so that readers never see a
+        // partially written element.
+        let mut buffer = WriteBuffer::new();
+        buffer.push_serializable(
+            self.get_element_addr(n),
+            elem,
+            self.get_io_metadata(),
+        );
+        buffer.push_flags(self.get_is_element_written_addr(n), vec![1]);
+        buffer.flush(self.get_backend()).unwrap();
+    }
 }
@@ -1,17 +1,333 @@
-use super::FileSerializable;
+use std::io;
+
+use crate::core::{
+    enums::LinkPtr,
+    structs::{Addr, DistListShard},
+    well_known_values::shard_limits::MAX_LINK_NUMBER,
+};
+
+use super::{
+    DistListShardReader, DistListShardWriter, FileSerializable,
+    FixedSizeOnDisk, Link, Locatable, ShardTrait,
+};
 
 /// A distributed list is a list that is distributed across multiple shards,
 /// where the shards may be in completely different locations in a file.
-pub(crate) trait DistListTrait<T: FileSerializable> {
-    /// Adds an element to the list.
-    fn add(&self, elem: T);
+/// Elements can only be appended, which is what allows readers to read the
+/// list while a writer is adding to it.
+pub(crate) trait DistListTrait<T: FileSerializable>:
+    Locatable + FixedSizeOnDisk
+{
+    /// Returns the first shard in the distributed list.
+    fn get_first_shard(&self) -> DistListShard<'_, '_, T> {
+        DistListShard::<T>::new(
+            0, // The first shard has link number 0.
+            self.get_first_shard_addr(),
+            self.get_io_metadata(),
+            self.get_backend(),
+            // If the distributed list is initialized, the first shard is
+            // initialized.
+            self.is_initialized(),
+        )
+    }
+
+    /// Returns the address of the first shard in the distributed list.
+    fn get_first_shard_addr(&self) -> Addr {
+        // The first shard is always located immediately after the dist list
+        // itself.
+        let dist_list_size = Self::get_size_on_disk(self.get_io_metadata());
+        let first_shard_loc = self.get_addr().get_loc() + dist_list_size;
+        Addr::new(first_shard_loc)
+    }
+
+    /// Returns the shard that follows the given shard, or None if the given
+    /// shard is the last shard in the list. Published next pointers never
+    /// change, so they're remembered in the shard cache and only the tail of
+    /// the chain is ever read from the file more than once.
+    fn get_next_shard<'a>(
+        &'a self,
+        shard: &DistListShard<'_, '_, T>,
+    ) -> Option<DistListShard<'a, 'a, T>> {
+        let cache = self.get_io_metadata().get_shard_cache();
+        let first_shard_addr = self.get_first_shard_addr();
+        let link_number = shard.get_link_number() + 1;
+
+        let addr = match cache.get_shard_addr(first_shard_addr, link_number) {
+            Some(addr) => addr,
+            None => match shard.get_next() {
+                LinkPtr::Null(_) => return None,
+                LinkPtr::Addr(addr) => {
+                    cache.insert_shard_addr(
+                        first_shard_addr,
+                        link_number,
+                        addr,
+                    );
+                    addr
+                }
+            },
+        };
+
+        Some(DistListShard::new(
+            link_number,
+            addr,
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        ))
+    }
+
+    /// Returns the number of elements in the given shard. Full shards never
+    /// change, so the number of leading full shards is remembered in the shard
+    /// cache, and their booleans don't need to be read again.
+    fn get_num_written_in_shard(
+        &self,
+        shard: &DistListShard<'_, '_, T>,
+    ) -> usize {
+        let cache = self.get_io_metadata().get_shard_cache();
+        let first_shard_addr = self.get_first_shard_addr();
+        let link_number = shard.get_link_number() as usize;
+        if link_number < cache.get_num_full(first_shard_addr) {
+            return shard.get_capacity();
+        }
+
+        let num_written = shard.get_num_written();
+        if num_written == shard.get_capacity()
+            && link_number == cache.get_num_full(first_shard_addr)
+        {
+            cache.set_num_full(first_shard_addr, link_number + 1);
+        }
+        num_written
+    }
+
+    /// Initializes the distributed list.
+    fn init(&mut self) {
+        // First we write the distributed list's internal data to the file.
+        // Currently, this is just the first shard's address.
+        self.get_first_shard_addr().write(
+            *self.get_addr(),
+            self.get_backend(),
+            self.get_io_metadata(),
+        );
+
+        // Initialize the first shard.
+        let mut shard = self.get_first_shard();
+        shard.init();
+
+        // Set the initialized flag to true.
+        self.set_initialization_state(true);
+    }
+
+    /// Returns whether the distributed list has been initialized.
+    fn is_initialized(&self) -> bool;
+
+    /// Sets the initialized flag to the given value.
+    fn set_initialization_state(&mut self, initialized: bool);
+
+    /// Appends an element to the end of the list.
+    fn add(&mut self, elem: &T) {
+        // Initialize the distributed list if it hasn't been initialized yet.
+        if !self.is_initialized() {
+            self.init();
+        }
+
+        // Find the first shard that isn't full.
+        let mut shard = self.get_first_shard();
+        loop {
+            let num_written = self.get_num_written_in_shard(&shard);
+            if num_written < shard.get_capacity() {
+                shard.add(num_written, elem);
+                return;
+            }
+
+            // This shard is full, so we need to move to the next shard.
+            shard = match self.get_next_shard(&shard) {
+                Some(next_shard) => next_shard,
+                None => {
+                    // There is no next shard, so we need to create a new
+                    // shard at the end of the file.
+                    let file_end = self.get_backend().len().unwrap();
+                    let new_shard_addr = Addr::new(file_end);
+                    let mut new_shard = DistListShard::<T>::new(
+                        shard.get_link_number() + 1,
+                        new_shard_addr,
+                        self.get_io_metadata(),
+                        self.get_backend(),
+                        false,
+                    );
+
+                    // The new shard must be fully initialized before anything
+                    // points to it, so that readers following the next
+                    // pointer never find uninitialized data.
+                    new_shard.init();
+
+                    // Set the next pointer of the current shard to point to
+                    // the new shard.
+                    shard.set_next(&LinkPtr::Addr(new_shard_addr));
+                    new_shard
+                }
+            };
+        }
+    }
+
+    /// Appends every element in the slice to the end of the list, in order.
+    fn add_all(&mut self, elems: &[T]) {
+        for elem in elems {
+            self.add(elem);
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    fn len(&self) -> usize {
+        // If the distributed list hasn't been initialized, it's empty.
+        if !self.is_initialized() {
+            return 0;
+        }
+
+        let mut len = 0;
+        let mut shard = self.get_first_shard();
+        loop {
+            let num_written = self.get_num_written_in_shard(&shard);
+            len += num_written;
+
+            // Only a full shard can be followed by another shard.
+            if num_written < shard.get_capacity() {
+                return len;
+            }
+            match self.get_next_shard(&shard) {
+                Some(next_shard) => shard = next_shard,
+                None => return len,
+            }
+        }
+    }
+
+    /// Returns whether the list is empty.
+    #[cfg(test)]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the element at the given index. Returns None if the index is out
+    /// of bounds.
+    fn get(&self, index: usize) -> Option<T> {
+        // If the distributed list hasn't been initialized, we can't get
+        // anything.
+        if !self.is_initialized() {
+            return None;
+        }
+
+        // Skip over whole shards until we reach the shard containing the
+        // index.
+        let mut idx = index;
+        let mut shard = self.get_first_shard();
+        while idx >= shard.get_capacity() {
+            idx -= shard.get_capacity();
+            shard = self.get_next_shard(&shard)?;
+        }
+
+        let link_number = shard.get_link_number() as usize;
+        let cache = self.get_io_metadata().get_shard_cache();
+        let is_full =
+            link_number < cache.get_num_full(self.get_first_shard_addr());
+        if is_full || shard.is_element_written(idx) {
+            Some(shard.get_element(idx))
+        } else {
+            None
+        }
+    }
+
+    /// Returns every element in the list, in order.
+    fn to_vec(&self) -> Vec<T> {
+        if !self.is_initialized() {
+            return Vec::new();
+        }
+
+        let mut elems = Vec::new();
+        let mut shard = self.get_first_shard();
+        loop {
+            let num_written = self.get_num_written_in_shard(&shard);
+            elems.extend((0..num_written).map(|i| shard.get_element(i)));
+
+            if num_written < shard.get_capacity() {
+                return elems;
+            }
+            match self.get_next_shard(&shard) {
+                Some(next_shard) => shard = next_shard,
+                None => return elems,
+            }
+        }
+    }
+
+    /// Checks that the distributed list is well formed. Every shard except
+    /// the last must be full, the written elements in each shard must be
+    /// contiguous, and the chain of shards must be finite and stay inside the
+    /// file.
+    fn verify(&self) -> io::Result<()> {
+        if !self.is_initialized() {
+            return Ok(());
+        }
+
+        let file_len = self.get_backend().len()?;
+        let mut shard = self.get_first_shard();
+        loop {
+            // The written booleans must all come before the unwritten ones.
+            let num_written = shard.get_num_written();
+            if let Some(n) = (num_written..shard.get_capacity())
+                .find(|n| shard.is_element_written(*n))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "List shard {} at {} has element {} written after a \
+                         gap at element {}.",
+                        shard.get_link_number(),
+                        shard.get_addr().get_loc(),
+                        n,
+                        num_written
+                    ),
+                ));
+            }
 
-    /// Adds a list of elements to the list.
-    fn add_all(&self, elems: Vec<T>);
+            // Read the next pointer from the file, rather than trusting the
+            // shard cache, as the whole point is to check what's on disk.
+            let next_shard = match shard.get_next() {
+                LinkPtr::Null(_) => return Ok(()),
+                LinkPtr::Addr(addr) => DistListShard::new(
+                    shard.get_link_number() + 1,
+                    addr,
+                    self.get_io_metadata(),
+                    self.get_backend(),
+                    true,
+                ),
+            };
 
-    /// Updates the element at the given index.
-    fn update(&self, index: i32, elem: T);
+            // A shard can only have a next shard once it's full.
+            if num_written < shard.get_capacity() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "List shard {} at {} isn't full, but has a next shard.",
+                        shard.get_link_number(),
+                        shard.get_addr().get_loc()
+                    ),
+                ));
+            }
 
-    /// Gets the element at the given index.
-    fn get(&self, index: i32) -> T;
+            // The next shard must be inside the file, and the chain can't go
+            // on forever.
+            let next_loc = next_shard.get_addr().get_loc();
+            if next_loc >= file_len
+                || next_shard.get_link_number() > MAX_LINK_NUMBER
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "List shard {} points to an invalid next shard at {}.",
+                        shard.get_link_number(),
+                        next_loc
+                    ),
+                ));
+            }
+            shard = next_shard;
+        }
+    }
 }
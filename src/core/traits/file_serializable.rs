@@ -1,17 +1,22 @@
-use std::{fs::File, os::unix::fs::FileExt};
+use std::io;
 
 use crate::core::{
     enums::FileFormat,
     structs::{Addr, IoMetadata},
 };
 
-use super::FixedSizeOnDisk;
+use super::{FixedSizeOnDisk, StorageBackend};
 
 /// The FileSerializable trait is used to define objects that can be written to
 /// and read from files. This trait is designed to be used with objects whose
 /// state is written to disk, and read from disk, in one go. This makes it
 /// useful for something like an individual key in a dictionary, but not for
 /// the dictionary itself (where writes will be on a per-key/value basis).
+///
+/// Objects are written in one of two layouts, depending on the file's format.
+/// The Binary layout is compact and hand written for every type, while the
+/// Text layout (see to_json) is derived from the object's serde
+/// representation and is meant to be read by people.
 pub(crate) trait FileSerializable: FixedSizeOnDisk
 where
     Self: serde::Serialize + serde::de::DeserializeOwned,
@@ -24,37 +29,58 @@ where
     /// to represent the absence of the object.
     fn null() -> Self;
 
-    /// Converts the object to a json string. We provide a default
-    /// implementation as this isn't performance critical and is only present
-    /// for debugging purposes.
+    /// The marker that starts every line of the object in the Text format,
+    /// saying what kind of object the line belongs to.
+    const TEXT_MARKER: &'static str;
+
+    /// Converts the object to its representation in the Text format. We
+    /// provide a default implementation as this isn't performance critical
+    /// and is only present for debugging purposes.
+    ///
+    /// Every field of the object goes on its own line, labelled with the
+    /// field's name and prefixed with TEXT_MARKER. Fields are in alphabetical
+    /// order, like this:
+    ///
+    /// DirEntry addr.loc: 456
+    /// DirEntry kind: "Array"
+    /// DirEntry name.loc: 120
+    ///
+    /// Objects that aren't structs, like numbers, are a single line with no
+    /// label. The last line is padded with spaces, so that every object of a
+    /// type takes up exactly get_json_size_on_disk bytes.
     fn to_json(&self) -> String {
-        let mut json_string = serde_json::to_string(self).unwrap();
-
-        // Pad the json string with spaces so that it is the same length as the
-        // largest possible json string.
-        let max_json_size = Self::get_json_size_on_disk();
-        let json_size = json_string.len() as u64;
-        let padding_size = max_json_size - json_size;
-        for _ in 0..padding_size {
-            json_string.push(' ');
-        }
+        let fields = text_fields_of(&serde_json::to_value(self).unwrap());
+        to_text(Self::TEXT_MARKER, &fields, Self::get_json_size_on_disk())
+    }
 
-        json_string
+    /// Returns the length of the object in the Text format, before padding.
+    /// Implementations of get_json_size_on_disk call this on the largest
+    /// possible object of their type.
+    fn get_text_len(&self) -> u64 {
+        let fields = text_fields_of(&serde_json::to_value(self).unwrap());
+        to_text(Self::TEXT_MARKER, &fields, 0).len() as u64
     }
 
     /// Constructs the object from a byte array.
     fn from_bin(bytes: &[u8]) -> Self;
 
-    /// Constructs the object from a json string.
+    /// Constructs the object from its representation in the Text format. This
+    /// is the inverse of to_json. As with from_bin, text that isn't a valid
+    /// object gives the null object, which readers report as invalid data.
     fn from_json(json: String) -> Self {
-        serde_json::from_str(&json).unwrap()
+        let fields = parse_text(Self::TEXT_MARKER, &json);
+        fields
+            .and_then(|fields| value_from_text_fields(&fields))
+            .and_then(|value| serde_json::from_value(value).map_err(invalid))
+            .unwrap_or_else(|_| Self::null())
     }
 
-    /// Writes the object to the file at the given location.
-    fn write(&self, addr: Addr, file: &File, io_metadata: &IoMetadata) {
+    /// Converts the object to the bytes that represent it on disk, according
+    /// to the file format in the given IoMetadata.
+    fn to_bytes(&self, io_metadata: &IoMetadata) -> Vec<u8> {
         // Depending on whether we're in binary or text mode, we'll write the
         // object differently.
-        let bytes = match io_metadata.get_tsdf_metadata().get_file_format() {
+        match io_metadata.get_tsdf_metadata().get_file_format() {
             FileFormat::Binary => {
                 // Convert the object to bytes.
                 self.to_bin()
@@ -64,42 +90,255 @@ where
                 let json = self.to_json();
                 json.as_bytes().to_vec()
             }
-        };
+        }
+    }
+
+    /// Constructs the object from the bytes that represent it on disk. This is
+    /// the inverse of to_bytes.
+    fn from_bytes(bytes: &[u8], io_metadata: &IoMetadata) -> Self
+    where
+        Self: Sized,
+    {
+        // Depending on whether we're in binary or text mode, we'll read the
+        // object differently.
+        match io_metadata.get_tsdf_metadata().get_file_format() {
+            FileFormat::Binary => {
+                // Convert the bytes to the object.
+                Self::from_bin(bytes)
+            }
+            FileFormat::Text => {
+                // Convert the bytes to a json string.
+                match String::from_utf8(bytes.to_vec()) {
+                    Ok(json) => Self::from_json(json),
+                    Err(_) => Self::null(),
+                }
+            }
+        }
+    }
 
-        file.write_all_at(&bytes, addr.get_loc()).unwrap();
+    /// Writes the object to the file at the given location.
+    fn write(
+        &self,
+        addr: Addr,
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) {
+        let bytes = self.to_bytes(io_metadata);
+        backend.write_all_at(&bytes, addr.get_loc()).unwrap();
     }
 
     /// Removes the object from the file at the given location. Removal is
     /// implemented by writing a null representation of the object to the file.
-    fn remove(addr: Addr, file: &File, io_metadata: &IoMetadata) {
+    // Only used by DistDictShardWriter::remove, which nothing calls yet.
+    #[allow(dead_code)]
+    fn remove(
+        addr: Addr,
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) {
         // To remove an object, we just add a null representation of the object
         // to the file at the given location.
         let null = Self::null();
-        null.write(addr, file, io_metadata);
+        null.write(addr, backend, io_metadata);
     }
 
     /// Reads the object from the file at the given location. Returns None if
     /// the object is null.
-    fn from_addr(addr: Addr, file: &File, io_metadata: &IoMetadata) -> Self
+    fn from_addr(
+        addr: Addr,
+        backend: &dyn StorageBackend,
+        io_metadata: &IoMetadata,
+    ) -> Self
     where
         Self: Sized,
     {
         // Read the bytes from the file at the given location.
         let mut bytes = vec![0; Self::get_size_on_disk(io_metadata) as usize];
-        file.read_at(&mut bytes, addr.get_loc()).unwrap();
+        backend.read_full_at(&mut bytes, addr.get_loc()).unwrap();
 
-        // Depending on whether we're in binary or text mode, we'll read the
-        // object differently.
-        match io_metadata.get_tsdf_metadata().get_file_format() {
-            FileFormat::Binary => {
-                // Convert the bytes to the object.
-                Self::from_bin(bytes.as_slice())
-            }
-            FileFormat::Text => {
-                // Convert the bytes to a json string.
-                let json = String::from_utf8(bytes).unwrap();
-                Self::from_json(json)
+        Self::from_bytes(&bytes, io_metadata)
+    }
+}
+
+/// Lays out an object's fields in the Text format, as described in
+/// FileSerializable::to_json. Fields with an empty label are written without
+/// one. The last line is padded with spaces up to the given size, if it's
+/// shorter.
+pub(crate) fn to_text(
+    marker: &str,
+    fields: &[(String, String)],
+    size: u64,
+) -> String {
+    let mut text = String::new();
+    for (label, value) in fields {
+        text.push_str(marker);
+        text.push(' ');
+        if !label.is_empty() {
+            text.push_str(label);
+            text.push_str(": ");
+        }
+        text.push_str(value);
+        text.push('\n');
+    }
+
+    // Pad the last line before its line break, so that the padding doesn't
+    // leave a run of spaces at the start of the next object's first line.
+    let padding = (size as usize).saturating_sub(text.len());
+    text.pop();
+    text.extend(std::iter::repeat_n(' ', padding));
+    text.push('\n');
+    text
+}
+
+/// Splits an object in the Text format back into its (label, value) fields,
+/// with the markers and padding removed. This is the inverse of to_text.
+/// Returns an InvalidData error if any line doesn't start with the marker.
+pub(crate) fn parse_text(
+    marker: &str,
+    text: &str,
+) -> io::Result<Vec<(String, String)>> {
+    text.lines()
+        .map(|line| {
+            // Drop the marker. Labels never contain ": ", and nor do the
+            // values of unlabelled fields, which are always numbers.
+            let line = line
+                .strip_prefix(marker)
+                .and_then(|rest| rest.strip_prefix(' '))
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "Expected a {} line, found {:?}.",
+                        marker, line
+                    ))
+                })?;
+            Ok(match line.split_once(": ") {
+                Some((label, value)) => {
+                    (label.to_string(), value.trim().to_string())
+                }
+                None => (String::new(), line.trim().to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Makes the error returned when an object in the Text format can't be read.
+fn invalid<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Flattens a serde value into labelled fields. The fields of nested structs
+/// are labelled with their path, like "name.loc". Anything that isn't a
+/// struct, including lists, is a single field written as compact json.
+fn text_fields_of(value: &serde_json::Value) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    push_text_fields(&mut fields, "", value);
+    fields
+}
+
+/// Does the work of text_fields_of, prefixing labels with the given path.
+fn push_text_fields(
+    fields: &mut Vec<(String, String)>,
+    path: &str,
+    value: &serde_json::Value,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{}.{}", path, key),
+                };
+                push_text_fields(fields, &path, value);
             }
         }
+        _ => fields.push((path.to_string(), value.to_string())),
+    }
+}
+
+/// Rebuilds a serde value from labelled fields. This is the inverse of
+/// text_fields_of. Returns an InvalidData error if a value isn't valid json,
+/// or if a label is used both for a value and for a nested struct.
+fn value_from_text_fields(
+    fields: &[(String, String)],
+) -> io::Result<serde_json::Value> {
+    let parse = |value: &str| serde_json::from_str(value).map_err(invalid);
+    if let [(label, value)] = fields {
+        if label.is_empty() {
+            return parse(value);
+        }
+    }
+
+    let mut root = serde_json::Map::new();
+    for (label, value) in fields {
+        let conflict = || invalid(format!("Conflicting label {:?}.", label));
+        let mut keys: Vec<&str> = label.split('.').collect();
+        let last = keys.pop().unwrap_or_default();
+        let mut map = &mut root;
+        for key in keys {
+            map = map
+                .entry(key)
+                .or_insert_with(
+                    || serde_json::Value::Object(Default::default()),
+                )
+                .as_object_mut()
+                .ok_or_else(conflict)?;
+        }
+        if map.insert(last.to_string(), parse(value)?).is_some() {
+            return Err(conflict());
+        }
+    }
+    Ok(serde_json::Value::Object(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        enums::ObjectKind,
+        structs::{Dir, DirEntry, MemoryBackend},
+        test_utils::make_io_metadata,
+    };
+
+    /// Text that isn't a valid object should be reported as invalid data by
+    /// the parsers, and read as the null object, just like garbage in the
+    /// Binary format. Readers then report it as invalid data too.
+    #[test]
+    fn test_corrupt_text() {
+        let err = parse_text("Addr", "Addr 12\nDirEntry 3\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        for fields in [
+            [("a", "{not json"), ("b", "1")],
+            [("a", "1"), ("a.b", "2")],
+            [("a", "1"), ("a", "2")],
+        ] {
+            let fields: Vec<(String, String)> = fields
+                .iter()
+                .map(|(label, value)| (label.to_string(), value.to_string()))
+                .collect();
+            let err = value_from_text_fields(&fields).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let entry = DirEntry::new(ObjectKind::Dir, Addr::new(1), Addr::new(2));
+        let json = entry.to_json();
+        for corrupt in [
+            json.replace("DirEntry", "Dirt"),
+            json.replace("\"Dir\"", "\"Nope\""),
+            json.replace(": ", "? "),
+            "\0".repeat(json.len()),
+        ] {
+            assert_eq!(DirEntry::from_json(corrupt), DirEntry::null());
+        }
+
+        let io_metadata = make_io_metadata(FileFormat::Text);
+        let backend = MemoryBackend::new();
+        Dir::create(Addr::new(0), "/", &io_metadata, &backend).unwrap();
+        backend.write_all_at(b"garbage", 0).unwrap();
+        let err = Dir::open(Addr::new(0), "/", &io_metadata, &backend)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
     }
 }
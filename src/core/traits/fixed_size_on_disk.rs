@@ -16,10 +16,11 @@ pub(crate) trait FixedSizeOnDisk {
     /// Returns the size of the object once serialized to binary, in bytes.
     fn get_bin_size_on_disk() -> u64;
 
-    /// Returns the size of the object once serialized to json, in bytes.
+    /// Returns the size of the object in the Text format (see
+    /// FileSerializable::to_json), in bytes.
     /// # BE CAREFUL
-    /// Please make sure that you consider the maximum possible size of the json
-    /// string when implementing this method. If you aren't sure, bigger is
+    /// Please make sure that you consider the maximum possible size of the
+    /// text when implementing this method. If you aren't sure, bigger is
     /// better.
     ///
     /// Don't worry about performance in implementations. A good example of how
@@ -1,8 +1,117 @@
-use crate::core::structs::DistDict;
+use std::io;
 
-/// The high level HasMetadataTags trait. This is implemented by any object in a tsdf file that can be
-/// associated with metadata, which is both Dir and Array.
-/// The metadata itself is a dictionary mapping string keys to string values.
-pub(crate) trait HasMetadataTags {
-    fn get_metadata(&self) -> DistDict<String, String>;
+use crate::core::structs::{Addr, Blob, DistDict, TagEntry};
+
+use super::{DistDictTrait, Locatable};
+
+/// The high level HasMetadataTags trait. This is implemented by any object in
+/// a tsdf file that can be associated with metadata, which is both Dir and
+/// Array.
+///
+/// The metadata itself maps string names to string values. On disk, it's a
+/// distributed dictionary of TagEntries, keyed by tag name, whose names and
+/// values are stored in Blobs. Changing the value of a tag writes a new value
+/// Blob and leaves the old one behind.
+pub(crate) trait HasMetadataTags: Locatable {
+    /// Returns the address of the dictionary holding the object's tags.
+    fn get_tags_addr(&self) -> Addr;
+
+    /// Returns the dictionary holding the object's tags.
+    fn get_metadata(&self) -> DistDict<'_, '_, String, TagEntry> {
+        DistDict::new(
+            self.get_tags_addr(),
+            self.get_io_metadata(),
+            self.get_backend(),
+            true,
+        )
+    }
+
+    /// Returns the entry for the tag with the given name, along with whether
+    /// the entry's name really is the given name. If it isn't, the entry
+    /// belongs to a different tag whose name hashes to the same value.
+    fn get_tag_entry(
+        &self,
+        name: &str,
+    ) -> io::Result<Option<(TagEntry, bool)>> {
+        let entry = match self.get_metadata().get(&name.to_string()) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let entry_name = Blob::read_string(
+            entry.get_name_addr(),
+            self.get_backend(),
+            self.get_io_metadata(),
+        )?;
+
+        Ok(Some((entry, entry_name == name)))
+    }
+
+    /// Returns the value of the tag with the given name, or None if the object
+    /// has no such tag.
+    fn get_tag(&self, name: &str) -> io::Result<Option<String>> {
+        match self.get_tag_entry(name)? {
+            Some((entry, true)) => Blob::read_string(
+                entry.get_value_addr(),
+                self.get_backend(),
+                self.get_io_metadata(),
+            )
+            .map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Sets the tag with the given name to the given value, adding the tag if
+    /// the object doesn't already have it.
+    fn set_tag(&self, name: &str, value: &str) -> io::Result<()> {
+        let backend = self.get_backend();
+        let io_metadata = self.get_io_metadata();
+
+        // Reuse the name Blob if the tag already exists.
+        let name_addr = match self.get_tag_entry(name)? {
+            Some((entry, true)) => entry.get_name_addr(),
+            Some((_, false)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("Tag name {:?} collides with another tag.", name),
+                ))
+            }
+            None => Blob::append_str(name, backend, io_metadata)?,
+        };
+
+        // Both Blobs must be on disk before the entry that points at them. A
+        // durable write syncs them first, so that a crash can't persist the
+        // entry without them and lose the tag's old value.
+        let value_addr = Blob::append_str(value, backend, io_metadata)?;
+        self.sync_if_durable()?;
+        self.get_metadata()
+            .add(&name.to_string(), &TagEntry::new(name_addr, value_addr));
+        self.sync_if_durable()
+    }
+
+    /// Returns every tag on the object as (name, value) pairs, sorted by name.
+    fn get_tags(&self) -> io::Result<Vec<(String, String)>> {
+        let backend = self.get_backend();
+        let io_metadata = self.get_io_metadata();
+        let mut tags = self
+            .get_metadata()
+            .values()
+            .into_iter()
+            .map(|entry| {
+                let name = Blob::read_string(
+                    entry.get_name_addr(),
+                    backend,
+                    io_metadata,
+                )?;
+                let value = Blob::read_string(
+                    entry.get_value_addr(),
+                    backend,
+                    io_metadata,
+                )?;
+                Ok((name, value))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        tags.sort();
+        Ok(tags)
+    }
 }
@@ -1,15 +1,31 @@
-use std::fs::File;
+use std::io;
 
-use crate::core::structs::{Addr, IoMetadata};
+use crate::core::{
+    enums::Durability,
+    structs::{Addr, IoMetadata},
+};
+
+use super::StorageBackend;
 
 /// A trait for objects that have a location in a file.
 pub(crate) trait Locatable {
     /// Returns a reference to the address of the object in the file.
     fn get_addr(&self) -> &Addr;
 
-    /// Returns a reference to the file that the shard is stored in.
-    fn get_file(&self) -> &File;
+    /// Returns a reference to the storage backend that the object is stored
+    /// in.
+    fn get_backend(&self) -> &dyn StorageBackend;
 
     /// Returns the metadata needed to read/write to the file.
     fn get_io_metadata(&self) -> &IoMetadata;
+
+    /// Syncs the backend to disk if the file was opened with
+    /// Durability::Synced. This is called at the end of every high level
+    /// write.
+    fn sync_if_durable(&self) -> io::Result<()> {
+        match self.get_io_metadata().get_durability() {
+            Durability::Synced => self.get_backend().sync(),
+            Durability::Buffered => Ok(()),
+        }
+    }
 }
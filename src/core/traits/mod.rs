@@ -1,4 +1,9 @@
+pub mod array_element;
+pub mod condition;
+
+pub(crate) mod array_queries;
 pub(crate) mod array_trait;
+pub(crate) mod decimation_levels;
 pub(crate) mod dir_trait;
 pub(crate) mod dist_dict_shard_reader;
 pub(crate) mod dist_dict_shard_writer;
@@ -10,16 +15,22 @@ pub(crate) mod file_serializable;
 pub(crate) mod fixed_size_on_disk;
 pub(crate) mod has_metadata_tags;
 pub(crate) mod has_name;
-pub(crate) mod initializable;
 pub(crate) mod link;
 pub(crate) mod locatable;
+pub(crate) mod resampling;
 pub(crate) mod shard_trait;
+pub(crate) mod storage_backend;
 pub(crate) mod tsdf_file_trait;
 pub(crate) mod tsdf_hashable;
 pub(crate) mod variable_size_on_disk;
 
 // Export the traits.
+pub use self::array_element::ArrayElement;
+pub use self::condition::Condition;
+
+pub(crate) use self::array_queries::ArrayQueries;
 pub(crate) use self::array_trait::ArrayTrait;
+pub(crate) use self::decimation_levels::{Bucket, DecimationLevels};
 pub(crate) use self::dir_trait::DirTrait;
 pub(crate) use self::dist_dict_shard_reader::DistDictShardReader;
 pub(crate) use self::dist_dict_shard_writer::DistDictShardWriter;
@@ -27,14 +38,17 @@ pub(crate) use self::dist_dict_trait::DistDictTrait;
 pub(crate) use self::dist_list_shard_reader::DistListShardReader;
 pub(crate) use self::dist_list_shard_writer::DistListShardWriter;
 pub(crate) use self::dist_list_trait::DistListTrait;
-pub(crate) use self::file_serializable::FileSerializable;
+pub(crate) use self::file_serializable::{
+    parse_text, to_text, FileSerializable,
+};
 pub(crate) use self::fixed_size_on_disk::FixedSizeOnDisk;
 pub(crate) use self::has_metadata_tags::HasMetadataTags;
 pub(crate) use self::has_name::HasName;
-pub(crate) use self::initializable::Initializable;
 pub(crate) use self::link::Link;
 pub(crate) use self::locatable::Locatable;
+pub(crate) use self::resampling::Resampling;
 pub(crate) use self::shard_trait::ShardTrait;
+pub(crate) use self::storage_backend::StorageBackend;
 pub(crate) use self::tsdf_file_trait::TsdfFileTrait;
 pub(crate) use self::tsdf_hashable::TsdfHashable;
 pub(crate) use self::variable_size_on_disk::VariableSizeOnDisk;
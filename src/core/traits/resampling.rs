@@ -0,0 +1,628 @@
+use std::{collections::VecDeque, io};
+
+use crate::core::{
+    enums::{with_element_type, Aggregation, Alignment},
+    structs::Array,
+};
+
+use super::{ArrayElement, ArrayTrait};
+
+/// Aggregations over the frames of an array with a time axis: resampling into
+/// fixed buckets, rolling windows, and aligning the array onto another time
+/// base. The frames are read a chunk at a time, so only the output and, for
+/// rolling windows, one window of frames are ever held in memory. Values are
+/// worked with as f64s, and each element of a frame is aggregated on its own.
+pub(crate) trait Resampling: ArrayTrait {
+    /// Calls f with the index, time and values of each of the frames
+    /// start..end in turn, reading them a chunk at a time.
+    fn for_each_frame<F>(
+        &self,
+        start: usize,
+        end: usize,
+        mut f: F,
+    ) -> io::Result<()>
+    where
+        F: FnMut(usize, i64, &[f64]) -> io::Result<()>,
+    {
+        self.check_has_time_axis()?;
+        self.check_bounds(start, end)?;
+        let batch = self.get_header().get_frames_per_chunk() as usize;
+        let frame_len = self.get_header().get_frame_len() as usize;
+        let mut values = Vec::with_capacity(batch * frame_len);
+        let mut batch_start = start;
+        while batch_start < end {
+            // Keep to chunk boundaries, so that each batch is a single read.
+            let batch_end = ((batch_start / batch + 1) * batch).min(end);
+            let timestamps = with_element_type!(self.get_data_type(), T => {
+                let (timestamps, frames) =
+                    self.read_frames::<T>(batch_start, batch_end)?;
+                values.clear();
+                values.extend(frames.into_iter().map(|v| v.to_f64()));
+                timestamps
+            });
+            for (i, timestamp) in timestamps.iter().enumerate() {
+                let frame = &values[i * frame_len..(i + 1) * frame_len];
+                f(batch_start + i, timestamp.get_nanos(), frame)?;
+            }
+            batch_start = batch_end;
+        }
+        Ok(())
+    }
+
+    /// Aggregates the frames taken from t_start (inclusive) to t_end
+    /// (exclusive) into buckets of the given width, starting at t_start.
+    /// Returns the start time of each bucket along with its aggregated frame,
+    /// leaving out buckets without any frames.
+    fn resample(
+        &self,
+        aggregation: Aggregation,
+        t_start: i64,
+        t_end: i64,
+        width: i64,
+    ) -> io::Result<Vec<(i64, Vec<f64>)>> {
+        check_positive(width, "bucket width")?;
+        let (start, end) = self.find_time_range(t_start, t_end)?;
+        let frame_len = self.get_header().get_frame_len() as usize;
+        let mut buckets = Vec::new();
+        let mut bucket: Option<(i64, Accumulator)> = None;
+        self.for_each_frame(start, end, |_, nanos, frame| {
+            let offset = (nanos as i128 - t_start as i128) / width as i128;
+            let bucket_start =
+                (t_start as i128 + offset * width as i128) as i64;
+            match &mut bucket {
+                Some((current, acc)) if *current == bucket_start => {
+                    acc.push(frame)
+                }
+                _ => {
+                    if let Some((current, acc)) = bucket.take() {
+                        buckets.push((current, acc.finish(aggregation)));
+                    }
+                    let mut acc = Accumulator::new(frame_len);
+                    acc.push(frame);
+                    bucket = Some((bucket_start, acc));
+                }
+            }
+            Ok(())
+        })?;
+        if let Some((current, acc)) = bucket {
+            buckets.push((current, acc.finish(aggregation)));
+        }
+        Ok(buckets)
+    }
+
+    /// Aggregates a trailing window over each of the frames taken from t_start
+    /// (inclusive) to t_end (exclusive). The window of a frame taken at t
+    /// holds the frames taken after t - window, up to and including t, even
+    /// if they were taken before t_start. Returns the time of each frame
+    /// along with the aggregated frame.
+    fn rolling(
+        &self,
+        aggregation: Aggregation,
+        window: i64,
+        t_start: i64,
+        t_end: i64,
+    ) -> io::Result<Vec<(i64, Vec<f64>)>> {
+        check_positive(window, "window")?;
+        let (start, end) = self.find_time_range(t_start, t_end)?;
+        let first = match start < end {
+            true => {
+                let first_nanos = self.read_timestamps(start, start + 1)?[0];
+                let window_start =
+                    first_nanos.get_nanos().saturating_sub(window);
+                self.find_time(window_start.saturating_add(1))?
+            }
+            false => start,
+        };
+
+        let frame_len = self.get_header().get_frame_len() as usize;
+        let mut frames = RollingWindow::new(aggregation, frame_len);
+        let mut rolled = Vec::new();
+        self.for_each_frame(first, end, |index, nanos, frame| {
+            frames.pop_until(nanos.saturating_sub(window));
+            frames.push(nanos, frame);
+            if index >= start {
+                rolled.push((nanos, frames.get()));
+            }
+            Ok(())
+        })?;
+        Ok(rolled)
+    }
+
+    /// Works out the array's frame at each of the given times, which must not
+    /// go backwards. Times without a value, like those before the first frame,
+    /// get None. Aligning several arrays onto the timestamps of one of them
+    /// gives an as-of join (or an interpolated one).
+    fn align(
+        &self,
+        times: &[i64],
+        alignment: Alignment,
+    ) -> io::Result<Vec<Option<Vec<f64>>>> {
+        if times.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Times must not go backwards.",
+            ));
+        }
+        let (Some(&t_first), Some(&t_last)) = (times.first(), times.last())
+        else {
+            return Ok(Vec::new());
+        };
+
+        // Read from the last frame at or before the first time, up to and
+        // including the first frame after the last time.
+        let start =
+            self.find_time(t_first.saturating_add(1))?.saturating_sub(1);
+        let end =
+            (self.find_time(t_last.saturating_add(1))? + 1).min(self.len());
+
+        let mut aligned = Vec::with_capacity(times.len());
+        let mut prev: Option<(i64, Vec<f64>)> = None;
+        let mut remaining = times.iter().peekable();
+        self.for_each_frame(start, end, |_, nanos, frame| {
+            while let Some(&t) = remaining.next_if(|t| **t < nanos) {
+                let next = Some((nanos, frame));
+                aligned.push(interpolate(alignment, &prev, next, t));
+            }
+            prev = Some((nanos, frame.to_vec()));
+            Ok(())
+        })?;
+        for &t in remaining {
+            aligned.push(interpolate(alignment, &prev, None, t));
+        }
+        Ok(aligned)
+    }
+}
+
+impl Resampling for Array<'_, '_> {}
+
+/// Returns an error unless the given length of time is positive.
+fn check_positive(nanos: i64, what: &str) -> io::Result<()> {
+    if nanos <= 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("The {} must be positive, but got {}.", what, nanos),
+        ));
+    }
+    Ok(())
+}
+
+/// Works out the value at time t from the frames either side of it: prev,
+/// taken at or before t, and next, taken after t.
+fn interpolate(
+    alignment: Alignment,
+    prev: &Option<(i64, Vec<f64>)>,
+    next: Option<(i64, &[f64])>,
+    t: i64,
+) -> Option<Vec<f64>> {
+    let (t0, v0) = prev.as_ref()?;
+    if alignment == Alignment::AsOf || *t0 == t {
+        return Some(v0.clone());
+    }
+    let (t1, v1) = next?;
+    let fraction = (t - t0) as f64 / (t1 - t0) as f64;
+    Some(
+        v0.iter()
+            .zip(v1)
+            .map(|(a, b)| a + (b - a) * fraction)
+            .collect(),
+    )
+}
+
+/// The running state of an aggregation over any number of frames, kept for
+/// each element separately.
+struct Accumulator {
+    /// The number of frames pushed so far.
+    num_frames: usize,
+
+    /// The first frame pushed.
+    first: Vec<f64>,
+
+    /// The last frame pushed.
+    last: Vec<f64>,
+
+    /// The smallest value of each element, ignoring NaNs.
+    min: Vec<f64>,
+
+    /// The largest value of each element, ignoring NaNs.
+    max: Vec<f64>,
+
+    /// The sum of each element, ignoring NaNs.
+    sum: Vec<f64>,
+
+    /// The number of values of each element that weren't NaN.
+    count: Vec<u64>,
+}
+
+impl Accumulator {
+    /// Creates an accumulator for frames with the given number of elements.
+    fn new(frame_len: usize) -> Self {
+        Self {
+            num_frames: 0,
+            first: Vec::new(),
+            last: Vec::new(),
+            min: vec![f64::NAN; frame_len],
+            max: vec![f64::NAN; frame_len],
+            sum: vec![0.0; frame_len],
+            count: vec![0; frame_len],
+        }
+    }
+
+    /// Adds a frame to the aggregation.
+    fn push(&mut self, frame: &[f64]) {
+        if self.num_frames == 0 {
+            self.first = frame.to_vec();
+        }
+        self.num_frames += 1;
+        self.last.clear();
+        self.last.extend_from_slice(frame);
+        for (i, &value) in frame.iter().enumerate() {
+            if value.is_nan() {
+                continue;
+            }
+            // f64::min and f64::max ignore NaN, so the first value replaces
+            // the NaN that we start with.
+            self.min[i] = self.min[i].min(value);
+            self.max[i] = self.max[i].max(value);
+            self.sum[i] += value;
+            self.count[i] += 1;
+        }
+    }
+
+    /// Returns the aggregated frame.
+    fn finish(self, aggregation: Aggregation) -> Vec<f64> {
+        match aggregation {
+            Aggregation::Mean => self
+                .sum
+                .iter()
+                .zip(&self.count)
+                .map(|(sum, count)| match count {
+                    0 => f64::NAN,
+                    _ => sum / *count as f64,
+                })
+                .collect(),
+            Aggregation::Min => self.min,
+            Aggregation::Max => self.max,
+            Aggregation::Sum => self.sum,
+            Aggregation::Count => {
+                self.count.iter().map(|count| *count as f64).collect()
+            }
+            Aggregation::First => self.first,
+            Aggregation::Last => self.last,
+        }
+    }
+}
+
+/// The running state of an aggregation over a window of frames that moves
+/// forward in time, kept for each element separately. Frames join the window
+/// at the back and leave it from the front, and both cost O(1) amortised per
+/// element, however many frames the window holds.
+struct RollingWindow {
+    /// The aggregation being worked out.
+    aggregation: Aggregation,
+
+    /// The number of elements in each frame.
+    frame_len: usize,
+
+    /// The time of each frame in the window, oldest first.
+    times: VecDeque<i64>,
+
+    /// The values of each frame in the window, oldest first, one frame after
+    /// another. These are needed to take a frame back out of the sums.
+    values: VecDeque<f64>,
+
+    /// The number of frames that have left the window so far. Frames are
+    /// numbered in the order that they were pushed, so this is the number of
+    /// the oldest frame in the window.
+    num_popped: usize,
+
+    /// The sum of the finite values of each element.
+    sum: Vec<f64>,
+
+    /// The number of values of each element that weren't NaN.
+    count: Vec<u64>,
+
+    /// The number of values of each element that were infinite, positive
+    /// then negative. These are kept out of the sums, as taking an infinity
+    /// back out of a sum would leave a NaN.
+    num_infinite: Vec<[u64; 2]>,
+
+    /// For Min and Max, the number and value of the frames that could still
+    /// be the extreme of each element, ignoring NaNs. The values get worse
+    /// from front to back, and the front is the extreme of the window.
+    extremes: Vec<VecDeque<(usize, f64)>>,
+}
+
+impl RollingWindow {
+    /// Creates an empty window for frames with the given number of elements.
+    fn new(aggregation: Aggregation, frame_len: usize) -> Self {
+        Self {
+            aggregation,
+            frame_len,
+            times: VecDeque::new(),
+            values: VecDeque::new(),
+            num_popped: 0,
+            sum: vec![0.0; frame_len],
+            count: vec![0; frame_len],
+            num_infinite: vec![[0; 2]; frame_len],
+            extremes: vec![VecDeque::new(); frame_len],
+        }
+    }
+
+    /// Adds a frame taken at the given time to the back of the window.
+    fn push(&mut self, nanos: i64, frame: &[f64]) {
+        let number = self.num_popped + self.times.len();
+        self.times.push_back(nanos);
+        self.values.extend(frame);
+        for (i, &value) in frame.iter().enumerate() {
+            if value.is_nan() {
+                continue;
+            }
+            self.count[i] += 1;
+            match value.is_infinite() {
+                true => self.num_infinite[i][(value < 0.0) as usize] += 1,
+                false => self.sum[i] += value,
+            }
+
+            // Anything at the back that's no better than the new value can
+            // never be the extreme again, as it leaves the window first.
+            let extremes = &mut self.extremes[i];
+            match self.aggregation {
+                Aggregation::Min => {
+                    while extremes.back().is_some_and(|(_, v)| *v >= value) {
+                        extremes.pop_back();
+                    }
+                }
+                Aggregation::Max => {
+                    while extremes.back().is_some_and(|(_, v)| *v <= value) {
+                        extremes.pop_back();
+                    }
+                }
+                _ => continue,
+            }
+            extremes.push_back((number, value));
+        }
+    }
+
+    /// Takes the frames taken at or before the given time out of the front
+    /// of the window.
+    fn pop_until(&mut self, nanos: i64) {
+        while self.times.front().is_some_and(|t| *t <= nanos) {
+            self.times.pop_front();
+            for i in 0..self.frame_len {
+                let value = self.values.pop_front().unwrap();
+                if value.is_nan() {
+                    continue;
+                }
+                self.count[i] -= 1;
+                match value.is_infinite() {
+                    true => self.num_infinite[i][(value < 0.0) as usize] -= 1,
+                    false => self.sum[i] -= value,
+                }
+
+                // Start again from zero once the last finite value has left,
+                // so that rounding errors don't build up.
+                let [positive, negative] = self.num_infinite[i];
+                if self.count[i] == positive + negative {
+                    self.sum[i] = 0.0;
+                }
+            }
+            for extremes in &mut self.extremes {
+                if extremes.front().is_some_and(|(n, _)| *n == self.num_popped)
+                {
+                    extremes.pop_front();
+                }
+            }
+            self.num_popped += 1;
+        }
+    }
+
+    /// Returns the sum of the values of the given element, as adding them up
+    /// in order would have.
+    fn get_sum(&self, i: usize) -> f64 {
+        match self.num_infinite[i] {
+            [0, 0] => self.sum[i],
+            [_, 0] => f64::INFINITY,
+            [0, _] => f64::NEG_INFINITY,
+            _ => f64::NAN,
+        }
+    }
+
+    /// Returns the aggregated frame. The window must not be empty.
+    fn get(&self) -> Vec<f64> {
+        let elements = 0..self.frame_len;
+        match self.aggregation {
+            Aggregation::Mean => elements
+                .map(|i| match self.count[i] {
+                    0 => f64::NAN,
+                    count => self.get_sum(i) / count as f64,
+                })
+                .collect(),
+            Aggregation::Min | Aggregation::Max => self
+                .extremes
+                .iter()
+                .map(|extremes| extremes.front().map_or(f64::NAN, |(_, v)| *v))
+                .collect(),
+            Aggregation::Sum => elements.map(|i| self.get_sum(i)).collect(),
+            Aggregation::Count => {
+                self.count.iter().map(|count| *count as f64).collect()
+            }
+            Aggregation::First => {
+                self.values.range(..self.frame_len).copied().collect()
+            }
+            Aggregation::Last => {
+                let start = self.values.len() - self.frame_len;
+                self.values.range(start..).copied().collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        enums::{ArrayDataType, FileFormat},
+        structs::{Addr, ArrayOptions, MemoryBackend, Timestamp},
+        test_utils::make_io_metadata,
+    };
+
+    /// Resampling and rolling windows should aggregate each element of the
+    /// frames on its own, across chunk boundaries, leaving NaNs out.
+    #[test]
+    fn test_resample_and_rolling() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Float32)
+                .frame_shape(&[2])
+                .frames_per_chunk(3)
+                .timestamped(),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        let nanos = [0, 4, 10, 12, 15, 31, 33];
+        let timestamps: Vec<Timestamp> = nanos
+            .iter()
+            .map(|n| Timestamp::new(*n, Default::default()))
+            .collect();
+        let frames: Vec<f32> = [
+            [1.0, 0.0],
+            [3.0, 0.0],
+            [5.0, f32::NAN],
+            [6.0, 1.0],
+            [7.0, 2.0],
+            [8.0, 3.0],
+            [9.0, 4.0],
+        ]
+        .concat();
+        array.append_frames_at(&timestamps, &frames).unwrap();
+
+        let means = array.resample(Aggregation::Mean, 0, 40, 10).unwrap();
+        assert_eq!(means.len(), 3);
+        assert_eq!(means[0], (0, vec![2.0, 0.0]));
+        assert_eq!(means[1], (10, vec![6.0, 1.5]));
+        assert_eq!(means[2], (30, vec![8.5, 3.5]));
+        let lasts = array.resample(Aggregation::Last, 4, 16, 5).unwrap();
+        let starts: Vec<i64> = lasts.iter().map(|(t, _)| *t).collect();
+        assert_eq!(starts, [4, 9, 14]);
+        assert_eq!(lasts[1].1, [6.0, 1.0]);
+        assert!(array.resample(Aggregation::Min, 0, 40, 0).is_err());
+
+        // The first window reaches back before t_start.
+        let sums = array.rolling(Aggregation::Sum, 5, 10, 32).unwrap();
+        let sums: Vec<(i64, f64)> =
+            sums.iter().map(|(t, frame)| (*t, frame[0])).collect();
+        assert_eq!(sums, [(10, 5.0), (12, 11.0), (15, 13.0), (31, 8.0)]);
+        let counts = array.rolling(Aggregation::Count, 20, 15, 16).unwrap();
+        assert_eq!(counts, [(15, vec![5.0, 4.0])]);
+    }
+
+    /// A rolling window should give exactly what aggregating the frames in
+    /// it from scratch gives, including for NaNs and infinities.
+    #[test]
+    fn test_rolling_window_matches_accumulator() {
+        let aggregations = [
+            Aggregation::Mean,
+            Aggregation::Min,
+            Aggregation::Max,
+            Aggregation::Sum,
+            Aggregation::Count,
+            Aggregation::First,
+            Aggregation::Last,
+        ];
+        let special = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+        let frames: Vec<Vec<f64>> = (0..200_u64)
+            .map(|n| {
+                let x = n * 7919 % 31;
+                let special = special[(n % 3) as usize];
+                vec![x as f64 - 15.0, if x < 4 { special } else { x as f64 }]
+            })
+            .collect();
+        let window = 9;
+        for aggregation in aggregations {
+            let mut frames_in_window = RollingWindow::new(aggregation, 2);
+            for (t, frame) in frames.iter().enumerate() {
+                let t = t as i64;
+                frames_in_window.pop_until(t - window);
+                frames_in_window.push(t, frame);
+
+                let mut acc = Accumulator::new(2);
+                let first = (t - window + 1).max(0) as usize;
+                frames[first..=t as usize].iter().for_each(|f| acc.push(f));
+                let expected = acc.finish(aggregation);
+                let got = frames_in_window.get();
+                for (e, g) in expected.iter().zip(&got) {
+                    assert!(
+                        e == g || (e.is_nan() && g.is_nan()),
+                        "{:?} at {}: {:?} != {:?}",
+                        aggregation,
+                        t,
+                        got,
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    /// Aligning should find the frame at or before each time, or interpolate
+    /// between the frames either side of it.
+    #[test]
+    fn test_align() {
+        let io_metadata = make_io_metadata(FileFormat::Binary);
+        let backend = MemoryBackend::new();
+        let t0 = Timestamp::new(100, Default::default());
+        let array = Array::create(
+            Addr::new(0),
+            ArrayOptions::new()
+                .data_type(ArrayDataType::Int32)
+                .frames_per_chunk(2)
+                .regular(t0, 10),
+            &io_metadata,
+            &backend,
+        )
+        .unwrap();
+        array.append_frames(&[0_i32, 10, 20, 30, 40]).unwrap();
+
+        let times = [95, 100, 105, 105, 139, 140, 141];
+        let as_of = array.align(&times, Alignment::AsOf).unwrap();
+        let as_of: Vec<Option<f64>> = as_of
+            .iter()
+            .map(|frame| frame.as_ref().map(|f| f[0]))
+            .collect();
+        assert_eq!(
+            as_of,
+            [
+                None,
+                Some(0.0),
+                Some(0.0),
+                Some(0.0),
+                Some(30.0),
+                Some(40.0),
+                Some(40.0)
+            ]
+        );
+        let linear = array.align(&times, Alignment::Linear).unwrap();
+        let linear: Vec<Option<f64>> = linear
+            .iter()
+            .map(|frame| frame.as_ref().map(|f| f[0]))
+            .collect();
+        assert_eq!(
+            linear,
+            [
+                None,
+                Some(0.0),
+                Some(5.0),
+                Some(5.0),
+                Some(39.0),
+                Some(40.0),
+                None
+            ]
+        );
+        assert!(array.align(&[], Alignment::AsOf).unwrap().is_empty());
+        assert!(array.align(&[120, 110], Alignment::AsOf).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::structs::Addr;
+use crate::core::{enums::LinkPtr, structs::Addr};
 
 use super::{FileSerializable, Link, VariableSizeOnDisk};
 
@@ -13,6 +13,8 @@ pub(crate) trait ShardTrait<TVal: FileSerializable>:
     fn get_capacity(&self) -> usize;
 
     /// Returns the number of things currently stored in the shard.
+    // Lists count with get_num_written, and dicts never need a count.
+    #[allow(dead_code)]
     fn get_count(&self) -> usize;
 
     /// Gets the location of the is_next_written boolean in the shard.
@@ -33,4 +35,33 @@ pub(crate) trait ShardTrait<TVal: FileSerializable>:
 
         Addr::new(addr)
     }
+
+    /// Gets the boolean that says whether the next pointer has been written.
+    /// This works in the same way as the per-slot written booleans, but for
+    /// the next pointer.
+    fn is_next_written(&self) -> bool {
+        // Read the boolean from the file.
+        let loc = self.get_is_next_written_addr().get_loc();
+        let mut bytes = vec![0; 1];
+        self.get_backend().read_full_at(&mut bytes, loc).unwrap();
+
+        // Convert the bytes to a boolean.
+        bytes[0] == 1
+    }
+
+    /// Gets the next pointer in the shard.
+    fn get_next_ptr(&self) -> LinkPtr {
+        // If the next pointer has not been written, we return a null pointer.
+        if !self.is_next_written() {
+            return LinkPtr::Null(Addr::null());
+        }
+
+        // Since LinkPtr is guaranteed to be FileSerializable, we can use the
+        // from_addr method to read the next pointer from the file.
+        LinkPtr::from_addr(
+            self.get_next_addr(),
+            self.get_backend(),
+            self.get_io_metadata(),
+        )
+    }
 }
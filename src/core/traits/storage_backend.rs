@@ -0,0 +1,130 @@
+use std::io;
+
+/// The StorageBackend trait abstracts over the place that a tsdf file's bytes
+/// actually live. Every on-disk structure in the library (shards, dictionaries,
+/// lists, etc.) reads and writes through this trait, rather than talking to a
+/// std::fs::File directly. This means that all of the DistDict/shard logic can
+/// be reused with local files, in-memory buffers (great for tests and
+/// bindings), or any future remote backend.
+///
+/// All methods take `&self`, mirroring the positional read/write calls in
+/// std::os::unix::fs::FileExt. Implementations must therefore be safe to share
+/// between threads.
+pub(crate) trait StorageBackend: Send + Sync {
+    /// Reads bytes starting at the given offset into `buf`. Returns the number
+    /// of bytes read, which may be fewer than `buf.len()` (for example, when
+    /// reading past the end of the backend).
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+
+    /// Writes bytes from `buf` starting at the given offset. Returns the number
+    /// of bytes written, which may be fewer than `buf.len()`.
+    fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize>;
+
+    /// Writes the buffers one after another, starting at the given offset, as
+    /// if they were a single buffer. Returns the number of bytes written,
+    /// which may be fewer than the total length of the buffers. The default
+    /// implementation copies the buffers into one and calls write_at, but
+    /// backends that can do better (e.g. with pwritev) should override it.
+    fn write_vectored_at(
+        &self,
+        bufs: &[&[u8]],
+        offset: u64,
+    ) -> io::Result<usize> {
+        self.write_at(&bufs.concat(), offset)
+    }
+
+    /// Returns the total number of bytes currently stored in the backend.
+    fn len(&self) -> io::Result<u64>;
+
+    /// Flushes all written data to the underlying storage medium.
+    fn sync(&self) -> io::Result<()>;
+
+    /// Attempts to take an exclusive lock on the backend. This never blocks;
+    /// if the lock is already held, an error of kind
+    /// io::ErrorKind::WouldBlock is returned.
+    fn lock(&self) -> io::Result<()>;
+
+    /// Releases a lock taken with `lock`.
+    fn unlock(&self) -> io::Result<()>;
+
+    /// Reads bytes starting at the given offset until either `buf` is full or
+    /// the end of the backend is reached. Returns the number of bytes read.
+    /// Any part of `buf` that couldn't be filled is left untouched.
+    fn read_full_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let mut num_read = 0;
+        while num_read < buf.len() {
+            match self.read_at(&mut buf[num_read..], offset + num_read as u64) {
+                // A read of zero bytes means that we've hit the end.
+                Ok(0) => break,
+                Ok(n) => num_read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(num_read)
+    }
+
+    /// Writes the whole of `buf` starting at the given offset, retrying until
+    /// every byte has been written.
+    fn write_all_at(&self, buf: &[u8], offset: u64) -> io::Result<()> {
+        let mut num_written = 0;
+        while num_written < buf.len() {
+            match self
+                .write_at(&buf[num_written..], offset + num_written as u64)
+            {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "Failed to write whole buffer.",
+                    ))
+                }
+                Ok(n) => num_written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the whole of every buffer, one after another, starting at the
+    /// given offset. This retries until every byte has been written.
+    fn write_all_vectored_at(
+        &self,
+        bufs: &[&[u8]],
+        offset: u64,
+    ) -> io::Result<()> {
+        let total_len: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let mut num_written = 0;
+        while num_written < total_len {
+            // Skip over everything that has already been written.
+            let mut to_skip = num_written;
+            let mut remaining = Vec::with_capacity(bufs.len());
+            for buf in bufs {
+                if to_skip >= buf.len() {
+                    to_skip -= buf.len();
+                    continue;
+                }
+                remaining.push(&buf[to_skip..]);
+                to_skip = 0;
+            }
+
+            match self
+                .write_vectored_at(&remaining, offset + num_written as u64)
+            {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "Failed to write whole buffer.",
+                    ))
+                }
+                Ok(n) => num_written += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,13 +1,15 @@
-use crate::core::structs::tsdf_hash::TsdfHash;
+use crate::core::structs::{tsdf_hash::TsdfHash, IoMetadata};
 
 /// A trait for objects that can be hashed into a TsdfHash.
 pub(crate) trait TsdfHashable {
-    fn hash(&self) -> TsdfHash;
+    /// Hashes the object with the hash algorithm used by the file.
+    fn hash(&self, io_metadata: &IoMetadata) -> TsdfHash;
 }
 
 // Implement this for all types that implement the std library Hash trait.
 impl<T: std::hash::Hash> TsdfHashable for T {
-    fn hash(&self) -> TsdfHash {
-        TsdfHash::new(&self)
+    fn hash(&self, io_metadata: &IoMetadata) -> TsdfHash {
+        let algorithm = io_metadata.get_tsdf_metadata().get_hash_algorithm();
+        TsdfHash::new(&self, algorithm)
     }
 }
@@ -2,6 +2,9 @@ use crate::core::{enums::FileFormat, structs::IoMetadata};
 
 /// This trait is the same as FixedSizeOnDisk, but every method is an instance
 /// method, rather than a static method.
+// Shards are written slot by slot from their capacity, so nothing asks for
+// their total size yet.
+#[allow(dead_code)]
 pub(crate) trait VariableSizeOnDisk {
     /// Get's the size of the object on disk, according to the current
     /// IoMetadata.
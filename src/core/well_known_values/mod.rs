@@ -1 +1,2 @@
 pub(crate) mod metadata_strings;
+pub(crate) mod shard_limits;
@@ -0,0 +1,17 @@
+/// The number of slots in the first shard of a distributed list or dictionary,
+/// unless the file says otherwise.
+pub(crate) const DEFAULT_SHARD_CAPACITY: u64 = 8;
+
+/// The largest number of slots that the first shard of a distributed list or
+/// dictionary can have. Along with MAX_LINK_NUMBER, this keeps the capacity of
+/// every shard well within a u64.
+pub(crate) const MAX_SHARD_CAPACITY: u64 = 4096;
+
+/// The largest link number that a well formed shard can have. Shard
+/// capacities double with every link, so a chain longer than this would need
+/// more slots than could ever be addressed.
+pub(crate) const MAX_LINK_NUMBER: i32 = 48;
+
+/// The number of shard chains whose layout a reader remembers by default. See
+/// ShardCache for what is cached.
+pub(crate) const DEFAULT_SHARD_CACHE_CAPACITY: usize = 1024;
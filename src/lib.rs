@@ -0,0 +1,59 @@
+//! tsdf is a file format for time series data, in which a single writer can
+//! keep adding to a file while any number of readers read it.
+//!
+//! A file holds a tree of [Dir]s, which hold [Array]s and other Dirs. Both
+//! Dirs and Arrays can carry string metadata tags. Arrays are appended to one
+//! frame at a time, where every frame has the same shape and [ArrayDataType].
+//!
+//! ```no_run
+//! use tsdf::{ArrayDataType, TsdfFile};
+//!
+//! # fn main() -> tsdf::Result<()> {
+//! let file = TsdfFile::new_writer("run.tsdf", None, None)?;
+//! let root = file.get_root_dir()?;
+//! let detector = root.create_dir("detector")?;
+//! detector.set_tag("units", "counts")?;
+//!
+//! let counts =
+//!     detector.create_array("counts", ArrayDataType::UInt16, &[2, 2], 64)?;
+//! counts.append(&[1_u16, 2, 3, 4])?;
+//! assert_eq!(counts.get_frame::<u16>(0)?, vec![1, 2, 3, 4]);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Errors
+//! Every fallible call returns a [std::io::Error]. Its [ErrorKind] says what
+//! went wrong: NotFound for missing objects and tags, AlreadyExists for names
+//! that are taken, InvalidInput for bad arguments (including reading an
+//! array with the wrong type), and InvalidData for corrupt files.
+
+// Declare the project structure. The core is private; everything that's part
+// of the public API is re-exported below.
+mod core;
+
+// Language bindings.
+#[cfg(feature = "capi")]
+mod capi;
+#[cfg(feature = "python")]
+mod python;
+
+// The public API.
+pub use crate::core::enums::{
+    Aggregation, Alignment, ArrayDataType, ClockSource, Durability, ErrorBound,
+    FileFormat, Filter, HashAlgorithm, HighLevelObject, IoMode, Predicate,
+    ReadMode, TimeAxis, WriteMode,
+};
+pub use crate::core::structs::{
+    Array, ArrayOptions, ChunkStats, CsvImportOptions, Dir, OpenOptions,
+    Timestamp, TsdfFile,
+};
+#[cfg(feature = "async")]
+pub use crate::core::structs::{AsyncArray, AsyncDir, AsyncTsdfFile};
+pub use crate::core::traits::{ArrayElement, Condition};
+#[cfg(feature = "arrow")]
+pub use arrow_array::RecordBatch;
+pub use std::io::{Error, ErrorKind};
+
+/// The result of every fallible call in tsdf.
+pub type Result<T> = std::io::Result<T>;
@@ -1,27 +1,26 @@
-// Declare the project structure.
-pub(crate) mod core;
+mod cli;
 
-use std::path::Path;
+use std::{
+    io::{self, Write},
+    process::ExitCode,
+};
 
-use crate::core::structs::TsdfFile;
-use crate::core::traits::TsdfFileTrait;
+use clap::Parser;
 
-fn main() {
-    // Make a path to a test file.
-    let path = Path::new("test.tsdf");
-
-    // Create a new TsdfFile overwriting writer.
-    let tsdf_file = TsdfFile::new_overwriting_writer(path, None, None)
-        .expect("Failed to create file.");
-
-    // Print the version of the file, the write mode, the path, the size, and
-    // the file format.
-    println!(
-        "Version: {}\nIoMode: {:?}\nPath: {:?}\nSize: {}\nFileFormat: {:?}",
-        tsdf_file.get_version(),
-        tsdf_file.get_io_mode(),
-        tsdf_file.get_path(),
-        tsdf_file.get_size(),
-        tsdf_file.get_file_format()
-    );
+fn main() -> ExitCode {
+    let cli = cli::Cli::parse();
+    let mut stdout = io::stdout().lock();
+    let result = cli.run(&mut stdout).and_then(|_| stdout.flush());
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        // Being piped into something like head, which stops reading early,
+        // isn't an error.
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("tsdf: {}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,307 @@
+use std::{collections::BTreeMap, io, sync::Arc};
+
+use numpy::{
+    Element, PyArray1, PyArrayMethods, PyReadonlyArrayDyn,
+    PyUntypedArrayMethods,
+};
+use pyo3::{
+    exceptions::{PyIndexError, PyValueError},
+    prelude::*,
+    types::PySlice,
+};
+
+use crate::core::{
+    enums::{with_element_type, ArrayDataType},
+    structs::{Addr, Array},
+    traits::{ArrayElement, ArrayTrait, TsdfFileTrait},
+};
+
+use super::{to_dtype_name, to_py_err, wrong_dtype, SharedFile};
+
+/// An array in a tsdf file, as seen from Python. Frames are read back as NumPy
+/// arrays whose first axis is time, and appended from NumPy arrays of the
+/// matching dtype.
+#[pyclass(name = "Array", module = "tsdf", frozen)]
+pub(crate) struct ArrayHandle {
+    /// The file that the Array lives in.
+    file: Arc<SharedFile>,
+
+    /// The address of the Array in the file.
+    loc: Addr,
+}
+
+impl ArrayHandle {
+    /// Constructs a new ArrayHandle for the Array at the given location.
+    pub(crate) fn new(file: Arc<SharedFile>, loc: Addr) -> Self {
+        Self { file, loc }
+    }
+
+    /// Opens the Array and runs the closure against it, with the GIL
+    /// released. The closure must only read.
+    fn with_array<F, R>(&self, py: Python<'_>, f: F) -> PyResult<R>
+    where
+        F: FnOnce(&Array) -> io::Result<R> + Send,
+        R: Send,
+    {
+        py.detach(|| f(&self.open()?)).map_err(to_py_err)
+    }
+
+    /// Opens the Array and runs the closure against it, with the GIL
+    /// released and the file's write lock held.
+    fn write_array<F, R>(&self, py: Python<'_>, f: F) -> PyResult<R>
+    where
+        F: FnOnce(&Array) -> io::Result<R> + Send,
+        R: Send,
+    {
+        py.detach(|| {
+            let _guard = self.file.lock_writes();
+            f(&self.open()?)
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Opens the Array.
+    fn open(&self) -> io::Result<Array<'_, '_>> {
+        let file = self.file.get_file();
+        Array::open(self.loc, file.get_io_metadata(), file.get_backend())
+    }
+
+    /// Returns the array's data type and frame shape.
+    fn get_layout(
+        &self,
+        py: Python<'_>,
+    ) -> PyResult<(ArrayDataType, Vec<u64>)> {
+        self.with_array(py, |array| {
+            let frame_shape = array.get_header().get_frame_shape().to_vec();
+            Ok((array.get_data_type(), frame_shape))
+        })
+    }
+
+    /// Reads frames start..end as a NumPy array with the given shape. The
+    /// frames are decoded from the file into a new vector, which the NumPy
+    /// array then takes over without copying it again.
+    fn read_frames<T: ArrayElement + Element + Send>(
+        &self,
+        py: Python<'_>,
+        start: usize,
+        end: usize,
+        shape: Vec<usize>,
+    ) -> PyResult<Py<PyAny>> {
+        let data =
+            self.with_array(py, |array| array.get_frames::<T>(start, end))?;
+        let array = PyArray1::from_vec(py, data).reshape(shape)?;
+        Ok(array.into_any().unbind())
+    }
+
+    /// Appends the frames in a NumPy array of T.
+    fn append_frames<T: ArrayElement + Element + Send>(
+        &self,
+        py: Python<'_>,
+        frames: &Bound<'_, PyAny>,
+        frame_shape: &[u64],
+    ) -> PyResult<()> {
+        let array: PyReadonlyArrayDyn<'_, T> = frames
+            .extract()
+            .map_err(|_| wrong_dtype(to_dtype_name(T::DATA_TYPE)))?;
+        check_frames_shape(array.shape(), frame_shape)
+            .map_err(PyValueError::new_err)?;
+
+        // Arrays that aren't contiguous are copied into order first.
+        let data: Vec<T> = match array.as_slice() {
+            Ok(data) => data.to_vec(),
+            Err(_) => array.as_array().iter().copied().collect(),
+        };
+        self.write_array(py, |array| array.append_frames(&data))
+    }
+
+    /// Reads frames start..end, returning them as a NumPy array whose first
+    /// axis is time. If `squeeze` is set, a single frame is returned without
+    /// the time axis.
+    fn read_range(
+        &self,
+        py: Python<'_>,
+        start: usize,
+        end: usize,
+        squeeze: bool,
+    ) -> PyResult<Py<PyAny>> {
+        let (data_type, frame_shape) = self.get_layout(py)?;
+        let mut shape: Vec<usize> = Vec::new();
+        if !squeeze {
+            shape.push(end.saturating_sub(start));
+        }
+        shape.extend(frame_shape.iter().map(|dim| *dim as usize));
+
+        with_element_type!(data_type, T => {
+            self.read_frames::<T>(py, start, end, shape)
+        })
+    }
+}
+
+#[pymethods]
+impl ArrayHandle {
+    fn __len__(&self, py: Python<'_>) -> PyResult<usize> {
+        self.with_array(py, |array| Ok(array.len()))
+    }
+
+    /// The shape of the array. The first dimension is the number of frames.
+    #[getter]
+    fn shape(&self, py: Python<'_>) -> PyResult<Vec<u64>> {
+        self.with_array(py, |array| Ok(array.shape()))
+    }
+
+    /// The name of the NumPy dtype of the array's elements.
+    #[getter]
+    fn dtype(&self, py: Python<'_>) -> PyResult<&'static str> {
+        let (data_type, _) = self.get_layout(py)?;
+        Ok(to_dtype_name(data_type))
+    }
+
+    /// The shape of a single frame of the array.
+    #[getter]
+    fn frame_shape(&self, py: Python<'_>) -> PyResult<Vec<u64>> {
+        let (_, frame_shape) = self.get_layout(py)?;
+        Ok(frame_shape)
+    }
+
+    /// Reads frames `start..end` as a NumPy array. Both default to the ends of
+    /// the array.
+    #[pyo3(signature = (start=None, end=None))]
+    fn read(
+        &self,
+        py: Python<'_>,
+        start: Option<usize>,
+        end: Option<usize>,
+    ) -> PyResult<Py<PyAny>> {
+        let len = self.__len__(py)?;
+        self.read_range(py, start.unwrap_or(0), end.unwrap_or(len), false)
+    }
+
+    /// Indexing with an integer returns a single frame, and indexing with a
+    /// slice returns a stack of frames. Slices must have a step of 1.
+    fn __getitem__(
+        &self,
+        py: Python<'_>,
+        key: &Bound<'_, PyAny>,
+    ) -> PyResult<Py<PyAny>> {
+        let len = self.__len__(py)?;
+        if let Ok(slice) = key.cast::<PySlice>() {
+            let indices = slice.indices(len as isize)?;
+            if indices.step != 1 {
+                return Err(PyValueError::new_err(
+                    "Array slices must have a step of 1.",
+                ));
+            }
+            let start = indices.start as usize;
+            let end = (indices.stop as usize).max(start);
+            return self.read_range(py, start, end, false);
+        }
+
+        let index: isize = key.extract()?;
+        let index = resolve_index(index, len).ok_or_else(|| {
+            PyIndexError::new_err(format!(
+                "Frame {} is out of bounds for {} frames.",
+                index, len
+            ))
+        })?;
+        self.read_range(py, index, index + 1, true)
+    }
+
+    /// Appends frames from a NumPy array. The array's dtype must match the
+    /// array's, and its shape must either be the frame shape (one frame) or
+    /// the frame shape with an extra leading axis (a stack of frames).
+    fn append(
+        &self,
+        py: Python<'_>,
+        frames: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        let (data_type, frame_shape) = self.get_layout(py)?;
+        with_element_type!(data_type, T => {
+            self.append_frames::<T>(py, frames, &frame_shape)
+        })
+    }
+
+    /// Returns the value of the tag with the given name, or None if the array
+    /// has no such tag.
+    fn get_tag(&self, py: Python<'_>, name: &str) -> PyResult<Option<String>> {
+        self.with_array(py, |array| array.get_tag(name))
+    }
+
+    /// Sets the tag with the given name to the given value.
+    fn set_tag(&self, py: Python<'_>, name: &str, value: &str) -> PyResult<()> {
+        self.write_array(py, |array| array.set_tag(name, value))
+    }
+
+    /// Returns every tag on the array as a dict.
+    fn tags(&self, py: Python<'_>) -> PyResult<BTreeMap<String, String>> {
+        let tags = self.with_array(py, |array| array.get_tags())?;
+        Ok(tags.into_iter().collect())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let shape = self.shape(py)?;
+        let dtype = self.dtype(py)?;
+        Ok(format!("<tsdf.Array shape={:?} dtype={}>", shape, dtype))
+    }
+}
+
+/// Resolves a Python style index, which may count back from the end, into an
+/// index into a sequence of the given length.
+fn resolve_index(index: isize, len: usize) -> Option<usize> {
+    let resolved = if index < 0 {
+        index + len as isize
+    } else {
+        index
+    };
+    (0..len as isize)
+        .contains(&resolved)
+        .then_some(resolved as usize)
+}
+
+/// Checks that an array of the given shape holds whole frames of the given
+/// frame shape: either a single frame, or a stack of them.
+fn check_frames_shape(
+    shape: &[usize],
+    frame_shape: &[u64],
+) -> Result<(), String> {
+    let matches = |dims: &[usize]| {
+        dims.len() == frame_shape.len()
+            && dims.iter().zip(frame_shape).all(|(a, b)| *a as u64 == *b)
+    };
+    if matches(shape) || (!shape.is_empty() && matches(&shape[1..])) {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Can't append an array of shape {:?} to an array of frames of \
+         shape {:?}.",
+        shape, frame_shape
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Make sure that negative indices count back from the end, and that
+    /// out of bounds indices are rejected.
+    #[test]
+    fn test_resolve_index() {
+        assert_eq!(resolve_index(0, 3), Some(0));
+        assert_eq!(resolve_index(-1, 3), Some(2));
+        assert_eq!(resolve_index(3, 3), None);
+        assert_eq!(resolve_index(-4, 3), None);
+        assert_eq!(resolve_index(0, 0), None);
+    }
+
+    /// Make sure that single frames and stacks of frames are accepted, and
+    /// that anything else is rejected.
+    #[test]
+    fn test_check_frames_shape() {
+        assert!(check_frames_shape(&[2, 3], &[2, 3]).is_ok());
+        assert!(check_frames_shape(&[5, 2, 3], &[2, 3]).is_ok());
+        assert!(check_frames_shape(&[], &[]).is_ok());
+        assert!(check_frames_shape(&[7], &[]).is_ok());
+        assert!(check_frames_shape(&[3, 2], &[2, 3]).is_err());
+        assert!(check_frames_shape(&[5, 1, 2, 3], &[2, 3]).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use crate::core::enums::ArrayDataType;
+
+/// Every ArrayDataType, alongside the name of the matching NumPy dtype.
+const DTYPE_NAMES: [(ArrayDataType, &str); 10] = [
+    (ArrayDataType::Int8, "int8"),
+    (ArrayDataType::Int16, "int16"),
+    (ArrayDataType::Int32, "int32"),
+    (ArrayDataType::Int64, "int64"),
+    (ArrayDataType::UInt8, "uint8"),
+    (ArrayDataType::UInt16, "uint16"),
+    (ArrayDataType::UInt32, "uint32"),
+    (ArrayDataType::UInt64, "uint64"),
+    (ArrayDataType::Float32, "float32"),
+    (ArrayDataType::Float64, "float64"),
+];
+
+/// Returns the name of the NumPy dtype that matches the data type.
+pub(crate) fn to_dtype_name(data_type: ArrayDataType) -> &'static str {
+    DTYPE_NAMES
+        .iter()
+        .find(|(t, _)| *t == data_type)
+        .map(|(_, name)| *name)
+        .unwrap()
+}
+
+/// Returns the data type that matches the NumPy dtype name, or None if tsdf
+/// can't store that dtype.
+pub(crate) fn from_dtype_name(name: &str) -> Option<ArrayDataType> {
+    DTYPE_NAMES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(t, _)| *t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every data type should round trip through its dtype name.
+    #[test]
+    fn test_dtype_names() {
+        for (data_type, name) in DTYPE_NAMES {
+            assert_eq!(to_dtype_name(data_type), name);
+            assert_eq!(from_dtype_name(name), Some(data_type));
+        }
+        assert_eq!(from_dtype_name("complex128"), None);
+    }
+}
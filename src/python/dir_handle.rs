@@ -0,0 +1,173 @@
+use std::{collections::BTreeMap, io, sync::Arc};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::core::{
+    enums::HighLevelObject,
+    structs::{Addr, Dir},
+    traits::{DirTrait, Locatable, TsdfFileTrait},
+};
+
+use super::{from_dtype_name, to_py_err, ArrayHandle, SharedFile};
+
+/// A dir in a tsdf file, as seen from Python. Dirs behave like read-only
+/// mappings from names to their child Dirs and Arrays.
+#[pyclass(name = "Dir", module = "tsdf", frozen)]
+pub(crate) struct DirHandle {
+    /// The file that the Dir lives in.
+    file: Arc<SharedFile>,
+
+    /// The address of the Dir in the file.
+    loc: Addr,
+
+    /// The name of the Dir within its parent.
+    name: String,
+}
+
+impl DirHandle {
+    /// Constructs a new DirHandle for the Dir at the given location.
+    pub(crate) fn new(file: Arc<SharedFile>, loc: Addr, name: String) -> Self {
+        Self { file, loc, name }
+    }
+
+    /// Opens the Dir and runs the closure against it, with the GIL released.
+    /// The closure must only read.
+    fn with_dir<F, R>(&self, py: Python<'_>, f: F) -> PyResult<R>
+    where
+        F: FnOnce(&Dir) -> io::Result<R> + Send,
+        R: Send,
+    {
+        py.detach(|| f(&self.open()?)).map_err(to_py_err)
+    }
+
+    /// Opens the Dir and runs the closure against it, with the GIL released
+    /// and the file's write lock held.
+    fn write_dir<F, R>(&self, py: Python<'_>, f: F) -> PyResult<R>
+    where
+        F: FnOnce(&Dir) -> io::Result<R> + Send,
+        R: Send,
+    {
+        py.detach(|| {
+            let _guard = self.file.lock_writes();
+            f(&self.open()?)
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Opens the Dir.
+    fn open(&self) -> io::Result<Dir<'_, '_>> {
+        let file = self.file.get_file();
+        Dir::open(
+            self.loc,
+            &self.name,
+            file.get_io_metadata(),
+            file.get_backend(),
+        )
+    }
+
+    /// Wraps a child of this Dir.
+    fn child_dir(&self, loc: Addr, name: &str) -> DirHandle {
+        DirHandle::new(self.file.clone(), loc, name.to_string())
+    }
+}
+
+#[pymethods]
+impl DirHandle {
+    /// The name of the dir within its parent.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the names of every Array and Dir in the dir, sorted.
+    fn list(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        self.with_dir(py, |dir| dir.list_dir())
+    }
+
+    fn keys(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        self.list(py)
+    }
+
+    fn __contains__(&self, py: Python<'_>, name: &str) -> PyResult<bool> {
+        self.with_dir(py, |dir| {
+            Ok(matches!(dir.get_entry(name)?, Some((_, true))))
+        })
+    }
+
+    /// Returns the child Dir or Array with the given name.
+    fn __getitem__(&self, py: Python<'_>, name: &str) -> PyResult<Py<PyAny>> {
+        let (is_dir, loc) =
+            self.with_dir(py, |dir| match dir.get(name)? {
+                HighLevelObject::Dir(child) => Ok((true, *child.get_addr())),
+                HighLevelObject::Array(child) => Ok((false, *child.get_addr())),
+                // Tags live in their own namespace in Python.
+                HighLevelObject::MetadataTag(_) => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{:?} does not exist.", name),
+                )),
+            })?;
+
+        if is_dir {
+            Ok(Py::new(py, self.child_dir(loc, name))?.into_any())
+        } else {
+            let array = ArrayHandle::new(self.file.clone(), loc);
+            Ok(Py::new(py, array)?.into_any())
+        }
+    }
+
+    /// Creates a new, empty dir with the given name.
+    fn create_dir(&self, py: Python<'_>, name: &str) -> PyResult<DirHandle> {
+        let loc =
+            self.write_dir(py, |dir| Ok(*dir.create_dir(name)?.get_addr()))?;
+        Ok(self.child_dir(loc, name))
+    }
+
+    /// Creates a new, empty array with the given name. `dtype` is the name of
+    /// a NumPy dtype, like "float64", and every frame appended to the array
+    /// must have the shape `frame_shape`. Frames are stored in chunks of
+    /// `frames_per_chunk`.
+    #[pyo3(signature = (name, dtype, frame_shape=Vec::new(), frames_per_chunk=1024))]
+    fn create_array(
+        &self,
+        py: Python<'_>,
+        name: &str,
+        dtype: &str,
+        frame_shape: Vec<u64>,
+        frames_per_chunk: u64,
+    ) -> PyResult<ArrayHandle> {
+        let data_type = from_dtype_name(dtype).ok_or_else(|| {
+            PyValueError::new_err(format!("Unsupported dtype {:?}.", dtype))
+        })?;
+        let loc = self.write_dir(py, |dir| {
+            let array = dir.create_array(
+                name,
+                data_type,
+                &frame_shape,
+                frames_per_chunk,
+            )?;
+            Ok(*array.get_addr())
+        })?;
+        Ok(ArrayHandle::new(self.file.clone(), loc))
+    }
+
+    /// Returns the value of the tag with the given name, or None if the dir
+    /// has no such tag.
+    fn get_tag(&self, py: Python<'_>, name: &str) -> PyResult<Option<String>> {
+        self.with_dir(py, |dir| dir.get_tag(name))
+    }
+
+    /// Sets the tag with the given name to the given value.
+    fn set_tag(&self, py: Python<'_>, name: &str, value: &str) -> PyResult<()> {
+        self.write_dir(py, |dir| dir.set_tag(name, value))
+    }
+
+    /// Returns every tag on the dir as a dict.
+    fn tags(&self, py: Python<'_>) -> PyResult<BTreeMap<String, String>> {
+        let tags = self.with_dir(py, |dir| dir.get_tags())?;
+        Ok(tags.into_iter().collect())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<tsdf.Dir {:?}>", self.name)
+    }
+}
@@ -0,0 +1,27 @@
+use std::io;
+
+use pyo3::{
+    exceptions::{
+        PyFileExistsError, PyKeyError, PyOSError, PyTypeError, PyValueError,
+    },
+    PyErr,
+};
+
+/// Converts an error from the library into the closest Python exception.
+/// Missing objects become KeyErrors, so that dirs behave like mappings. Errors
+/// opening a file should go through pyo3's own conversion instead, so that a
+/// missing file becomes a FileNotFoundError.
+pub(crate) fn to_py_err(err: io::Error) -> PyErr {
+    let msg = err.to_string();
+    match err.kind() {
+        io::ErrorKind::NotFound => PyKeyError::new_err(msg),
+        io::ErrorKind::InvalidInput => PyValueError::new_err(msg),
+        io::ErrorKind::AlreadyExists => PyFileExistsError::new_err(msg),
+        _ => PyOSError::new_err(msg),
+    }
+}
+
+/// Makes the exception raised when a NumPy array has the wrong dtype.
+pub(crate) fn wrong_dtype(expected: &str) -> PyErr {
+    PyTypeError::new_err(format!("Expected a NumPy array of {}.", expected))
+}
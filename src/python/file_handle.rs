@@ -0,0 +1,131 @@
+use std::{path::PathBuf, sync::Arc};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::core::{
+    enums::{FileFormat, WriteMode},
+    structs::TsdfFile,
+    traits::Locatable,
+};
+
+use super::{to_py_err, DirHandle, SharedFile};
+
+/// A tsdf file, as seen from Python. Dirs and arrays taken from the file keep
+/// it open for as long as they're alive.
+#[pyclass(name = "File", module = "tsdf", frozen)]
+pub(crate) struct FileHandle {
+    /// The file, shared with every Dir and Array taken from it.
+    file: Arc<SharedFile>,
+}
+
+impl FileHandle {
+    /// Wraps a TsdfFile that has already been opened.
+    fn new(file: TsdfFile) -> Self {
+        Self {
+            file: SharedFile::new(file).into(),
+        }
+    }
+}
+
+#[pymethods]
+impl FileHandle {
+    /// Opens an existing file for reading.
+    #[staticmethod]
+    fn open_reader(py: Python<'_>, path: PathBuf) -> PyResult<Self> {
+        let file = py.detach(|| TsdfFile::new_reader(&path))?;
+        Ok(Self::new(file))
+    }
+
+    /// Opens a file for writing, creating it if it doesn't exist.
+    /// `write_mode` is "lockless" (the default) or "locking", and
+    /// `file_format` is "binary" (the default) or "text".
+    #[staticmethod]
+    #[pyo3(signature = (path, write_mode=None, file_format=None))]
+    fn open_writer(
+        py: Python<'_>,
+        path: PathBuf,
+        write_mode: Option<&str>,
+        file_format: Option<&str>,
+    ) -> PyResult<Self> {
+        let write_mode = write_mode.map(parse_write_mode).transpose()?;
+        let file_format = file_format.map(parse_file_format).transpose()?;
+        let file =
+            py.detach(|| TsdfFile::new_writer(&path, write_mode, file_format))?;
+        Ok(Self::new(file))
+    }
+
+    /// Creates a new file for writing, overwriting any existing file. The
+    /// arguments are the same as for open_writer.
+    #[staticmethod]
+    #[pyo3(signature = (path, write_mode=None, file_format=None))]
+    fn create(
+        py: Python<'_>,
+        path: PathBuf,
+        write_mode: Option<&str>,
+        file_format: Option<&str>,
+    ) -> PyResult<Self> {
+        let write_mode = write_mode.map(parse_write_mode).transpose()?;
+        let file_format = file_format.map(parse_file_format).transpose()?;
+        let file = py.detach(|| {
+            TsdfFile::new_overwriting_writer(&path, write_mode, file_format)
+        })?;
+        Ok(Self::new(file))
+    }
+
+    /// The version of tsdf that wrote the file.
+    #[getter]
+    fn version(&self) -> String {
+        self.file.get_file().get_version().to_string()
+    }
+
+    /// The file's format, "binary" or "text".
+    #[getter]
+    fn file_format(&self) -> &'static str {
+        match self.file.get_file().get_file_format() {
+            FileFormat::Binary => "binary",
+            FileFormat::Text => "text",
+        }
+    }
+
+    /// The size of the file, in bytes.
+    #[getter]
+    fn size(&self) -> u64 {
+        self.file.get_file().get_size()
+    }
+
+    /// Returns the root dir of the file.
+    fn root(&self, py: Python<'_>) -> PyResult<DirHandle> {
+        let loc = py
+            .detach(|| Ok(*self.file.get_file().get_root_dir()?.get_addr()))
+            .map_err(to_py_err)?;
+        Ok(DirHandle::new(self.file.clone(), loc, "/".to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<tsdf.File {:?}>", self.file.get_file().get_path())
+    }
+}
+
+/// Parses a write mode passed in from Python.
+fn parse_write_mode(write_mode: &str) -> PyResult<WriteMode> {
+    match write_mode {
+        "lockless" => Ok(WriteMode::LocklessWrite),
+        "locking" => Ok(WriteMode::LockingWrite),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown write mode {:?}, expected \"lockless\" or \"locking\".",
+            write_mode
+        ))),
+    }
+}
+
+/// Parses a file format passed in from Python.
+fn parse_file_format(file_format: &str) -> PyResult<FileFormat> {
+    match file_format {
+        "binary" => Ok(FileFormat::Binary),
+        "text" => Ok(FileFormat::Text),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown file format {:?}, expected \"binary\" or \"text\".",
+            file_format
+        ))),
+    }
+}
@@ -0,0 +1,131 @@
+//! Python bindings for tsdf, built with pyo3. The module is called `tsdf`, and
+//! exposes File, Dir and Array classes. Arrays are read from and appended to
+//! with NumPy arrays.
+//!
+//! Every call releases the GIL while it touches the file, so Python threads
+//! can read from a file while another thread writes to it. Writes through the
+//! same File, or any Dir or Array taken from it, run one at a time.
+
+pub(crate) mod array_handle;
+pub(crate) mod data_types;
+pub(crate) mod dir_handle;
+pub(crate) mod errors;
+pub(crate) mod file_handle;
+pub(crate) mod shared_file;
+
+use pyo3::prelude::*;
+
+pub(crate) use self::array_handle::ArrayHandle;
+pub(crate) use self::data_types::{from_dtype_name, to_dtype_name};
+pub(crate) use self::dir_handle::DirHandle;
+pub(crate) use self::errors::{to_py_err, wrong_dtype};
+pub(crate) use self::file_handle::FileHandle;
+pub(crate) use self::shared_file::SharedFile;
+
+/// The tsdf Python module.
+#[pymodule]
+fn tsdf(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<FileHandle>()?;
+    m.add_class::<DirHandle>()?;
+    m.add_class::<ArrayHandle>()?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::types::IntoPyDict;
+
+    use super::*;
+
+    /// Drive files, dirs and tags from Python, and, where NumPy is installed,
+    /// round trip frames through NumPy arrays, including from several
+    /// threads writing at once.
+    #[test]
+    fn test_from_python() {
+        Python::initialize();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "tsdf").unwrap();
+            tsdf(&module).unwrap();
+            let locals = [("tsdf", module)].into_py_dict(py).unwrap();
+            py.run(
+                cr#"
+import os, tempfile, threading
+
+def raises(exc, f, *args):
+    try:
+        f(*args)
+    except exc:
+        return True
+    return False
+
+path = os.path.join(tempfile.mkdtemp(), "test.tsdf")
+writer = tsdf.File.create(path)
+assert writer.file_format == "binary"
+detector = writer.root().create_dir("detector")
+detector.set_tag("units", "counts")
+detector.create_array("counts", "uint16", [2], 4)
+assert raises(ValueError, detector.create_array, "bad", "complex128")
+assert raises(FileExistsError, detector.create_dir, "counts")
+assert raises(ValueError, tsdf.File.create, path, "sometimes")
+
+reader = tsdf.File.open_reader(path)
+root = reader.root()
+assert root.list() == ["detector"]
+assert "detector" in root and "nope" not in root
+assert raises(KeyError, root.__getitem__, "nope")
+detector = root["detector"]
+assert detector.name == "detector"
+assert detector.tags() == {"units": "counts"}
+counts = detector["counts"]
+assert counts.shape == [0, 2] and counts.dtype == "uint16"
+assert len(counts) == 0 and counts.get_tag("units") is None
+assert raises(FileNotFoundError, tsdf.File.open_reader, path + ".missing")
+
+try:
+    import numpy as np
+except ImportError:
+    np = None
+
+if np is not None:
+    path = os.path.join(tempfile.mkdtemp(), "numpy.tsdf")
+    writer = tsdf.File.create(path)
+    counts = writer.root().create_array("counts", "uint16", [2], 4)
+    frames = np.arange(20, dtype=np.uint16).reshape(10, 2)
+    counts.append(frames[0])
+    counts.append(frames[1:6])
+    counts.append(np.asfortranarray(frames[6:]))
+    assert raises(TypeError, counts.append, frames.astype(np.float32))
+    assert raises(ValueError, counts.append, np.zeros((3, 3), np.uint16))
+
+    counts = tsdf.File.open_reader(path).root()["counts"]
+    read = counts.read()
+    assert read.dtype == np.uint16 and read.shape == (10, 2)
+    assert (read == frames).all()
+    assert (counts[3] == frames[3]).all() and counts[3].shape == (2,)
+    assert (counts[-1] == frames[-1]).all()
+    assert (counts[2:5] == frames[2:5]).all()
+    assert (counts.read(4, 7) == frames[4:7]).all()
+
+    root = writer.root()
+    arrays = [root.create_array(f"a{i}", "int64", [], 1) for i in range(4)]
+    def append(i):
+        for j in range(100):
+            arrays[i].append(np.array([i * 1000 + j], dtype=np.int64))
+    threads = [threading.Thread(target=append, args=(i,)) for i in range(4)]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    root = tsdf.File.open_reader(path).root()
+    for i in range(4):
+        expected = np.arange(100, dtype=np.int64) + i * 1000
+        assert (root[f"a{i}"].read() == expected).all()
+"#,
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::core::structs::TsdfFile;
+
+/// A TsdfFile shared by a File and every Dir and Array taken from it. Reads go
+/// straight to the file, but writes from any of them take the same lock, as a
+/// TsdfFile only supports one writer at a time.
+pub(crate) struct SharedFile {
+    /// The file itself.
+    file: TsdfFile,
+
+    /// Held by every write.
+    write_lock: Mutex<()>,
+}
+
+impl SharedFile {
+    /// Wraps a TsdfFile that has already been opened.
+    pub(crate) fn new(file: TsdfFile) -> Self {
+        Self {
+            file,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the file.
+    pub(crate) fn get_file(&self) -> &TsdfFile {
+        &self.file
+    }
+
+    /// Waits until no other write is running, and keeps them all out until
+    /// the guard is dropped. This must be called with the GIL released, or a
+    /// writer holding the lock could wait forever for the GIL.
+    pub(crate) fn lock_writes(&self) -> MutexGuard<'_, ()> {
+        // A writer that panicked leaves the file as a crash would, which
+        // readers and later writers cope with.
+        self.write_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}